@@ -0,0 +1,98 @@
+use std::cmp::Reverse;
+
+use crate::duty::{self, DutyKind};
+use crate::job;
+
+/// Dungeon runs a party fits into an hour, queue included.
+const DUNGEON_RUNS_PER_HOUR: u64 = 2;
+/// What a dungeon run earns altogether, compared with its completion EXP alone, counting the enemies along the way.
+const DUNGEON_RUN_MULTIPLIER: u64 = 2;
+
+/// EXP an hour of each kind of content earns, compared with running the highest dungeon available.
+const DEEP_DUNGEON_RATE: f64 = 1.1;
+const FIELD_OPERATION_RATE: f64 = 1.0;
+const FATE_TRAIN_RATE: f64 = 0.6;
+
+/// The daily bonus of each roulette worth running to level, as a share of the EXP to the next level.
+static ROULETTE_BONUSES: &[(&str, f64)] = &[
+    ("Leveling", 0.5),
+    ("Level 50/60/70/80 Dungeons", 0.3),
+    ("Trials", 0.1),
+    ("Alliance Raids", 0.2),
+    ("Main Scenario", 0.6)
+];
+
+/// How often a piece of advice's EXP can be earned.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Hourly,
+    /// Only once a day, from a roulette's daily bonus.
+    Daily
+}
+
+/// Something a party synced to a level can do to earn EXP, and roughly how much.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Advice {
+    pub activity: String,
+    pub exp: u64,
+    pub frequency: Frequency,
+    /// What the party needs to have done before they can do it, if anything.
+    pub requirement: Option<&'static str>
+}
+
+/// The biggest daily roulette bonus a party synced to `level` can queue for, as a share of the EXP to the next level.
+pub(crate) fn best_roulette_bonus(level: u8) -> f64 {
+    duty::available_roulettes(level).into_iter()
+        .filter_map(|roulette| ROULETTE_BONUSES.iter().find(|&&(name, _)| name == roulette.name))
+        .map(|&(_, bonus)| bonus)
+        .fold(0.0, f64::max)
+}
+
+/// What a party synced to `level` can do to level, with the content earning the most EXP an hour first, followed by
+/// the roulettes whose daily bonus is worth the queue. The numbers are estimates from the content table, so they are
+/// only good for comparing one activity against another.
+pub fn advise(level: u8) -> Vec<Advice> {
+    if level >= job::LEVEL_CAP {
+        return Vec::new();
+    }
+
+    let Some(dungeon) = duty::highest_available(level, DutyKind::Dungeon, 1).pop() else {
+        return Vec::new();
+    };
+    let dungeon_exp = duty::completion_exp(dungeon).unwrap_or_default() as u64 * DUNGEON_RUN_MULTIPLIER * DUNGEON_RUNS_PER_HOUR;
+    let hourly = |activity: String, rate: f64, requirement: Option<&'static str>| Advice {
+        activity,
+        exp: (dungeon_exp as f64 * rate) as u64,
+        frequency: Frequency::Hourly,
+        requirement
+    };
+
+    let mut advice = vec![hourly(format!("{} (Lv {})", dungeon.name, dungeon.synced_level(level)), 1.0, None)];
+    if let Some((deep_dungeon, floor_set)) = duty::deep_dungeon(level) {
+        advice.push(hourly(format!("{}, floors {}", deep_dungeon.name, floor_set.floors), DEEP_DUNGEON_RATE, floor_set.requirement));
+    }
+    for field_operation in duty::field_operations(level) {
+        advice.push(hourly(field_operation.name.to_owned(), FIELD_OPERATION_RATE, Some(field_operation.requirement)));
+    }
+    if let Some(zone) = duty::fate_zone_for(level) {
+        advice.push(hourly(format!("FATE train in {}", zone.name), FATE_TRAIN_RATE, None));
+    }
+    advice.sort_by_key(|advice| Reverse(advice.exp));
+
+    let to_next_level = job::EXP_TO_NEXT_LEVEL[level as usize - 1] as f64;
+    let mut daily: Vec<Advice> = duty::available_roulettes(level).into_iter()
+        .filter_map(|roulette| {
+            let &(_, bonus) = ROULETTE_BONUSES.iter().find(|&&(name, _)| name == roulette.name)?;
+            Some(Advice {
+                activity: format!("{} Roulette", roulette.name),
+                exp: (to_next_level * bonus) as u64,
+                frequency: Frequency::Daily,
+                requirement: None
+            })
+        })
+        .collect();
+    daily.sort_by_key(|advice| Reverse(advice.exp));
+
+    advice.extend(daily);
+    advice
+}
@@ -0,0 +1,131 @@
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use xiv_levelling::cache::{Cache, Entry};
+
+use crate::exit::Exit;
+use crate::store::{CharacterStore, StoredCharacter};
+use crate::{describe_age, CacheCommand};
+
+/// `size` in bytes, in the largest unit it's at least one of, e.g. "12.3 KB".
+fn describe_size(size: u64) -> String {
+    match size {
+        _ if size >= 1024 * 1024 => format!("{:.1} MB", size as f64 / (1024.0 * 1024.0)),
+        _ if size >= 1024 => format!("{:.1} KB", size as f64 / 1024.0),
+        _ => format!("{} B", size)
+    }
+}
+
+fn stored_age(stored: &StoredCharacter) -> Duration {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    Duration::from_secs(now.saturating_sub(stored.fetched_at))
+}
+
+/// Whether something called `name` that is `age` old is picked out by `prune`'s options.
+fn pruned(name: &str, age: Duration, older_than: Option<Duration>, matching: Option<&str>) -> bool {
+    older_than.is_none_or(|older_than| age >= older_than)
+        && matching.is_none_or(|matching| name.to_lowercase().contains(&matching.to_lowercase()))
+}
+
+fn load_store() -> CharacterStore {
+    let Some(path) = CharacterStore::default_path() else {
+        return CharacterStore::default();
+    };
+    match CharacterStore::load(&path) {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("Failed to load {}: {}", path.display(), e);
+            process::exit(Exit::Failure.code());
+        }
+    }
+}
+
+fn entries(cache: &Cache) -> Vec<Entry> {
+    match cache.entries() {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Failed to read the cache: {}", e);
+            process::exit(Exit::Failure.code());
+        }
+    }
+}
+
+fn ls(cache: &Cache) {
+    let store = load_store();
+    println!("Stored characters:");
+    let mut characters: Vec<&StoredCharacter> = store.characters().collect();
+    characters.sort_by(|a, b| a.name.cmp(&b.name));
+    if characters.is_empty() {
+        println!("- None");
+    }
+    for stored in characters {
+        let world = if stored.world.is_empty() { "an unknown world" } else { &stored.world };
+        println!("- {} ({}) on {}, looked up {} ago", stored.name, stored.id, world, describe_age(stored_age(stored)));
+    }
+
+    println!("\nCached responses:");
+    let entries = entries(cache);
+    if entries.is_empty() {
+        println!("- None");
+    }
+    for entry in &entries {
+        println!("- {} ({}, {} old)", entry.name, describe_size(entry.size), describe_age(entry.age));
+    }
+}
+
+/// Prints the levels of the stored characters called `name`, or failing that the body of the cached response.
+fn show(cache: &Cache, name: &str) {
+    let store = load_store();
+    let mut found = false;
+    for stored in store.characters().filter(|stored| stored.name.eq_ignore_ascii_case(name)) {
+        found = true;
+        println!("{} ({}), looked up {} ago:", stored.name, stored.id, describe_age(stored_age(stored)));
+        for (job, level) in stored.levels.iter().filter(|&(_, &level)| level > 0) {
+            println!("- {} Lv {}", job, level);
+        }
+    }
+    if found {
+        return;
+    }
+
+    match cache.read_entry(name) {
+        Ok(body) => println!("{}", body),
+        Err(e) => {
+            eprintln!("There is no stored character or cached response called {}: {}", name, e);
+            process::exit(Exit::NotFound.code());
+        }
+    }
+}
+
+fn prune(cache: &Cache, older_than: Option<Duration>, matching: Option<&str>, characters: bool) {
+    let mut deleted = 0;
+    for entry in entries(cache).iter().filter(|entry| pruned(&entry.name, entry.age, older_than, matching)) {
+        match cache.remove_entry(&entry.name) {
+            Ok(()) => deleted += 1,
+            Err(e) => eprintln!("Failed to delete {}: {}", entry.name, e)
+        }
+    }
+    println!("Deleted {} cached response(s).", deleted);
+
+    if !characters {
+        return;
+    }
+    let Some(path) = CharacterStore::default_path() else {
+        return;
+    };
+    let mut store = load_store();
+    let forgotten = store.forget(|stored| pruned(&stored.name, stored_age(stored), older_than, matching));
+    if let Err(e) = store.save(&path) {
+        eprintln!("Failed to save {}: {}", path.display(), e);
+        process::exit(Exit::Failure.code());
+    }
+    println!("Forgot {} stored character(s).", forgotten.len());
+}
+
+pub fn run(cache: &Cache, command: CacheCommand) {
+    match command {
+        CacheCommand::Ls => ls(cache),
+        CacheCommand::Show { name } => show(cache, &name),
+        CacheCommand::Prune { older_than, matching, characters } => prune(cache, older_than, matching.as_deref(), characters)
+    }
+}
@@ -0,0 +1,222 @@
+use serenity::all::{
+    Command, CommandInteraction, CommandOptionType, Context, CreateCommand, CreateCommandOption, CreateEmbed,
+    EditInteractionResponse, EventHandler, GatewayIntents, Interaction, Ready
+};
+use serenity::{async_trait, Client};
+use std::sync::{Arc, Mutex};
+
+use xiv_levelling::cache::Cache;
+use xiv_levelling::constraints::Constraints;
+use xiv_levelling::duty::DutyKind;
+use xiv_levelling::job::Mode;
+use xiv_levelling::language::Language;
+use xiv_levelling::optimizer::{self, Scoring};
+use xiv_levelling::provider::CharacterProvider;
+use xiv_levelling::xivapi::{PlayerCharacter, XivApi};
+
+use crate::output;
+use crate::verify::{self, Registry};
+
+const TOP_CONFIGS: usize = 5;
+
+struct Handler {
+    api: Arc<XivApi>,
+    registry: Arc<Mutex<Registry>>
+}
+
+fn levelling_command() -> CreateCommand {
+    CreateCommand::new("levelling")
+        .description("Find the most balanced party configurations for levelling")
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "server", "The FFXIV server the characters are on")
+            .required(true))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "chars", "Comma-separated list of two to four character names")
+            .required(true))
+}
+
+fn register_command() -> CreateCommand {
+    CreateCommand::new("register")
+        .description("Start registering a character to yourself, by putting a token in its Lodestone bio")
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "server", "The FFXIV server the character is on")
+            .required(true))
+        .add_option(CreateCommandOption::new(CommandOptionType::String, "name", "The character's full name")
+            .required(true))
+}
+
+fn verify_command() -> CreateCommand {
+    CreateCommand::new("verify")
+        .description("Finish registering a character, once its Lodestone bio has your token from /register")
+}
+
+fn option_str(command: &CommandInteraction, name: &str) -> String {
+    command.data.options.iter()
+        .find(|option| option.name == name)
+        .and_then(|option| option.value.as_str())
+        .unwrap_or_default()
+        .trim()
+        .to_owned()
+}
+
+/// Resolves the party and builds the results embed. This blocks on XIVAPI, so it must not run on the async runtime.
+fn levelling(api: &dyn CharacterProvider, server: &str, chars: &str) -> Result<CreateEmbed, String> {
+    let server_list = api.servers().map_err(|e| e.to_string())?;
+    if !server_list.exists(server) {
+        return Err(format!("Server {} does not exist!", server));
+    }
+
+    let names: Vec<&str> = chars.split(',').map(str::trim).filter(|name| !name.is_empty()).collect();
+    if names.len() < 2 || names.len() > 4 {
+        return Err("Party must consist of between two and four characters!".to_owned());
+    }
+
+    let mut party: Vec<PlayerCharacter> = Vec::new();
+    for name in names {
+        let player_search = api.search_character(name, server).map_err(|e| e.to_string())?;
+
+        match player_search.pagination.results {
+            1 => {
+                let mut character = api.character(player_search.results[0].id).map_err(|e| e.to_string())?;
+                if party.iter().any(|member| member.is_same_character(&character)) {
+                    return Err(format!("{} was entered more than once!", character.name));
+                }
+                character.retain_mode(Mode::Combat);
+                party.push(character);
+            },
+            0 => return Err(format!("No character named {} was found on {}!", name, server)),
+            _ => return Err(format!("Multiple characters named {} were found on {}!", name, server))
+        }
+    }
+
+    let scoring = Scoring::default();
+    let constraints = Constraints {
+        dedup: true,
+        ..Constraints::default()
+    };
+    let mut party_configs = optimizer::optimize_top(&party, &scoring, &constraints, TOP_CONFIGS);
+
+    let mut embed = CreateEmbed::new().title(format!("Levelling configurations on {}", server));
+    if party_configs.is_empty() {
+        embed = embed.description("No valid party configurations were found!");
+    }
+
+    for rank in 1..=TOP_CONFIGS {
+        let Some(party_config) = party_configs.pop() else {
+            break;
+        };
+
+        let mut lines: Vec<String> = party.iter().zip(&party_config.index)
+            .map(|(character, &job_index)| {
+                let class_job = &character.class_jobs[job_index];
+                let bonus = if character.has_armoury_bonus(class_job) { " (armoury bonus)" } else { "" };
+                let icon = class_job.role().map(|role| role.icon()).unwrap_or_default();
+                // Discord can't show an image per line, so the name links to the character's portrait instead.
+                let name = match character.portrait.as_ref().or(character.avatar.as_ref()) {
+                    Some(url) => format!("[{}]({})", character.name, url),
+                    None => character.name.clone()
+                };
+                format!("{} **{}**: {} Lv {}{}", icon, name, class_job.display_name(), class_job.level, bonus)
+            })
+            .collect();
+        if output::is_premade_only(&party, &party_config) {
+            lines.push("Premade only: Blue Mage can't use the Duty Finder".to_owned());
+        }
+        let level = output::duty_level(&party, &party_config);
+        if let Some(sync) = output::sync_summary(level) {
+            lines.push(format!("Synced Level: {}", sync));
+        }
+        lines.push(format!("Dungeons: {}", output::duty_list(level, DutyKind::Dungeon)));
+        lines.push(format!("Roulettes: {}", output::roulette_list(level)));
+        if let Some(deep_dungeon) = output::deep_dungeon_suggestion(level) {
+            lines.push(format!("Deep Dungeon: {}", deep_dungeon));
+        }
+        if let Some(field_operations) = output::field_operation_list(level) {
+            lines.push(format!("Field Operations: {}", field_operations));
+        }
+        if let Some(estimate) = output::exp_estimate(&party, &party_config) {
+            lines.push(format!("Est. EXP: {}", estimate));
+        }
+
+        embed = embed.field(
+            format!("#{} ({} {}, Lv Avg {})", rank, scoring.spread_label(), scoring.metric.format(party_config.spread), party_config.avg),
+            lines.join("\n"),
+            false
+        );
+    }
+
+    Ok(embed)
+}
+
+#[async_trait]
+impl EventHandler for Handler {
+    async fn ready(&self, ctx: Context, ready: Ready) {
+        println!("Connected to Discord as {}!", ready.user.name);
+
+        if let Err(e) = Command::set_global_commands(&ctx.http, vec![levelling_command(), register_command(), verify_command()]).await {
+            eprintln!("Failed to register the slash commands: {}", e);
+        }
+    }
+
+    async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        let Interaction::Command(command) = interaction else {
+            return;
+        };
+
+        let name = command.data.name.clone();
+        if !matches!(name.as_str(), "levelling" | "register" | "verify") {
+            return;
+        }
+
+        // Looking up characters can take longer than Discord's three second response window.
+        if let Err(e) = command.defer(&ctx.http).await {
+            eprintln!("Failed to acknowledge /{}: {}", name, e);
+            return;
+        }
+
+        let api = Arc::clone(&self.api);
+        let registry = Arc::clone(&self.registry);
+        let user = command.user.id.to_string();
+        let server = option_str(&command, "server");
+        let chars = option_str(&command, "chars");
+        let character_name = option_str(&command, "name");
+
+        let response = tokio::task::spawn_blocking(move || match name.as_str() {
+            "levelling" => levelling(api.as_ref(), &server, &chars).map(|embed| EditInteractionResponse::new().embed(embed)),
+            "register" => verify::begin(api.as_ref(), &registry, &user, &character_name, &server).map(|(_, token)| {
+                EditInteractionResponse::new().content(format!(
+                    "Paste `{}` into the bio of {} on the Lodestone, then run /verify within an hour to finish registering them.",
+                    token, character_name
+                ))
+            }),
+            _ => verify::finish(api.as_ref(), &registry, &user)
+                .map(|character| EditInteractionResponse::new().content(format!("{} is now registered to you!", character.name)))
+        }).await;
+
+        let response = match response {
+            Ok(Ok(response)) => response,
+            Ok(Err(message)) => EditInteractionResponse::new().content(message),
+            Err(_) => EditInteractionResponse::new().content("Something went wrong!")
+        };
+
+        if let Err(e) = command.edit_response(&ctx.http, response).await {
+            eprintln!("Failed to respond to /{}: {}", command.data.name, e);
+        }
+    }
+}
+
+pub fn run(token: &str, cache: Cache, client: reqwest::blocking::Client) {
+    let handler = Handler {
+        api: Arc::new(XivApi::new(cache, Language::default(), client)),
+        registry: Arc::new(Mutex::new(Registry::load_default()))
+    };
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+        let mut client = Client::builder(token, GatewayIntents::empty())
+            .event_handler(handler)
+            .await
+            .unwrap();
+
+        if let Err(e) = client.start().await {
+            eprintln!("Discord client error: {}", e);
+        }
+    });
+}
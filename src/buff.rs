@@ -0,0 +1,61 @@
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// An EXP bonus a character may have active while levelling.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Buff {
+    /// Road to 90, granted on preferred worlds and to new or returning players.
+    RoadTo90,
+    /// The free company's Heat of Battle III action.
+    FreeCompany,
+    Rested,
+    Food
+}
+
+impl Buff {
+    /// Extra EXP this buff grants, as a fraction of the base EXP.
+    pub fn bonus(&self) -> f64 {
+        match self {
+            Buff::RoadTo90 => 1.0,
+            Buff::FreeCompany => 0.15,
+            Buff::Rested => 0.5,
+            Buff::Food => 0.03
+        }
+    }
+}
+
+impl FromStr for Buff {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "road-to-90" => Ok(Buff::RoadTo90),
+            "free-company" | "fc" => Ok(Buff::FreeCompany),
+            "rested" => Ok(Buff::Rested),
+            "food" => Ok(Buff::Food),
+            _ => Err(format!("unknown buff {} (expected road-to-90, free-company, rested or food)", s))
+        }
+    }
+}
+
+/// Gives a member an EXP buff, e.g. `Alice=road-to-90`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BuffFlag {
+    pub character: String,
+    pub buff: Buff
+}
+
+impl FromStr for BuffFlag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((character, buff)) if !character.trim().is_empty() => Ok(BuffFlag {
+                character: character.trim().to_owned(),
+                buff: buff.trim().parse()?
+            }),
+            _ => Err(format!("invalid buff {} (expected Name=Buff)", s))
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// A response stored in the cache.
+#[derive(Debug)]
+pub struct Entry {
+    /// What the entry is stored as: its key, with everything but letters and digits replaced by underscores.
+    pub name: String,
+    /// Size of the body in bytes.
+    pub size: u64,
+    pub age: Duration
+}
+
+/// On-disk cache of raw API responses, keyed by request URL.
+pub struct Cache {
+    dir: Option<PathBuf>,
+    offline: bool,
+    refresh: bool
+}
+
+impl Cache {
+    pub fn new() -> Self {
+        Cache {
+            dir: dirs::cache_dir().map(|dir| dir.join("xiv-levelling")),
+            offline: false,
+            refresh: false
+        }
+    }
+
+    /// A cache kept in `dir` rather than the user cache directory.
+    pub fn in_dir(dir: PathBuf) -> Self {
+        Cache { dir: Some(dir), ..Cache::new() }
+    }
+
+    /// A cache that never stores anything.
+    pub fn disabled() -> Self {
+        Cache { dir: None, offline: false, refresh: false }
+    }
+
+    /// Makes this cache hand out entries however old they are, for running without the network. Anything using it
+    /// must not make requests, which `is_offline` tells them.
+    pub fn offline(self) -> Self {
+        Cache { offline: true, ..self }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Makes this cache ignore what it has stored, so everything is fetched again, while still storing the new
+    /// responses for next time.
+    pub fn refreshing(self) -> Self {
+        Cache { refresh: true, ..self }
+    }
+
+    fn path(&self, key: &str) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(entry_name(key) + ".json"))
+    }
+
+    /// Every entry stored, by name. A cache that was never written to has none.
+    pub fn entries(&self) -> io::Result<Vec<Entry>> {
+        let Some(dir) = &self.dir else {
+            return Ok(Vec::new());
+        };
+        let dir_entries = match fs::read_dir(dir) {
+            Ok(dir_entries) => dir_entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e)
+        };
+
+        let mut entries = Vec::new();
+        for dir_entry in dir_entries {
+            let path = dir_entry?.path();
+            // Anything else, like a write that was interrupted, isn't an entry.
+            let (Some(name), Some("json")) = (path.file_stem().and_then(|name| name.to_str()), path.extension().and_then(|extension| extension.to_str())) else {
+                continue;
+            };
+
+            let metadata = fs::metadata(&path)?;
+            let age = metadata.modified().ok()
+                .map(|modified| SystemTime::now().duration_since(modified).unwrap_or_default())
+                .unwrap_or_default();
+            entries.push(Entry { name: name.to_owned(), size: metadata.len(), age });
+        }
+
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    /// The file the entry called `name` is stored in. Only names `entries` could give are looked for, so nothing
+    /// outside the cache can be reached.
+    fn entry_path(&self, name: &str) -> io::Result<PathBuf> {
+        match &self.dir {
+            Some(dir) if !name.is_empty() && entry_name(name) == name => Ok(dir.join(format!("{}.json", name))),
+            _ => Err(io::Error::new(io::ErrorKind::NotFound, format!("no cache entry called {}", name)))
+        }
+    }
+
+    /// The body stored in the entry called `name`.
+    pub fn read_entry(&self, name: &str) -> io::Result<String> {
+        fs::read_to_string(self.entry_path(name)?)
+    }
+
+    pub fn remove_entry(&self, name: &str) -> io::Result<()> {
+        fs::remove_file(self.entry_path(name)?)
+    }
+
+    /// Returns the cached body for `key` if it was stored less than `max_age` ago, or at all when offline, unless
+    /// refreshing.
+    pub fn get(&self, key: &str, max_age: Duration) -> Option<String> {
+        if self.refresh {
+            return None;
+        }
+
+        let path = self.path(key)?;
+
+        match self.age(key) {
+            Some(age) if age <= max_age || self.offline => fs::read_to_string(&path).ok(),
+            _ => None
+        }
+    }
+
+    /// How long ago the entry for `key` was stored, if there is one.
+    pub fn age(&self, key: &str) -> Option<Duration> {
+        let modified = fs::metadata(self.path(key)?).and_then(|meta| meta.modified()).ok()?;
+        // An entry from the future, after the clock was changed, is as fresh as it gets.
+        Some(SystemTime::now().duration_since(modified).unwrap_or_default())
+    }
+
+    pub fn put(&self, key: &str, body: &str) -> io::Result<()> {
+        if let (Some(dir), Some(path)) = (&self.dir, self.path(key)) {
+            fs::create_dir_all(dir)?;
+            // Writing to a temporary file first means an interrupted write never leaves a truncated entry.
+            let partial = path.with_extension("partial");
+            fs::write(&partial, body)?;
+            fs::rename(partial, path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The name the entry for `key` is stored under.
+fn entry_name(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Cache::new()
+    }
+}
@@ -0,0 +1,259 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use xiv_levelling::plan::Session;
+use xiv_levelling::xivapi::PlayerCharacter;
+
+/// Roughly how long a dungeon run takes, queue included, for how long to book each session for.
+const MINUTES_PER_RUN: u32 = 30;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Longest a line of an iCalendar file may be, in bytes, before it has to be folded onto the next.
+const LINE_LENGTH: usize = 75;
+
+/// A day in the proleptic Gregorian calendar.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Date {
+    year: i32,
+    month: u32,
+    day: u32
+}
+
+impl Date {
+    /// Today in UTC, which is close enough for planning sessions from.
+    pub fn today() -> Date {
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        Date::from_days((secs / SECS_PER_DAY) as i64)
+    }
+
+    /// The date `days` days after the Unix epoch, using Howard Hinnant's `civil_from_days`.
+    fn from_days(days: i64) -> Date {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (year_of_era + era * 400 + i64::from(month <= 2)) as i32;
+
+        Date { year, month, day }
+    }
+
+    /// Days since the Unix epoch, the inverse of `from_days`.
+    fn days(self) -> i64 {
+        let year = self.year as i64 - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month = self.month as i64;
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    pub fn add_days(self, days: i64) -> Date {
+        Date::from_days(self.days() + days)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    /// Parses an ISO 8601 date like `2024-03-01`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid date {} (expected YYYY-MM-DD)", s);
+        let mut parts = s.splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+
+        let date = Date {
+            year: year.parse().map_err(|_| invalid())?,
+            month: month.parse().map_err(|_| invalid())?,
+            day: day.parse().map_err(|_| invalid())?
+        };
+        // Days past the end of the month would roll over into the next.
+        if (1..=12).contains(&date.month) && date.day >= 1 && Date::from_days(date.days()) == date {
+            Ok(date)
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}{:02}{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A time of day, e.g. `20:00`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TimeOfDay {
+    hour: u32,
+    minute: u32
+}
+
+impl TimeOfDay {
+    /// Minutes since midnight.
+    fn minutes(self) -> u32 {
+        self.hour * 60 + self.minute
+    }
+}
+
+impl FromStr for TimeOfDay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid time {} (expected HH:MM)", s);
+        let (hour, minute) = s.split_once(':').ok_or_else(invalid)?;
+        let hour = hour.parse().ok().filter(|hour| *hour < 24).ok_or_else(invalid)?;
+        let minute = minute.parse().ok().filter(|minute| *minute < 60).ok_or_else(invalid)?;
+
+        Ok(TimeOfDay { hour, minute })
+    }
+}
+
+/// Escapes `text` for a TEXT value, such as a summary or description.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+/// Appends `line` to `ics`, folding it onto continuation lines if it is too long.
+fn push_line(ics: &mut String, line: &str) {
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > LINE_LENGTH {
+            ics.push_str("\r\n ");
+            // The space starting a continuation line counts towards its length.
+            length = 1;
+        }
+        ics.push(c);
+        length += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+/// An iCalendar file with an event for each of `sessions`, on consecutive days from `start` at `at` local time. The
+/// description of each says who plays what and how far it gets them.
+pub fn ics(party: &[PlayerCharacter], sessions: &[Session], start: Date, at: TimeOfDay) -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let stamp = format!(
+        "{}T{:02}{:02}{:02}Z",
+        Date::from_days((secs / SECS_PER_DAY) as i64),
+        secs % SECS_PER_DAY / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    );
+
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(&mut ics, "PRODID:-//xiv-levelling//Levelling plan//EN");
+
+    for (day, session) in sessions.iter().enumerate() {
+        let date = start.add_days(day as i64);
+        let length = session.runs * MINUTES_PER_RUN + if session.roulette.is_some() { MINUTES_PER_RUN } else { 0 };
+        let end = at.minutes() + length;
+
+        let mut summary = String::new();
+        if let Some(roulette) = session.roulette {
+            summary.push_str(&format!("{} roulette, then ", roulette.name));
+        }
+        summary.push_str(&format!("{} run(s) of {}", session.runs, session.duty.name));
+
+        let mut description = Vec::new();
+        for ((character, &job_index), (before, after)) in party.iter().zip(&session.party_config.index).zip(&session.levels) {
+            let class_job = &character.class_jobs[job_index];
+            description.push(format!("{}: {} Lv {} -> {}", character.display_name(), class_job.display_name(), before, after));
+        }
+        if let Some(member) = session.featured {
+            description.push(format!("{}'s turn on their favourite role", party[member].display_name()));
+        }
+
+        push_line(&mut ics, "BEGIN:VEVENT");
+        push_line(&mut ics, &format!("UID:{}-{}@xiv-levelling", date, day + 1));
+        push_line(&mut ics, &format!("DTSTAMP:{}", stamp));
+        push_line(&mut ics, &format!("DTSTART:{}T{:02}{:02}00", date, at.hour, at.minute));
+        // Sessions running past midnight end the next day.
+        push_line(&mut ics, &format!(
+            "DTEND:{}T{:02}{:02}00",
+            date.add_days((end / (24 * 60)) as i64),
+            end / 60 % 24,
+            end % 60
+        ));
+        push_line(&mut ics, &format!("SUMMARY:{}", escape(&format!("Levelling: {}", summary))));
+        push_line(&mut ics, &format!("DESCRIPTION:{}", escape(&description.join("\n"))));
+        push_line(&mut ics, "END:VEVENT");
+    }
+
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_iso_dates() {
+        assert_eq!(date("2024-03-01"), Date { year: 2024, month: 3, day: 1 });
+        assert_eq!(date("2024-02-29"), Date { year: 2024, month: 2, day: 29 });
+        assert_eq!(date("1970-01-01").days(), 0);
+
+        let invalid = [
+            "2023-02-29", "1900-02-29", "2024-04-31", "2024-13-01", "2024-00-10", "2024-01-00", "2024-01", "20240101",
+            "2024-01-01x", ""
+        ];
+        for invalid in invalid {
+            assert!(invalid.parse::<Date>().is_err(), "{} parsed", invalid);
+        }
+    }
+
+    #[test]
+    fn adds_days_across_months_and_years() {
+        assert_eq!(date("2024-01-31").add_days(1), date("2024-02-01"));
+        assert_eq!(date("2024-02-28").add_days(1), date("2024-02-29"));
+        assert_eq!(date("2024-02-29").add_days(1), date("2024-03-01"));
+        assert_eq!(date("2023-02-28").add_days(1), date("2023-03-01"));
+        assert_eq!(date("2000-02-28").add_days(1), date("2000-02-29"));
+        assert_eq!(date("2100-02-28").add_days(1), date("2100-03-01"));
+        assert_eq!(date("2024-12-31").add_days(1), date("2025-01-01"));
+        assert_eq!(date("2025-01-01").add_days(-1), date("2024-12-31"));
+        assert_eq!(date("2024-01-01").add_days(366), date("2025-01-01"));
+        assert_eq!(date("1969-12-31").add_days(1), date("1970-01-01"));
+    }
+
+    #[test]
+    fn displays_as_an_ics_date() {
+        assert_eq!(date("0999-01-02").to_string(), "09990102");
+    }
+
+    #[test]
+    fn folds_long_lines_by_bytes() {
+        let mut ics = String::new();
+        push_line(&mut ics, &"a".repeat(LINE_LENGTH));
+        assert_eq!(ics, format!("{}\r\n", "a".repeat(LINE_LENGTH)));
+
+        // 74 bytes then a 3 byte character, which has to move onto the next line whole.
+        let line = format!("{}€€", "a".repeat(LINE_LENGTH - 1));
+        let mut ics = String::new();
+        push_line(&mut ics, &line);
+        assert_eq!(ics, format!("{}\r\n €€\r\n", "a".repeat(LINE_LENGTH - 1)));
+
+        let mut ics = String::new();
+        push_line(&mut ics, &"é".repeat(100));
+        for folded in ics.split("\r\n").filter(|folded| !folded.is_empty()) {
+            assert!(folded.len() <= LINE_LENGTH, "{:?} is {} bytes", folded, folded.len());
+        }
+        assert_eq!(ics.replace("\r\n ", "").trim_end(), "é".repeat(100));
+    }
+}
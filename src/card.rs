@@ -0,0 +1,211 @@
+use image::{ImageResult, Rgb, RgbImage};
+use std::path::Path;
+
+use xiv_levelling::job::Role;
+use xiv_levelling::optimizer::{PartyConfig, Scoring};
+use xiv_levelling::xivapi::PlayerCharacter;
+
+/// Pixels per dot of the font.
+const SCALE: u32 = 2;
+
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+
+/// Width of a character, including the gap before the next.
+const ADVANCE: u32 = (GLYPH_WIDTH + 1) * SCALE;
+
+const PADDING: u32 = 16;
+
+/// Height of each line of the card, with room around the text for the job badges.
+const LINE_HEIGHT: u32 = 28;
+
+/// Size of the badge showing a member's job abbreviation.
+const BADGE_WIDTH: u32 = 3 * ADVANCE + 8;
+const BADGE_HEIGHT: u32 = 22;
+
+/// Colours in the style of Discord's dark theme, so the card sits well in a channel.
+const BACKGROUND: Rgb<u8> = Rgb([0x2b, 0x2d, 0x31]);
+const TEXT: Rgb<u8> = Rgb([0xf2, 0xf3, 0xf5]);
+const MUTED: Rgb<u8> = Rgb([0xb5, 0xba, 0xc1]);
+
+/// A 5x7 dot font covering printable ASCII, from space to tilde. Each row is five bits, leftmost dot first.
+const FONT: [[u8; 7]; 95] = [
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000], // space
+    [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100], // !
+    [0b01010, 0b01010, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000], // "
+    [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010], // #
+    [0b00100, 0b01111, 0b10100, 0b01110, 0b00101, 0b11110, 0b00100], // $
+    [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011], // %
+    [0b01100, 0b10010, 0b10100, 0b01000, 0b10101, 0b10010, 0b01101], // &
+    [0b00100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000], // '
+    [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010], // (
+    [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000], // )
+    [0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000], // *
+    [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000], // +
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000], // ,
+    [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000], // -
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100], // .
+    [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000], // /
+    [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110], // 0
+    [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110], // 1
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111], // 2
+    [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110], // 3
+    [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010], // 4
+    [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110], // 5
+    [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110], // 6
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000], // 7
+    [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110], // 8
+    [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100], // 9
+    [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000], // :
+    [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b00100, 0b01000], // ;
+    [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010], // <
+    [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000], // =
+    [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000], // >
+    [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100], // ?
+    [0b01110, 0b10001, 0b00001, 0b01101, 0b10101, 0b10101, 0b01110], // @
+    [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001], // A
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110], // B
+    [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110], // C
+    [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100], // D
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111], // E
+    [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000], // F
+    [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111], // G
+    [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001], // H
+    [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110], // I
+    [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100], // J
+    [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001], // K
+    [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111], // L
+    [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001], // M
+    [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001], // N
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110], // O
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000], // P
+    [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101], // Q
+    [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001], // R
+    [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110], // S
+    [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100], // T
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110], // U
+    [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100], // V
+    [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010], // W
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001], // X
+    [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100], // Y
+    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111], // Z
+    [0b01110, 0b01000, 0b01000, 0b01000, 0b01000, 0b01000, 0b01110], // [
+    [0b00000, 0b10000, 0b01000, 0b00100, 0b00010, 0b00001, 0b00000], // \
+    [0b01110, 0b00010, 0b00010, 0b00010, 0b00010, 0b00010, 0b01110], // ]
+    [0b00100, 0b01010, 0b10001, 0b00000, 0b00000, 0b00000, 0b00000], // ^
+    [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111], // _
+    [0b01000, 0b00100, 0b00010, 0b00000, 0b00000, 0b00000, 0b00000], // `
+    [0b00000, 0b00000, 0b01110, 0b00001, 0b01111, 0b10001, 0b01111], // a
+    [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b11110], // b
+    [0b00000, 0b00000, 0b01110, 0b10000, 0b10000, 0b10001, 0b01110], // c
+    [0b00001, 0b00001, 0b01101, 0b10011, 0b10001, 0b10001, 0b01111], // d
+    [0b00000, 0b00000, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110], // e
+    [0b00110, 0b01001, 0b01000, 0b11100, 0b01000, 0b01000, 0b01000], // f
+    [0b00000, 0b01111, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110], // g
+    [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001], // h
+    [0b00100, 0b00000, 0b01100, 0b00100, 0b00100, 0b00100, 0b01110], // i
+    [0b00010, 0b00000, 0b00110, 0b00010, 0b00010, 0b10010, 0b01100], // j
+    [0b10000, 0b10000, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010], // k
+    [0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110], // l
+    [0b00000, 0b00000, 0b11010, 0b10101, 0b10101, 0b10001, 0b10001], // m
+    [0b00000, 0b00000, 0b10110, 0b11001, 0b10001, 0b10001, 0b10001], // n
+    [0b00000, 0b00000, 0b01110, 0b10001, 0b10001, 0b10001, 0b01110], // o
+    [0b00000, 0b00000, 0b11110, 0b10001, 0b11110, 0b10000, 0b10000], // p
+    [0b00000, 0b00000, 0b01101, 0b10011, 0b01111, 0b00001, 0b00001], // q
+    [0b00000, 0b00000, 0b10110, 0b11001, 0b10000, 0b10000, 0b10000], // r
+    [0b00000, 0b00000, 0b01110, 0b10000, 0b01110, 0b00001, 0b11110], // s
+    [0b01000, 0b01000, 0b11100, 0b01000, 0b01000, 0b01001, 0b00110], // t
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10001, 0b10011, 0b01101], // u
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100], // v
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b10101, 0b10101, 0b01010], // w
+    [0b00000, 0b00000, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001], // x
+    [0b00000, 0b00000, 0b10001, 0b10001, 0b01111, 0b00001, 0b01110], // y
+    [0b00000, 0b00000, 0b11111, 0b00010, 0b00100, 0b01000, 0b11111], // z
+    [0b00010, 0b00100, 0b00100, 0b01000, 0b00100, 0b00100, 0b00010], // {
+    [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100], // |
+    [0b01000, 0b00100, 0b00100, 0b00010, 0b00100, 0b00100, 0b01000], // }
+    [0b00000, 0b00000, 0b01000, 0b10101, 0b00010, 0b00000, 0b00000], // ~
+];
+
+/// The colour of `role`, matching the role colours in game.
+fn role_colour(role: Option<Role>) -> Rgb<u8> {
+    match role {
+        Some(Role::Tank) => Rgb([0x36, 0x5f, 0xc9]),
+        Some(Role::Healer) => Rgb([0x3d, 0x8c, 0x40]),
+        Some(Role::Dps) => Rgb([0xa8, 0x35, 0x35]),
+        None => Rgb([0x60, 0x60, 0x60])
+    }
+}
+
+fn text_width(text: &str) -> u32 {
+    text.chars().count() as u32 * ADVANCE
+}
+
+fn fill(image: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, colour: Rgb<u8>) {
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            image.put_pixel(px, py, colour);
+        }
+    }
+}
+
+/// Draws `text` with its top left corner at `x`, `y`. Anything the font doesn't cover is drawn as a question mark.
+fn draw_text(image: &mut RgbImage, x: u32, y: u32, text: &str, colour: Rgb<u8>) {
+    for (i, c) in text.chars().enumerate() {
+        let glyph = FONT[if (' '..='~').contains(&c) { c as usize - ' ' as usize } else { '?' as usize - ' ' as usize }];
+        let left = x + i as u32 * ADVANCE;
+
+        for (row, bits) in (0..).zip(glyph) {
+            for column in (0..GLYPH_WIDTH).filter(|column| bits & (1 << (GLYPH_WIDTH - 1 - column)) != 0) {
+                fill(image, left + column * SCALE, y + row * SCALE, SCALE, SCALE, colour);
+            }
+        }
+    }
+}
+
+/// Draws `party_config` on a card: the spread and average on top, then each member with a badge of their job in
+/// its role's colour, their job and level. Job names are in English, as the font only covers ASCII.
+fn render(party: &[PlayerCharacter], party_config: &PartyConfig, scoring: &Scoring) -> RgbImage {
+    let heading = format!(
+        "{}: {}   Lv Avg: {}",
+        scoring.spread_label(),
+        scoring.metric.format(party_config.spread),
+        party_config.avg
+    );
+    let rows: Vec<_> = party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| {
+            let class_job = &character.class_jobs[job_index];
+            (class_job, character.display_name(), format!("{} Lv {}", class_job.name(), class_job.level))
+        })
+        .collect();
+
+    let name_width = rows.iter().map(|(_, name, _)| text_width(name)).max().unwrap_or_default();
+    let job_width = rows.iter().map(|(_, _, job)| text_width(job)).max().unwrap_or_default();
+    let row_width = BADGE_WIDTH + ADVANCE + name_width + 2 * ADVANCE + job_width;
+    let width = 2 * PADDING + row_width.max(text_width(&heading));
+    let height = 2 * PADDING + LINE_HEIGHT * (rows.len() as u32 + 1);
+
+    let mut image = RgbImage::from_pixel(width, height, BACKGROUND);
+    let text_offset = (LINE_HEIGHT - GLYPH_HEIGHT * SCALE) / 2;
+    draw_text(&mut image, PADDING, PADDING + text_offset, &heading, TEXT);
+
+    for (line, (class_job, name, job)) in (1..).zip(&rows) {
+        let top = PADDING + line * LINE_HEIGHT;
+        let badge_top = top + (LINE_HEIGHT - BADGE_HEIGHT) / 2;
+        fill(&mut image, PADDING, badge_top, BADGE_WIDTH, BADGE_HEIGHT, role_colour(class_job.role()));
+        let abbreviation = class_job.abbreviation();
+        let badge_text_left = PADDING + (BADGE_WIDTH - text_width(abbreviation) + SCALE) / 2;
+        draw_text(&mut image, badge_text_left, top + text_offset, abbreviation, TEXT);
+
+        let name_left = PADDING + BADGE_WIDTH + ADVANCE;
+        draw_text(&mut image, name_left, top + text_offset, name, TEXT);
+        draw_text(&mut image, name_left + name_width + 2 * ADVANCE, top + text_offset, job, MUTED);
+    }
+
+    image
+}
+
+/// Saves `party_config` drawn as a card to `path` as a PNG.
+pub fn png(path: &Path, party: &[PlayerCharacter], party_config: &PartyConfig, scoring: &Scoring) -> ImageResult<()> {
+    render(party, party_config, scoring).save_with_format(path, image::ImageFormat::Png)
+}
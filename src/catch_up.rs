@@ -0,0 +1,104 @@
+use crate::duty::{self, DeepDungeon, Duty, FloorSet};
+use crate::job;
+use crate::optimizer::PartyConfig;
+use crate::xivapi::PlayerCharacter;
+
+/// How many levels a member must trail everyone else by before they are worth a catch-up plan.
+pub const CATCH_UP_GAP: u8 = 5;
+
+/// A stretch of levels a member can level through alone in the same content.
+#[derive(Debug)]
+pub struct Bracket {
+    pub from: u8,
+    pub to: u8,
+    /// EXP needed to get from `from` to `to`, less any already earned towards the next level.
+    pub exp: u64,
+    /// The highest dungeon that can be run with Duty Support, if any.
+    pub duty_support: Option<&'static Duty>,
+    /// Roughly how many runs of `duty_support` earn `exp`, if there is EXP data for it.
+    pub runs: Option<u64>,
+    pub deep_dungeon: Option<(&'static DeepDungeon, &'static FloorSet)>
+}
+
+/// How a member who trails the rest of the party can catch up before the next session.
+#[derive(Debug)]
+pub struct CatchUp {
+    /// Index into the party of the member who is behind.
+    pub member: usize,
+    /// Index into their jobs of the job to level.
+    pub job_index: usize,
+    /// The level of the next-lowest member, which the party is synced to once they catch up.
+    pub target: u8,
+    pub brackets: Vec<Bracket>
+}
+
+impl CatchUp {
+    /// Total EXP needed to reach `target`.
+    pub fn exp(&self) -> u64 {
+        self.brackets.iter().map(|bracket| bracket.exp).sum()
+    }
+}
+
+/// The member whose job is lowest by at least `CATCH_UP_GAP` levels in every one of `party_configs`, if there is one.
+pub fn straggler(party: &[PlayerCharacter], party_configs: &[PartyConfig]) -> Option<usize> {
+    let mut straggler = None;
+
+    for party_config in party_configs {
+        let levels: Vec<u8> = party.iter().zip(&party_config.index)
+            .map(|(character, &job_index)| character.class_jobs[job_index].level)
+            .collect();
+        let member = (0..levels.len()).min_by_key(|&member| levels[member])?;
+        let next_lowest = (0..levels.len()).filter(|&other| other != member).map(|other| levels[other]).min()?;
+
+        if next_lowest < levels[member].saturating_add(CATCH_UP_GAP) || straggler.is_some_and(|straggler| straggler != member) {
+            return None;
+        }
+        straggler = Some(member);
+    }
+
+    straggler
+}
+
+/// Plans how `member` can level their job in `party_config` alone until they reach the next-lowest member.
+pub fn plan(party: &[PlayerCharacter], party_config: &PartyConfig, member: usize) -> CatchUp {
+    let character = &party[member];
+    let job_index = party_config.index[member];
+    let class_job = &character.class_jobs[job_index];
+    let target = party.iter().zip(&party_config.index)
+        .enumerate()
+        .filter(|&(other, _)| other != member)
+        .map(|(_, (other, &other_job))| other.class_jobs[other_job].level)
+        .min()
+        .unwrap_or(class_job.level)
+        .min(character.level_cap());
+
+    let content = |level: u8| (duty::highest_duty_support(level, 1).pop(), duty::deep_dungeon(level));
+
+    let mut brackets: Vec<Bracket> = Vec::new();
+    for level in class_job.level..target {
+        let (duty_support, deep_dungeon) = content(level);
+        match brackets.last_mut() {
+            Some(bracket) if bracket.duty_support == duty_support && bracket.deep_dungeon == deep_dungeon => bracket.to = level + 1,
+            _ => brackets.push(Bracket { from: level, to: level + 1, exp: 0, duty_support, runs: None, deep_dungeon })
+        }
+    }
+
+    let highest_level = character.highest_level_with(class_job);
+    for bracket in &mut brackets {
+        bracket.exp = job::exp_to_reach(bracket.to) - job::exp_to_reach(bracket.from);
+        if bracket.from == class_job.level {
+            bracket.exp = bracket.exp.saturating_sub(class_job.exp_level.unwrap_or_default() as u64);
+        }
+
+        // The armoury bonus only lasts while the job is below the character's highest level.
+        let mut multiplier = character.exp_multiplier(class_job);
+        if character.has_armoury_bonus(class_job) && bracket.from >= highest_level {
+            multiplier -= 1.0;
+        }
+        bracket.runs = bracket.duty_support
+            .and_then(duty::completion_exp)
+            .map(|exp| (bracket.exp as f64 / (exp as f64 * multiplier)).ceil() as u64);
+    }
+
+    CatchUp { member, job_index, target, brackets }
+}
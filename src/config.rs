@@ -0,0 +1,239 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::buff::Buff;
+use crate::constraints::{Composition, Constraints, JobFilter, Lock, RolePreference};
+use crate::job::{Expansion, Role};
+use crate::optimizer::Scoring;
+use crate::xivapi::PlayerCharacter;
+use crate::{Error, Result};
+
+/// Version of the config file format this release understands, given by a file's `version`. Files without one are
+/// from before it was added, and count as version 1.
+pub const CONFIG_VERSION: u32 = 2;
+
+/// What brings a config file from each version to the next, starting from version 1. Older files are brought up to
+/// date as they are loaded, leaving the file itself as it was.
+static MIGRATIONS: &[fn(&mut toml::Table)] = &[
+    // Version 2 only added `version` itself.
+    |_| {}
+];
+
+/// Settings for a single member of the roster, keyed by character name.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct MemberConfig {
+    /// Roles this member is willing to play. Any role if unset.
+    pub roles: Option<Vec<Role>>,
+    /// Jobs this member should never be suggested.
+    pub exclude_jobs: Vec<String>,
+    /// If set, the only jobs this member should be suggested.
+    pub only_jobs: Vec<String>,
+    /// EXP buffs this member levels with, e.g. `["road-to-90", "rested"]`.
+    pub buffs: Vec<Buff>,
+    /// Whether this member plays on the free trial.
+    pub trial: bool,
+    /// The latest expansion this member owns, if not every one.
+    pub expansion: Option<Expansion>,
+    /// What to call this member in the output instead of their Lodestone name, e.g. `"Tank Dad"`.
+    pub nickname: Option<String>
+}
+
+/// A named party that plays together, e.g. `[party.tuesday-static]`, picked with `--preset`. Its settings are laid
+/// over the rest of the config file's.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Preset {
+    /// The party's characters, as they would be entered at the prompt, e.g. `"Alice One@Zalera"`.
+    pub characters: Vec<String>,
+    /// Server to search for characters without a world.
+    pub server: Option<String>,
+    pub members: BTreeMap<String, MemberConfig>,
+    /// The job each member is locked to, e.g. `{ Alice = "Warrior" }`.
+    pub locks: BTreeMap<String, String>,
+    pub min_level: Option<u8>,
+    pub max_level: Option<u8>
+}
+
+/// A named set of constraints and weights, e.g. `[profile.casual]`, picked with `--profile`. Anything it leaves out
+/// falls back to the defaults, and options given on the command line take precedence over it.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub composition: Option<Composition>,
+    pub weight_var: Option<f64>,
+    pub weight_avg: Option<f64>,
+    pub weight_bonus: Option<f64>,
+    pub weight_diversity: Option<f64>,
+    pub weight_duplicate: Option<f64>,
+    /// Added to the config file's own job weights.
+    pub job_weights: BTreeMap<String, f64>,
+    pub min_level: Option<u8>,
+    pub max_level: Option<u8>,
+    pub min_avg: Option<u32>,
+    pub max_avg: Option<u32>,
+    pub max_gap: Option<u8>,
+    /// Jobs nobody should be suggested.
+    pub exclude_jobs: Vec<String>,
+    pub unique_jobs: bool,
+    pub diverse_dps: bool
+}
+
+/// The user's config file, including the saved roster of party members.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub members: BTreeMap<String, MemberConfig>,
+    /// How much the group likes each job, e.g. `Dancer = 5` or `"Black Mage" = -10`, added to the scoring.
+    pub job_weights: BTreeMap<String, f64>,
+    /// Lowest level a job may be to be considered, unless overridden by `--min-level` or `--all-levels`.
+    pub min_level: Option<u8>,
+    /// Highest level a job may be to be considered, unless overridden by `--max-level`.
+    pub max_level: Option<u8>,
+    /// The job each member is locked to, on top of any `--lock`.
+    pub locks: BTreeMap<String, String>,
+    /// Duties the group never wants suggested, e.g. `["The Vault"]`.
+    pub avoid_duties: Vec<String>,
+    /// Named parties, by name.
+    #[serde(rename = "party")]
+    pub presets: BTreeMap<String, Preset>,
+    /// Named constraint profiles, by name.
+    #[serde(rename = "profile")]
+    pub profiles: BTreeMap<String, Profile>
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("xiv-levelling").join("config.toml"))
+    }
+
+    /// Loads the config file at `path`, treating a missing file as an empty config.
+    pub fn load(path: &Path) -> Result<Config> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Config::parse(&contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    /// Reads a config file's `contents`, migrating them from older versions of the format.
+    pub fn parse(contents: &str) -> Result<Config> {
+        let mut table: toml::Table = contents.parse()?;
+        let version = match table.remove("version") {
+            None => 1,
+            Some(value) => match value.as_integer().and_then(|version| u32::try_from(version).ok()) {
+                Some(version @ 1..=CONFIG_VERSION) => version,
+                _ => return Err(Error::ConfigVersion(value.to_string()))
+            }
+        };
+
+        for migrate in &MIGRATIONS[version as usize - 1..] {
+            migrate(&mut table);
+        }
+        Ok(toml::Value::Table(table).try_into()?)
+    }
+
+    /// This config with the preset called `name` laid over it, or `None` if there is no such preset.
+    pub fn with_preset(&self, name: &str) -> Option<Config> {
+        let preset = self.presets.get(name)?;
+
+        let mut config = self.clone();
+        config.members.extend(preset.members.clone());
+        config.locks.extend(preset.locks.clone());
+        config.min_level = preset.min_level.or(config.min_level);
+        config.max_level = preset.max_level.or(config.max_level);
+        Some(config)
+    }
+
+    /// Adds the roster's per-member settings and locks to `constraints`.
+    pub fn apply(&self, constraints: &mut Constraints) {
+        constraints.locks.extend(self.locks.iter().map(|(character, job)| Lock {
+            character: character.clone(),
+            job: job.clone()
+        }));
+
+        for (name, member) in &self.members {
+            if let Some(roles) = &member.roles {
+                constraints.roles.push(RolePreference {
+                    character: name.clone(),
+                    roles: roles.clone()
+                });
+            }
+
+            if !member.exclude_jobs.is_empty() || !member.only_jobs.is_empty() {
+                constraints.job_filters.push(JobFilter {
+                    character: name.clone(),
+                    exclude: member.exclude_jobs.clone(),
+                    only: member.only_jobs.clone()
+                });
+            }
+        }
+    }
+
+    /// Adds the group's job weights to `scoring`.
+    pub fn apply_scoring(&self, scoring: &mut Scoring) {
+        scoring.job_weights.extend(self.job_weights.iter().map(|(name, &weight)| (name.clone(), weight)));
+    }
+
+    /// Gives each member of `party` the buffs, free trial flag, expansion and nickname listed for them in the roster.
+    pub fn apply_members(&self, party: &mut [PlayerCharacter]) {
+        for (name, member) in &self.members {
+            for character in party.iter_mut().filter(|character| character.matches_name(name)) {
+                character.buffs.extend(&member.buffs);
+                character.trial |= member.trial;
+                character.expansion = member.expansion.or(character.expansion);
+                character.nickname = member.nickname.clone().or(character.nickname.take());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: &str = r#"
+min_level = 15
+job_weights = { Dancer = 5, "Black Mage" = -10 }
+
+[members.Alice]
+roles = ["tank", "healer"]
+exclude_jobs = ["Paladin"]
+
+[party.tuesday-static]
+characters = ["Alice One@Zalera"]
+"#;
+
+    #[test]
+    fn a_missing_version_is_version_1() {
+        let config = Config::parse(SETTINGS).unwrap();
+        assert_eq!(config.min_level, Some(15));
+        assert_eq!(config.members["Alice"].exclude_jobs, ["Paladin"]);
+        assert_eq!(config.presets["tuesday-static"].characters, ["Alice One@Zalera"]);
+    }
+
+    #[test]
+    fn version_1_parses_the_same_as_the_current_version() {
+        let unversioned = Config::parse(SETTINGS).unwrap();
+        let v1 = Config::parse(&format!("version = 1\n{}", SETTINGS)).unwrap();
+        let current = Config::parse(&format!("version = {}\n{}", CONFIG_VERSION, SETTINGS)).unwrap();
+
+        // Config isn't PartialEq, but printing it shows every setting.
+        assert_eq!(format!("{:?}", v1), format!("{:?}", current));
+        assert_eq!(format!("{:?}", unversioned), format!("{:?}", current));
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        for version in ["0", "3", "-1", "\"2\"", "2.0", "4294967297"] {
+            match Config::parse(&format!("version = {}\n{}", version, SETTINGS)) {
+                Err(Error::ConfigVersion(found)) => assert_eq!(found, version),
+                other => panic!("version = {} gave {:?}", version, other)
+            }
+        }
+    }
+}
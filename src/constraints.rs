@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::job::{self, Mode, Role};
+use crate::xivapi::{ClassJob, PlayerCharacter};
+
+/// Pins a member to a single job, e.g. `Alice=Warrior`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Lock {
+    pub character: String,
+    pub job: String
+}
+
+impl FromStr for Lock {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((character, job)) if !character.trim().is_empty() && !job.trim().is_empty() => Ok(Lock {
+                character: character.trim().to_owned(),
+                job: job.trim().to_owned()
+            }),
+            _ => Err(format!("invalid lock {} (expected Name=Job)", s))
+        }
+    }
+}
+
+/// The roles a member is willing to play.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RolePreference {
+    pub character: String,
+    pub roles: Vec<Role>
+}
+
+/// Jobs a member should never be suggested, or the only jobs they should be suggested.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct JobFilter {
+    pub character: String,
+    pub exclude: Vec<String>,
+    /// If non-empty, the member may only play these jobs.
+    pub only: Vec<String>
+}
+
+impl JobFilter {
+    fn allows(&self, class_job: &ClassJob) -> bool {
+        !self.exclude.iter().any(|name| class_job.job.matches(name))
+            && (self.only.is_empty() || self.only.iter().any(|name| class_job.job.matches(name)))
+    }
+}
+
+/// Which role compositions a configuration may have.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Composition {
+    /// One tank and one healer, as the Duty Finder requires.
+    #[default]
+    Standard,
+    /// Any mix of roles, for unsynced or "any composition" content.
+    Any
+}
+
+impl FromStr for Composition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Composition::Standard),
+            "any" => Ok(Composition::Any),
+            _ => Err(format!("unknown composition {} (expected standard or any)", s))
+        }
+    }
+}
+
+/// Restrictions on which jobs each member may be assigned.
+///
+/// Sessions from older versions may be missing newer fields, which take their defaults.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Constraints {
+    pub locks: Vec<Lock>,
+    pub roles: Vec<RolePreference>,
+    pub job_filters: Vec<JobFilter>,
+    /// Lowest level a job may be to be considered.
+    pub min_level: Option<u8>,
+    /// Highest level a job may be to be considered.
+    pub max_level: Option<u8>,
+    /// Lowest average level a configuration may have.
+    pub min_avg: Option<u32>,
+    /// Highest average level a configuration may have.
+    pub max_avg: Option<u32>,
+    /// Most the highest and lowest levels in a configuration may differ by.
+    pub max_gap: Option<u8>,
+    /// Only consider one of each member's jobs that share a role, level and EXP, since swapping between them
+    /// produces an equivalent configuration.
+    pub dedup: bool,
+    pub mode: Mode,
+    /// Whether members may be assigned Blue Mage.
+    pub blue_mage: bool,
+    /// Whether missing roles will be filled by Duty Support NPCs, so a configuration only needs at most one tank,
+    /// one healer and two DPS.
+    pub duty_support: bool,
+    /// Whether a party of two or three will queue through the Duty Finder, which fills the missing slots with other
+    /// players, so a configuration only needs at most one tank, one healer and two DPS.
+    pub partial: bool,
+    pub composition: Composition,
+    /// Whether every DPS in a configuration must be a different sub-role: melee, physical ranged or caster.
+    pub diverse_dps: bool,
+    /// Whether no two members may play the same job in a configuration.
+    pub unique_jobs: bool,
+    /// Jobs nobody may be assigned.
+    pub excluded_jobs: Vec<String>
+}
+
+impl Constraints {
+    /// Whether `character` may play `class_job` in a configuration.
+    pub fn allows(&self, character: &PlayerCharacter, class_job: &ClassJob) -> bool {
+        let locked = self.locks.iter()
+            .filter(|lock| character.matches_name(&lock.character))
+            .all(|lock| class_job.job.matches(&lock.job));
+
+        let preferred = self.roles.iter()
+            .filter(|preference| character.matches_name(&preference.character))
+            .all(|preference| class_job.role().is_some_and(|role| preference.roles.contains(&role)));
+
+        let filtered = self.job_filters.iter()
+            .filter(|filter| character.matches_name(&filter.character))
+            .all(|filter| filter.allows(class_job));
+
+        let excluded = self.excluded_jobs.iter().any(|name| class_job.job.matches(name));
+
+        let in_band = self.min_level.is_none_or(|min_level| class_job.level >= min_level)
+            && self.max_level.is_none_or(|max_level| class_job.level <= max_level);
+
+        let blue_mage = self.blue_mage || !class_job.job.is_limited();
+        let trial = !character.trial || class_job.job.in_free_trial();
+
+        locked
+            && preferred
+            && filtered
+            && !excluded
+            && in_band
+            && blue_mage
+            && trial
+            && character.owns(class_job)
+            && !character.is_walled(class_job)
+    }
+
+    /// Whether configurations are limited to one tank and one healer.
+    pub fn requires_roles(&self) -> bool {
+        self.mode.requires_roles() && self.composition == Composition::Standard
+    }
+
+    /// Whether roles the party doesn't fill are filled by someone else, Duty Support NPCs or the Duty Finder.
+    pub fn fills_roles(&self) -> bool {
+        self.duty_support || self.partial
+    }
+
+    /// Whether a configuration with the given average level is within the requested band.
+    pub fn allows_average(&self, avg: u32) -> bool {
+        self.min_avg.is_none_or(|min_avg| avg >= min_avg) && self.max_avg.is_none_or(|max_avg| avg <= max_avg)
+    }
+
+    /// Whether `levels` are close enough together for a configuration, if there is a maximum gap.
+    pub fn allows_gap(&self, levels: impl IntoIterator<Item = u8>) -> bool {
+        let Some(max_gap) = self.max_gap else {
+            return true;
+        };
+        let (lowest, highest) = levels.into_iter().fold((u8::MAX, u8::MIN), |(lowest, highest), level| {
+            (lowest.min(level), highest.max(level))
+        });
+        highest.saturating_sub(lowest) <= max_gap
+    }
+
+    /// Whether the level bands leave anything to find, i.e. neither minimum is above its maximum.
+    pub fn is_satisfiable(&self) -> bool {
+        let levels = match (self.min_level, self.max_level) {
+            (Some(min_level), Some(max_level)) => min_level <= max_level,
+            _ => true
+        };
+        let avgs = match (self.min_avg, self.max_avg) {
+            (Some(min_avg), Some(max_avg)) => min_avg <= max_avg,
+            _ => true
+        };
+
+        levels && avgs
+    }
+
+    /// Whether any role preference applies to `character`.
+    pub fn has_role_preference(&self, character: &PlayerCharacter) -> bool {
+        self.roles.iter().any(|preference| character.matches_name(&preference.character))
+    }
+
+    /// Describes any constraint that can't apply to the party, e.g. a lock naming a job nobody has.
+    pub fn warnings(&self, party: &[PlayerCharacter]) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        for lock in &self.locks {
+            match party.iter().find(|character| character.matches_name(&lock.character)) {
+                None => warnings.push(format!("Nobody in the party is called {}!", lock.character)),
+                Some(character) if !character.class_jobs.iter().any(|class_job| class_job.job.matches(&lock.job)) => {
+                    warnings.push(format!("{} does not have a job called {}!", character.name, lock.job))
+                },
+                Some(_) => {}
+            }
+        }
+
+        for name in &self.excluded_jobs {
+            if !job::JOBS.iter().any(|info| info.matches(name)) {
+                warnings.push(format!("There is no job called {} (in the excluded jobs)!", name));
+            }
+        }
+
+        for filter in &self.job_filters {
+            for name in filter.exclude.iter().chain(&filter.only) {
+                if !job::JOBS.iter().any(|info| info.matches(name)) {
+                    warnings.push(format!("There is no job called {} (in the job list for {})!", name, filter.character));
+                }
+            }
+        }
+
+        warnings
+    }
+}
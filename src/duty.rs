@@ -0,0 +1,405 @@
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DutyKind {
+    Dungeon,
+    Trial
+}
+
+/// A duty that can be queued for in the Duty Finder.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Duty {
+    pub name: &'static str,
+    pub kind: DutyKind,
+    /// Level required to enter.
+    pub level: u8,
+    /// Level that members above it are synced down to.
+    pub sync: u8,
+    /// Whether the party can be filled out with NPCs through Duty Support.
+    pub duty_support: bool
+}
+
+impl Duty {
+    /// The level a party whose lowest member is `level` actually plays this duty at.
+    pub fn synced_level(&self, level: u8) -> u8 {
+        level.min(self.sync)
+    }
+}
+
+const fn dungeon(name: &'static str, level: u8, sync: u8) -> Duty {
+    Duty { name, kind: DutyKind::Dungeon, level, sync, duty_support: false }
+}
+
+/// A main scenario dungeon, which can be run with Duty Support.
+const fn support_dungeon(name: &'static str, level: u8, sync: u8) -> Duty {
+    Duty { name, kind: DutyKind::Dungeon, level, sync, duty_support: true }
+}
+
+const fn trial(name: &'static str, level: u8, sync: u8) -> Duty {
+    Duty { name, kind: DutyKind::Trial, level, sync, duty_support: false }
+}
+
+/// Every duty we know about, ordered by level.
+pub static DUTIES: &[Duty] = &[
+    support_dungeon("Sastasha", 15, 20),
+    support_dungeon("The Tam-Tara Deepcroft", 16, 20),
+    support_dungeon("Copperbell Mines", 17, 21),
+    dungeon("Halatali", 20, 24),
+    trial("The Bowl of Embers", 20, 20),
+    support_dungeon("The Thousand Maws of Toto-Rak", 24, 28),
+    support_dungeon("Haukke Manor", 28, 32),
+    support_dungeon("Brayflox's Longstop", 32, 36),
+    trial("The Navel", 34, 34),
+    support_dungeon("The Sunken Temple of Qarn", 35, 39),
+    dungeon("Cutter's Cry", 38, 42),
+    dungeon("The Stone Vigil", 41, 45),
+    support_dungeon("Dzemael Darkhold", 44, 48),
+    trial("The Howling Eye", 44, 44),
+    support_dungeon("The Aurum Vale", 47, 50),
+    dungeon("The Wanderer's Palace", 50, 50),
+    support_dungeon("Castrum Meridianum", 50, 50),
+    support_dungeon("The Praetorium", 50, 50),
+    dungeon("Amdapor Keep", 50, 50),
+    dungeon("Pharos Sirius", 50, 50),
+    dungeon("Copperbell Mines (Hard)", 50, 50),
+    dungeon("Haukke Manor (Hard)", 50, 50),
+    dungeon("The Lost City of Amdapor", 50, 50),
+    dungeon("Halatali (Hard)", 50, 50),
+    dungeon("Brayflox's Longstop (Hard)", 50, 50),
+    dungeon("Hullbreaker Isle", 50, 50),
+    dungeon("The Tam-Tara Deepcroft (Hard)", 50, 50),
+    dungeon("The Stone Vigil (Hard)", 50, 50),
+    dungeon("Snowcloak", 50, 50),
+    dungeon("Sastasha (Hard)", 50, 50),
+    dungeon("The Sunken Temple of Qarn (Hard)", 50, 50),
+    dungeon("The Keeper of the Lake", 50, 50),
+    dungeon("The Wanderer's Palace (Hard)", 50, 50),
+    dungeon("Amdapor Keep (Hard)", 50, 50),
+    trial("Cape Westwind", 50, 50),
+    trial("The Dragon's Neck", 50, 50),
+    trial("Thornmarch (Hard)", 50, 50),
+    trial("The Striking Tree (Hard)", 50, 50),
+    trial("Akh Afah Amphitheatre (Hard)", 50, 50),
+    trial("Urth's Fount", 50, 50),
+    support_dungeon("The Dusk Vigil", 51, 55),
+    support_dungeon("Sohm Al", 53, 57),
+    trial("Thok ast Thok (Hard)", 53, 53),
+    support_dungeon("The Aery", 55, 59),
+    support_dungeon("The Vault", 57, 60),
+    trial("The Limitless Blue (Hard)", 57, 57),
+    support_dungeon("The Great Gubal Library", 59, 60),
+    support_dungeon("The Aetherochemical Research Facility", 60, 60),
+    dungeon("Neverreap", 60, 60),
+    dungeon("The Fractal Continuum", 60, 60),
+    dungeon("Saint Mocianne's Arboretum", 60, 60),
+    dungeon("Pharos Sirius (Hard)", 60, 60),
+    support_dungeon("The Antitower", 60, 60),
+    dungeon("The Lost City of Amdapor (Hard)", 60, 60),
+    support_dungeon("Sohr Khai", 60, 60),
+    dungeon("Hullbreaker Isle (Hard)", 60, 60),
+    support_dungeon("Xelphatol", 60, 60),
+    dungeon("The Great Gubal Library (Hard)", 60, 60),
+    support_dungeon("Baelsar's Wall", 60, 60),
+    dungeon("Sohm Al (Hard)", 60, 60),
+    trial("The Singularity Reactor", 60, 60),
+    trial("The Final Steps of Faith", 60, 60),
+    trial("Containment Bay S1T7", 60, 60),
+    trial("Containment Bay P1T6", 60, 60),
+    trial("Containment Bay Z1T9", 60, 60),
+    support_dungeon("The Sirensong Sea", 61, 65),
+    dungeon("Shisui of the Violet Tides", 63, 67),
+    trial("The Pool of Tribute", 63, 63),
+    support_dungeon("Bardam's Mettle", 65, 69),
+    trial("Emanation", 65, 65),
+    support_dungeon("Doma Castle", 67, 70),
+    support_dungeon("Castrum Abania", 69, 70),
+    support_dungeon("Ala Mhigo", 70, 70),
+    dungeon("Kugane Castle", 70, 70),
+    dungeon("The Temple of the Fist", 70, 70),
+    dungeon("The Drowned City of Skalla", 70, 70),
+    dungeon("Hells' Lid", 70, 70),
+    dungeon("The Fractal Continuum (Hard)", 70, 70),
+    dungeon("The Swallow's Compass", 70, 70),
+    dungeon("The Burn", 70, 70),
+    dungeon("Saint Mocianne's Arboretum (Hard)", 70, 70),
+    support_dungeon("The Ghimlyt Dark", 70, 70),
+    trial("The Royal Menagerie", 70, 70),
+    trial("Castrum Fluminis", 70, 70),
+    trial("The Great Hunt", 70, 70),
+    trial("The Jade Stoa", 70, 70),
+    trial("Hells' Kier", 70, 70),
+    trial("The Wreath of Snakes", 70, 70),
+    support_dungeon("Holminster Switch", 71, 75),
+    support_dungeon("Dohn Mheg", 73, 77),
+    trial("The Dancing Plague", 73, 73),
+    support_dungeon("The Qitana Ravel", 75, 79),
+    trial("The Crown of the Immaculate", 75, 75),
+    support_dungeon("Malikah's Well", 77, 80),
+    support_dungeon("Mt. Gulg", 79, 80),
+    trial("The Dying Gasp", 79, 79),
+    support_dungeon("Amaurot", 80, 80),
+    dungeon("The Twinning", 80, 80),
+    dungeon("Akadaemia Anyder", 80, 80),
+    support_dungeon("The Grand Cosmos", 80, 80),
+    support_dungeon("Anamnesis Anyder", 80, 80),
+    support_dungeon("The Heroes' Gauntlet", 80, 80),
+    support_dungeon("Matoya's Relict", 80, 80),
+    support_dungeon("Paglth'an", 80, 80),
+    trial("Cinder Drift", 80, 80),
+    trial("The Seat of Sacrifice", 80, 80),
+    trial("Castrum Marinum", 80, 80),
+    trial("The Cloud Deck", 80, 80),
+    support_dungeon("The Tower of Zot", 81, 85),
+    support_dungeon("The Tower of Babil", 83, 87),
+    trial("The Dark Inside", 83, 83),
+    support_dungeon("Vanaspati", 85, 89),
+    support_dungeon("Ktisis Hyperboreia", 87, 90),
+    support_dungeon("The Aitiascope", 89, 90),
+    trial("The Mothercrystal", 89, 89),
+    support_dungeon("The Dead Ends", 90, 90),
+    dungeon("Smileton", 90, 90),
+    dungeon("The Stigma Dreamscape", 90, 90),
+    dungeon("Alzadaal's Legacy", 90, 90),
+    support_dungeon("The Fell Court of Troia", 90, 90),
+    support_dungeon("Lapis Manalis", 90, 90),
+    support_dungeon("The Aetherfont", 90, 90),
+    support_dungeon("The Lunar Subterrane", 90, 90),
+    trial("The Final Day", 90, 90),
+    trial("Storm's Crown", 90, 90),
+    trial("Mount Ordeals", 90, 90),
+    trial("The Voidcast Dais", 90, 90),
+    trial("The Abyssal Fracture", 90, 90)
+];
+
+/// The `count` highest-level duties of `kind` that a party whose lowest member is `level` can enter, highest first.
+pub fn highest_available(level: u8, kind: DutyKind, count: usize) -> Vec<&'static Duty> {
+    DUTIES.iter().rev()
+        .filter(|duty| duty.kind == kind && duty.level <= level)
+        .take(count)
+        .collect()
+}
+
+/// Like `highest_available`, but only dungeons that support Duty Support.
+pub fn highest_duty_support(level: u8, count: usize) -> Vec<&'static Duty> {
+    DUTIES.iter().rev()
+        .filter(|duty| duty.duty_support && duty.level <= level)
+        .take(count)
+        .collect()
+}
+
+/// The duty called `name`, ignoring case.
+pub fn find(name: &str) -> Option<&'static Duty> {
+    DUTIES.iter().find(|duty| duty.name.eq_ignore_ascii_case(name))
+}
+
+/// Approximate EXP for completing a dungeon, by the dungeon's entry level. Dungeons between entries use the one below.
+static DUNGEON_EXP: &[(u8, u32)] = &[
+    (15, 9000),
+    (17, 10500),
+    (20, 13000),
+    (24, 19000),
+    (28, 25000),
+    (32, 33000),
+    (35, 40000),
+    (38, 47000),
+    (41, 56000),
+    (44, 66000),
+    (47, 76000),
+    (50, 300000),
+    (51, 310000),
+    (53, 340000),
+    (55, 370000),
+    (57, 400000),
+    (59, 440000),
+    (60, 480000),
+    (61, 700000),
+    (63, 760000),
+    (65, 820000),
+    (67, 880000),
+    (69, 950000),
+    (70, 1000000),
+    (71, 1500000),
+    (73, 1620000),
+    (75, 1750000),
+    (77, 1880000),
+    (79, 2020000),
+    (80, 2150000),
+    (81, 3200000),
+    (83, 3450000),
+    (85, 3700000),
+    (87, 3950000),
+    (89, 4250000),
+    (90, 4500000)
+];
+
+/// Approximate EXP each member earns from completing `duty`, if it is a dungeon.
+pub fn completion_exp(duty: &Duty) -> Option<u32> {
+    if duty.kind != DutyKind::Dungeon {
+        return None;
+    }
+
+    DUNGEON_EXP.iter().rev()
+        .find(|&&(level, _)| level <= duty.level)
+        .map(|&(_, exp)| exp)
+}
+
+/// A daily Duty Roulette and the level every member must be to queue for it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Roulette {
+    pub name: &'static str,
+    pub level: u8
+}
+
+const fn roulette(name: &'static str, level: u8) -> Roulette {
+    Roulette { name, level }
+}
+
+pub static ROULETTES: &[Roulette] = &[
+    roulette("Leveling", 16),
+    roulette("Level 50/60/70/80 Dungeons", 50),
+    roulette("Trials", 50),
+    roulette("Alliance Raids", 50),
+    roulette("Main Scenario", 50),
+    roulette("Level 90 Dungeons", 90),
+    roulette("Expert", 90)
+];
+
+/// The roulettes a party whose lowest member is `level` can queue for.
+pub fn available_roulettes(level: u8) -> Vec<&'static Roulette> {
+    ROULETTES.iter().filter(|roulette| roulette.level <= level).collect()
+}
+
+/// Names of the dungeons and roulettes that open up when a party's lowest member goes from `from` to `to`.
+pub fn unlocked_between(from: u8, to: u8) -> Vec<&'static str> {
+    let dungeons = DUTIES.iter()
+        .filter(|duty| duty.kind == DutyKind::Dungeon && duty.level > from && duty.level <= to)
+        .map(|duty| duty.name);
+    let roulettes = ROULETTES.iter()
+        .filter(|roulette| roulette.level > from && roulette.level <= to)
+        .map(|roulette| roulette.name);
+
+    dungeons.chain(roulettes).collect()
+}
+
+/// A set of floors a party can start a deep dungeon run from.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FloorSet {
+    pub floors: &'static str,
+    /// Lowest party level this set is worth starting at.
+    pub level: u8,
+    /// What the party needs to have done before they can start here, if anything.
+    pub requirement: Option<&'static str>
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DeepDungeon {
+    pub name: &'static str,
+    pub min_level: u8,
+    pub max_level: u8,
+    /// Entry points, ordered by level.
+    pub floor_sets: &'static [FloorSet]
+}
+
+pub static DEEP_DUNGEONS: &[DeepDungeon] = &[
+    DeepDungeon {
+        name: "Palace of the Dead",
+        min_level: 1,
+        max_level: 60,
+        floor_sets: &[
+            FloorSet { floors: "1-10", level: 1, requirement: None },
+            FloorSet { floors: "51-60", level: 50, requirement: Some("a save file that has cleared floor 50") }
+        ]
+    },
+    DeepDungeon {
+        name: "Heaven-on-High",
+        min_level: 61,
+        max_level: 70,
+        floor_sets: &[
+            FloorSet { floors: "1-10", level: 61, requirement: None },
+            FloorSet { floors: "21-30", level: 61, requirement: Some("a save file that has cleared floor 20") }
+        ]
+    },
+    DeepDungeon {
+        name: "Eureka Orthos",
+        min_level: 81,
+        max_level: 90,
+        floor_sets: &[
+            FloorSet { floors: "1-10", level: 81, requirement: None },
+            FloorSet { floors: "21-30", level: 81, requirement: Some("a save file that has cleared floor 20") }
+        ]
+    }
+];
+
+/// The deep dungeon a party whose lowest member is `level` can level in, with the best floor set to start from.
+pub fn deep_dungeon(level: u8) -> Option<(&'static DeepDungeon, &'static FloorSet)> {
+    let deep_dungeon = DEEP_DUNGEONS.iter().find(|deep_dungeon| deep_dungeon.min_level <= level && level <= deep_dungeon.max_level)?;
+    let floor_set = deep_dungeon.floor_sets.iter().rev().find(|floor_set| floor_set.level <= level)?;
+
+    Some((deep_dungeon, floor_set))
+}
+
+/// A field operation: an instanced zone where a party can level outside the Duty Finder.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FieldOperation {
+    pub name: &'static str,
+    /// Lowest level every member must be to enter, and to earn worthwhile EXP.
+    pub min_level: u8,
+    /// Highest level it is still worth levelling in.
+    pub max_level: u8,
+    /// What the party needs to have done before they can enter.
+    pub requirement: &'static str
+}
+
+pub static FIELD_OPERATIONS: &[FieldOperation] = &[
+    FieldOperation {
+        name: "Eureka",
+        min_level: 70,
+        max_level: 79,
+        requirement: "\"And We Shall Call It Eureka\", after the Stormblood main scenario"
+    },
+    FieldOperation {
+        name: "The Bozjan Southern Front",
+        min_level: 80,
+        max_level: 89,
+        requirement: "\"Where Eagles Nest\", from the Save the Queen quests"
+    }
+];
+
+/// The field operations a party whose lowest member is `level` can level in.
+pub fn field_operations(level: u8) -> Vec<&'static FieldOperation> {
+    FIELD_OPERATIONS.iter().filter(|field_operation| field_operation.min_level <= level && level <= field_operation.max_level).collect()
+}
+
+/// An open world zone whose FATEs a party can follow around as a train.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FateZone {
+    pub name: &'static str,
+    pub min_level: u8,
+    pub max_level: u8
+}
+
+const fn fate_zone(name: &'static str, min_level: u8, max_level: u8) -> FateZone {
+    FateZone { name, min_level, max_level }
+}
+
+/// The zones usually busiest with FATE trains for each stretch of levels, ordered by level.
+pub static FATE_ZONES: &[FateZone] = &[
+    fate_zone("Eastern La Noscea", 30, 34),
+    fate_zone("Coerthas Central Highlands", 35, 44),
+    fate_zone("Northern Thanalan", 45, 49),
+    fate_zone("Coerthas Western Highlands", 50, 53),
+    fate_zone("The Churning Mists", 54, 56),
+    fate_zone("The Dravanian Hinterlands", 57, 59),
+    fate_zone("The Fringes", 60, 63),
+    fate_zone("Yanxia", 64, 66),
+    fate_zone("The Azim Steppe", 67, 69),
+    fate_zone("Lakeland", 70, 72),
+    fate_zone("Il Mheg", 73, 75),
+    fate_zone("The Rak'tika Greatwood", 76, 79),
+    fate_zone("Labyrinthos", 80, 82),
+    fate_zone("Garlemald", 83, 85),
+    fate_zone("Elpis", 86, 89)
+];
+
+/// The zone a party synced to `level` would run FATE trains in, if any.
+pub fn fate_zone_for(level: u8) -> Option<&'static FateZone> {
+    FATE_ZONES.iter().find(|zone| zone.min_level <= level && level <= zone.max_level)
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum Error {
+    #[cfg(feature = "network")]
+    Http(reqwest::Error),
+    /// A request took too long, which is worth trying again.
+    #[cfg(feature = "network")]
+    Timeout(reqwest::Error),
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// A response from XIVAPI didn't have the expected structure at this JSON path, e.g. `Character.ClassJobs[3]`.
+    Schema(String, serde_json::Error),
+    Config(toml::de::Error),
+    /// The config file's `version` is one this release doesn't understand, most likely from a newer release.
+    ConfigVersion(String),
+    /// A Lodestone page didn't have the expected structure.
+    Scrape(String),
+    /// A request was needed for this URL, but nothing may be requested offline.
+    Offline(String),
+    /// The API in use has no way of serving this request.
+    Unsupported(&'static str),
+    #[cfg(feature = "history")]
+    History(rusqlite::Error),
+    /// A score script couldn't be compiled.
+    #[cfg(feature = "script")]
+    Script(String)
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Whether the same request might succeed if it is made again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            #[cfg(feature = "network")]
+            Error::Timeout(_) => true,
+            _ => false
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "network")]
+            Error::Http(e) => write!(f, "request to XIVAPI failed: {}", e),
+            #[cfg(feature = "network")]
+            Error::Timeout(e) => write!(f, "request timed out, so the server may be busy (try again): {}", e),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Json(e) => write!(f, "unexpected response from XIVAPI: {}", e),
+            Error::Schema(path, e) => write!(
+                f,
+                "unexpected response from XIVAPI at {}: {} (XIVAPI may have changed, so check for a newer version)",
+                path,
+                e
+            ),
+            Error::Config(e) => write!(f, "invalid config file: {}", e),
+            Error::ConfigVersion(version) => write!(
+                f,
+                "the config file is version {}, but this release only understands up to version {} (check for a \
+                 newer version)",
+                version,
+                crate::config::CONFIG_VERSION
+            ),
+            Error::Scrape(e) => write!(f, "unexpected page from the Lodestone: {}", e),
+            Error::Offline(url) => write!(f, "{} isn't cached, and requests can't be made offline", url),
+            Error::Unsupported(e) => write!(f, "{}", e),
+            #[cfg(feature = "history")]
+            Error::History(e) => write!(f, "history database error: {}", e),
+            #[cfg(feature = "script")]
+            Error::Script(e) => write!(f, "invalid score script: {}", e)
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(feature = "network")]
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Error::Timeout(e)
+        } else {
+            Error::Http(e)
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::Config(e)
+    }
+}
+
+#[cfg(feature = "history")]
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::History(e)
+    }
+}
@@ -0,0 +1,75 @@
+use reqwest::StatusCode;
+
+/// Why a run failed, so scripts can tell without reading its output. Clap exits with 2 on invalid arguments and
+/// Ctrl+C with 130, so neither is used here.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Exit {
+    Failure,
+    /// No configuration satisfied the constraints.
+    NoConfigurations,
+    /// A character or free company couldn't be found.
+    NotFound,
+    /// XIVAPI or the Lodestone couldn't be reached.
+    Unreachable,
+    /// The constraints or party can never be satisfied, e.g. a minimum level above the maximum.
+    InvalidConstraints,
+    /// More than one character or free company had the name, and there was nobody to ask which was meant.
+    Ambiguous,
+    /// A server given for the party or a character doesn't exist.
+    ServerNotFound,
+    /// A character's jobs are hidden on the Lodestone, and there was nobody to enter them by hand.
+    ProfilePrivate,
+    /// XIVAPI or the Lodestone turned a request away for coming too often.
+    RateLimited,
+    /// Something needed wasn't cached, and `--offline` forbade fetching it.
+    NotCached,
+    /// The config file couldn't be read, or doesn't have the profile or party asked for.
+    InvalidConfig
+}
+
+impl Exit {
+    pub fn code(self) -> i32 {
+        match self {
+            Exit::Failure => 1,
+            Exit::NoConfigurations => 3,
+            Exit::NotFound => 4,
+            Exit::Unreachable => 5,
+            Exit::InvalidConstraints => 6,
+            Exit::Ambiguous => 7,
+            Exit::ServerNotFound => 8,
+            Exit::ProfilePrivate => 9,
+            Exit::RateLimited => 10,
+            Exit::NotCached => 11,
+            Exit::InvalidConfig => 12
+        }
+    }
+
+    /// A name for the failure that won't change between versions, for machine-readable output.
+    pub fn kind(self) -> &'static str {
+        match self {
+            Exit::Failure => "failure",
+            Exit::NoConfigurations => "no-configurations",
+            Exit::NotFound => "not-found",
+            Exit::Unreachable => "unreachable",
+            Exit::InvalidConstraints => "invalid-constraints",
+            Exit::Ambiguous => "ambiguous",
+            Exit::ServerNotFound => "server-not-found",
+            Exit::ProfilePrivate => "profile-private",
+            Exit::RateLimited => "rate-limited",
+            Exit::NotCached => "not-cached",
+            Exit::InvalidConfig => "invalid-config"
+        }
+    }
+}
+
+impl From<&xiv_levelling::Error> for Exit {
+    fn from(e: &xiv_levelling::Error) -> Self {
+        match e {
+            xiv_levelling::Error::Http(e) if e.status() == Some(StatusCode::TOO_MANY_REQUESTS) => Exit::RateLimited,
+            xiv_levelling::Error::Http(_) | xiv_levelling::Error::Timeout(_) => Exit::Unreachable,
+            xiv_levelling::Error::Offline(_) => Exit::NotCached,
+            xiv_levelling::Error::Config(_) => Exit::InvalidConfig,
+            _ => Exit::Failure
+        }
+    }
+}
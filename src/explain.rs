@@ -0,0 +1,110 @@
+use crate::constraints::Constraints;
+use crate::optimizer::{self, PartyConfig, Scoring};
+use crate::xivapi::PlayerCharacter;
+
+/// How far apart two members are, in whatever the scoring balances.
+#[derive(Debug)]
+pub struct Gap {
+    /// Indices into the party of the two members.
+    pub members: (usize, usize),
+    pub gap: f64
+}
+
+/// One statistic of a configuration and how much it is weighted by. Weights are signed so that each term adds
+/// `value * weight` to the score, where lower is better.
+#[derive(Debug)]
+pub struct Term {
+    pub name: String,
+    pub value: f64,
+    pub weight: f64
+}
+
+impl Term {
+    fn new(name: impl Into<String>, value: f64, weight: f64) -> Term {
+        Term { name: name.into(), value, weight }
+    }
+
+    pub fn contribution(&self) -> f64 {
+        self.value * self.weight
+    }
+}
+
+/// How a configuration's score was worked out.
+#[derive(Debug)]
+pub struct Explanation {
+    /// The gap between every pair of members, which the spread is worked out from.
+    pub gaps: Vec<Gap>,
+    /// Sum of the members' levels, which divided by the party size is the average level.
+    pub level_sum: u32,
+    pub average: f64,
+    /// Every term with a weight, and every job bonus, that makes up the score.
+    pub terms: Vec<Term>,
+    /// Whether a custom score replaced the sum of the terms.
+    pub custom: bool,
+    pub score: f64
+}
+
+/// Breaks `party_config`'s score down into the terms that it is the sum of.
+pub fn explain(party: &[PlayerCharacter], party_config: &PartyConfig, scoring: &Scoring, constraints: &Constraints) -> Explanation {
+    let members: Vec<_> = party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| (character, &character.class_jobs[job_index]))
+        .collect();
+    let class_jobs = || members.iter().map(|(_, class_job)| *class_job);
+    let values: Vec<f64> = class_jobs().map(|class_job| scoring.balance.value(class_job)).collect();
+
+    let mut gaps = Vec::new();
+    for (i, value1) in values.iter().enumerate() {
+        for (j, value2) in values.iter().enumerate().skip(i + 1) {
+            gaps.push(Gap { members: (i, j), gap: (value1 - value2).abs() });
+        }
+    }
+
+    let level_sum: u32 = class_jobs().map(|class_job| class_job.level as u32).sum();
+    let average = level_sum as f64 / members.len() as f64;
+    let (sub_role_count, _) = optimizer::sub_roles(class_jobs());
+
+    let mut terms = vec![
+        Term::new(scoring.spread_label(), party_config.spread, scoring.weight_var),
+        Term::new("Average level", average, -scoring.weight_avg),
+        Term::new("Armoury bonuses", party_config.bonus_count as f64, -scoring.weight_bonus),
+        Term::new("DPS sub-roles", sub_role_count as f64, -scoring.weight_diversity),
+        Term::new("Duplicate jobs", optimizer::duplicate_jobs(class_jobs()) as f64, scoring.weight_duplicate)
+    ];
+    if scoring.in_need.is_some() {
+        let in_need = scoring.in_need_bonus(&values, class_jobs()) != 0.0;
+        terms.push(Term::new("Furthest behind playing the in-need role", if in_need { 1.0 } else { 0.0 }, -scoring.weight_in_need));
+    }
+    if constraints.partial {
+        let missing = optimizer::missing_roles(class_jobs()) as f64;
+        terms.push(Term::new("Tanks and healers left to the Duty Finder", missing, scoring.weight_queue));
+    }
+    terms.retain(|term| term.weight != 0.0);
+
+    // Each member's job bonus only shows up for the jobs it applies to.
+    for (character, class_job) in &members {
+        let member_terms = vec![
+            Term::new(format!("{}'s weight for {}", character.display_name(), class_job.name()), scoring.job_weight(class_job.job), -1.0),
+            Term::new(format!("{} below the target level cap", character.display_name()), scoring.waste(class_job), 1.0),
+            Term::new(
+                format!("{}'s familiarity with {}", character.display_name(), class_job.name()),
+                character.familiarity(class_job),
+                -scoring.weight_familiarity
+            ),
+            Term::new(
+                format!("{}'s turn at {}", character.display_name(), class_job.role().map_or("no role", |role| role.name())),
+                character.fairness(class_job),
+                -scoring.weight_fairness
+            )
+        ];
+        terms.extend(member_terms.into_iter().filter(|term| term.contribution() != 0.0));
+    }
+
+    Explanation {
+        gaps,
+        level_sum,
+        average,
+        terms,
+        custom: scoring.custom_score.is_some(),
+        score: party_config.score
+    }
+}
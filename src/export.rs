@@ -0,0 +1,102 @@
+#[cfg(feature = "xlsx")]
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+#[cfg(feature = "xlsx")]
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(feature = "xlsx")]
+use xiv_levelling::optimizer::{PartyConfig, Scoring};
+#[cfg(feature = "xlsx")]
+use xiv_levelling::xivapi::PlayerCharacter;
+
+#[cfg(feature = "xlsx")]
+use crate::output;
+
+/// A format the results can be exported to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Export {
+    /// A spreadsheet of every member's levels and the ranked configurations.
+    #[cfg(feature = "xlsx")]
+    Xlsx,
+    /// An image of the best configuration, for sharing.
+    #[cfg(feature = "png")]
+    Png
+}
+
+impl Export {
+    /// Where to export to if no path is given.
+    pub fn default_path(self) -> &'static str {
+        match self {
+            #[cfg(feature = "xlsx")]
+            Export::Xlsx => "levelling.xlsx",
+            #[cfg(feature = "png")]
+            Export::Png => "levelling.png"
+        }
+    }
+}
+
+impl FromStr for Export {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            #[cfg(feature = "xlsx")]
+            "xlsx" => Ok(Export::Xlsx),
+            #[cfg(feature = "png")]
+            "png" => Ok(Export::Png),
+            _ => Err(format!("unknown export format {}", s))
+        }
+    }
+}
+
+/// Writes a workbook to `path` with a sheet of every member's level in every job, and another of `party_configs` in
+/// order.
+#[cfg(feature = "xlsx")]
+pub fn xlsx(path: &Path, party: &[PlayerCharacter], party_configs: &[PartyConfig], scoring: &Scoring) -> Result<(), XlsxError> {
+    let bold = Format::new().set_bold();
+    let mut workbook = Workbook::new();
+
+    let levels = workbook.add_worksheet().set_name("Levels")?;
+    levels.write_with_format(0, 0, "Job", &bold)?;
+    for (column, character) in (1..).zip(party) {
+        levels.write_with_format(0, column, character.display_name(), &bold)?;
+    }
+    for (row, (class_job, member_levels)) in (1..).zip(output::matrix(party)) {
+        levels.write(row, 0, class_job.display_name())?;
+        for (column, level) in (1..).zip(member_levels) {
+            if let Some(level) = level {
+                levels.write(row, column, level)?;
+            }
+        }
+    }
+    levels.autofit();
+
+    let configs = workbook.add_worksheet().set_name("Configurations")?;
+    let mut headings = vec!["Rank".to_owned()];
+    for character in party {
+        headings.push(character.name.clone());
+        headings.push("Lv".to_owned());
+    }
+    headings.extend([scoring.spread_label(), "Lv Avg".to_owned(), "Armoury Bonus".to_owned(), "Score".to_owned()]);
+    for (column, heading) in (0..).zip(&headings) {
+        configs.write_with_format(0, column, heading, &bold)?;
+    }
+
+    for (row, party_config) in (1..).zip(party_configs) {
+        configs.write(row, 0, row)?;
+        let mut column = 1;
+        for (character, &job_index) in party.iter().zip(&party_config.index) {
+            let class_job = &character.class_jobs[job_index];
+            configs.write(row, column, class_job.display_name())?;
+            configs.write(row, column + 1, class_job.level)?;
+            column += 2;
+        }
+        configs.write(row, column, party_config.spread)?;
+        configs.write(row, column + 1, party_config.avg)?;
+        configs.write(row, column + 2, party_config.bonus_count)?;
+        configs.write(row, column + 3, party_config.score)?;
+    }
+    configs.autofit();
+
+    workbook.save(path)
+}
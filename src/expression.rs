@@ -0,0 +1,267 @@
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+
+use crate::optimizer::{CustomScore, ScoreInput};
+
+/// A statistic of a configuration an expression can refer to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Variable {
+    /// The spread, by whichever metric is chosen.
+    Var,
+    Avg,
+    Min,
+    Max,
+    Range,
+    BonusCount,
+    /// The score the built-in weights give the configuration.
+    Score
+}
+
+impl Variable {
+    const NAMES: [(&'static str, Variable); 7] = [
+        ("var", Variable::Var),
+        ("avg", Variable::Avg),
+        ("min", Variable::Min),
+        ("max", Variable::Max),
+        ("range", Variable::Range),
+        ("bonus_count", Variable::BonusCount),
+        ("score", Variable::Score)
+    ];
+
+    fn value(self, input: &ScoreInput) -> f64 {
+        match self {
+            Variable::Var => input.spread,
+            Variable::Avg => input.avg,
+            Variable::Min => input.min as f64,
+            Variable::Max => input.max as f64,
+            Variable::Range => (input.max - input.min) as f64,
+            Variable::BonusCount => input.bonus_count as f64,
+            Variable::Score => input.score
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Number(f64),
+    Variable(Variable),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>)
+}
+
+impl Expr {
+    fn eval(&self, input: &ScoreInput) -> f64 {
+        match self {
+            Expr::Number(number) => *number,
+            Expr::Variable(variable) => variable.value(input),
+            Expr::Neg(expr) => -expr.eval(input),
+            Expr::Add(left, right) => left.eval(input) + right.eval(input),
+            Expr::Sub(left, right) => left.eval(input) - right.eval(input),
+            Expr::Mul(left, right) => left.eval(input) * right.eval(input),
+            Expr::Div(left, right) => left.eval(input) / right.eval(input)
+        }
+    }
+}
+
+/// An arithmetic expression scoring each configuration, e.g. `2*var - avg + 10*bonus_count`, where lower is better.
+/// It can use numbers, `+`, `-`, `*`, `/`, brackets and these variables:
+///
+/// - `var`: the spread of levels, by whichever metric is chosen;
+/// - `avg`, `min` and `max`: the average, lowest and highest levels;
+/// - `range`: the difference between the highest and lowest levels;
+/// - `bonus_count`: how many members earn the armoury bonus;
+/// - `score`: the score the built-in weights give the configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoreExpression(Expr);
+
+impl FromStr for ScoreExpression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { chars: s.chars().peekable() };
+        let expr = parser.sum()?;
+
+        match parser.next_token() {
+            None => Ok(ScoreExpression(expr)),
+            Some(c) => Err(format!("unexpected {} in score expression", c))
+        }
+    }
+}
+
+impl CustomScore for ScoreExpression {
+    fn score(&self, input: &ScoreInput) -> f64 {
+        let score = self.0.eval(input);
+        // Dividing by zero, say, shouldn't put a configuration ahead of the rest.
+        if score.is_nan() { f64::INFINITY } else { score }
+    }
+}
+
+/// A recursive descent parser over the characters of an expression.
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>
+}
+
+impl Parser<'_> {
+    /// The next character that isn't whitespace, without consuming it.
+    fn peek_token(&mut self) -> Option<char> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+
+    fn next_token(&mut self) -> Option<char> {
+        self.peek_token()?;
+        self.chars.next()
+    }
+
+    /// Terms added or subtracted together.
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut expr = self.product()?;
+
+        loop {
+            match self.peek_token() {
+                Some('+') => {
+                    self.chars.next();
+                    expr = Expr::Add(Box::new(expr), Box::new(self.product()?));
+                },
+                Some('-') => {
+                    self.chars.next();
+                    expr = Expr::Sub(Box::new(expr), Box::new(self.product()?));
+                },
+                _ => return Ok(expr)
+            }
+        }
+    }
+
+    /// Factors multiplied or divided together.
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut expr = self.factor()?;
+
+        loop {
+            match self.peek_token() {
+                Some('*') => {
+                    self.chars.next();
+                    expr = Expr::Mul(Box::new(expr), Box::new(self.factor()?));
+                },
+                Some('/') => {
+                    self.chars.next();
+                    expr = Expr::Div(Box::new(expr), Box::new(self.factor()?));
+                },
+                _ => return Ok(expr)
+            }
+        }
+    }
+
+    fn factor(&mut self) -> Result<Expr, String> {
+        match self.peek_token() {
+            Some('-') => {
+                self.chars.next();
+                Ok(Expr::Neg(Box::new(self.factor()?)))
+            },
+            Some('(') => {
+                self.chars.next();
+                let expr = self.sum()?;
+                match self.next_token() {
+                    Some(')') => Ok(expr),
+                    _ => Err("missing ) in score expression".to_owned())
+                }
+            },
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let number = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                number.parse().map(Expr::Number).map_err(|_| format!("invalid number {} in score expression", number))
+            },
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+                Variable::NAMES.iter()
+                    .find(|(variable_name, _)| *variable_name == name)
+                    .map(|&(_, variable)| Expr::Variable(variable))
+                    .ok_or_else(|| format!("unknown variable {} in score expression (expected var, avg, min, max, range, bonus_count or score)", name))
+            },
+            Some(c) => Err(format!("unexpected {} in score expression", c)),
+            None => Err("score expression ended early".to_owned())
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(c) = self.chars.next_if(|&c| predicate(c)) {
+            taken.push(c);
+        }
+        taken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scores `expression` for a configuration with these statistics.
+    fn score(expression: &str) -> f64 {
+        let input = ScoreInput {
+            members: Vec::new(),
+            spread: 2.0,
+            avg: 50.0,
+            min: 48,
+            max: 53,
+            bonus_count: 3,
+            score: 7.5
+        };
+        expression.parse::<ScoreExpression>().unwrap().score(&input)
+    }
+
+    fn error(expression: &str) -> String {
+        expression.parse::<ScoreExpression>().unwrap_err()
+    }
+
+    #[test]
+    fn products_bind_tighter_than_sums() {
+        assert_eq!(score("1 + 2 * 3"), 7.0);
+        assert_eq!(score("avg - var * bonus_count"), 44.0);
+        assert_eq!(score("12 / 3 - 1"), 3.0);
+        assert_eq!(score("10 - 4 - 3"), 3.0);
+        assert_eq!(score("24 / 4 / 2"), 3.0);
+    }
+
+    #[test]
+    fn unary_minus() {
+        assert_eq!(score("-var"), -2.0);
+        assert_eq!(score("--var"), 2.0);
+        assert_eq!(score("3 * -var"), -6.0);
+        assert_eq!(score("1 - -1"), 2.0);
+    }
+
+    #[test]
+    fn brackets() {
+        assert_eq!(score("(1 + 2) * 3"), 9.0);
+        assert_eq!(score("-(max - min)"), -5.0);
+        assert_eq!(score("((range))"), 5.0);
+        assert_eq!(error("(1 + 2"), "missing ) in score expression");
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(score("var + avg + min + max + range + bonus_count + score"), 168.5);
+    }
+
+    #[test]
+    fn unknown_variables_are_rejected() {
+        assert!(error("2 * spread").starts_with("unknown variable spread "));
+        assert!(error("Var").starts_with("unknown variable Var "));
+    }
+
+    #[test]
+    fn trailing_input_is_rejected() {
+        assert_eq!(error("var avg"), "unexpected a in score expression");
+        assert_eq!(error("1 + 2)"), "unexpected ) in score expression");
+        assert_eq!(error("var +"), "score expression ended early");
+        assert_eq!(error("1..2"), "invalid number 1..2 in score expression");
+        assert_eq!(error(""), "score expression ended early");
+    }
+
+    #[test]
+    fn nan_ranks_last() {
+        assert_eq!(score("0 / 0"), f64::INFINITY);
+    }
+}
@@ -0,0 +1,164 @@
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+use crate::cache::Cache;
+use crate::xivapi::PlayHistory;
+use crate::{Error, Result};
+
+const TOKEN_URL: &str = "https://www.fflogs.com/oauth/token";
+const API_URL: &str = "https://www.fflogs.com/api/v2/client";
+
+/// How many of a character's most recent reports to look through.
+const REPORTS: u32 = 25;
+const HISTORY_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The players in each of a character's recent reports, looked up by their Lodestone ID.
+const QUERY: &str = "query($id: Int!, $limit: Int!) {
+    characterData {
+        character(lodestoneID: $id) {
+            recentReports(limit: $limit) {
+                data {
+                    startTime
+                    masterData {
+                        actors(type: \"Player\") { name subType }
+                    }
+                }
+            }
+        }
+    }
+}";
+
+#[derive(Deserialize)]
+struct Token {
+    access_token: String
+}
+
+#[derive(Deserialize)]
+struct Response {
+    data: Data
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Data {
+    character_data: CharacterData
+}
+
+#[derive(Deserialize)]
+struct CharacterData {
+    /// Missing if the character has never been logged.
+    character: Option<Character>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Character {
+    recent_reports: Reports
+}
+
+#[derive(Deserialize)]
+struct Reports {
+    data: Vec<Report>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Report {
+    /// Milliseconds since the Unix epoch.
+    start_time: f64,
+    master_data: Option<MasterData>
+}
+
+#[derive(Deserialize)]
+struct MasterData {
+    actors: Vec<Actor>
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Actor {
+    name: String,
+    /// The job the player was on, e.g. "Paladin".
+    sub_type: String
+}
+
+/// Looks up which jobs characters have been logged playing, through the FFLogs API. It needs a client registered at
+/// https://www.fflogs.com/api/clients.
+pub struct FfLogs {
+    cache: Cache,
+    client: Client,
+    client_id: String,
+    client_secret: String
+}
+
+impl FfLogs {
+    pub fn new(cache: Cache, client: Client, client_id: String, client_secret: String) -> Self {
+        FfLogs { cache, client, client_id, client_secret }
+    }
+
+    fn token(&self) -> Result<String> {
+        let token: Token = self.client.post(TOKEN_URL)
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(&[("grant_type", "client_credentials")])
+            .send()?
+            .error_for_status()?
+            .json()?;
+
+        Ok(token.access_token)
+    }
+
+    /// The play history of the character with the Lodestone ID `id` and name `name`, which is empty if they have
+    /// never been logged.
+    pub fn play_history(&self, id: u32, name: &str) -> Result<PlayHistory> {
+        let key = format!("fflogs/character/{}", id);
+
+        let body = match self.cache.get(&key, HISTORY_MAX_AGE) {
+            Some(body) => body,
+            None if self.cache.is_offline() => return Err(Error::Offline(key)),
+            None => {
+                let body = self.client.post(API_URL)
+                    .bearer_auth(self.token()?)
+                    .json(&json!({ "query": QUERY, "variables": { "id": id, "limit": REPORTS } }))
+                    .send()?
+                    .error_for_status()?
+                    .text()?;
+                // The cache is only an optimisation, so failing to write to it is not fatal.
+                let _ = self.cache.put(&key, &body);
+                body
+            }
+        };
+
+        let response: Response = serde_json::from_str(&body)?;
+        let mut play_history = PlayHistory::default();
+        let Some(character) = response.data.character_data.character else {
+            return Ok(play_history);
+        };
+
+        for report in character.recent_reports.data {
+            let started = (report.start_time / 1000.0) as u64;
+            let actors = report.master_data.map(|master_data| master_data.actors).unwrap_or_default();
+            // Players on more than one job in a report are counted on each.
+            let mut jobs: Vec<String> = actors.into_iter()
+                .filter(|actor| actor.name.eq_ignore_ascii_case(name))
+                .map(|actor| actor.sub_type)
+                .collect();
+            jobs.sort();
+            jobs.dedup();
+
+            if jobs.is_empty() {
+                continue;
+            }
+
+            play_history.reports += 1;
+            for job in jobs {
+                let (reports, last_played) = play_history.jobs.entry(job).or_default();
+                *reports += 1;
+                *last_played = (*last_played).max(started);
+            }
+        }
+
+        Ok(play_history)
+    }
+}
@@ -0,0 +1,212 @@
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::optimizer::PartyConfig;
+use crate::projection;
+use crate::xivapi::PlayerCharacter;
+
+/// A way of writing out ranked configurations, chosen by name, e.g. with `--format`.
+pub trait OutputFormatter {
+    /// What the format is chosen by.
+    fn name(&self) -> &str;
+
+    /// Writes `party_configs` of `party`, best first.
+    fn write(&self, out: &mut dyn Write, party: &[PlayerCharacter], party_configs: &[PartyConfig]) -> io::Result<()>;
+}
+
+/// Formatters by name. New formats only need registering to be chosen, including those from outside this crate.
+pub struct Registry {
+    formatters: BTreeMap<String, Box<dyn OutputFormatter>>
+}
+
+impl Registry {
+    /// A registry with no formats.
+    pub fn empty() -> Self {
+        Registry { formatters: BTreeMap::new() }
+    }
+
+    /// Adds `formatter`, replacing any registered by the same name.
+    pub fn register(&mut self, formatter: Box<dyn OutputFormatter>) {
+        self.formatters.insert(formatter.name().to_owned(), formatter);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn OutputFormatter> {
+        self.formatters.get(name).map(|formatter| formatter.as_ref())
+    }
+
+    /// Names of every registered format, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.formatters.keys().map(String::as_str)
+    }
+}
+
+impl Default for Registry {
+    /// A registry with the formats built in: json, markdown and csv.
+    fn default() -> Self {
+        let mut registry = Registry::empty();
+        registry.register(Box::new(Json));
+        registry.register(Box::new(Markdown));
+        registry.register(Box::new(Csv));
+        registry
+    }
+}
+
+/// The name and level of the job each member of `party` plays in `party_config`.
+fn jobs<'a>(party: &'a [PlayerCharacter], party_config: &PartyConfig) -> Vec<(&'a str, u8)> {
+    party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| {
+            let class_job = &character.class_jobs[job_index];
+            (class_job.display_name(), class_job.level)
+        })
+        .collect()
+}
+
+/// A JSON array of the configurations.
+pub struct Json;
+
+impl OutputFormatter for Json {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn write(&self, out: &mut dyn Write, party: &[PlayerCharacter], party_configs: &[PartyConfig]) -> io::Result<()> {
+        let configurations: Vec<_> = party_configs.iter().enumerate()
+            .map(|(rank, party_config)| {
+                let members: Vec<_> = party.iter().zip(&party_config.index)
+                    .map(|(character, &job_index)| {
+                        let class_job = &character.class_jobs[job_index];
+                        json!({
+                            "name": character.name,
+                            "nickname": character.nickname,
+                            "job": class_job.display_name(),
+                            "level": class_job.level,
+                            "role": class_job.role()
+                        })
+                    })
+                    .collect();
+
+                json!({
+                    "rank": rank + 1,
+                    "members": members,
+                    "spread": party_config.spread,
+                    "avg": party_config.avg,
+                    "min_level": party_config.min_level,
+                    "exp_waste": projection::exp_waste(party, party_config),
+                    "score": party_config.score
+                })
+            })
+            .collect();
+
+        serde_json::to_writer_pretty(&mut *out, &configurations)?;
+        writeln!(out)
+    }
+}
+
+/// A Markdown table with a row for each configuration and a column for each member, e.g. for a Discord message or
+/// a wiki page.
+pub struct Markdown;
+
+impl Markdown {
+    /// `text` with anything that would end a table cell escaped.
+    fn cell(text: &str) -> String {
+        text.replace('|', "\\|")
+    }
+}
+
+impl OutputFormatter for Markdown {
+    fn name(&self) -> &str {
+        "markdown"
+    }
+
+    fn write(&self, out: &mut dyn Write, party: &[PlayerCharacter], party_configs: &[PartyConfig]) -> io::Result<()> {
+        let names: Vec<String> = party.iter().map(|character| Markdown::cell(character.display_name())).collect();
+        writeln!(out, "| Rank | {} | Avg | Spread | EXP Waste |", names.join(" | "))?;
+        writeln!(out, "|---:|{}---:|---:|---:|", ":---|".repeat(party.len()))?;
+
+        for (rank, party_config) in party_configs.iter().enumerate() {
+            let jobs: Vec<String> = jobs(party, party_config).into_iter()
+                .map(|(job, level)| format!("{} {}", job, level))
+                .collect();
+            let waste = projection::exp_waste(party, party_config).map(|waste| waste.to_string()).unwrap_or_default();
+            writeln!(
+                out,
+                "| {} | {} | {} | {:.1} | {} |",
+                rank + 1,
+                jobs.join(" | "),
+                party_config.avg,
+                party_config.spread,
+                waste
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Comma-separated values with a row for each configuration, for spreadsheets.
+pub struct Csv;
+
+impl Csv {
+    /// `text` quoted if it has anything that would otherwise split or end the field.
+    fn field(text: &str) -> String {
+        if text.contains([',', '"', '\n']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_owned()
+        }
+    }
+}
+
+impl OutputFormatter for Csv {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn write(&self, out: &mut dyn Write, party: &[PlayerCharacter], party_configs: &[PartyConfig]) -> io::Result<()> {
+        let mut header = vec!["rank".to_owned()];
+        for character in party {
+            header.push(Csv::field(&format!("{} job", character.display_name())));
+            header.push(Csv::field(&format!("{} level", character.display_name())));
+        }
+        header.extend(["avg", "min_level", "spread", "exp_waste", "score"].iter().map(|column| column.to_string()));
+        writeln!(out, "{}", header.join(","))?;
+
+        for (rank, party_config) in party_configs.iter().enumerate() {
+            let mut row = vec![(rank + 1).to_string()];
+            for (job, level) in jobs(party, party_config) {
+                row.push(Csv::field(job));
+                row.push(level.to_string());
+            }
+            row.push(party_config.avg.to_string());
+            row.push(party_config.min_level.to_string());
+            row.push(party_config.spread.to_string());
+            row.push(projection::exp_waste(party, party_config).map(|waste| waste.to_string()).unwrap_or_default());
+            row.push(party_config.score.to_string());
+            writeln!(out, "{}", row.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Posts the configurations as a Markdown table to a Discord-compatible webhook, rather than writing them out.
+#[cfg(feature = "network")]
+pub struct Webhook {
+    pub client: reqwest::blocking::Client,
+    pub url: String
+}
+
+#[cfg(feature = "network")]
+impl OutputFormatter for Webhook {
+    fn name(&self) -> &str {
+        "webhook"
+    }
+
+    fn write(&self, _out: &mut dyn Write, party: &[PlayerCharacter], party_configs: &[PartyConfig]) -> io::Result<()> {
+        let mut table = Vec::new();
+        Markdown.write(&mut table, party, party_configs)?;
+        // Discord doesn't render tables, but they still line up in a code block.
+        let content = format!("```\n{}```", String::from_utf8_lossy(&table));
+        crate::webhook::post(&self.client, &self.url, &content)
+            .map_err(|e| io::Error::other(e.to_string()))
+    }
+}
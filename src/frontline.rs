@@ -0,0 +1,20 @@
+use crate::constraints::Constraints;
+use crate::job;
+use crate::xivapi::PlayerCharacter;
+
+/// Level a job must be to queue for Frontline.
+pub const FRONTLINE_LEVEL: u8 = 30;
+
+/// The job `character` should take into the daily Frontline: their lowest-level job that can queue and still earns
+/// EXP. Frontline rewards the same EXP whatever the job's level, so it goes furthest on the job furthest behind.
+pub fn recommend(character: &PlayerCharacter, constraints: &Constraints) -> Option<usize> {
+    character.class_jobs.iter().enumerate()
+        .filter(|(_, class_job)| {
+            class_job.role().is_some()
+                && class_job.level >= FRONTLINE_LEVEL
+                && class_job.level < job::LEVEL_CAP
+                && constraints.allows(character, class_job)
+        })
+        .min_by_key(|(_, class_job)| (class_job.level, class_job.exp_level.unwrap_or_default()))
+        .map(|(job_index, _)| job_index)
+}
@@ -0,0 +1,235 @@
+use juniper::{graphql_object, EmptyMutation, EmptySubscription, FieldResult, GraphQLEnum, GraphQLInputObject, GraphQLObject, RootNode};
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use xiv_levelling::constraints::{Constraints, Lock};
+use xiv_levelling::job;
+use xiv_levelling::xivapi::XivApi;
+
+use crate::server::{self, ConfigurationResponse, ManualMember, MemberResponse, Metrics};
+
+/// Most configurations a query may page through, since all of them before the page have to be ranked too.
+const MAX_RANKED: usize = 1000;
+
+pub type Schema = RootNode<'static, Query, EmptyMutation<Context>, EmptySubscription<Context>>;
+
+/// What queries are resolved with, shared with the REST API.
+pub struct Context {
+    pub api: Arc<XivApi>,
+    pub metrics: Arc<Metrics>
+}
+
+impl juniper::Context for Context {}
+
+/// A job a member has, and its level.
+#[derive(GraphQLInputObject)]
+struct JobLevelInput {
+    /// Name or abbreviation of the job, e.g. Warrior or WHM.
+    job: String,
+    level: i32
+}
+
+/// A member given by their job levels rather than looked up on the Lodestone.
+#[derive(GraphQLInputObject)]
+struct MemberInput {
+    name: String,
+    jobs: Vec<JobLevelInput>
+}
+
+/// Who is in the party: characters looked up on the Lodestone, then members given by their job levels.
+#[derive(GraphQLInputObject)]
+struct PartyInput {
+    /// Server the characters are on, needed to look any up.
+    server: Option<String>,
+    characters: Option<Vec<String>>,
+    members: Option<Vec<MemberInput>>
+}
+
+/// Only consider the given job for a character.
+#[derive(GraphQLInputObject)]
+struct LockInput {
+    character: String,
+    job: String
+}
+
+#[derive(GraphQLInputObject)]
+struct ConstraintsInput {
+    locks: Option<Vec<LockInput>>,
+    /// Lowest level a job may be to be considered.
+    min_level: Option<i32>,
+    /// Highest level a job may be to be considered.
+    max_level: Option<i32>,
+    /// Lowest average level a configuration may have.
+    min_avg: Option<i32>,
+    /// Highest average level a configuration may have.
+    max_avg: Option<i32>,
+    /// Most the highest and lowest levels in a configuration may differ by.
+    max_gap: Option<i32>,
+    /// Jobs nobody may be assigned.
+    excluded_jobs: Option<Vec<String>>,
+    /// Whether members may be assigned Blue Mage.
+    blue_mage: Option<bool>,
+    /// Whether missing roles will be filled by Duty Support NPCs.
+    duty_support: Option<bool>,
+    /// Whether every DPS must be a different sub-role: melee, physical ranged or caster.
+    diverse_dps: Option<bool>,
+    /// Whether no two members may play the same job.
+    unique_jobs: Option<bool>
+}
+
+#[derive(GraphQLEnum)]
+enum Role {
+    Tank,
+    Healer,
+    Dps
+}
+
+impl From<job::Role> for Role {
+    fn from(role: job::Role) -> Self {
+        match role {
+            job::Role::Tank => Role::Tank,
+            job::Role::Healer => Role::Healer,
+            job::Role::Dps => Role::Dps
+        }
+    }
+}
+
+/// A member and the job they play in a configuration.
+#[derive(GraphQLObject)]
+struct Member {
+    name: String,
+    job: String,
+    level: i32,
+    role: Option<Role>,
+    /// Whether the member gets the Armoury Bonus for playing the job.
+    armoury_bonus: bool,
+    avatar: Option<String>,
+    portrait: Option<String>
+}
+
+impl From<MemberResponse> for Member {
+    fn from(member: MemberResponse) -> Self {
+        Member {
+            name: member.name,
+            job: member.job,
+            level: member.level.into(),
+            role: member.role.map(Role::from),
+            armoury_bonus: member.armoury_bonus,
+            avatar: member.avatar,
+            portrait: member.portrait
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+struct Configuration {
+    /// Where the configuration ranks, starting at 1 for the most balanced.
+    rank: i32,
+    spread: f64,
+    avg: i32,
+    min_level: i32,
+    members: Vec<Member>
+}
+
+impl From<ConfigurationResponse> for Configuration {
+    fn from(configuration: ConfigurationResponse) -> Self {
+        Configuration {
+            rank: configuration.rank as i32,
+            spread: configuration.spread,
+            avg: configuration.avg as i32,
+            min_level: configuration.min_level.into(),
+            members: configuration.members.into_iter().map(Member::from).collect()
+        }
+    }
+}
+
+/// Some of the configurations, in order of rank.
+#[derive(GraphQLObject)]
+struct ConfigurationPage {
+    configurations: Vec<Configuration>,
+    /// Whether there are more configurations after these.
+    has_next_page: bool
+}
+
+fn level(level: i32) -> Result<u8, String> {
+    u8::try_from(level).map_err(|_| format!("Invalid level {}!", level))
+}
+
+fn constraints(input: ConstraintsInput) -> Result<Constraints, String> {
+    let default = Constraints::default();
+    Ok(Constraints {
+        locks: input.locks.unwrap_or_default()
+            .into_iter()
+            .map(|lock| Lock { character: lock.character, job: lock.job })
+            .collect(),
+        min_level: input.min_level.map(level).transpose()?,
+        max_level: input.max_level.map(level).transpose()?,
+        min_avg: input.min_avg.map(level).transpose()?.map(u32::from),
+        max_avg: input.max_avg.map(level).transpose()?.map(u32::from),
+        max_gap: input.max_gap.map(level).transpose()?,
+        excluded_jobs: input.excluded_jobs.unwrap_or_default(),
+        blue_mage: input.blue_mage.unwrap_or(default.blue_mage),
+        duty_support: input.duty_support.unwrap_or(default.duty_support),
+        diverse_dps: input.diverse_dps.unwrap_or(default.diverse_dps),
+        unique_jobs: input.unique_jobs.unwrap_or(default.unique_jobs),
+        dedup: true,
+        ..default
+    })
+}
+
+pub struct Query;
+
+#[graphql_object(context = Context)]
+impl Query {
+    /// The most balanced configurations of the party, skipping the first `offset`.
+    fn configurations(
+        context: &Context,
+        party: PartyInput,
+        constraints: Option<ConstraintsInput>,
+        #[graphql(default = server::DEFAULT_TOP as i32)] first: i32,
+        #[graphql(default = 0)] offset: i32
+    ) -> FieldResult<ConfigurationPage> {
+        let first = usize::try_from(first).map_err(|_| "first can't be negative!")?.min(server::MAX_TOP);
+        let offset = usize::try_from(offset).map_err(|_| "offset can't be negative!")?;
+        if offset + first > MAX_RANKED {
+            return Err(format!("Only the best {} configurations can be paged through!", MAX_RANKED).into());
+        }
+
+        let members = party.members.unwrap_or_default()
+            .into_iter()
+            .map(|member| {
+                let jobs = member.jobs.into_iter()
+                    .map(|job| Ok((job.job, level(job.level)?)))
+                    .collect::<Result<_, String>>()?;
+                Ok(ManualMember { name: member.name, jobs })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let party = server::party(
+            context.api.as_ref(),
+            party.server.as_deref(),
+            &party.characters.unwrap_or_default(),
+            &members
+        )?;
+        let constraints = match constraints {
+            Some(input) => self::constraints(input)?,
+            None => Constraints { dedup: true, ..Constraints::default() }
+        };
+
+        // One more than asked for, to tell whether there is another page.
+        let party_configs = server::rank(&context.metrics, &party, &constraints, offset + first + 1);
+        let has_next_page = party_configs.len() > offset + first;
+        let page = &party_configs[offset.min(party_configs.len())..(offset + first).min(party_configs.len())];
+
+        Ok(ConfigurationPage {
+            configurations: server::configurations(&party, page, offset + 1)
+                .into_iter()
+                .map(Configuration::from)
+                .collect(),
+            has_next_page
+        })
+    }
+}
+
+pub fn schema() -> Schema {
+    Schema::new(Query, EmptyMutation::new(), EmptySubscription::new())
+}
@@ -0,0 +1,240 @@
+use rusqlite::{params, Connection};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::job::{self, JobInfo, Role};
+use crate::xivapi::{ClassJob, PlayerCharacter};
+use crate::Result;
+
+/// One job's level, as it was when a character was looked up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub character_id: u32,
+    pub character_name: String,
+    pub job: &'static JobInfo,
+    pub level: u8,
+    pub exp_level: Option<u32>,
+    pub recorded_at: SystemTime
+}
+
+/// Local database of every character the tool has looked up, for tracking progress over time.
+pub struct History {
+    connection: Connection
+}
+
+fn to_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64
+}
+
+impl History {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("xiv-levelling").join("history.sqlite"))
+    }
+
+    /// Opens the database at `path`, creating it if it doesn't exist yet.
+    pub fn open(path: &Path) -> Result<History> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let connection = Connection::open(path)?;
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS snapshots (
+                character_id INTEGER NOT NULL,
+                character_name TEXT NOT NULL,
+                class_id INTEGER NOT NULL,
+                job_id INTEGER NOT NULL,
+                level INTEGER NOT NULL,
+                exp_level INTEGER,
+                recorded_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS snapshots_character ON snapshots (character_id, recorded_at);
+            CREATE TABLE IF NOT EXISTS sessions (
+                character_id INTEGER NOT NULL,
+                role TEXT NOT NULL,
+                played_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS sessions_character ON sessions (character_id, played_at);"
+        )?;
+
+        Ok(History { connection })
+    }
+
+    /// Stores the current level of every job `character` has unlocked.
+    pub fn record(&mut self, character: &PlayerCharacter) -> Result<()> {
+        let recorded_at = to_secs(SystemTime::now());
+        let transaction = self.connection.transaction()?;
+
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO snapshots (character_id, character_name, class_id, job_id, level, exp_level, recorded_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
+            )?;
+            for class_job in character.class_jobs.iter().filter(|class_job| class_job.level > 0) {
+                insert.execute(params![
+                    character.id,
+                    character.name,
+                    class_job.job.class_id,
+                    class_job.job.job_id,
+                    class_job.level,
+                    class_job.exp_level,
+                    recorded_at
+                ])?;
+            }
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Stores that the party played a session now, with each member, by Lodestone ID, playing their role.
+    pub fn record_session(&mut self, roles: &[(u32, Role)]) -> Result<()> {
+        let played_at = to_secs(SystemTime::now());
+        let transaction = self.connection.transaction()?;
+
+        {
+            let mut insert = transaction.prepare("INSERT INTO sessions (character_id, role, played_at) VALUES (?1, ?2, ?3)")?;
+            for (character_id, role) in roles {
+                insert.execute(params![character_id, role.name(), played_at])?;
+            }
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// The role the character with Lodestone ID `character_id` played in each of their last `count` sessions, most
+    /// recent first.
+    pub fn roles_played(&self, character_id: u32, count: usize) -> Result<Vec<Role>> {
+        let mut query = self.connection.prepare(
+            "SELECT role FROM sessions WHERE character_id = ?1 ORDER BY played_at DESC LIMIT ?2"
+        )?;
+
+        let rows = query.query_map(params![character_id, count as i64], |row| row.get::<_, String>(0))?;
+        let mut roles = Vec::new();
+        for row in rows {
+            // Roles this version doesn't know about can't be weighed, so they are skipped.
+            if let Ok(role) = row?.parse() {
+                roles.push(role);
+            }
+        }
+
+        Ok(roles)
+    }
+
+    /// The Lodestone ID and most recent name of every character in the history, by name.
+    pub fn characters(&self) -> Result<Vec<(u32, String)>> {
+        let mut query = self.connection.prepare(
+            "SELECT character_id, character_name FROM snapshots AS latest
+            WHERE recorded_at = (SELECT MAX(recorded_at) FROM snapshots WHERE character_id = latest.character_id)
+            GROUP BY character_id
+            ORDER BY character_name"
+        )?;
+
+        let rows = query.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// The most recent snapshot of each job of the character with Lodestone ID `character_id`, if they have been
+    /// looked up before.
+    pub fn latest(&self, character_id: u32) -> Result<Vec<Snapshot>> {
+        let mut snapshots = self.snapshots(character_id, UNIX_EPOCH)?;
+        if let Some(recorded_at) = snapshots.last().map(|snapshot| snapshot.recorded_at) {
+            snapshots.retain(|snapshot| snapshot.recorded_at == recorded_at);
+        }
+        Ok(snapshots)
+    }
+
+    /// Every snapshot of the character with Lodestone ID `character_id` recorded at or after `since`, oldest first.
+    pub fn snapshots(&self, character_id: u32, since: SystemTime) -> Result<Vec<Snapshot>> {
+        let mut query = self.connection.prepare(
+            "SELECT character_id, character_name, class_id, job_id, level, exp_level, recorded_at FROM snapshots
+            WHERE character_id = ?1 AND recorded_at >= ?2
+            ORDER BY recorded_at, class_id, job_id"
+        )?;
+
+        let rows = query.query_map(params![character_id, to_secs(since)], |row| {
+            Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u8>(2)?,
+                row.get::<_, u8>(3)?,
+                row.get::<_, u8>(4)?,
+                row.get::<_, Option<u32>>(5)?,
+                row.get::<_, i64>(6)?
+            ))
+        })?;
+
+        let mut snapshots = Vec::new();
+        for row in rows {
+            let (character_id, character_name, class_id, job_id, level, exp_level, recorded_at) = row?;
+
+            // Jobs the job table no longer knows about can't be shown, so they are skipped.
+            if let Some(job) = job::lookup(class_id, job_id) {
+                snapshots.push(Snapshot {
+                    character_id,
+                    character_name,
+                    job,
+                    level,
+                    exp_level,
+                    recorded_at: UNIX_EPOCH + Duration::from_secs(recorded_at.max(0) as u64)
+                });
+            }
+        }
+
+        Ok(snapshots)
+    }
+}
+
+/// Each of `character`'s jobs whose level differs from `previous`, with the level it was then.
+pub fn level_changes<'a>(character: &'a PlayerCharacter, previous: &[Snapshot]) -> Vec<(&'a ClassJob, u8)> {
+    character.class_jobs.iter()
+        .filter_map(|class_job| {
+            let snapshot = previous.iter().find(|snapshot| snapshot.job == class_job.job)?;
+            (snapshot.level != class_job.level).then_some((class_job, snapshot.level))
+        })
+        .collect()
+}
+
+/// Levels worth celebrating a job reaching: the level cap of each expansion.
+pub const MILESTONE_LEVELS: [u8; 5] = [50, 60, 70, 80, 90];
+
+/// Something a character has reached since they were last looked up, worth celebrating.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Milestone {
+    /// A job reached a level, the highest of `MILESTONE_LEVELS` it passed.
+    Level(&'static JobInfo, u8),
+    /// The character unlocked their first job in a role, so can now play it.
+    Role(Role, &'static JobInfo)
+}
+
+/// The milestones `character` has reached since `previous`, their snapshots from the last time they were looked up.
+/// A character never looked up before hasn't reached any, as there's no telling what is new.
+pub fn milestones(character: &PlayerCharacter, previous: &[Snapshot]) -> Vec<Milestone> {
+    if previous.is_empty() {
+        return Vec::new();
+    }
+
+    let previous_level = |job| previous.iter().find(|snapshot| snapshot.job == job).map_or(0, |snapshot| snapshot.level);
+    let own_jobs = || character.class_jobs.iter().filter(|class_job| class_job.alt.is_none() && class_job.level > 0);
+
+    let mut milestones: Vec<Milestone> = own_jobs()
+        .filter_map(|class_job| {
+            let was = previous_level(class_job.job);
+            let level = MILESTONE_LEVELS.iter().rev().find(|&&level| was < level && level <= class_job.level)?;
+            Some(Milestone::Level(class_job.job, *level))
+        })
+        .collect();
+
+    for role in [Role::Tank, Role::Healer, Role::Dps] {
+        if previous.iter().any(|snapshot| snapshot.job.role == Some(role) && snapshot.level > 0) {
+            continue;
+        }
+        if let Some(class_job) = own_jobs().filter(|class_job| class_job.job.role == Some(role)).max_by_key(|class_job| class_job.level) {
+            milestones.push(Milestone::Role(role, class_job.job));
+        }
+    }
+
+    milestones
+}
@@ -0,0 +1,58 @@
+use reqwest::blocking::Client;
+use reqwest::Proxy;
+use std::time::Duration;
+
+use crate::Result;
+
+/// How long an unused connection is kept open for the next request to the same server.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// How requests are made.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    /// Overrides the proxies set in the `HTTP_PROXY` and `HTTPS_PROXY` environment variables.
+    pub proxy: Option<Proxy>,
+    /// How long to wait to connect to a server.
+    pub connect_timeout: Duration,
+    /// How long to wait for a whole response, including connecting.
+    pub timeout: Duration
+}
+
+impl Settings {
+    /// The default settings, but giving up on a response after `timeout`. Connecting never takes longer than the
+    /// whole response is allowed to.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        let defaults = Settings::default();
+        Settings {
+            connect_timeout: defaults.connect_timeout.min(timeout),
+            timeout,
+            ..defaults
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            proxy: None,
+            connect_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(30)
+        }
+    }
+}
+
+/// Builds a client following `settings`. It keeps connections open between requests, so it should be built once
+/// and shared, which cloning it does.
+pub fn client(settings: &Settings) -> Result<Client> {
+    let mut builder = Client::builder()
+        .user_agent(concat!("xiv-levelling/", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(settings.connect_timeout)
+        .timeout(settings.timeout)
+        .pool_idle_timeout(IDLE_TIMEOUT)
+        .tcp_keepalive(IDLE_TIMEOUT);
+    if let Some(proxy) = &settings.proxy {
+        builder = builder.proxy(proxy.clone());
+    }
+
+    Ok(builder.build()?)
+}
@@ -0,0 +1,527 @@
+use std::env;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use xiv_levelling::language::Language;
+use xiv_levelling::optimizer::{Balance, Metric, Scoring};
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// A piece of user-facing text. Each `{}` in a translation is filled in by `format`, in order.
+#[derive(Copy, Clone, Debug)]
+pub enum Message {
+    ServerPrompt,
+    ServerSelectPrompt,
+    ServerNotFound,
+    AmbiguousServer,
+    CharacterPrompt,
+    RetryCharacterPrompt,
+    Searching,
+    FoundCharacter,
+    GettingCharacter,
+    NoCharacter,
+    MultipleCharacters,
+    ChooseCharacter,
+    ConfirmCharacter,
+    TooFewCharacters,
+    RolesPrompt,
+    RolesSelectPrompt,
+    InvalidRoles,
+    LockPrompt,
+    InvalidLock,
+    Determining,
+    NoConfigurations,
+    ArmouryBonusMarker,
+    AltMarker,
+    UnderGearedMarker,
+    BottleneckMarker,
+    PlayedMarker,
+    NotPlayedMarker,
+    Average,
+    ArmouryBonus,
+    DataCenterTravel,
+    Queue,
+    SyncedLevel,
+    Dungeons,
+    Trials,
+    Roulettes,
+    Resets,
+    DeepDungeon,
+    FieldOperations,
+    EstimatedExp,
+    ExpWaste,
+    WeeksToCap,
+    SwapAvailable,
+    PremadeOnly,
+    DutySupport,
+    DutySupportPartySize,
+    PartialPartySize,
+    Frontline,
+    NoFrontlineJob,
+    CommandPrompt,
+    UnknownCommand,
+    InvalidAdd,
+    PartyFull,
+    NotInParty,
+    InvalidSet,
+    UnknownJob,
+    InvalidWeight,
+    InvalidCompare,
+    InvalidPartyFinder,
+    NoSuchConfiguration,
+    JobNotUnlocked,
+    LevelSet,
+    HiddenJobs,
+    DuplicateCharacter,
+    JobsPrompt,
+    InvalidJobs,
+    LeftOut,
+    FetchFailed,
+    FetchFailedPrompt,
+    OfflineData,
+    DataAge,
+    None
+}
+
+/// Sets the language every message is shown in. Only the first call has any effect.
+pub fn init(language: Language) {
+    let _ = LANGUAGE.set(language);
+}
+
+/// Guesses the user's language from the usual locale environment variables, defaulting to English.
+pub fn detect() -> Language {
+    ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| value.get(..2).and_then(|code| code.parse().ok()))
+        .unwrap_or_default()
+}
+
+pub fn text(message: Message) -> &'static str {
+    use Language::*;
+    use Message::*;
+
+    match (LANGUAGE.get().copied().unwrap_or_default(), message) {
+
+        (English, ServerPrompt) => "Please enter the name of your FFXIV server:",
+        (French, ServerPrompt) => "Veuillez saisir le nom de votre serveur FFXIV :",
+        (German, ServerPrompt) => "Bitte gib den Namen deines FFXIV-Servers ein:",
+        (Japanese, ServerPrompt) => "FFXIVのワールド名を入力してください:",
+
+        (English, ServerSelectPrompt) => "Which FFXIV server are you on? (type to filter)",
+        (French, ServerSelectPrompt) => "Sur quel serveur FFXIV jouez-vous ? (tapez pour filtrer)",
+        (German, ServerSelectPrompt) => "Auf welchem FFXIV-Server spielst du? (tippe zum Filtern)",
+        (Japanese, ServerSelectPrompt) => "FFXIVのワールドを選んでください（入力で絞り込み）",
+
+        (English, ServerNotFound) => "Server {} does not exist!",
+        (French, ServerNotFound) => "Le serveur {} n'existe pas !",
+        (German, ServerNotFound) => "Der Server {} existiert nicht!",
+        (Japanese, ServerNotFound) => "ワールド {} は存在しません！",
+
+        (English, AmbiguousServer) => "More than one server starts with {}: {}",
+        (French, AmbiguousServer) => "Plusieurs serveurs commencent par {} : {}",
+        (German, AmbiguousServer) => "Mehrere Server beginnen mit {}: {}",
+        (Japanese, AmbiguousServer) => "{}で始まるワールドが複数あります: {}",
+
+        (English, CharacterPrompt) => "Character {} Name, or Name@World if on another world, with any alts after a + (press \
+            enter to stop):",
+        (French, CharacterPrompt) => "Nom du personnage {}, ou Nom@Monde s'il est sur un autre monde, suivi de ses alts \
+            après un + (appuyez sur Entrée pour terminer) :",
+        (German, CharacterPrompt) => "Name von Charakter {}, oder Name@Welt auf einer anderen Welt, mit Zweitcharakteren nach \
+            einem + (Eingabetaste zum Beenden):",
+        (Japanese, CharacterPrompt) => "キャラクター{}の名前（別のワールドなら名前@ワールド）、サブキャラは+の後に（Enterで終了）:",
+
+        (English, RetryCharacterPrompt) => "Character {} Name, corrected or as Name@World to search another world (press \
+            enter to skip):",
+        (French, RetryCharacterPrompt) => "Nom du personnage {}, corrigé ou sous la forme Nom@Monde pour chercher sur un \
+            autre monde (appuyez sur Entrée pour passer) :",
+        (German, RetryCharacterPrompt) => "Name von Charakter {}, korrigiert oder als Name@Welt, um auf einer anderen Welt \
+            zu suchen (Eingabetaste zum Überspringen):",
+        (Japanese, RetryCharacterPrompt) => "キャラクター{}の名前（修正するか、名前@ワールドで別のワールドを検索。Enterでスキップ）:",
+
+        (English, Searching) => "Searching for {} in the Lodestone...",
+        (French, Searching) => "Recherche de {} sur le Lodestone...",
+        (German, Searching) => "{} wird im Lodestone gesucht...",
+        (Japanese, Searching) => "ロードストーンで{}を検索しています...",
+
+        (English, FoundCharacter) => "Found character {} with ID {}!",
+        (French, FoundCharacter) => "Personnage {} trouvé avec l'ID {} !",
+        (German, FoundCharacter) => "Charakter {} mit der ID {} gefunden!",
+        (Japanese, FoundCharacter) => "キャラクター {}（ID {}）が見つかりました！",
+
+        (English, GettingCharacter) => "Getting character data for {}...",
+        (French, GettingCharacter) => "Récupération des données de {}...",
+        (German, GettingCharacter) => "Charakterdaten für {} werden abgerufen...",
+        (Japanese, GettingCharacter) => "{}のキャラクターデータを取得しています...",
+
+        (English, NoCharacter) => "No character with that name was found!",
+        (French, NoCharacter) => "Aucun personnage portant ce nom n'a été trouvé !",
+        (German, NoCharacter) => "Es wurde kein Charakter mit diesem Namen gefunden!",
+        (Japanese, NoCharacter) => "その名前のキャラクターは見つかりませんでした！",
+
+        (English, MultipleCharacters) => "Multiple characters were found!",
+        (French, MultipleCharacters) => "Plusieurs personnages ont été trouvés !",
+        (German, MultipleCharacters) => "Es wurden mehrere Charaktere gefunden!",
+        (Japanese, MultipleCharacters) => "複数のキャラクターが見つかりました！",
+
+        (English, ChooseCharacter) => "Which {} did you mean? (escape to leave them out)",
+        (French, ChooseCharacter) => "De quel {} s'agit-il ? (Échap pour l'exclure)",
+        (German, ChooseCharacter) => "Welchen {} meinst du? (Escape zum Auslassen)",
+        (Japanese, ChooseCharacter) => "どの{}ですか？（Escで除外）",
+
+        (English, ConfirmCharacter) => "Did you mean {} ({})?",
+        (French, ConfirmCharacter) => "Vouliez-vous dire {} ({}) ?",
+        (German, ConfirmCharacter) => "Meintest du {} ({})?",
+        (Japanese, ConfirmCharacter) => "{}（{}）でよろしいですか？",
+
+        (English, TooFewCharacters) => "Party must consist of at least two characters!",
+        (French, TooFewCharacters) => "Le groupe doit compter au moins deux personnages !",
+        (German, TooFewCharacters) => "Die Gruppe muss aus mindestens zwei Charakteren bestehen!",
+        (Japanese, TooFewCharacters) => "パーティは2人以上のキャラクターで構成する必要があります！",
+
+        (English, RolesPrompt) => "Roles {} is willing to play, e.g. tank,dps (press enter for any):",
+        (French, RolesPrompt) => "Rôles que {} accepte de jouer, ex. tank,dps (Entrée pour tous) :",
+        (German, RolesPrompt) => "Rollen, die {} spielen möchte, z. B. tank,dps (Eingabetaste für alle):",
+        (Japanese, RolesPrompt) => "{}が担当できるロール（例: tank,dps、Enterで全て）:",
+
+        (English, RolesSelectPrompt) => "Roles {} is willing to play (space to pick, enter to confirm, none for any)",
+        (French, RolesSelectPrompt) => "Rôles que {} accepte de jouer (Espace pour choisir, Entrée pour valider, aucun pour tous)",
+        (German, RolesSelectPrompt) => "Rollen, die {} spielen möchte (Leertaste zum Wählen, Eingabetaste zum Bestätigen, keine für alle)",
+        (Japanese, RolesSelectPrompt) => "{}が担当できるロール（Spaceで選択、Enterで決定、未選択で全て）",
+
+        (English, InvalidRoles) => "Roles must be a list of tank, healer and dps!",
+        (French, InvalidRoles) => "Les rôles doivent être une liste parmi tank, healer et dps !",
+        (German, InvalidRoles) => "Rollen müssen eine Liste aus tank, healer und dps sein!",
+        (Japanese, InvalidRoles) => "ロールは tank、healer、dps の一覧で指定してください！",
+
+        (English, LockPrompt) => "Lock a character to a job, e.g. Alice=Warrior (press enter to continue):",
+        (French, LockPrompt) => "Fixer la classe d'un personnage, ex. Alice=Warrior (Entrée pour continuer) :",
+        (German, LockPrompt) => "Charakter an einen Job binden, z. B. Alice=Warrior (Eingabetaste zum Fortfahren):",
+        (Japanese, LockPrompt) => "キャラクターのジョブを固定（例: Alice=Warrior、Enterで続行）:",
+
+        (English, InvalidLock) => "Locks must look like Name=Job!",
+        (French, InvalidLock) => "Le format doit être Nom=Classe !",
+        (German, InvalidLock) => "Bindungen müssen die Form Name=Job haben!",
+        (Japanese, InvalidLock) => "固定は 名前=ジョブ の形式で指定してください！",
+
+        (English, Determining) => "Determining best possible party configurations for levelling...",
+        (French, Determining) => "Recherche des meilleures compositions de groupe pour monter en niveau...",
+        (German, Determining) => "Die besten Gruppenzusammenstellungen zum Leveln werden ermittelt...",
+        (Japanese, Determining) => "レベル上げに最適なパーティ構成を探しています...",
+
+        (English, NoConfigurations) => "No valid party configurations were found!",
+        (French, NoConfigurations) => "Aucune composition de groupe valide n'a été trouvée !",
+        (German, NoConfigurations) => "Es wurden keine gültigen Gruppenzusammenstellungen gefunden!",
+        (Japanese, NoConfigurations) => "有効なパーティ構成が見つかりませんでした！",
+
+        (English, ArmouryBonusMarker) => "(armoury bonus)",
+        (French, ArmouryBonusMarker) => "(bonus d'armurerie)",
+        (German, ArmouryBonusMarker) => "(Arsenal-Bonus)",
+        (Japanese, ArmouryBonusMarker) => "（アーマリーボーナス）",
+
+        (English, AltMarker) => "(on {})",
+        (French, AltMarker) => "(avec {})",
+        (German, AltMarker) => "(mit {})",
+        (Japanese, AltMarker) => "（{}で）",
+
+        (English, UnderGearedMarker) => "(under-geared: Lv {} gear)",
+        (French, UnderGearedMarker) => "(sous-équipé : équipement niv. {})",
+        (German, UnderGearedMarker) => "(unterausgerüstet: Ausrüstung Stufe {})",
+        (Japanese, UnderGearedMarker) => "（装備不足：Lv{}装備）",
+
+        (English, BottleneckMarker) => "(bottleneck: {} below average)",
+        (French, BottleneckMarker) => "(goulot d'étranglement : {} sous la moyenne)",
+        (German, BottleneckMarker) => "(Engpass: {} unter dem Durchschnitt)",
+        (Japanese, BottleneckMarker) => "（ボトルネック：平均より{}低い）",
+
+        (English, PlayedMarker) => "(in {} of {} recent logs, last {} day(s) ago)",
+        (French, PlayedMarker) => "(dans {} des {} derniers logs, il y a {} jour(s))",
+        (German, PlayedMarker) => "(in {} von {} letzten Logs, zuletzt vor {} Tag(en))",
+        (Japanese, PlayedMarker) => "（{}件のログに登場／最近{}件中、最終{}日前）",
+
+        (English, NotPlayedMarker) => "(not in recent logs)",
+        (French, NotPlayedMarker) => "(absent des derniers logs)",
+        (German, NotPlayedMarker) => "(nicht in den letzten Logs)",
+        (Japanese, NotPlayedMarker) => "（最近のログになし）",
+
+        (English, Average) => "Lv Avg",
+        (French, Average) => "Niv. moyen",
+        (German, Average) => "Ø Stufe",
+        (Japanese, Average) => "平均Lv",
+
+        (English, ArmouryBonus) => "Armoury Bonus: {} of {}",
+        (French, ArmouryBonus) => "Bonus d'armurerie : {} sur {}",
+        (German, ArmouryBonus) => "Arsenal-Bonus: {} von {}",
+        (Japanese, ArmouryBonus) => "アーマリーボーナス: {}/{}",
+
+        (English, DataCenterTravel) => "Requires data center travel",
+        (French, DataCenterTravel) => "Voyage entre centres de données nécessaire",
+        (German, DataCenterTravel) => "Datenzentrum-Reise erforderlich",
+        (Japanese, DataCenterTravel) => "データセンタートラベルが必要",
+
+        (English, Queue) => "Queue",
+        (French, Queue) => "File d'attente",
+        (German, Queue) => "Warteschlange",
+        (Japanese, Queue) => "コンテンツファインダー",
+
+        (English, SyncedLevel) => "Synced Level",
+        (French, SyncedLevel) => "Niveau synchronisé",
+        (German, SyncedLevel) => "Synchronisierte Stufe",
+        (Japanese, SyncedLevel) => "シンクLv",
+
+        (English, Dungeons) => "Dungeons",
+        (French, Dungeons) => "Donjons",
+        (German, Dungeons) => "Dungeons",
+        (Japanese, Dungeons) => "ダンジョン",
+
+        (English, Trials) => "Trials",
+        (French, Trials) => "Défis",
+        (German, Trials) => "Prüfungen",
+        (Japanese, Trials) => "討伐・討滅戦",
+
+        (English, Roulettes) => "Roulettes",
+        (French, Roulettes) => "Missions aléatoires",
+        (German, Roulettes) => "Zufallsinhalte",
+        (Japanese, Roulettes) => "コンテンツルーレット",
+        (English, Resets) => "Resets",
+        (French, Resets) => "Réinitialisations",
+        (German, Resets) => "Rücksetzungen",
+        (Japanese, Resets) => "リセット",
+
+        (English, DeepDungeon) => "Deep Dungeon",
+        (French, DeepDungeon) => "Donjon sans fond",
+        (German, DeepDungeon) => "Tiefes Gewölbe",
+        (Japanese, DeepDungeon) => "ディープダンジョン",
+
+        (English, FieldOperations) => "Field Operations",
+        (French, FieldOperations) => "Opérations de terrain",
+        (German, FieldOperations) => "Feldexpeditionen",
+        (Japanese, FieldOperations) => "特殊フィールド探索",
+
+        (English, EstimatedExp) => "Est. EXP",
+        (French, EstimatedExp) => "EXP estimée",
+        (German, EstimatedExp) => "Geschätzte EP",
+        (Japanese, EstimatedExp) => "推定経験値",
+        (English, ExpWaste) => "EXP Lost to Sync",
+        (French, ExpWaste) => "EXP perdue à la synchro",
+        (German, ExpWaste) => "Durch Synchronisierung verlorene EP",
+        (Japanese, ExpWaste) => "レベルシンクで失う経験値",
+
+        (English, WeeksToCap) => "Weeks to Cap",
+        (French, WeeksToCap) => "Semaines jusqu'au niveau max",
+        (German, WeeksToCap) => "Wochen bis zur Maximalstufe",
+        (Japanese, WeeksToCap) => "レベルキャップまでの週数",
+
+        (English, SwapAvailable) => "Swap Available",
+        (French, SwapAvailable) => "Échange possible",
+        (German, SwapAvailable) => "Tausch möglich",
+        (Japanese, SwapAvailable) => "ロール交代可",
+
+        (English, PremadeOnly) => "Premade only: Blue Mage can't use the Duty Finder",
+        (French, PremadeOnly) => "Groupe préformé uniquement : le Mage bleu ne peut pas utiliser l'outil de mission",
+        (German, PremadeOnly) => "Nur feste Gruppe: Blaumagier können die Inhaltssuche nicht nutzen",
+        (Japanese, PremadeOnly) => "フルパーティ限定: 青魔道士はコンテンツファインダーを利用できません",
+
+        (English, DutySupport) => "Duty Support",
+        (French, DutySupport) => "Soutien de mission",
+        (German, DutySupport) => "Gefährtensystem",
+        (Japanese, DutySupport) => "フェイス",
+
+        (English, DutySupportPartySize) => "Duty Support requires two or three characters!",
+        (French, DutySupportPartySize) => "Le soutien de mission nécessite deux ou trois personnages !",
+        (German, DutySupportPartySize) => "Das Gefährtensystem erfordert zwei oder drei Charaktere!",
+        (Japanese, DutySupportPartySize) => "フェイスには2人または3人のキャラクターが必要です！",
+
+        (English, PartialPartySize) => "Queueing as a partial party requires two or three characters!",
+        (French, PartialPartySize) => "S'inscrire en groupe incomplet nécessite deux ou trois personnages !",
+        (German, PartialPartySize) => "Als unvollständige Gruppe anzumelden erfordert zwei oder drei Charaktere!",
+        (Japanese, PartialPartySize) => "少人数パーティでの申請には2人または3人のキャラクターが必要です！",
+
+        (English, Frontline) => "Daily Frontline",
+        (French, Frontline) => "Front de bataille quotidien",
+        (German, Frontline) => "Tägliche Front",
+        (Japanese, Frontline) => "デイリーフロントライン",
+
+        (English, NoFrontlineJob) => "no job at Lv 30 or above still earns EXP",
+        (French, NoFrontlineJob) => "aucune classe de niveau 30 ou plus ne gagne encore d'EXP",
+        (German, NoFrontlineJob) => "kein Job ab Stufe 30 erhält noch EP",
+        (Japanese, NoFrontlineJob) => "経験値を得られるLv30以上のジョブがありません",
+
+        (English, CommandPrompt) => "Type add <name> or remove <name> to change who is in the party, refresh <name> to get a \
+            member's latest data, set <name> <job> <level> to see what \
+            a job at another level would change, lock <name>=<job> or unlock <name>, exclude <job> or include <job>, \
+            weight <var|avg|bonus|diversity|duplicate> <value>, compare <rank> <rank>, pf <rank> for a Party Finder \
+            description, sort [score|spread|avg|min-level|waste] to reorder them, or press enter to quit:",
+        (French, CommandPrompt) => "Tapez add <nom> ou remove <nom> pour changer la composition du groupe, refresh <nom> \
+            pour récupérer les dernières données d'un membre, set <nom> <classe> <niveau> pour voir ce que changerait une classe à un autre niveau, lock <nom>=<classe> ou unlock <nom>, \
+            exclude <classe> ou include <classe>, weight <var|avg|bonus|diversity|duplicate> <valeur>, compare <rang> \
+            <rang>, pf <rang> pour une description de la recherche d'équipe, sort [score|spread|avg|min-level|waste] pour les \
+            réordonner, ou appuyez sur Entrée pour quitter :",
+        (German, CommandPrompt) => "Gib add <Name> oder remove <Name> ein, um die Gruppe zu ändern, refresh <Name>, um die \
+            neuesten Daten eines Mitglieds abzurufen, set <Name> <Job> <Stufe>, um zu sehen, was ein Job auf einer anderen Stufe ändern würde, lock <Name>=<Job> oder unlock <Name>, \
+            exclude <Job> oder include <Job>, weight <var|avg|bonus|diversity|duplicate> <Wert>, compare <Rang> <Rang>, \
+            pf <Rang> für eine Gruppensuche-Beschreibung, sort [score|spread|avg|min-level|waste] zum Umsortieren, oder \
+            drücke Enter zum Beenden:",
+        (Japanese, CommandPrompt) => "add <名前> / remove <名前> でメンバーを追加・削除、refresh <名前> でメンバーの最新データを取得、\
+            set <名前> <ジョブ> <レベル> でレベルが違った場合を確認、lock <名前>=<ジョブ> / unlock <名前> でジョブを固定・解除、exclude <ジョブ> / include <ジョブ> でジョブを除外・\
+            復帰、weight <var|avg|bonus|diversity|duplicate> <値> で重みを変更、compare <順位> <順位> で2つの編成を比較、pf <順位> でパーティ募集のコメントを作成、sort [score|spread|avg|min-level|waste] で並べ替えできます（Enterで終了）：",
+
+        (English, UnknownCommand) => "Unknown command! Try add, remove, refresh, set, lock, unlock, exclude, include, weight, compare, pf or sort.",
+        (French, UnknownCommand) => "Commande inconnue ! Essayez add, remove, refresh, set, lock, unlock, exclude, include, weight, compare, pf ou sort.",
+        (German, UnknownCommand) => "Unbekannter Befehl! Versuche add, remove, refresh, set, lock, unlock, exclude, include, weight, compare, pf oder sort.",
+        (Japanese, UnknownCommand) => "不明なコマンドです！add、remove、refresh、set、lock、unlock、exclude、include、weight、compare、pf、sort のいずれかを試してください。",
+
+        (English, InvalidAdd) => "Expected add <name> or add <name>@<world>, e.g. add Alice One!",
+        (French, InvalidAdd) => "Format attendu : add <nom> ou add <nom>@<monde>, par ex. add Alice One !",
+        (German, InvalidAdd) => "Erwartet: add <Name> oder add <Name>@<Welt>, z. B. add Alice One!",
+        (Japanese, InvalidAdd) => "add <名前> または add <名前>@<ワールド> の形式で入力してください（例：add Alice One）！",
+
+        (English, PartyFull) => "The party is already full at {} members!",
+        (French, PartyFull) => "Le groupe est déjà complet avec {} membres !",
+        (German, PartyFull) => "Die Gruppe ist mit {} Mitgliedern bereits voll!",
+        (Japanese, PartyFull) => "パーティは既に{}人で満員です！",
+
+        (English, InvalidSet) => "Expected set <name> <job> <level>, e.g. set Alice DRK 63!",
+        (French, InvalidSet) => "Format attendu : set <nom> <classe> <niveau>, par ex. set Alice DRK 63 !",
+        (German, InvalidSet) => "Erwartet: set <Name> <Job> <Stufe>, z. B. set Alice DRK 63!",
+        (Japanese, InvalidSet) => "set <名前> <ジョブ> <レベル> の形式で入力してください（例：set Alice DRK 63）！",
+
+        (English, UnknownJob) => "There is no job called {}!",
+        (French, UnknownJob) => "Aucune classe ne s'appelle {} !",
+        (German, UnknownJob) => "Es gibt keinen Job namens {}!",
+        (Japanese, UnknownJob) => "{} というジョブはありません！",
+
+        (English, InvalidWeight) => "Weights must be var, avg, bonus, diversity or duplicate followed by a number, e.g. weight bonus 2!",
+        (French, InvalidWeight) => "Un poids doit être var, avg, bonus, diversity ou duplicate suivi d'un nombre, ex. weight bonus 2 !",
+        (German, InvalidWeight) => "Gewichte müssen var, avg, bonus, diversity oder duplicate gefolgt von einer Zahl sein, z. B. weight bonus 2!",
+        (Japanese, InvalidWeight) => "重みは var、avg、bonus、diversity、duplicate のいずれかと数値で指定してください（例: weight bonus 2）！",
+
+        (English, InvalidCompare) => "Expected compare <rank> <rank>, e.g. compare 1 2!",
+        (French, InvalidCompare) => "Format attendu : compare <rang> <rang>, par ex. compare 1 2 !",
+        (German, InvalidCompare) => "Erwartet: compare <Rang> <Rang>, z. B. compare 1 2!",
+        (Japanese, InvalidCompare) => "compare <順位> <順位> の形式で入力してください（例：compare 1 2）！",
+
+        (English, InvalidPartyFinder) => "Expected pf <rank>, e.g. pf 1!",
+        (French, InvalidPartyFinder) => "Format attendu : pf <rang>, par ex. pf 1 !",
+        (German, InvalidPartyFinder) => "Erwartet: pf <Rang>, z. B. pf 1!",
+        (Japanese, InvalidPartyFinder) => "pf <順位> の形式で入力してください（例：pf 1）！",
+
+        (English, NoSuchConfiguration) => "There is no configuration #{}!",
+        (French, NoSuchConfiguration) => "Il n'y a pas de configuration n°{} !",
+        (German, NoSuchConfiguration) => "Es gibt keine Konfiguration #{}!",
+        (Japanese, NoSuchConfiguration) => "#{} の編成はありません！",
+
+        (English, JobNotUnlocked) => "{} hasn't unlocked {}!",
+        (French, JobNotUnlocked) => "{} n'a pas débloqué {} !",
+        (German, JobNotUnlocked) => "{} hat {} nicht freigeschaltet!",
+        (Japanese, JobNotUnlocked) => "{}は{}を解放していません！",
+
+        (English, LevelSet) => "Pretending {}'s {} is Lv {} until the program exits.",
+        (French, LevelSet) => "Jusqu'à la fermeture du programme, {} est considéré comme ayant {} au niveau {}.",
+        (German, LevelSet) => "{}s {} wird bis zum Beenden des Programms als Stufe {} behandelt.",
+        (Japanese, LevelSet) => "プログラム終了まで、{}の{}をLv{}として扱います。",
+
+        (English, HiddenJobs) => "{} has hidden their classes and jobs on the Lodestone!",
+        (French, HiddenJobs) => "{} a masqué ses classes et jobs sur le Lodestone !",
+        (German, HiddenJobs) => "{} hat die eigenen Klassen und Jobs im Lodestone verborgen!",
+        (Japanese, HiddenJobs) => "{}はLodestoneでクラス・ジョブを非公開にしています！",
+
+        (English, DuplicateCharacter) => "{} is already in the party!",
+        (French, DuplicateCharacter) => "{} fait déjà partie du groupe !",
+        (German, DuplicateCharacter) => "{} ist bereits in der Gruppe!",
+        (Japanese, DuplicateCharacter) => "{}は既にパーティにいます！",
+
+        (English, JobsPrompt) => "Jobs {} plays and their levels, e.g. WAR 63, WHM 58 (press enter to leave them out):",
+        (French, JobsPrompt) => "Jobs joués par {} et leurs niveaux, par ex. WAR 63, WHM 58 (appuyez sur Entrée pour l'exclure) :",
+        (German, JobsPrompt) => "Jobs, die {} spielt, mit Stufe, z. B. WAR 63, WHM 58 (Enter drücken, um das Mitglied auszulassen):",
+        (Japanese, JobsPrompt) => "{}のジョブとレベル（例：WAR 63, WHM 58、Enterで除外）：",
+
+        (English, InvalidJobs) => "Each job must be a name or abbreviation followed by a level, e.g. WAR 63!",
+        (French, InvalidJobs) => "Chaque job doit être un nom ou une abréviation suivi d'un niveau, par ex. WAR 63 !",
+        (German, InvalidJobs) => "Jeder Job muss ein Name oder eine Abkürzung gefolgt von einer Stufe sein, z. B. WAR 63!",
+        (Japanese, InvalidJobs) => "各ジョブは名前か略称の後にレベルを入力してください（例：WAR 63）！",
+
+        (English, LeftOut) => "{} has been left out of the party.",
+        (French, LeftOut) => "{} a été exclu du groupe.",
+        (German, LeftOut) => "{} wurde aus der Gruppe ausgelassen.",
+        (Japanese, LeftOut) => "{}はパーティから除外されました。",
+
+        (English, FetchFailed) => "Failed to get {}: {}",
+        (French, FetchFailed) => "Échec de la récupération de {} : {}",
+        (German, FetchFailed) => "{} konnte nicht abgerufen werden: {}",
+        (Japanese, FetchFailed) => "{}の取得に失敗しました：{}",
+
+        (English, FetchFailedPrompt) => "Type r to try again, m to enter their jobs by hand, or press enter to leave them out:",
+        (French, FetchFailedPrompt) => "Tapez r pour réessayer, m pour saisir ses jobs à la main, ou appuyez sur Entrée pour l'exclure :",
+        (German, FetchFailedPrompt) => "Gib r ein, um es erneut zu versuchen, m, um die Jobs von Hand einzugeben, oder drücke Enter, um \
+            das Mitglied auszulassen:",
+        (Japanese, FetchFailedPrompt) => "r で再試行、m でジョブを手入力、Enterで除外：",
+
+        (English, OfflineData) => "Offline, so using saved data:",
+        (French, OfflineData) => "Hors ligne, les données enregistrées sont utilisées :",
+        (German, OfflineData) => "Offline, daher werden gespeicherte Daten verwendet:",
+        (Japanese, OfflineData) => "オフラインのため、保存済みのデータを使用します:",
+
+        (English, DataAge) => "{}: saved {} ago",
+        (French, DataAge) => "{} : enregistré il y a {}",
+        (German, DataAge) => "{}: vor {} gespeichert",
+        (Japanese, DataAge) => "{}: {}前に保存",
+
+        (English, NotInParty) => "Nobody in the party is called {}!",
+        (French, NotInParty) => "Personne dans le groupe ne s'appelle {} !",
+        (German, NotInParty) => "Niemand in der Gruppe heißt {}!",
+        (Japanese, NotInParty) => "パーティに{}というメンバーはいません！",
+
+        (English, None) => "None",
+        (French, None) => "Aucun",
+        (German, None) => "Keine",
+        (Japanese, None) => "なし"
+    }
+}
+
+/// Fills each `{}` in `message`'s translation with the next of `args`.
+pub fn format(message: Message, args: &[&dyn Display]) -> String {
+    let mut parts = text(message).split("{}");
+    let mut formatted = parts.next().unwrap_or_default().to_owned();
+
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            formatted.push_str(&arg.to_string());
+        }
+        formatted.push_str(part);
+    }
+
+    formatted
+}
+
+/// Label for a configuration's spread, e.g. "Lv Var".
+pub fn spread_label(scoring: &Scoring) -> String {
+    let language = LANGUAGE.get().copied().unwrap_or_default();
+
+    let prefix = match (language, scoring.balance) {
+        (Language::French, Balance::Level) => "Niv.",
+        (Language::German, Balance::Level) => "Stufe",
+        (Language::German, Balance::Exp) => "EP",
+        (Language::Japanese, Balance::Level) => "Lv",
+        (Language::Japanese, Balance::Exp) => "経験値",
+        _ => scoring.balance.prefix()
+    };
+
+    let label = match (language, scoring.metric) {
+        (Language::French, Metric::Pairwise) => "écart",
+        (Language::French, Metric::Range) => "étendue",
+        (Language::French, Metric::StdDev) => "écart type",
+        (Language::French, Metric::MaxGap) => "écart max",
+        (Language::German, Metric::Pairwise) => "Abweichung",
+        (Language::German, Metric::Range) => "Spanne",
+        (Language::German, Metric::StdDev) => "Standardabw.",
+        (Language::German, Metric::MaxGap) => "Max. Abstand",
+        (Language::Japanese, Metric::Pairwise) => "差",
+        (Language::Japanese, Metric::Range) => "幅",
+        (Language::Japanese, Metric::StdDev) => "標準偏差",
+        (Language::Japanese, Metric::MaxGap) => "最大差",
+        _ => scoring.metric.label()
+    };
+
+    format!("{} {}", prefix, label)
+}
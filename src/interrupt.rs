@@ -0,0 +1,83 @@
+use signal_hook::consts::SIGINT;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The exit code of a process killed by SIGINT.
+const INTERRUPTED_CODE: i32 = 130;
+
+/// Whether everything needed to `--resume` has been saved.
+static SESSION_SAVED: AtomicBool = AtomicBool::new(false);
+
+/// The characters found so far, as `Name@World` lines ready for `--characters-file`.
+static FOUND: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("xiv-levelling").join("interrupted.txt"))
+}
+
+/// Remembers that `name` was found on `world`, so they can be saved if the run is interrupted.
+/// Saved characters have no world, so `world` may be empty.
+pub fn found(name: &str, world: &str) {
+    let entry = if world.is_empty() { name.to_owned() } else { format!("{}@{}", name, world) };
+    FOUND.lock().unwrap().push(entry);
+}
+
+/// Remembers that the session has been saved, so an interrupted search can be resumed.
+pub fn session_saved() {
+    SESSION_SAVED.store(true, Ordering::Relaxed);
+}
+
+/// Exits cleanly on Ctrl+C, saving the characters found so far. A second Ctrl+C exits immediately.
+pub fn install() {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let registered = signal_hook::flag::register_conditional_shutdown(SIGINT, INTERRUPTED_CODE, Arc::clone(&interrupted))
+        .and_then(|_| signal_hook::flag::register(SIGINT, Arc::clone(&interrupted)));
+    if let Err(e) = registered {
+        eprintln!("Failed to handle Ctrl+C: {}", e);
+        return;
+    }
+
+    thread::spawn(move || {
+        while !interrupted.load(Ordering::Relaxed) {
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        save();
+        process::exit(INTERRUPTED_CODE);
+    });
+}
+
+fn save() {
+    if SESSION_SAVED.load(Ordering::Relaxed) {
+        eprintln!("\nInterrupted! Run the same command with --resume to pick up from there.");
+        return;
+    }
+
+    let found = FOUND.lock().unwrap();
+    if found.is_empty() {
+        eprintln!("\nInterrupted!");
+        return;
+    }
+
+    let Some(path) = path() else {
+        eprintln!("\nInterrupted! There is nowhere to save the characters found so far on this system.");
+        return;
+    };
+
+    let saved = path.parent().map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, found.join("\n") + "\n"));
+    match saved {
+        Ok(()) => {
+            eprintln!("\nInterrupted! The {} characters found so far were saved.", found.len());
+            eprintln!("Run the same command with --characters-file {} to pick up from there.", path.display());
+        },
+        Err(e) => eprintln!("\nInterrupted! Failed to save the characters found so far: {}", e)
+    }
+}
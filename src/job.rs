@@ -0,0 +1,370 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use Discipline::*;
+use Expansion::*;
+
+/// Blue Mage can't use the Duty Finder and has a lower level cap, so it is left out unless asked for.
+const BLUE_MAGE: u8 = 36;
+pub const BLUE_MAGE_LEVEL_CAP: u8 = 70;
+
+/// Level a base class has to reach before it can become its job, e.g. Gladiator into Paladin.
+pub const JOB_UNLOCK_LEVEL: u8 = 30;
+
+/// Highest level a free trial account can reach.
+pub const TRIAL_LEVEL_CAP: u8 = 70;
+/// Latest expansion whose jobs the free trial includes.
+const TRIAL_EXPANSION: Expansion = Expansion::Stormblood;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Tank,
+    Healer,
+    Dps
+}
+
+impl Role {
+    /// Symbol for the role, for output without colour.
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Role::Tank => "⛊",
+            Role::Healer => "✚",
+            Role::Dps => "⚔"
+        }
+    }
+
+    /// Lowercase name of the role, as it is parsed from.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Role::Tank => "tank",
+            Role::Healer => "healer",
+            Role::Dps => "dps"
+        }
+    }
+
+    /// Single-letter abbreviation of the role.
+    pub fn letter(&self) -> &'static str {
+        match self {
+            Role::Tank => "T",
+            Role::Healer => "H",
+            Role::Dps => "D"
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tank" => Ok(Role::Tank),
+            "healer" => Ok(Role::Healer),
+            "dps" => Ok(Role::Dps),
+            _ => Err(format!("unknown role {} (expected tank, healer or dps)", s))
+        }
+    }
+}
+
+/// Kind of DPS, since a party with one of each tends to be more pleasant to run than one with several melee.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SubRole {
+    Melee,
+    PhysicalRanged,
+    Caster
+}
+
+/// An expansion, which a player needs to own to play its jobs and go past the level cap before it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+pub enum Expansion {
+    #[serde(rename = "arr")]
+    ARealmReborn,
+    #[serde(rename = "hw")]
+    Heavensward,
+    #[serde(rename = "sb")]
+    Stormblood,
+    #[serde(rename = "shb")]
+    Shadowbringers,
+    #[serde(rename = "ew")]
+    Endwalker
+}
+
+impl Expansion {
+    /// Highest level someone who owns this expansion, but none after it, can reach.
+    pub fn level_cap(&self) -> u8 {
+        match self {
+            Expansion::ARealmReborn => 50,
+            Expansion::Heavensward => 60,
+            Expansion::Stormblood => 70,
+            Expansion::Shadowbringers => 80,
+            Expansion::Endwalker => 90
+        }
+    }
+}
+
+impl FromStr for Expansion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "arr" | "a realm reborn" => Ok(Expansion::ARealmReborn),
+            "hw" | "heavensward" => Ok(Expansion::Heavensward),
+            "sb" | "stormblood" => Ok(Expansion::Stormblood),
+            "shb" | "shadowbringers" => Ok(Expansion::Shadowbringers),
+            "ew" | "endwalker" => Ok(Expansion::Endwalker),
+            _ => Err(format!("unknown expansion {} (expected arr, hw, sb, shb or ew)", s))
+        }
+    }
+}
+
+/// Says which expansions a member owns, by the latest of them, e.g. `Alice=shb`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpansionFlag {
+    pub character: String,
+    pub expansion: Expansion
+}
+
+impl FromStr for ExpansionFlag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((character, expansion)) if !character.trim().is_empty() => Ok(ExpansionFlag {
+                character: character.trim().to_owned(),
+                expansion: expansion.trim().parse()?
+            }),
+            _ => Err(format!("invalid expansion {} (expected Name=Expansion)", s))
+        }
+    }
+}
+
+/// Static metadata for a class and the job it upgrades into.
+///
+/// Every `ClassJob` points into `JOBS`, so names are never duplicated per character.
+#[derive(Debug, PartialEq, Eq)]
+pub struct JobInfo {
+    pub class_id: u8,
+    pub job_id: u8,
+    pub class_name: &'static str,
+    /// Standard three-letter abbreviation of the class, e.g. GLA.
+    pub class_abbreviation: &'static str,
+    pub job_name: &'static str,
+    pub job_abbreviation: &'static str,
+    /// `None` for Disciples of the Hand and Land.
+    pub role: Option<Role>,
+    /// `None` for anything but DPS.
+    pub sub_role: Option<SubRole>,
+    /// The expansion that added the class, which a player needs to own to play it.
+    pub expansion: Expansion,
+    /// Level the class starts at once it is unlocked.
+    pub starting_level: u8
+}
+
+impl JobInfo {
+    /// Whether this is a Disciple of War or Magic.
+    pub fn is_combat(&self) -> bool {
+        self.role.is_some()
+    }
+
+    /// Whether this is a Disciple of the Hand or Land.
+    pub fn is_crafting(&self) -> bool {
+        self.role.is_none()
+    }
+
+    /// Whether the job is reached by levelling a base class, rather than starting as a job.
+    pub fn has_base_class(&self) -> bool {
+        self.class_id != self.job_id
+    }
+
+    /// Whether a character at `level` could be playing the job rather than its base class.
+    pub fn job_available_at(&self, level: u8) -> bool {
+        !self.has_base_class() || level >= JOB_UNLOCK_LEVEL
+    }
+
+    /// Whether this is a limited job, which can't use the Duty Finder and has its own level cap.
+    pub fn is_limited(&self) -> bool {
+        self.job_id == BLUE_MAGE
+    }
+
+    /// Highest level the job can reach, which is lower for limited jobs.
+    pub fn level_cap(&self) -> u8 {
+        if self.is_limited() { BLUE_MAGE_LEVEL_CAP } else { LEVEL_CAP }
+    }
+
+    /// Whether a free trial account can play this.
+    pub fn in_free_trial(&self) -> bool {
+        self.expansion <= TRIAL_EXPANSION
+    }
+
+    /// Whether `name` refers to this job or its base class, by full name or abbreviation, ignoring case.
+    pub fn matches(&self, name: &str) -> bool {
+        [self.job_name, self.job_abbreviation, self.class_name, self.class_abbreviation].iter()
+            .any(|candidate| candidate.eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for JobInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.job_name)
+    }
+}
+
+/// What a class does in a party, which decides its role and sub-role.
+#[derive(Copy, Clone)]
+enum Discipline {
+    Tank,
+    Healer,
+    Melee,
+    PhysicalRanged,
+    Caster,
+    HandOrLand
+}
+
+const fn job(
+    class_id: u8,
+    job_id: u8,
+    (class_name, class_abbreviation): (&'static str, &'static str),
+    (job_name, job_abbreviation): (&'static str, &'static str),
+    discipline: Discipline,
+    expansion: Expansion,
+    starting_level: u8
+) -> JobInfo {
+    let (role, sub_role) = match discipline {
+        Tank => (Some(Role::Tank), None),
+        Healer => (Some(Role::Healer), None),
+        Melee => (Some(Role::Dps), Some(SubRole::Melee)),
+        PhysicalRanged => (Some(Role::Dps), Some(SubRole::PhysicalRanged)),
+        Caster => (Some(Role::Dps), Some(SubRole::Caster)),
+        HandOrLand => (None, None)
+    };
+
+    JobInfo {
+        class_id,
+        job_id,
+        class_name,
+        class_abbreviation,
+        job_name,
+        job_abbreviation,
+        role,
+        sub_role,
+        expansion,
+        starting_level
+    }
+}
+
+/// Every class and job, as in the game's ClassJob sheet. Jobs that don't upgrade from a class are their own class.
+pub static JOBS: [JobInfo; 31] = [
+    job(1, 19, ("Gladiator", "GLA"), ("Paladin", "PLD"), Tank, ARealmReborn, 1),
+    job(2, 20, ("Pugilist", "PGL"), ("Monk", "MNK"), Melee, ARealmReborn, 1),
+    job(3, 21, ("Marauder", "MRD"), ("Warrior", "WAR"), Tank, ARealmReborn, 1),
+    job(4, 22, ("Lancer", "LNC"), ("Dragoon", "DRG"), Melee, ARealmReborn, 1),
+    job(5, 23, ("Archer", "ARC"), ("Bard", "BRD"), PhysicalRanged, ARealmReborn, 1),
+    job(6, 24, ("Conjurer", "CNJ"), ("White Mage", "WHM"), Healer, ARealmReborn, 1),
+    job(7, 25, ("Thaumaturge", "THM"), ("Black Mage", "BLM"), Caster, ARealmReborn, 1),
+    job(8, 8, ("Carpenter", "CRP"), ("Carpenter", "CRP"), HandOrLand, ARealmReborn, 1),
+    job(9, 9, ("Blacksmith", "BSM"), ("Blacksmith", "BSM"), HandOrLand, ARealmReborn, 1),
+    job(10, 10, ("Armorer", "ARM"), ("Armorer", "ARM"), HandOrLand, ARealmReborn, 1),
+    job(11, 11, ("Goldsmith", "GSM"), ("Goldsmith", "GSM"), HandOrLand, ARealmReborn, 1),
+    job(12, 12, ("Leatherworker", "LTW"), ("Leatherworker", "LTW"), HandOrLand, ARealmReborn, 1),
+    job(13, 13, ("Weaver", "WVR"), ("Weaver", "WVR"), HandOrLand, ARealmReborn, 1),
+    job(14, 14, ("Alchemist", "ALC"), ("Alchemist", "ALC"), HandOrLand, ARealmReborn, 1),
+    job(15, 15, ("Culinarian", "CUL"), ("Culinarian", "CUL"), HandOrLand, ARealmReborn, 1),
+    job(16, 16, ("Miner", "MIN"), ("Miner", "MIN"), HandOrLand, ARealmReborn, 1),
+    job(17, 17, ("Botanist", "BTN"), ("Botanist", "BTN"), HandOrLand, ARealmReborn, 1),
+    job(18, 18, ("Fisher", "FSH"), ("Fisher", "FSH"), HandOrLand, ARealmReborn, 1),
+    job(26, 27, ("Arcanist", "ACN"), ("Summoner", "SMN"), Caster, ARealmReborn, 1),
+    job(26, 28, ("Arcanist", "ACN"), ("Scholar", "SCH"), Healer, ARealmReborn, 1),
+    job(29, 30, ("Rogue", "ROG"), ("Ninja", "NIN"), Melee, ARealmReborn, 1),
+    job(31, 31, ("Machinist", "MCH"), ("Machinist", "MCH"), PhysicalRanged, Heavensward, 30),
+    job(32, 32, ("Dark Knight", "DRK"), ("Dark Knight", "DRK"), Tank, Heavensward, 30),
+    job(33, 33, ("Astrologian", "AST"), ("Astrologian", "AST"), Healer, Heavensward, 30),
+    job(34, 34, ("Samurai", "SAM"), ("Samurai", "SAM"), Melee, Stormblood, 50),
+    job(35, 35, ("Red Mage", "RDM"), ("Red Mage", "RDM"), Caster, Stormblood, 50),
+    job(36, 36, ("Blue Mage", "BLU"), ("Blue Mage", "BLU"), Caster, ARealmReborn, 1),
+    job(37, 37, ("Gunbreaker", "GNB"), ("Gunbreaker", "GNB"), Tank, Shadowbringers, 60),
+    job(38, 38, ("Dancer", "DNC"), ("Dancer", "DNC"), PhysicalRanged, Shadowbringers, 60),
+    job(39, 39, ("Reaper", "RPR"), ("Reaper", "RPR"), Melee, Endwalker, 70),
+    job(40, 40, ("Sage", "SGE"), ("Sage", "SGE"), Healer, Endwalker, 70)
+];
+
+/// Looks up the metadata for a class, using the job ID to tell apart classes with more than one job (Arcanist).
+pub fn lookup(class_id: u8, job_id: u8) -> Option<&'static JobInfo> {
+    JOBS.iter().find(|info| info.class_id == class_id && info.job_id == job_id)
+}
+
+/// Approximate EXP needed to advance from each level to the next, starting at level 1.
+pub static EXP_TO_NEXT_LEVEL: [u32; 89] = [
+    300, 600, 1100, 1700, 2300, 4200, 6000, 7350, 9930, 11800,
+    15600, 19600, 23700, 26400, 30500, 35400, 39200, 43100, 47800, 52800,
+    58000, 63400, 69000, 74900, 81000, 87500, 94200, 101200, 108400, 115900,
+    123700, 131800, 140200, 148800, 157700, 166900, 176400, 186200, 196300, 206700,
+    217400, 228400, 239700, 251300, 263200, 275400, 287900, 300700, 313800, 864000,
+    1018000, 1087000, 1159000, 1234000, 1312000, 1394000, 1479000, 1568000, 1662000, 1760000,
+    1863000, 1969000, 2079000, 2194000, 2313000, 2436000, 2565000, 2698000, 2837000, 3460000,
+    3630000, 3810000, 3990000, 4180000, 4380000, 4580000, 4790000, 5010000, 5230000, 8065000,
+    8520000, 8980000, 9460000, 9960000, 10480000, 11010000, 11560000, 12130000, 12720000
+];
+
+/// Total EXP a job has earned upon reaching `level`.
+pub fn exp_to_reach(level: u8) -> u64 {
+    EXP_TO_NEXT_LEVEL.iter().take(level.saturating_sub(1) as usize).map(|&exp| exp as u64).sum()
+}
+
+/// Highest level a job can reach.
+pub const LEVEL_CAP: u8 = EXP_TO_NEXT_LEVEL.len() as u8 + 1;
+
+/// The level and progress into it a job ends up at after earning `gain` EXP, stopping at the level cap.
+pub fn gain_exp(mut level: u8, exp_level: u32, gain: u64) -> (u8, u32) {
+    let mut remaining = gain + exp_level as u64;
+
+    while level < LEVEL_CAP {
+        let needed = EXP_TO_NEXT_LEVEL[level as usize - 1] as u64;
+        if remaining < needed {
+            return (level, remaining as u32);
+        }
+
+        remaining -= needed;
+        level += 1;
+    }
+
+    (LEVEL_CAP, 0)
+}
+
+/// Which kind of jobs a party is levelling together.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Disciples of War and Magic, in parties that need a tank and a healer.
+    #[default]
+    Combat,
+    /// Disciples of the Hand and Land, with no role requirements.
+    Crafting
+}
+
+impl Mode {
+    pub fn includes(&self, info: &JobInfo) -> bool {
+        match self {
+            Mode::Combat => info.is_combat(),
+            Mode::Crafting => info.is_crafting()
+        }
+    }
+
+    /// Whether configurations need exactly one tank and one healer.
+    pub fn requires_roles(&self) -> bool {
+        *self == Mode::Combat
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "combat" => Ok(Mode::Combat),
+            "crafting" => Ok(Mode::Crafting),
+            _ => Err(format!("unknown mode {} (expected combat or crafting)", s))
+        }
+    }
+}
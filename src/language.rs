@@ -0,0 +1,37 @@
+use std::str::FromStr;
+
+/// A language XIVAPI can return game data in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    French,
+    German,
+    Japanese
+}
+
+impl Language {
+    /// The code XIVAPI expects in its `language` parameter.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::French => "fr",
+            Language::German => "de",
+            Language::Japanese => "ja"
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "en" => Ok(Language::English),
+            "fr" => Ok(Language::French),
+            "de" => Ok(Language::German),
+            "ja" => Ok(Language::Japanese),
+            _ => Err(format!("unknown language {} (expected en, fr, de or ja)", s))
+        }
+    }
+}
@@ -0,0 +1,45 @@
+pub mod advice;
+pub mod buff;
+pub mod cache;
+pub mod catch_up;
+pub mod config;
+pub mod constraints;
+pub mod duty;
+pub mod error;
+pub mod explain;
+pub mod expression;
+#[cfg(feature = "fflogs")]
+pub mod fflogs;
+pub mod formatter;
+pub mod frontline;
+#[cfg(feature = "history")]
+pub mod history;
+#[cfg(feature = "network")]
+pub mod http;
+pub mod job;
+pub mod language;
+#[cfg(feature = "lodestone")]
+pub mod lodestone;
+pub mod metrics;
+pub mod optimizer;
+pub mod plan;
+pub mod projection;
+pub mod provider;
+pub mod recommend;
+pub mod reset;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "script")]
+pub mod script;
+pub mod selection;
+pub mod unlock;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "network")]
+pub mod webhook;
+pub mod world;
+pub mod xivapi;
+#[cfg(feature = "network")]
+pub mod xivapi_v2;
+
+pub use error::{Error, Result};
@@ -0,0 +1,178 @@
+use reqwest::blocking::Client;
+use scraper::{ElementRef, Html, Selector};
+use std::time::Duration;
+
+use crate::cache::Cache;
+use crate::job;
+use crate::provider::CharacterProvider;
+use crate::xivapi::{self, ClassJob, Pagination, PlayerCharacter, PlayerSearchEntry, PlayerSearchResult, ServerList};
+use crate::{Error, Result};
+
+const BASE_URL: &str = "https://na.finalfantasyxiv.com/lodestone";
+
+const SERVERS_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+const SEARCH_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+const CHARACTER_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+fn selector(selectors: &str) -> Selector {
+    Selector::parse(selectors).unwrap()
+}
+
+fn text(element: ElementRef) -> String {
+    element.text().collect::<String>().trim().to_owned()
+}
+
+/// Parses a count like "1,234" from the Lodestone, where "-" or "--" means there is nothing to count.
+fn number(text: &str) -> Option<u32> {
+    text.replace(',', "").trim().parse().ok()
+}
+
+/// Reads character data directly from the Lodestone's HTML, for when XIVAPI is unavailable.
+pub struct Lodestone {
+    cache: Cache,
+    client: Client
+}
+
+impl Lodestone {
+    pub fn new(cache: Cache, client: Client) -> Self {
+        Lodestone { cache, client }
+    }
+
+    fn get(&self, path: &str, max_age: Duration, force: bool) -> Result<Html> {
+        let url = format!("{}{}", BASE_URL, path);
+
+        let body = match self.cache.get(&url, max_age) {
+            Some(body) if !force => body,
+            _ if self.cache.is_offline() => return Err(Error::Offline(url)),
+            _ => {
+                let body = self.client.get(&url).send()?.error_for_status()?.text()?;
+                // The cache is only an optimisation, so failing to write to it is not fatal.
+                let _ = self.cache.put(&url, &body);
+                body
+            }
+        };
+
+        Ok(Html::parse_document(&body))
+    }
+
+    fn class_jobs(&self, id: u32, force: bool) -> Result<PlayerCharacter> {
+        let page = self.get(&format!("/character/{}/class_job/", id), CHARACTER_MAX_AGE, force)?;
+
+        let name = page.select(&selector(".frame__chara__name")).next()
+            .map(text)
+            .ok_or_else(|| Error::Scrape(format!("no name on the page for character {}", id)))?;
+        // Shown as e.g. "Zalera [Crystal]".
+        let server = page.select(&selector(".frame__chara__world")).next()
+            .and_then(|world| text(world).split_whitespace().next().map(str::to_owned));
+        let avatar = page.select(&selector(".frame__chara__face img")).next()
+            .and_then(|img| img.value().attr("src"))
+            .map(str::to_owned);
+
+        let mut class_jobs = Vec::new();
+        for entry in page.select(&selector(".character__job li")) {
+            let (Some(level), Some(job_name)) = (
+                entry.select(&selector(".character__job__level")).next(),
+                entry.select(&selector(".character__job__name")).next()
+            ) else {
+                continue;
+            };
+
+            let (exp_level, exp_level_max) = match entry.select(&selector(".character__job__exp")).next() {
+                Some(exp) => match text(exp).split_once('/') {
+                    Some((exp_level, exp_level_max)) => (number(exp_level), number(exp_level_max)),
+                    None => (None, None)
+                },
+                None => (None, None)
+            };
+
+            let level = number(&text(level)).unwrap_or_default() as u8;
+            let job_name = text(job_name);
+
+            // A job that hasn't been unlocked is listed under its class, which some jobs share.
+            for info in job::JOBS.iter().filter(|info| info.matches(&job_name)) {
+                class_jobs.push(ClassJob {
+                    job: info,
+                    level,
+                    exp_level,
+                    exp_level_max,
+                    job_unlocked: info.job_name.eq_ignore_ascii_case(&job_name),
+                    localised_name: None,
+                    alt: None
+                });
+            }
+        }
+
+        xivapi::merge_class_jobs(&mut class_jobs);
+
+        Ok(PlayerCharacter {
+            id,
+            class_jobs,
+            name,
+            server,
+            avatar,
+            // The portrait and bio are only on the character's profile page.
+            portrait: None,
+            bio: None,
+            buffs: Vec::new(),
+            trial: false,
+            expansion: None,
+            play_history: None,
+            role_history: None,
+            nickname: None,
+            alts: Vec::new(),
+            gear_set: None
+        })
+    }
+}
+
+impl CharacterProvider for Lodestone {
+    fn servers(&self) -> Result<ServerList> {
+        let page = self.get("/worldstatus/", SERVERS_MAX_AGE, false)?;
+        let servers = page.select(&selector(".world-list__world_name p")).map(text).collect();
+
+        Ok(ServerList::new(servers))
+    }
+
+    fn search_character(&self, name: &str, server: &str) -> Result<PlayerSearchResult> {
+        let page = self.get(&format!("/character/?q={}&worldname={}", name, server), SEARCH_MAX_AGE, false)?;
+
+        let mut results = Vec::new();
+        for entry in page.select(&selector("a.entry__link")) {
+            let id = entry.value().attr("href")
+                .and_then(|href| href.trim_end_matches('/').rsplit('/').next())
+                .and_then(|id| id.parse().ok());
+            let entry_name = entry.select(&selector(".entry__name")).next().map(text);
+            // Shown as e.g. "Zalera [Crystal]".
+            let world = entry.select(&selector(".entry__world")).next().map(text);
+            let avatar = entry.select(&selector(".entry__chara__face img")).next()
+                .and_then(|img| img.value().attr("src"))
+                .map(str::to_owned);
+
+            if let (Some(id), Some(entry_name)) = (id, entry_name) {
+                // The Lodestone's search also returns partial matches.
+                if entry_name.eq_ignore_ascii_case(name) {
+                    results.push(PlayerSearchEntry { id, name: entry_name, server: world, avatar });
+                }
+            }
+        }
+
+        Ok(PlayerSearchResult {
+            pagination: Pagination {
+                results: results.len() as u8
+            },
+            results
+        })
+    }
+
+    fn character(&self, id: u32) -> Result<PlayerCharacter> {
+        self.class_jobs(id, false)
+    }
+
+    fn refresh_character(&self, id: u32) -> Result<PlayerCharacter> {
+        self.class_jobs(id, true)
+    }
+
+    fn character_age(&self, id: u32) -> Option<Duration> {
+        self.cache.age(&format!("{}/character/{}/class_job/", BASE_URL, id))
+    }
+}
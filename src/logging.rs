@@ -0,0 +1,96 @@
+use std::fmt::{self, Write as _};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+/// Writes this crate's events at or above a level, one per line, stamped with the time since the program started.
+struct Logger {
+    level: Level,
+    started: Instant,
+    out: Mutex<Box<dyn Write + Send>>,
+    next_span: AtomicU64
+}
+
+/// Collects an event's fields into `message key=value...`.
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+impl Subscriber for Logger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        // Dependencies log far more than is useful for understanding a run.
+        *metadata.level() <= self.level && metadata.target().starts_with("xiv_levelling")
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::from_level(self.level))
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(String::new());
+        event.record(&mut fields);
+
+        let metadata = event.metadata();
+        let elapsed = self.started.elapsed().as_secs_f64();
+        // Logging must never take the program down with it.
+        let _ = writeln!(self.out.lock().unwrap(), "{:>9.3}s {:>5} {}: {}", elapsed, metadata.level(), metadata.target(), fields.0);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+/// Sends log events to `log_file`, or stderr if it isn't given. `verbose` is how many times -v was passed: -v
+/// shows info, -vv debug and -vvv trace. Otherwise only warnings are shown, or only errors when `quiet`, or info
+/// too when writing to a file.
+pub fn init(verbose: u8, quiet: bool, log_file: Option<&Path>) -> io::Result<()> {
+    let level = match verbose {
+        0 if quiet && log_file.is_none() => Level::ERROR,
+        0 if log_file.is_none() => Level::WARN,
+        0 | 1 => Level::INFO,
+        2 => Level::DEBUG,
+        _ => Level::TRACE
+    };
+
+    let out: Box<dyn Write + Send> = match log_file {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stderr())
+    };
+
+    let logger = Logger {
+        level,
+        started: Instant::now(),
+        out: Mutex::new(out),
+        // Span IDs must be non-zero.
+        next_span: AtomicU64::new(1)
+    };
+
+    // Only fails if a subscriber has already been set, in which case that one is kept.
+    let _ = tracing::subscriber::set_global_default(logger);
+    Ok(())
+}
@@ -1,252 +1,2981 @@
-use serde::Deserialize;
-use std::cmp::Ordering;
-use std::collections::BinaryHeap;
-use std::io;
+use clap::builder::{FalseyValueParser, PossibleValuesParser};
+use clap::error::ErrorKind;
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::Shell;
+use indicatif::{ProgressBar, ProgressStyle};
+use reqwest::blocking::Client;
+use reqwest::Proxy;
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, IsTerminal};
+use std::iter;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Deserialize, Debug)]
-#[serde(transparent)]
-struct ServerList {
-    servers: Vec<String>
+use xiv_levelling::buff::BuffFlag;
+use xiv_levelling::cache::Cache;
+use xiv_levelling::catch_up;
+use xiv_levelling::config::{Config, Preset, Profile};
+use xiv_levelling::constraints::{Composition, Constraints, Lock, RolePreference};
+use xiv_levelling::job::{self, ExpansionFlag, Mode, Role};
+use xiv_levelling::duty::{self, Duty, DutyKind};
+use xiv_levelling::explain;
+use xiv_levelling::expression::ScoreExpression;
+#[cfg(feature = "fflogs")]
+use xiv_levelling::fflogs::FfLogs;
+use xiv_levelling::formatter::{self, Webhook};
+use xiv_levelling::http;
+use xiv_levelling::language::Language;
+use xiv_levelling::optimizer::{self, Assignments, Balance, CustomScore, Metric, PartyConfig, Progress, Scoring, SearchStats};
+use xiv_levelling::plan::{self, Favourite};
+use xiv_levelling::projection;
+#[cfg(feature = "history")]
+use xiv_levelling::history::{self, History, Milestone};
+#[cfg(feature = "history")]
+use xiv_levelling::xivapi::RoleHistory;
+#[cfg(feature = "lodestone")]
+use xiv_levelling::lodestone::Lodestone;
+#[cfg(feature = "lodestone")]
+use xiv_levelling::provider::FallbackProvider;
+use xiv_levelling::provider::{CharacterProvider, MockProvider};
+#[cfg(feature = "script")]
+use xiv_levelling::script::ScoreScript;
+use xiv_levelling::recommend;
+use xiv_levelling::selection::{self, LeftOut};
+use xiv_levelling::unlock;
+use xiv_levelling::webhook;
+use xiv_levelling::xivapi::{self, ClassJob, PlayerCharacter, ServerList, XivApi};
+use xiv_levelling::xivapi_v2::{ApiVersion, XivApiV2};
+
+use calendar::{Date, TimeOfDay};
+use exit::Exit;
+#[cfg(any(feature = "xlsx", feature = "png"))]
+use export::Export;
+use i18n::Message;
+use output::{Format, Glyphs, Order};
+use servers::Servers;
+use session::Session;
+use store::{CharacterStore, StoredCharacter};
+
+/// Prints a message for the user, keeping stdout clear when it carries machine-readable output and saying nothing in
+/// quiet mode.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if output::is_quiet() {
+            // Nothing but the results is printed.
+        } else if output::is_machine_readable() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
+
+mod audit;
+#[cfg(feature = "discord")]
+mod bot;
+mod calendar;
+#[cfg(feature = "png")]
+mod card;
+mod exit;
+#[cfg(any(feature = "xlsx", feature = "png"))]
+mod export;
+#[cfg(feature = "graphql")]
+mod graphql;
+mod i18n;
+mod interrupt;
+mod logging;
+mod memory;
+mod output;
+#[cfg(feature = "notify")]
+mod notify;
+mod pager;
+mod party_file;
+#[cfg(feature = "history")]
+mod progress;
+mod prompt;
+#[cfg(feature = "server")]
+mod server;
+mod servers;
+mod session;
+mod store;
+#[cfg(feature = "tui")]
+mod tui;
+#[cfg(feature = "self-update")]
+mod update;
+#[cfg(any(feature = "discord", feature = "server"))]
+mod verify;
+mod wizard;
+
+#[global_allocator]
+static ALLOCATOR: memory::CountingAllocator = memory::CountingAllocator;
+
+const MAX_PROJECTED_RUNS: usize = 200;
+const PROGRESS_THRESHOLD: u64 = 1_000_000;
+
+/// Most sign-ups --event forms into parties, enough for three full parties.
+const MAX_EVENT_SIGN_UPS: usize = 24;
+
+/// Recorded sessions --weight-fairness looks back over for each member, around two months of weekly ones.
+#[cfg(feature = "history")]
+const FAIRNESS_SESSIONS: usize = 8;
+
+/// What the environment variables any option can also be given by start with, e.g. `XIV_LEVELLING_SERVER`.
+const ENV_PREFIX: &str = "XIV_LEVELLING";
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Find the most balanced FFXIV party configurations for levelling",
+    after_help = "Every option can also be given by an environment variable, named as shown in its help, e.g. \
+        XIV_LEVELLING_SERVER=Zalera. Options of subcommands have the subcommand's name too, e.g. \
+        XIV_LEVELLING_SERVE_PORT. An option that can be repeated takes one value from its variable. Options on the \
+        command line take precedence"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Browse the results in a full-screen terminal UI
+    #[cfg(feature = "tui")]
+    #[arg(long, conflicts_with = "quiet")]
+    tui: bool,
+
+    /// Read the party from character JSON files saved from XIVAPI in this directory, instead of the Lodestone
+    #[arg(long, value_name = "DIR", conflicts_with = "free_company")]
+    from_dir: Option<PathBuf>,
+
+    /// Run on a bundled party of fictional characters without going online at all, to see what the tool does
+    #[arg(long, conflicts_with_all = ["from_dir", "characters_file", "resume", "preset", "free_company"])]
+    demo: bool,
+
+    /// Language for job names and messages: en, fr, de or ja [default: detected from the locale]
+    #[arg(long)]
+    language: Option<Language>,
+
+    /// Version of XIVAPI to use: v1, or v2 for the beta that is replacing it. v2 only lists the servers, so characters
+    /// are found on the Lodestone instead
+    #[arg(long, value_name = "VERSION", default_value = "v1")]
+    api: ApiVersion,
+
+    /// File of characters to look up instead of asking, one Name or Name@World per line, followed by any alts of the
+    /// same player to pick from, e.g. "Alice One + Alicia Two@Zalera". Names are also read from stdin when it isn't a
+    /// terminal
+    #[arg(long, value_name = "FILE")]
+    characters_file: Option<PathBuf>,
+
+    /// Pick up the last session at the search, with the same characters and constraints
+    #[arg(long, conflicts_with = "characters_file")]
+    resume: bool,
+
+    /// Use the constraints and weights of this profile from the config file, e.g. --profile casual for
+    /// [profile.casual]. Options given on the command line take precedence
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Use the party of this name from the config file, e.g. --preset tuesday-static for [party.tuesday-static],
+    /// with its characters, server, members and locks
+    #[arg(long, value_name = "NAME", conflicts_with = "resume")]
+    preset: Option<String>,
+
+    /// FFXIV server the characters are on [default: ask]
+    #[arg(long)]
+    server: Option<String>,
+
+    /// Config file containing the saved roster [default: config.toml in the user config directory]
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Keep responses from XIVAPI, the Lodestone and FFLogs in this directory [default: xiv-levelling in the user
+    /// cache directory]
+    #[arg(long, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+
+    /// Send every request through this proxy, e.g. http://proxy.example.com:8080 [default: HTTP_PROXY or
+    /// HTTPS_PROXY]
+    #[arg(long, value_name = "URL", value_parser = parse_proxy)]
+    proxy: Option<Proxy>,
+
+    /// Give up on a request after this long, e.g. 30s or 2m [default: 30s]
+    #[arg(long, value_name = "DURATION", value_parser = parse_interval)]
+    timeout: Option<Duration>,
+
+    /// Never touch the network, using only the server list and characters cached by earlier runs, however old.
+    /// How old each member's data is gets shown before the search
+    #[arg(long, conflicts_with_all = ["watch", "webhook"])]
+    offline: bool,
+
+    /// Ignore everything cached and fetch it all again, keeping the new responses for later runs
+    #[arg(long, visible_alias = "no-cache", conflicts_with = "offline")]
+    refresh_all: bool,
+
+    /// Only consider the given job for a character, e.g. --lock "Alice=Warrior" or "Alice=WAR" (can be repeated)
+    #[arg(long = "lock", value_name = "NAME=JOB")]
+    locks: Vec<Lock>,
+
+    /// Give a character an EXP buff: road-to-90, free-company, rested or food, e.g. --buff "Alice=rested" (can be repeated)
+    #[arg(long = "buff", value_name = "NAME=BUFF")]
+    buffs: Vec<BuffFlag>,
+
+    /// Mark a character as playing on the free trial, which locks some jobs and caps duties at level 70 (can be repeated)
+    #[arg(long = "trial", value_name = "NAME")]
+    trials: Vec<String>,
+
+    /// The latest expansion a character owns: arr, hw, sb, shb or ew, e.g. --owns "Alice=shb". Jobs and duties from
+    /// later expansions aren't suggested for them (can be repeated)
+    #[arg(long = "owns", value_name = "NAME=EXPANSION")]
+    expansions: Vec<ExpansionFlag>,
+
+    /// Only consider jobs at or above this level [default: min_level from the config file]
+    #[arg(long)]
+    min_level: Option<u8>,
+
+    /// Consider jobs at every level, ignoring the config file's min_level, e.g. to level from scratch
+    #[arg(long, conflicts_with = "min_level")]
+    all_levels: bool,
+
+    /// Only consider jobs at or below this level
+    #[arg(long)]
+    max_level: Option<u8>,
+
+    /// Only suggest jobs that can enter this duty without being synced down, preferring those closest to its
+    /// level cap
+    #[arg(long, value_name = "DUTY", value_parser = parse_duty, conflicts_with = "target_level")]
+    target_duty: Option<&'static Duty>,
+
+    /// Like --target-duty, for the highest-level dungeon a party at this level can enter
+    #[arg(long, value_name = "LEVEL")]
+    target_level: Option<u8>,
+
+    /// Only show configurations with at least this average level
+    #[arg(long)]
+    min_avg: Option<u32>,
+
+    /// Only show configurations with at most this average level
+    #[arg(long)]
+    max_avg: Option<u32>,
+
+    /// Only show configurations whose highest and lowest levels are at most this far apart, since a bigger gap
+    /// wastes too much EXP to the level sync
+    #[arg(long, value_name = "LEVELS")]
+    max_gap: Option<u8>,
+
+    /// Only show the configurations no other beats at both a lower spread and a higher average level, from the lowest
+    /// spread up, to see the trade-off between the two rather than one order weighing them together
+    #[arg(long, conflicts_with_all = ["pager", "compare", "project", "split", "event", "format"])]
+    pareto: bool,
+
+    /// Enter five to eight characters and split them into two light parties
+    #[arg(long)]
+    split: bool,
+
+    /// Enter the sign-ups for a free company event, eight to 24 characters, and form as many light parties as their
+    /// roles allow, saying why anyone was left out
+    #[arg(long, conflicts_with_all = ["split", "free_company", "duty_support", "partial"])]
+    event: bool,
+
+    /// Once the characters are entered, walk through the composition, level band, locks and weights step by step,
+    /// seeing how many configurations each choice keeps, instead of giving them all as flags
+    #[arg(long, conflicts_with_all = ["quiet", "split", "event"])]
+    wizard: bool,
+
+    /// Pick the best party from the members of this free company instead of entering characters
+    #[arg(long, value_name = "NAME")]
+    free_company: Option<String>,
+
+    /// Size of the party to pick with --free-company: 4, or 8 for a full party
+    #[arg(long, default_value_t = 4)]
+    fc_size: usize,
+
+    /// Search for --free-company parties by simulated annealing, which takes longer but can find better parties
+    /// than the quick search when the roster is large
+    #[arg(long, requires = "free_company")]
+    anneal: bool,
+
+    /// Number of moves the --anneal search makes
+    #[arg(long, value_name = "N", default_value_t = 20_000, requires = "anneal")]
+    anneal_steps: u64,
+
+    /// Seed for the moves the --anneal search makes, which always finds the same parties for the same seed [default:
+    /// random]
+    #[arg(long, value_name = "N", requires = "anneal")]
+    anneal_seed: Option<u64>,
+
+    /// Number of configurations to print
+    #[arg(long, default_value_t = 5)]
+    top: usize,
+
+    /// Page through configurations one at a time (n/p to move, g <n> to jump, q to quit)
+    #[arg(long)]
+    pager: bool,
+
+    /// Also show configurations that only differ by swapping a member between jobs of the same role and level
+    #[arg(long)]
+    show_duplicates: bool,
+
+    /// Show configurations where two members swap roles separately, rather than as a note on the better one
+    #[arg(long)]
+    show_swaps: bool,
+
+    /// Let the same character be entered more than once, e.g. to fill two slots with one player's alts
+    #[arg(long)]
+    allow_same_character: bool,
+
+    /// Break down each configuration's score: the gaps between members, the average and every weighted term
+    #[arg(long)]
+    explain: bool,
+
+    /// After searching, say how many combinations were tried, what ruled the rest out, how long it took and the most
+    /// memory used, e.g. to find out why few configurations were found
+    #[arg(long)]
+    stats: bool,
+
+    /// Most configurations to keep for --pager and --tui, to cap memory use on large searches
+    #[arg(long, default_value_t = 10000)]
+    max_results: usize,
+
+    /// Stop searching after this long, e.g. 5s or 1m, and show the best configurations found so far
+    #[arg(long, value_name = "DURATION", value_parser = parse_interval)]
+    max_time: Option<Duration>,
+
+    /// How to measure the level spread: pairwise, range, stddev or max-gap
+    #[arg(long, default_value = "pairwise")]
+    metric: Metric,
+
+    /// What to level: combat jobs, or crafting to pair up crafters and gatherers regardless of role
+    #[arg(long, default_value = "combat")]
+    mode: Mode,
+
+    /// Which role compositions to allow: standard (one tank and one healer) or any, for unsynced content [default:
+    /// standard, or the profile's]
+    #[arg(long, conflicts_with = "duty_support")]
+    comp: Option<Composition>,
+
+    /// Also recommend the job each member should take into the daily Frontline
+    #[arg(long)]
+    frontline: bool,
+
+    /// Also suggest jobs members haven't unlocked yet but could, which start at a high enough level to open up more
+    /// of the best configurations
+    #[arg(long)]
+    suggest_unlocks: bool,
+
+    /// Also recommend the one job each member could start levelling that would most improve the best configurations
+    /// once it caught up with the party
+    #[arg(long)]
+    recommend_jobs: bool,
+
+    /// Keep running, refreshing the party on this interval (e.g. 30m, 6h or 1d) and printing whenever the best
+    /// configuration changes
+    #[arg(long, value_name = "INTERVAL", value_parser = parse_interval)]
+    watch: Option<Duration>,
+
+    /// Also post changes found by --watch to this Discord webhook
+    #[arg(long, value_name = "URL", requires = "watch")]
+    webhook: Option<String>,
+
+    /// Level two or three characters together with Duty Support NPCs filling the missing roles
+    #[arg(long, conflicts_with = "split")]
+    duty_support: bool,
+
+    /// Level two or three characters through the Duty Finder, letting it fill a missing tank or healer too, though
+    /// configurations only leaving DPS slots are preferred since those queues pop fastest
+    #[arg(long, conflicts_with_all = ["split", "duty_support"])]
+    partial: bool,
+
+    /// How strongly --partial avoids leaving a tank or healer slot for the Duty Finder to fill
+    #[arg(long, default_value_t = 10.0, requires = "partial")]
+    weight_queue: f64,
+
+    /// Also consider Blue Mage, which caps at level 70 and can only be run as a premade party
+    #[arg(long)]
+    blu: bool,
+
+    /// What to balance: level, or exp to also count progress within a level
+    #[arg(long, default_value = "level")]
+    balance: Balance,
+
+    /// How strongly to prefer configurations with a tighter level spread [default: 1, or the profile's]
+    #[arg(long)]
+    weight_var: Option<f64>,
+
+    /// How strongly to prefer configurations with a higher average level [default: 0, or the profile's]
+    #[arg(long)]
+    weight_avg: Option<f64>,
+
+    /// How strongly to prefer configurations where more members earn the armoury bonus [default: 0, or the
+    /// profile's]
+    #[arg(long)]
+    weight_bonus: Option<f64>,
+
+    /// How strongly to prefer configurations whose DPS cover more sub-roles (melee, physical ranged and caster)
+    /// [default: 0, or the profile's]
+    #[arg(long)]
+    weight_diversity: Option<f64>,
+
+    /// Only suggest configurations where every DPS is a different sub-role: melee, physical ranged or caster
+    #[arg(long)]
+    diverse_dps: bool,
+
+    /// How strongly to avoid configurations where more than one member plays the same job [default: 0, or the
+    /// profile's]
+    #[arg(long)]
+    weight_duplicate: Option<f64>,
+
+    /// Only suggest configurations where every member plays a different job
+    #[arg(long)]
+    unique_jobs: bool,
+
+    /// The role Duty Roulette is rewarding as in need today: tank, healer or dps. Configurations where the member
+    /// furthest behind plays it are preferred, so the bonus goes to whoever needs it most
+    #[arg(long, value_name = "ROLE")]
+    in_need: Option<Role>,
+
+    /// How strongly to prefer configurations where the member furthest behind plays the --in-need role
+    #[arg(long, default_value_t = 5.0)]
+    weight_in_need: f64,
+
+    /// FFLogs API client ID, to look up which jobs each member has been playing lately. Clients can be registered at
+    /// https://www.fflogs.com/api/clients
+    #[cfg(feature = "fflogs")]
+    #[arg(long, requires = "fflogs_client_secret")]
+    fflogs_client_id: Option<String>,
+
+    /// FFLogs API client secret, to go with --fflogs-client-id
+    #[cfg(feature = "fflogs")]
+    #[arg(long, requires = "fflogs_client_id")]
+    fflogs_client_secret: Option<String>,
+
+    /// How strongly to prefer jobs members have played in more of their recent FFLogs reports
+    #[cfg(feature = "fflogs")]
+    #[arg(long, default_value_t = 5.0)]
+    weight_familiarity: f64,
+
+    /// Expression scoring each configuration in place of the weights, where lower is better, e.g.
+    /// "2*var - avg + 10*bonus_count". It can use var, avg, min, max, range, bonus_count and score (the weighted score)
+    #[arg(long, value_name = "EXPR", allow_hyphen_values = true)]
+    score: Option<ScoreExpression>,
+
+    /// Randomise the order of configurations that tie on score and average level, so that the same party isn't
+    /// always suggested the same one
+    #[arg(long)]
+    shuffle_ties: bool,
+
+    /// Seed for --shuffle-ties, which always gives the same order for the same seed [default: random]
+    #[arg(long, value_name = "N", requires = "shuffle_ties")]
+    seed: Option<u64>,
+
+    /// Rhai script scoring each configuration in place of the weights, where lower is better
+    #[cfg(feature = "script")]
+    #[arg(long, value_name = "PATH", value_parser = parse_score_script, conflicts_with = "score")]
+    score_script: Option<Arc<ScoreScript>>,
+
+    /// Instead of listing configurations, simulate runs of a duty by the configuration at this rank until everyone is the same level
+    #[arg(long, value_name = "RANK")]
+    project: Option<usize>,
+
+    /// Estimate how many weeks each configuration takes to reach the level cap at this many sessions a week (1-7),
+    /// each a roulette for its daily bonus then a few dungeon runs
+    #[arg(long, value_name = "SESSIONS", value_parser = parse_sessions_per_week)]
+    sessions_per_week: Option<u32>,
+
+    /// Instead of listing configurations, show the configurations at these two ranks side by side
+    #[arg(long, num_args = 2, value_names = ["RANK", "RANK"], conflicts_with_all = ["project", "pager"])]
+    compare: Option<Vec<usize>>,
+
+    /// Also export the results in this format: xlsx for a spreadsheet of the level matrix and the ranked
+    /// configurations, or png for an image of the best configuration to share
+    #[cfg(any(feature = "xlsx", feature = "png"))]
+    #[arg(long, value_name = "FORMAT")]
+    export: Option<Export>,
+
+    /// Where to write the file for --export [default: levelling.xlsx or levelling.png]
+    #[cfg(any(feature = "xlsx", feature = "png"))]
+    #[arg(long, value_name = "PATH")]
+    export_path: Option<PathBuf>,
+
+    /// Duty to simulate with --project [default: the highest dungeon the configuration can enter]
+    #[arg(long)]
+    duty: Option<String>,
+
+    /// Show jobs by their three-letter abbreviations, e.g. WAR
+    #[arg(long)]
+    abbrev: bool,
+
+    /// How to mark each member's role: unicode (⛊/✚/⚔), ascii ([T]/[H]/[D]) or none
+    #[arg(long, default_value = "ascii")]
+    glyphs: Glyphs,
+
+    /// Don't record looked up characters in the history database
+    #[cfg(feature = "history")]
+    #[arg(long)]
+    no_history: bool,
+
+    /// Record in the history database that the party played the configuration ranked RANK this session, for
+    /// --weight-fairness to take into account in later ones
+    #[cfg(feature = "history")]
+    #[arg(long, value_name = "RANK", conflicts_with = "no_history")]
+    played: Option<usize>,
+
+    /// Also post the milestones members reach, like a job hitting Lv 60 or a first healer, to --webhook
+    #[cfg(feature = "history")]
+    #[arg(long, requires = "webhook", conflicts_with = "no_history")]
+    announce_milestones: bool,
+
+    /// How strongly to prefer configurations that give members their favourite role (that of their highest-level
+    /// job) if they've rarely had it in recorded sessions, and rotate them off roles they keep being given [default:
+    /// off]
+    #[cfg(feature = "history")]
+    #[arg(long, value_name = "WEIGHT")]
+    weight_fairness: Option<f64>,
+
+    /// Don't remember looked up characters, which finds them again without searching and shows how their levels have
+    /// changed since
+    #[arg(long)]
+    no_store: bool,
+
+    /// Print the best configuration as a macro announcing it in party chat, ready to paste into the game. With --quiet,
+    /// nothing else is printed
+    #[arg(long = "macro", conflicts_with_all = ["pager", "format", "watch"])]
+    chat_macro: bool,
+
+    /// How to write configurations: text, ndjson to stream every valid configuration as it's found, json, markdown or
+    /// csv for the best --top configurations, or webhook to post them to --webhook
+    #[arg(long, default_value = "text")]
+    format: Format,
+
+    /// What to show the best configurations in order of: score, spread for the lowest first, avg or min-level for
+    /// the highest first, or waste for the least EXP lost to level sync first
+    #[arg(long, default_value = "score")]
+    sort: Order,
+
+    /// Log what's going on to stderr: -v for info, -vv for debug, such as API requests and cache hits, and -vvv
+    /// for everything
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Write the log to FILE instead of stderr, at info level unless -v asks for more
+    #[arg(long, value_name = "FILE")]
+    log_file: Option<PathBuf>,
+
+    /// Print nothing but the best configurations, one JSON object per line, for scripts. Characters must come from
+    /// --characters-file or stdin. If a run fails, the last line is {"error": {"kind": ..., "code": ...}}, and the
+    /// exit code says why: 3 (no-configurations) if no configuration was found, 4 (not-found) if a character wasn't
+    /// found, 5 (unreachable) if XIVAPI couldn't be reached, 6 (invalid-constraints) if the constraints are invalid,
+    /// 7 (ambiguous) if several characters had the name, 8 (server-not-found) if a server doesn't exist, 9
+    /// (profile-private) if a character's jobs are hidden, 10 (rate-limited) if XIVAPI turned requests away, 11
+    /// (not-cached) if something wasn't cached with --offline and 12 (invalid-config) if the config file is invalid
+    #[arg(short, long, conflicts_with_all = ["pager", "split", "free_company", "watch", "project", "compare", "frontline"])]
+    quiet: bool,
+
+    /// Disable coloured output
+    #[arg(long)]
+    no_color: bool
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run as a Discord bot providing the /levelling command, and /register and /verify for claiming characters
+    #[cfg(feature = "discord")]
+    Bot {
+        /// Discord bot token
+        #[arg(long)]
+        token: String
+    },
+    /// Serve a REST API for finding configurations, at POST /party/optimize, and for registering characters by their
+    /// Lodestone bio, at POST /verify/start and /verify/finish. Prometheus metrics are at GET /metrics, and a GraphQL
+    /// endpoint at /graphql if built with the graphql feature
+    #[cfg(feature = "server")]
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16
+    },
+    /// Chart how much each character has levelled, from the history database
+    #[cfg(feature = "history")]
+    Progress {
+        /// Characters to chart [default: everyone in the history]
+        names: Vec<String>,
+        /// How many days back to chart
+        #[arg(long, default_value_t = 30)]
+        days: u64
+    },
+    /// Find the party as usual, then lay out a week of sessions: which configuration and dungeon to run each day for the
+    /// most EXP, taking turns at letting each member play their favourite role
+    Plan {
+        /// How many days to plan
+        #[arg(long, default_value_t = 7)]
+        days: usize,
+        /// Dungeon runs in each session
+        #[arg(long, default_value_t = 4)]
+        runs: u32,
+        /// A member's favourite role, e.g. --favourite "Alice=tank" [default: the role of their highest-level job]
+        /// (can be repeated)
+        #[arg(long = "favourite", value_name = "NAME=ROLE")]
+        favourites: Vec<Favourite>,
+        /// Also write the plan to this iCalendar file, with an event for each session
+        #[arg(long, value_name = "FILE")]
+        ics: Option<PathBuf>,
+        /// Day of the first session, for the calendar [default: today]
+        #[arg(long, value_name = "YYYY-MM-DD", requires = "ics")]
+        start: Option<Date>,
+        /// Local time each session starts at, for the calendar
+        #[arg(long, value_name = "HH:MM", default_value = "20:00", requires = "ics")]
+        at: TimeOfDay
+    },
+    /// Find the party as usual, then write it to a party file for optimize instead of searching. The file gives each
+    /// member's job levels, so it can be edited by hand before searching
+    Resolve {
+        /// File to write the party to [default: stdout]
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>
+    },
+    /// Search for the configurations of the party in a party file written by resolve, without looking anyone up
+    Optimize {
+        /// The party file, or - to read it from stdin
+        #[arg(value_name = "FILE")]
+        party: PathBuf
+    },
+    /// List the content earning the most EXP for a party synced to a level, e.g. the Synced Level of a configuration
+    Advise {
+        level: u8
+    },
+    /// Print a completion script for a shell, including the saved roster's names and any cached server names
+    Completions {
+        shell: Shell
+    },
+    /// Look through or clear what is kept locally: the characters looked up before and the cached responses
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand
+    },
+    /// Replace this binary with the latest GitHub release
+    #[cfg(feature = "self-update")]
+    SelfUpdate
+}
+
+#[derive(Subcommand)]
+enum CacheCommand {
+    /// List the stored characters and cached responses, and how old each is
+    Ls,
+    /// Print the levels of a stored character, or the body of a cached response by the name ls gives it
+    Show {
+        name: String
+    },
+    /// Delete cached responses, all of them unless narrowed down
+    Prune {
+        /// Only delete those older than this, e.g. 7d
+        #[arg(long, value_name = "DURATION", value_parser = parse_interval)]
+        older_than: Option<Duration>,
+        /// Only delete those whose name contains this, ignoring case, e.g. a character's ID
+        #[arg(long, value_name = "TEXT")]
+        matching: Option<String>,
+        /// Also forget the stored characters picked out the same way, so they are searched for again
+        #[arg(long)]
+        characters: bool
+    }
+}
+
+/// Lets every option of `command` and its subcommands be given by an environment variable instead, named after the
+/// option and the subcommands it's in, e.g. `XIV_LEVELLING_SERVE_PORT` for `serve --port`.
+fn with_env(command: clap::Command, prefix: &str) -> clap::Command {
+    let command = command.mut_args(|arg| {
+        let Some(long) = arg.get_long().filter(|long| !matches!(*long, "help" | "version")) else {
+            return arg;
+        };
+        let name = format!("{}_{}", prefix, long.to_uppercase().replace('-', "_"));
+        // The values are left out of --help, as some are secrets like the bot's token.
+        let arg = arg.env(name).hide_env_values(true);
+        // Flags are only turned on by their variable being set to something other than false, no, off or 0.
+        match arg.get_action() {
+            ArgAction::SetTrue => arg.value_parser(FalseyValueParser::new()),
+            _ => arg
+        }
+    });
+
+    command.mut_subcommands(|subcommand| {
+        let prefix = format!("{}_{}", prefix, subcommand.get_name().to_uppercase().replace('-', "_"));
+        with_env(subcommand, &prefix)
+    })
+}
+
+fn main() {
+    let matches = with_env(Cli::command(), ENV_PREFIX).get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    if cli.no_color {
+        colored::control::set_override(false);
+    }
+
+    if let Err(e) = logging::init(cli.verbose, cli.quiet, cli.log_file.as_deref()) {
+        eprintln!("Failed to open the log file: {}", e);
+    }
+
+    i18n::init(cli.language.unwrap_or_else(i18n::detect));
+    output::set_abbreviate(cli.abbrev);
+    output::set_glyphs(cli.glyphs);
+    output::set_duty_support(cli.duty_support);
+    output::set_format(&cli.format);
+    // A party file written to stdout is read by whatever it's piped into, so messages have to stay out of it.
+    if matches!(cli.command, Some(Command::Resolve { output: None })) {
+        output::reserve_stdout();
+    }
+    output::set_quiet(cli.quiet);
+    if let Some(sessions) = cli.sessions_per_week {
+        output::set_sessions_per_week(sessions);
+    }
+
+    // One client is shared by every request, so connections to the same server are reused.
+    let client = match http::client(&http_settings(&cli)) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to set up HTTP requests: {}", e);
+            process::exit(Exit::Failure.code());
+        }
+    };
+
+    if let Format::Formatter(name) = &cli.format {
+        let formatters = formatters(&cli, &client);
+        if name == "webhook" && cli.webhook.is_none() {
+            Cli::command().error(ErrorKind::MissingRequiredArgument, "--format webhook needs --webhook").exit();
+        } else if formatters.get(name).is_none() {
+            let names: Vec<&str> = formatters.names().collect();
+            let message = format!("unknown format {} (expected text, ndjson, {})", name, names.join(", "));
+            Cli::command().error(ErrorKind::InvalidValue, message).exit();
+        }
+    }
+
+    match cli.command.take() {
+        #[cfg(feature = "discord")]
+        Some(Command::Bot { token }) => bot::run(&token, base_cache(&cli), client),
+        #[cfg(feature = "server")]
+        Some(Command::Serve { port }) => server::run(port, base_cache(&cli), client),
+        #[cfg(feature = "history")]
+        Some(Command::Progress { names, days }) => progress::run(&names, days),
+        Some(Command::Advise { level }) => output::print_advice(level),
+        Some(Command::Completions { shell }) => print_completions(&cli, client, shell),
+        Some(Command::Cache { command }) => audit::run(&base_cache(&cli), command),
+        #[cfg(feature = "self-update")]
+        Some(Command::SelfUpdate) => update::run(),
+        // These need the party found or read first, so are handled once the search gets that far.
+        Some(command @ (Command::Plan { .. } | Command::Resolve { .. } | Command::Optimize { .. })) => {
+            cli.command = Some(command);
+            search(&cli, &client);
+        }
+        None => search(&cli, &client)
+    }
+}
+
+fn search(cli: &Cli, client: &Client) {
+    if let Err(exit) = run_interactive(cli, client) {
+        // Scripts reading JSON get the reason as JSON too, rather than having to go by the exit code alone.
+        if cli.quiet || cli.format == Format::Ndjson {
+            println!("{}", output::error_json(exit));
+        }
+        process::exit(exit.code());
+    }
+}
+
+/// The names given by --characters-file, the preset or piped into stdin, or `None` if they should be asked for.
+/// Blank lines and lines starting with # are skipped, and piped names stop at the first blank line.
+fn batch_names(cli: &Cli, preset: Option<&Preset>) -> io::Result<Option<Vec<String>>> {
+    if let Some(path) = &cli.characters_file {
+        let contents = fs::read_to_string(path)?;
+        return Ok(Some(contents.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_owned)
+            .collect()));
+    }
+
+    if let Some(preset) = preset.filter(|preset| !preset.characters.is_empty()) {
+        return Ok(Some(preset.characters.clone()));
+    }
+
+    if io::stdin().is_terminal() {
+        return Ok(None);
+    }
+
+    let mut names = Vec::new();
+    for line in io::stdin().lines() {
+        let line = line?;
+        let name = line.trim();
+        if name.is_empty() {
+            break;
+        }
+        if !name.starts_with('#') {
+            names.push(name.to_owned());
+        }
+    }
+    Ok(Some(names))
+}
+
+/// Prints completions for `shell`. Roster, stored character and server names can't be looked up as the user types,
+/// so the ones known now are written into the script.
+fn print_completions(cli: &Cli, client: Client, shell: Shell) {
+    let config = cli.config.clone().or_else(Config::default_path)
+        .and_then(|path| Config::load(&path).ok())
+        .unwrap_or_default();
+    let mut names: Vec<String> = config.members.keys().cloned().collect();
+    let store = CharacterStore::default_path().and_then(|path| CharacterStore::load(&path).ok()).unwrap_or_default();
+    names.extend(store.names().filter(|name| !config.members.contains_key(*name)).map(str::to_owned));
+    let api = XivApi::new(base_cache(cli), cli.language.unwrap_or_else(i18n::detect), client);
+    let servers = match cli.api {
+        ApiVersion::V1 => api.cached_servers(),
+        ApiVersion::V2 => XivApiV2::new(api).cached_servers()
+    }
+        .map(|server_list| server_list.names().to_vec())
+        .unwrap_or_default();
+
+    let mut command = Cli::command();
+    if !servers.is_empty() {
+        command = command.mut_arg("server", |arg| arg.value_parser(PossibleValuesParser::new(servers)));
+    }
+    if !names.is_empty() {
+        command = command.mut_arg("trials", |arg| arg.value_parser(PossibleValuesParser::new(names.clone())));
+        #[cfg(feature = "history")]
+        {
+            command = command.mut_subcommand("progress", |progress| {
+                progress.mut_arg("names", |arg| arg.value_parser(PossibleValuesParser::new(names)))
+            });
+        }
+    }
+
+    clap_complete::generate(shell, &mut command, "xiv-levelling", &mut io::stdout());
+}
+
+/// The cache of responses in `--cache-dir`, or the user cache directory.
+fn base_cache(cli: &Cli) -> Cache {
+    cli.cache_dir.clone().map_or_else(Cache::new, Cache::in_dir)
+}
+
+/// The cache of responses, which is all there is to go on with `--offline`, and ignored with `--refresh-all`.
+fn cache(cli: &Cli) -> Cache {
+    if cli.offline {
+        base_cache(cli).offline()
+    } else if cli.refresh_all {
+        base_cache(cli).refreshing()
+    } else {
+        base_cache(cli)
+    }
+}
+
+fn run_interactive(cli: &Cli, client: &Client) -> Result<(), Exit> {
+    interrupt::install();
+
+    let config = match cli.config.clone().or_else(Config::default_path) {
+        Some(path) => match Config::load(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                status!("Failed to load {}: {}", path.display(), e);
+                return Err(Exit::InvalidConfig);
+            }
+        },
+        None => Config::default()
+    };
+    if let Some(name) = cli.profile.as_ref().filter(|name| !config.profiles.contains_key(*name)) {
+        status!("There is no profile called {} in the config file!", name);
+        return Err(Exit::InvalidConfig);
+    }
+    let config = match &cli.preset {
+        Some(name) => match config.with_preset(name) {
+            Some(config) => config,
+            None => {
+                status!("There is no party called {} in the config file!", name);
+                return Err(Exit::InvalidConfig);
+            }
+        },
+        None => config
+    };
+    let mut avoided_duties = Vec::new();
+    for name in &config.avoid_duties {
+        match duty::find(name) {
+            Some(duty) => avoided_duties.push(duty),
+            None => {
+                status!("There is no duty called {} to avoid!", name);
+                return Err(Exit::InvalidConfig);
+            }
+        }
+    }
+    output::set_avoided_duties(avoided_duties);
+
+    let api = XivApi::new(cache(cli), cli.language.unwrap_or_else(i18n::detect), client.clone());
+
+    let fixtures = match &cli.from_dir {
+        Some(dir) => match MockProvider::load_dir(dir) {
+            Ok(fixtures) => Some(fixtures),
+            Err(e) => {
+                status!("Failed to load characters from {}: {}", dir.display(), e);
+                return Err(Exit::Failure);
+            }
+        },
+        None if cli.demo => match MockProvider::demo() {
+            Ok(fixtures) => Some(fixtures),
+            Err(e) => {
+                status!("Failed to load the demo party: {}", e);
+                return Err(Exit::Failure);
+            }
+        },
+        None => None
+    };
+
+    let api_v2 = XivApiV2::new(XivApi::new(cache(cli), cli.language.unwrap_or_else(i18n::detect), client.clone()));
+    let primary: &dyn CharacterProvider = match cli.api {
+        ApiVersion::V1 => &api,
+        ApiVersion::V2 => &api_v2
+    };
+
+    #[cfg(feature = "lodestone")]
+    let online = FallbackProvider::new(primary, Lodestone::new(cache(cli), client.clone()));
+    #[cfg(not(feature = "lodestone"))]
+    let online = primary;
+
+    let provider: &dyn CharacterProvider = match &fixtures {
+        Some(fixtures) => fixtures,
+        None => &online
+    };
+
+    // Saved characters are only test data, so they aren't worth keeping a history of.
+    #[cfg(feature = "history")]
+    let mut history = if cli.no_history || fixtures.is_some() {
+        None
+    } else {
+        History::default_path().and_then(|path| History::open(&path).ok())
+    };
+    let store_path = CharacterStore::default_path().filter(|_| !cli.no_store && fixtures.is_none());
+    // The store is only a convenience, so failing to read or write it is not fatal.
+    let mut store = store_path.as_ref().map(|path| CharacterStore::load(path).unwrap_or_default());
+
+    // A party file already has everyone's levels, so nobody needs looking up.
+    if let Some(Command::Optimize { party: path }) = &cli.command {
+        let mut party = match party_file::read(path) {
+            Ok(party) => party,
+            Err(e) => {
+                status!("Failed to read the party from {}: {}", path.display(), e);
+                return Err(Exit::Failure);
+            }
+        };
+        for character in &mut party {
+            character.retain_mode(cli.mode);
+        }
+        check_party_size(cli, &party, None)?;
+
+        // A party piped into stdin has used it up, so there's nobody left to answer questions.
+        let interactive = path.as_os_str() != "-" && io::stdin().is_terminal();
+        let server_name = cli.server.clone().unwrap_or_default();
+        return search_party(cli, client, provider, &config, party, server_name, interactive);
+    }
+
+    if cli.resume {
+        let (party, constraints, left_out) = resume_session(cli, &config, provider)?;
+        run_search(cli, client, provider, party, cli_scoring(cli, &config), constraints)?;
+        return left_out.map_or(Ok(()), Err);
+    }
+
+    let preset = cli.preset.as_ref().and_then(|name| config.presets.get(name));
+    let names = match &fixtures {
+        // The demo party is everyone bundled with it, so there's nobody to ask for.
+        Some(fixtures) if cli.demo => Ok(Some(fixtures.names())),
+        _ => batch_names(cli, preset)
+    };
+    let batch = match names {
+        Ok(batch) => batch,
+        Err(e) => {
+            status!("Failed to read character names: {}", e);
+            return Err(Exit::Failure);
+        }
+    };
+
+    // Prompts aren't shown in quiet mode, so there would be no telling what is being asked.
+    if cli.quiet && batch.is_none() {
+        eprintln!("Characters must be given with --characters-file or --preset, or piped into stdin, with --quiet!");
+        return Err(Exit::Failure);
+    }
+    // Names given as Name@World don't need a server to search. Names typed in are asked for one as they need it, so
+    // members on different worlds can be mixed.
+    let needs_server = cli.free_company.is_some()
+        || batch.as_ref().is_some_and(|names| names.iter().any(|name| !name.contains('@')));
+
+    let mut server_name = cli.server.clone().or_else(|| preset.and_then(|preset| preset.server.clone())).unwrap_or_default();
+    // Saved characters aren't tied to a server, so there is nothing to check.
+    let mut servers = fixtures.is_none().then(|| {
+        let cached_servers = match cli.api {
+            ApiVersion::V1 => api.cached_servers(),
+            ApiVersion::V2 => api_v2.cached_servers()
+        };
+        Servers::fetch(cached_servers, servers_fetch(cli, client))
+    });
+
+    if let Some(servers) = &mut servers {
+        // Names piped into stdin have already used it up, so there's nobody left to ask.
+        if needs_server && batch.is_some() && cli.characters_file.is_none() && !servers.exists(&server_name) {
+            if server_name.is_empty() {
+                status!("Characters without a world need --server when their names are piped in!");
+                return Err(Exit::Failure);
+            }
+            status!("{}", i18n::format(Message::ServerNotFound, &[&server_name]));
+            return Err(Exit::ServerNotFound);
+        }
+
+        if needs_server {
+            ask_server(servers, &mut server_name);
+        }
+    }
+
+    if let Some(free_company) = &cli.free_company {
+        return run_free_company(cli, &api, &config, &server_name, free_company);
+    }
+
+    let mut party: Vec<PlayerCharacter> = Vec::new();
+    // The reason the last member was left out, so scripts can tell the party is incomplete.
+    let mut left_out = None;
+    let mut level_changes = Vec::new();
+    #[cfg(feature = "history")]
+    let mut milestones = Vec::new();
+
+    let max_members = max_members(cli);
+    let interactive = batch.is_none();
+    // Whether the last name entered wasn't found, so the same member is asked for again.
+    let mut retrying = false;
+    let mut batch_names = batch.map(Vec::into_iter);
+    while party.len() < max_members {
+        let entry = match &mut batch_names {
+            Some(names) => names.next().unwrap_or_default(),
+            None => {
+                let mut input = String::new();
+                let prompt = if retrying { Message::RetryCharacterPrompt } else { Message::CharacterPrompt };
+                status!("{}", i18n::format(prompt, &[&(party.len() + 1)]));
+                io::stdin().read_line(&mut input).unwrap();
+                input.trim().to_owned()
+            }
+        };
+
+        if entry.is_empty() {
+            if retrying {
+                // Skipping a member who wasn't found asks for somebody else instead of stopping.
+                retrying = false;
+                continue;
+            }
+            break;
+        }
+        retrying = false;
+
+        // Nobody has needed a server yet if everyone so far gave their world.
+        if let Some(servers) = servers.as_mut().filter(|_| interactive && entry.split('+').any(|part| !part.contains('@'))) {
+            ask_server(servers, &mut server_name);
+        }
+
+        // A player's alts follow their main character, e.g. "Alice One + Alicia Two@Zalera".
+        let mut alt_entries = entry.split('+').map(str::trim);
+        let (character_name, world) = split_world(alt_entries.next().unwrap_or_default(), &server_name);
+
+        if servers.as_mut().is_some_and(|servers| !servers.exists(world)) {
+            status!("{}", i18n::format(Message::ServerNotFound, &[&world]));
+            if interactive {
+                retrying = true;
+            } else {
+                left_out = Some(Exit::ServerNotFound);
+            }
+            continue;
+        }
+
+        let stored = store.as_ref().and_then(|store| store.find(character_name, world));
+        let fetched = match stored.and_then(|stored| fetch_stored(provider, stored)) {
+            Some(character) => Fetched::Found(character),
+            None => fetch_character(provider, character_name, world, fixtures.is_none(), interactive)
+        };
+        let mut character = match fetched {
+            Fetched::Found(character) => character,
+            Fetched::Manual(mut character) => {
+                if already_in_party(cli, &party, &character) {
+                    retrying = interactive;
+                    continue;
+                }
+                character.retain_mode(cli.mode);
+                if let Some(exit) = add_alts(provider, &mut character, alt_entries, &server_name, servers.as_mut(), fixtures.is_none(), cli.mode) {
+                    left_out = Some(exit);
+                }
+                party.push(character);
+                continue;
+            },
+            Fetched::NotFound if interactive => {
+                retrying = true;
+                continue;
+            },
+            Fetched::NotFound => {
+                left_out = Some(Exit::NotFound);
+                continue;
+            },
+            Fetched::Skipped(exit) => {
+                left_out = Some(exit);
+                continue;
+            }
+        };
+        if already_in_party(cli, &party, &character) {
+            retrying = interactive;
+            continue;
+        }
+
+        #[cfg(feature = "history")]
+        if let Some(history) = &mut history {
+            milestones.extend(record_history(history, &character));
+        }
+        if character.is_hidden() {
+            status!("{}", i18n::format(Message::HiddenJobs, &[&character.name]));
+            if !interactive || !prompt_jobs(&mut character) {
+                status!("{}", i18n::format(Message::LeftOut, &[&character.name]));
+                left_out = Some(Exit::ProfilePrivate);
+                continue;
+            }
+        }
+        character.retain_mode(cli.mode);
+        if character.server.is_none() && !world.is_empty() {
+            character.server = Some(world.to_owned());
+        }
+        let previous = store.as_mut().and_then(|store| store.record(&character, world));
+        if let Some(changes) = previous.and_then(|previous| describe_level_changes(&character, &previous)) {
+            level_changes.push(changes);
+        }
+        if let Some(exit) = add_alts(provider, &mut character, alt_entries, &server_name, servers.as_mut(), fixtures.is_none(), cli.mode) {
+            left_out = Some(exit);
+        }
+        interrupt::found(&character.name, world);
+        party.push(character);
+    }
+
+    if batch_names.is_some_and(|mut names| names.any(|name| !name.is_empty())) {
+        status!("Only the first {} characters were used!", max_members);
+    }
+
+    if let (Some(store), Some(path)) = (&store, &store_path) {
+        let _ = store.save(path);
+    }
+
+    if !level_changes.is_empty() {
+        status!("\nSince last time:");
+        for changes in &level_changes {
+            status!("{}", changes);
+        }
+        status!();
+    }
+    #[cfg(feature = "history")]
+    announce_milestones(cli, client, &milestones);
+
+    check_party_size(cli, &party, left_out)?;
+
+    if let Some(Command::Resolve { output }) = &cli.command {
+        if let Err(e) = party_file::write(output.as_deref(), &party) {
+            status!("Failed to write the party: {}", e);
+            return Err(Exit::Failure);
+        }
+        if let Some(path) = output {
+            status!("Saved the party to {}, which optimize can search.", path.display());
+        }
+        return left_out.map_or(Ok(()), Err);
+    }
+
+    search_party(cli, client, provider, &config, party, server_name, interactive)?;
+    left_out.map_or(Ok(()), Err)
+}
+
+/// Checks the party is a size the options allow, failing with why the last member was left out if that's to blame.
+fn check_party_size(cli: &Cli, party: &[PlayerCharacter], left_out: Option<Exit>) -> Result<(), Exit> {
+    if party.len() < 2 {
+        status!("{}", i18n::text(Message::TooFewCharacters));
+        return Err(left_out.unwrap_or(Exit::InvalidConstraints));
+    }
+
+    if cli.split && party.len() < 5 {
+        status!("Splitting into two parties requires at least five characters!");
+        return Err(left_out.unwrap_or(Exit::InvalidConstraints));
+    }
+
+    if cli.event && party.len() < 8 {
+        status!("Forming parties for an event requires at least eight characters!");
+        return Err(left_out.unwrap_or(Exit::InvalidConstraints));
+    }
+
+    if cli.duty_support && party.len() > 3 {
+        status!("{}", i18n::text(Message::DutySupportPartySize));
+        return Err(Exit::InvalidConstraints);
+    }
+
+    if cli.partial && party.len() > 3 {
+        status!("{}", i18n::text(Message::PartialPartySize));
+        return Err(Exit::InvalidConstraints);
+    }
+
+    // Names entered stop at the limit, but a party file edited by hand can have anyone in it.
+    if party.len() > max_members(cli) {
+        status!("There can be at most {} characters with these options!", max_members(cli));
+        return Err(Exit::InvalidConstraints);
+    }
+
+    Ok(())
+}
+
+/// The most characters the search can take: a light party, unless it is splitting them or forming event parties.
+fn max_members(cli: &Cli) -> usize {
+    if cli.event { MAX_EVENT_SIGN_UPS } else if cli.split { 8 } else { 4 }
+}
+
+/// Asks about anything the flags and config file leave open, saves the session to resume and searches.
+fn search_party(
+    cli: &Cli,
+    client: &Client,
+    provider: &dyn CharacterProvider,
+    config: &Config,
+    mut party: Vec<PlayerCharacter>,
+    server_name: String,
+    interactive: bool
+) -> Result<(), Exit> {
+    apply_members(cli, config, &mut party);
+
+    let mut constraints = cli_constraints(cli, config);
+    let mut scoring = cli_scoring(cli, config);
+
+    // Scripts feeding in names can't answer questions, so they get the roster and flags alone.
+    if interactive {
+        for character in &party {
+            if cli.mode.requires_roles() && !constraints.has_role_preference(character) {
+                prompt_roles(character, &mut constraints);
+            }
+        }
+
+        if cli.wizard {
+            wizard::run(&party, &mut scoring, &mut constraints);
+        } else if constraints.locks.is_empty() {
+            prompt_locks(&mut constraints);
+        }
+    }
+
+    let session = Session {
+        server: server_name,
+        characters: session_ids(&party),
+        best: last_best(&party),
+        alts: party.iter()
+            .filter(|character| character.id != 0 && !character.alts.is_empty())
+            .map(|character| (character.id, character.alts.iter().map(|alt| alt.id).collect()))
+            .collect(),
+        constraints
+    };
+    // Resuming is only a convenience, so failing to save the session is not fatal.
+    if Session::default_path().is_some_and(|path| session.save(&path).is_ok()) {
+        interrupt::session_saved();
+    }
+
+    run_search(cli, client, provider, party, scoring, session.constraints)
+}
+
+/// Whether `character` has already been entered, in which case they are left out unless that is allowed.
+fn already_in_party(cli: &Cli, party: &[PlayerCharacter], character: &PlayerCharacter) -> bool {
+    if !party.iter().any(|member| member.is_same_character(character)) {
+        return false;
+    }
+
+    status!("{}", i18n::format(Message::DuplicateCharacter, &[&character.name]));
+    if cli.allow_same_character {
+        return false;
+    }
+    status!("{}", i18n::format(Message::LeftOut, &[&character.name]));
+    true
+}
+
+/// Asks which server to search until `server_name` is one of `servers`, or anything at all if there's no list of them.
+fn ask_server(servers: &mut Servers, server_name: &mut String) {
+    while !servers.exists(server_name) {
+        // Picking from the list can't wait for it, so it's typed in until the list arrives.
+        if let Some(list) = servers.ready().filter(|_| prompt::available()) {
+            *server_name = prompt::server(list).unwrap_or_default();
+            continue;
+        }
+
+        server_name.clear();
+
+        status!("{}", i18n::text(Message::ServerPrompt));
+        io::stdin().read_line(server_name).unwrap();
+
+        *server_name = server_name.trim().to_owned();
+        if server_name.is_empty() || servers.exists(server_name) {
+            continue;
+        }
+
+        // The start of a name is enough if only one server begins with it.
+        let matches = servers.ready().map(|list| list.starting_with(server_name)).unwrap_or_default();
+        match matches.as_slice() {
+            [server] => *server_name = server.to_string(),
+            [] => status!("{}", i18n::format(Message::ServerNotFound, &[&server_name])),
+            _ => status!("{}", i18n::format(Message::AmbiguousServer, &[&server_name, &matches.join(", ")]))
+        }
+    }
+}
+
+/// Fetches the server list the way the character provider would, without borrowing anything so it can be done in the
+/// background.
+fn servers_fetch(cli: &Cli, client: &Client) -> impl FnOnce() -> xiv_levelling::error::Result<ServerList> + Send + 'static {
+    let api = XivApi::new(cache(cli), cli.language.unwrap_or_else(i18n::detect), client.clone());
+    let version = cli.api;
+    #[cfg(feature = "lodestone")]
+    let lodestone = Lodestone::new(cache(cli), client.clone());
+
+    move || {
+        let servers = match version {
+            ApiVersion::V1 => api.servers(),
+            ApiVersion::V2 => XivApiV2::new(api).servers()
+        };
+        #[cfg(feature = "lodestone")]
+        let servers = servers.or_else(|_| lodestone.servers());
+        servers
+    }
+}
+
+/// Splits a Name@World entry into its name and world, which is `server_name` if it isn't given.
+fn split_world<'a>(entry: &'a str, server_name: &'a str) -> (&'a str, &'a str) {
+    match entry.split_once('@') {
+        Some((name, world)) => (name.trim(), world.trim()),
+        None => (entry, server_name)
+    }
+}
+
+/// Looks up each of `entries`, given as Name or Name@World, and pools their jobs into `character` as alts. Alts
+/// that can't be found are left out, returning why the last one was.
+fn add_alts<'a>(
+    provider: &dyn CharacterProvider,
+    character: &mut PlayerCharacter,
+    entries: impl Iterator<Item = &'a str>,
+    server_name: &str,
+    mut servers: Option<&mut Servers>,
+    searching: bool,
+    mode: Mode
+) -> Option<Exit> {
+    let mut left_out = None;
+
+    for entry in entries.filter(|entry| !entry.is_empty()) {
+        let (name, world) = split_world(entry, server_name);
+        if servers.as_mut().is_some_and(|servers| !servers.exists(world)) {
+            status!("{}", i18n::format(Message::ServerNotFound, &[&world]));
+            status!("{}", i18n::format(Message::LeftOut, &[&name]));
+            left_out = Some(Exit::ServerNotFound);
+            continue;
+        }
+
+        // Alts are only a bonus, so there's no asking for their jobs by hand.
+        match fetch_character(provider, name, world, searching, false) {
+            Fetched::Found(alt) if alt.is_hidden() => {
+                status!("{}", i18n::format(Message::HiddenJobs, &[&alt.name]));
+                status!("{}", i18n::format(Message::LeftOut, &[&alt.name]));
+            },
+            Fetched::Found(mut alt) | Fetched::Manual(mut alt) => {
+                alt.retain_mode(mode);
+                character.add_alt(alt);
+            },
+            Fetched::NotFound => {
+                status!("{}", i18n::format(Message::LeftOut, &[&name]));
+                left_out = Some(Exit::NotFound);
+            },
+            Fetched::Skipped(exit) => left_out = Some(exit)
+        }
+    }
+
+    left_out
+}
+
+enum Fetched {
+    Found(PlayerCharacter),
+    /// The character couldn't be fetched, so the user entered their jobs instead.
+    Manual(PlayerCharacter),
+    /// Nobody with the name was found, which may just be a typo.
+    NotFound,
+    /// The character was left out, for this reason.
+    Skipped(Exit)
+}
+
+/// Looks up `name` on `world`. If a request fails, the user can try again, enter the character's jobs by hand, or
+/// leave them out, though scripts can't answer, so they only try again once after a timeout before leaving them out.
+fn fetch_character(provider: &dyn CharacterProvider, name: &str, world: &str, searching: bool, interactive: bool) -> Fetched {
+    let mut input = String::new();
+    let mut retried = false;
+
+    loop {
+        let error = match search_and_fetch(provider, name, world, searching, interactive) {
+            Ok(fetched) => return fetched,
+            Err(e) => e
+        };
+
+        status!("{}", i18n::format(Message::FetchFailed, &[&name, &error]));
+
+        if !interactive && !retried && error.is_retryable() {
+            retried = true;
+            continue;
+        }
+
+        loop {
+            input.clear();
+            if interactive {
+                println!("{}", i18n::text(Message::FetchFailedPrompt));
+                io::stdin().read_line(&mut input).unwrap();
+            }
+
+            match input.trim() {
+                "r" => break,
+                "m" => {
+                    let mut character = PlayerCharacter {
+                        id: 0,
+                        class_jobs: Vec::new(),
+                        name: name.to_owned(),
+                        server: Some(world.to_owned()).filter(|world| !world.is_empty()),
+                        avatar: None,
+                        portrait: None,
+                        bio: None,
+                        buffs: Vec::new(),
+                        trial: false,
+                        expansion: None,
+                        play_history: None,
+                        role_history: None,
+                        nickname: None,
+                        alts: Vec::new(),
+                        gear_set: None
+                    };
+
+                    if prompt_jobs(&mut character) {
+                        return Fetched::Manual(character);
+                    }
+                },
+                "" => {
+                    status!("{}", i18n::format(Message::LeftOut, &[&name]));
+                    return Fetched::Skipped(Exit::from(&error));
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Gets a character found before straight from their ID, skipping the search. Anything going wrong falls back to
+/// searching, which reports it.
+fn fetch_stored(provider: &dyn CharacterProvider, stored: &StoredCharacter) -> Option<PlayerCharacter> {
+    status!("{}", i18n::format(Message::GettingCharacter, &[&stored.name]));
+    match provider.character(stored.id) {
+        Ok(character) => Some(character),
+        Err(e) => {
+            tracing::debug!(id = stored.id, "failed to get a stored character: {}", e);
+            None
+        }
+    }
+}
+
+fn search_and_fetch(provider: &dyn CharacterProvider, name: &str, world: &str, searching: bool, interactive: bool) -> xiv_levelling::Result<Fetched> {
+    if searching {
+        status!("{}", i18n::format(Message::Searching, &[&name]));
+    }
+    let player_search = provider.search_character(name, world)?;
+
+    let asking = interactive && prompt::available();
+    let search_result = match player_search.pagination.results {
+        1 => {
+            let search_result = &player_search.results[0];
+            // A search can match more loosely than the name that was entered, so make sure it's the right person.
+            if asking && !search_result.matches_name(name) && !prompt::confirm_character(search_result) {
+                return Ok(Fetched::NotFound);
+            }
+            search_result
+        },
+        0 => {
+            status!("{}", i18n::text(Message::NoCharacter));
+            return Ok(Fetched::NotFound);
+        },
+        _ => {
+            status!("{}", i18n::text(Message::MultipleCharacters));
+            match prompt::character(name, &player_search.results).filter(|_| asking) {
+                Some(search_result) => search_result,
+                None => return Ok(Fetched::Skipped(Exit::Ambiguous))
+            }
+        }
+    };
+
+    status!("{}", i18n::format(Message::FoundCharacter, &[&search_result.name, &search_result.id]));
+
+    status!("{}", i18n::format(Message::GettingCharacter, &[&search_result.name]));
+    Ok(Fetched::Found(provider.character(search_result.id)?))
+}
+
+/// Loads the last saved session, getting its characters again, along with why the last member was left out if any
+/// couldn't be.
+fn resume_session(cli: &Cli, config: &Config, provider: &dyn CharacterProvider) -> Result<(Vec<PlayerCharacter>, Constraints, Option<Exit>), Exit> {
+    let Some(path) = Session::default_path() else {
+        status!("There is nowhere to keep a session on this system!");
+        return Err(Exit::Failure);
+    };
+
+    let session = match Session::load(&path) {
+        Ok(session) => session,
+        Err(xiv_levelling::Error::Io(e)) if e.kind() == io::ErrorKind::NotFound => {
+            status!("There is no session to resume!");
+            return Err(Exit::Failure);
+        },
+        Err(e) => {
+            status!("Failed to load {}: {}", path.display(), e);
+            return Err(Exit::Failure);
+        }
+    };
+
+    if session.server.is_empty() {
+        status!("Resuming the last session of {} characters...", session.characters.len());
+    } else {
+        status!("Resuming the last session of {} characters on {}...", session.characters.len(), session.server);
+    }
+
+    let mut party = Vec::with_capacity(session.characters.len());
+    let mut left_out = None;
+    for id in session.characters {
+        match provider.character(id) {
+            // The jobs entered by hand for hidden profiles aren't saved, so there's nothing to resume them with.
+            Ok(character) if character.is_hidden() => {
+                status!("{}", i18n::format(Message::HiddenJobs, &[&character.name]));
+                status!("{}", i18n::format(Message::LeftOut, &[&character.name]));
+                left_out = Some(Exit::ProfilePrivate);
+            },
+            Ok(mut character) => {
+                status!("{}", i18n::format(Message::GettingCharacter, &[&character.name]));
+                for &alt_id in session.alts.get(&id).into_iter().flatten() {
+                    match provider.character(alt_id) {
+                        Ok(alt) => character.add_alt(alt),
+                        Err(e) => {
+                            status!("{}", i18n::format(Message::FetchFailed, &[&alt_id, &e]));
+                            status!("{}", i18n::format(Message::LeftOut, &[&alt_id]));
+                            left_out = Some(Exit::from(&e));
+                        }
+                    }
+                }
+                character.retain_mode(session.constraints.mode);
+                party.push(character);
+            },
+            Err(e) => {
+                status!("{}", i18n::format(Message::FetchFailed, &[&id, &e]));
+                status!("{}", i18n::format(Message::LeftOut, &[&id]));
+                left_out = Some(Exit::from(&e));
+            }
+        }
+    }
+
+    apply_members(cli, config, &mut party);
+    Ok((party, session.constraints, left_out))
+}
+
+/// Searches for the best configurations of `party` and shows them however `cli` asks.
+fn run_search(
+    cli: &Cli,
+    client: &Client,
+    provider: &dyn CharacterProvider,
+    party: Vec<PlayerCharacter>,
+    scoring: Scoring,
+    constraints: Constraints
+) -> Result<(), Exit> {
+    #[cfg(feature = "fflogs")]
+    let party = add_play_history(cli, client, party);
+    #[cfg(feature = "history")]
+    let party = add_role_history(cli, party);
+
+    for warning in constraints.warnings(&party).into_iter().chain(scoring.warnings()) {
+        status!("{}", warning);
+    }
+
+    if cli.offline {
+        print_data_ages(provider, &party);
+    }
+
+    if cli.balance == Balance::Exp && party.iter().any(|character| character.class_jobs.iter().any(|class_job| class_job.total_exp().is_none())) {
+        status!("EXP progress is unavailable for some jobs, so they are treated as having just reached their level.");
+    }
+
+    if let (Some(level), None) = (cli.target_level, target_duty(cli)) {
+        status!("No dungeon can be entered at level {}!", level);
+        return Err(Exit::InvalidConstraints);
+    }
+
+    if !constraints.is_satisfiable() {
+        status!("The minimum levels can't be above the maximum levels!");
+        return Err(Exit::InvalidConstraints);
+    }
+
+    #[cfg(feature = "history")]
+    if let Some(rank) = cli.played {
+        record_played(cli, &party, &scoring, &constraints, rank)?;
+    }
+
+    if cli.split {
+        return run_split(cli, &party, &scoring, &constraints);
+    }
+
+    if cli.event {
+        return run_event(&party, &scoring, &constraints);
+    }
+
+    if let Some(Command::Plan { days, runs, favourites, ics, start, at }) = &cli.command {
+        let sessions = plan::plan(&party, &scoring, &constraints, &plan::favourite_roles(&party, favourites), *days, *runs);
+        if sessions.is_empty() {
+            status!("{}", i18n::text(Message::NoConfigurations));
+            return Err(Exit::NoConfigurations);
+        }
+
+        output::print_plan(&party, &sessions);
+
+        if let Some(path) = ics {
+            let calendar = calendar::ics(&party, &sessions, start.unwrap_or_else(Date::today), *at);
+            match fs::write(path, calendar) {
+                Ok(()) => status!("Saved the plan to {}", path.display()),
+                Err(e) => {
+                    status!("Failed to save the plan to {}: {}", path.display(), e);
+                    return Err(Exit::Failure);
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    #[cfg(feature = "tui")]
+    if cli.tui {
+        if let Err(e) = tui::run(provider, scoring, constraints, party, cli.max_results) {
+            status!("Failed to run the TUI: {}", e);
+            return Err(Exit::Failure);
+        }
+        return Ok(());
+    }
+
+    if cli.format == Format::Ndjson {
+        return stream_ndjson(&party, &scoring, &constraints, cli.top);
+    }
+
+    if cli.pareto {
+        return run_pareto(cli, &party, &scoring, &constraints);
+    }
+
+    status!("{}\n", i18n::text(Message::Determining));
+    // Configurations that will never be shown don't need to be kept.
+    let limit = match cli.project.or_else(|| cli.compare.as_ref().and_then(|ranks| ranks.iter().copied().max())) {
+        _ if cli.pager => cli.max_results,
+        Some(rank) => rank.max(1),
+        None => cli.top
+    };
+    let started = Instant::now();
+    let known = optimizer::rescore(&party, &scoring, &constraints, &last_best(&party));
+    let (mut party_configs, stats) = rank_with_progress(&party, &scoring, &constraints, limit, cli.max_time, &known);
+    remember_best(&party, &party_configs);
+    cli.sort.sort(&party, &mut party_configs);
+    if cli.stats {
+        print_search_stats(&stats, started.elapsed());
+    }
+
+    if let Some(e) = scoring.custom_score.as_ref().and_then(|custom_score| custom_score.error()) {
+        status!("Failed to score a configuration: {}", e);
+        return Err(Exit::Failure);
+    }
+
+    if party_configs.is_empty() {
+        status!("{}", i18n::text(Message::NoConfigurations));
+        return Err(Exit::NoConfigurations);
+    }
+
+    #[cfg(any(feature = "xlsx", feature = "png"))]
+    if let Some(format) = cli.export {
+        let path = cli.export_path.clone().unwrap_or_else(|| PathBuf::from(format.default_path()));
+        let result = match format {
+            #[cfg(feature = "xlsx")]
+            Export::Xlsx => export::xlsx(&path, &party, &party_configs[..party_configs.len().min(cli.top)], &scoring).map_err(|e| e.to_string()),
+            #[cfg(feature = "png")]
+            Export::Png => card::png(&path, &party, &party_configs[0], &scoring).map_err(|e| e.to_string())
+        };
+        match result {
+            Ok(()) => status!("Exported the configurations to {}", path.display()),
+            Err(e) => status!("Failed to export the configurations to {}: {}", path.display(), e)
+        }
+    }
+
+    if let Some(rank) = cli.project {
+        return run_projection(cli, &party, &party_configs, rank);
+    }
+
+    if let Some(ranks) = &cli.compare {
+        return compare(&party, &party_configs, &scoring, ranks[0], ranks[1]).map_err(|e| {
+            status!("{}", e);
+            Exit::NoConfigurations
+        });
+    }
+
+    if cli.pager {
+        pager::run(&party, &party_configs, &scoring);
+        return Ok(());
+    }
+
+    if cli.chat_macro {
+        println!("{}", output::party_macro(&party, &party_configs[0], &scoring).join("\n"));
+        return Ok(());
+    }
+
+    if let Format::Formatter(name) = &cli.format {
+        let shown = &party_configs[..party_configs.len().min(cli.top)];
+        // Which formats there are was checked when the options were read.
+        if let Some(formatter) = formatters(cli, client).get(name) {
+            if let Err(e) = formatter.write(&mut io::stdout(), &party, shown) {
+                status!("Failed to write the configurations as {}: {}", name, e);
+                return Err(Exit::Failure);
+            }
+        }
+        return Ok(());
+    }
+
+    if cli.quiet {
+        for party_config in party_configs.iter().take(cli.top) {
+            println!("{}", output::config_json(&party, party_config, party_configs[0].score));
+        }
+        return Ok(());
+    }
+
+    output::print_matrix(&party);
+    println!();
+    print_configs(cli, &party, &party_configs, &scoring, &constraints);
+
+    let shown = &party_configs[..party_configs.len().min(cli.top)];
+    if let Some(member) = catch_up::straggler(&party, shown) {
+        output::print_catch_up(&party, &catch_up::plan(&party, &party_configs[0], member));
+        println!();
+    }
+
+    if cli.suggest_unlocks {
+        output::print_unlocks(&unlock::unlocks(&party, shown, &scoring, &constraints), &scoring);
+        println!();
+    }
+
+    if cli.recommend_jobs {
+        output::print_recommendations(&party, &recommend::recommend(&party, &party_configs, &scoring, &constraints, cli.top), cli.top);
+        println!();
+    }
+
+    if cli.watch.is_some() {
+        run_watch(cli, client, provider, party, &scoring, &constraints, party_configs[0]);
+    } else if io::stdin().is_terminal() {
+        run_commands(cli, provider, party, party_configs, scoring, constraints);
+    }
+    Ok(())
+}
+
+/// Looks up which jobs each member has been playing on FFLogs, if a client was given for it.
+#[cfg(feature = "fflogs")]
+fn add_play_history(cli: &Cli, client: &Client, mut party: Vec<PlayerCharacter>) -> Vec<PlayerCharacter> {
+    let (Some(client_id), Some(client_secret)) = (&cli.fflogs_client_id, &cli.fflogs_client_secret) else {
+        return party;
+    };
+
+    let fflogs = FfLogs::new(cache(cli), client.clone(), client_id.clone(), client_secret.clone());
+    // Manually entered members have no Lodestone ID to look up.
+    for character in party.iter_mut().filter(|character| character.id != 0) {
+        status!("Getting FFLogs reports for {}...", character.name);
+        match fflogs.play_history(character.id, &character.name) {
+            Ok(play_history) => character.play_history = Some(play_history),
+            Err(e) => status!("Failed to get FFLogs reports for {}: {}", character.name, e)
+        }
+    }
+
+    party
+}
+
+/// The history database, unless it is turned off or the characters are only saved test data.
+#[cfg(feature = "history")]
+fn open_history(cli: &Cli) -> Option<History> {
+    if cli.no_history || cli.from_dir.is_some() || cli.demo {
+        return None;
+    }
+    History::default_path().and_then(|path| History::open(&path).ok())
+}
+
+/// Records `character` in the history, describing the milestones they've reached since they were last recorded.
+#[cfg(feature = "history")]
+fn record_history(history: &mut History, character: &PlayerCharacter) -> Vec<String> {
+    // The history is only a convenience, so failing to read or write it is not fatal.
+    let previous = history.latest(character.id).unwrap_or_default();
+    let _ = history.record(character);
+
+    history::milestones(character, &previous).iter()
+        .map(|milestone| describe_milestone(character, milestone))
+        .collect()
+}
+
+/// Describes `milestone`, e.g. "Bob's Dragoon reached Lv 60!".
+#[cfg(feature = "history")]
+fn describe_milestone(character: &PlayerCharacter, milestone: &Milestone) -> String {
+    // Below the level a job is unlocked at it's still its base class, which is what was levelled.
+    let job_name = |job: &job::JobInfo| character.class_jobs.iter()
+        .find(|class_job| class_job.job == job)
+        .map_or_else(|| job.job_name.to_owned(), |class_job| class_job.display_name().to_owned());
+
+    match *milestone {
+        Milestone::Level(job, level) => format!("{}'s {} reached Lv {}!", character.display_name(), job_name(job), level),
+        Milestone::Role(role, job) => {
+            let role = if role == Role::Dps { "DPS" } else { role.name() };
+            format!("{} can play {} now, as {}!", character.display_name(), role, job_name(job))
+        }
+    }
+}
+
+/// Celebrates the milestones members have reached, posting them to --webhook too with --announce-milestones.
+#[cfg(feature = "history")]
+fn announce_milestones(cli: &Cli, client: &Client, milestones: &[String]) {
+    if milestones.is_empty() {
+        return;
+    }
+
+    status!("Milestones reached:");
+    for milestone in milestones {
+        status!("- {}", milestone);
+    }
+    status!();
+
+    if let Some(url) = cli.webhook.as_deref().filter(|_| cli.announce_milestones) {
+        if let Err(e) = webhook::post(client, url, &format!("Milestones reached!\n{}", milestones.join("\n"))) {
+            status!("Failed to post to the webhook: {}", e);
+        }
+    }
+}
+
+/// Looks up which roles each member played in their last recorded sessions, if --weight-fairness asks for it.
+#[cfg(feature = "history")]
+fn add_role_history(cli: &Cli, mut party: Vec<PlayerCharacter>) -> Vec<PlayerCharacter> {
+    if cli.weight_fairness.is_none() {
+        return party;
+    }
+    let Some(history) = open_history(cli) else {
+        return party;
+    };
+
+    let favourites = plan::favourite_roles(&party, &[]);
+    // Manually entered members have no Lodestone ID to record sessions by.
+    for (character, favourite) in party.iter_mut().zip(favourites).filter(|(character, _)| character.id != 0) {
+        match history.roles_played(character.id, FAIRNESS_SESSIONS) {
+            Ok(roles) => character.role_history = Some(RoleHistory { roles, favourite }),
+            Err(e) => status!("Failed to read the sessions {} played: {}", character.name, e)
+        }
+    }
+
+    party
+}
+
+/// Records that the party played the configuration ranked `rank`, for --played.
+#[cfg(feature = "history")]
+fn record_played(cli: &Cli, party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints, rank: usize) -> Result<(), Exit> {
+    let party_configs = optimizer::rank_top(party, scoring, constraints, rank);
+    let Some(party_config) = rank.checked_sub(1).and_then(|index| party_configs.get(index)) else {
+        status!("{}", i18n::format(Message::NoSuchConfiguration, &[&rank]));
+        return Err(Exit::NoConfigurations);
+    };
+    let Some(mut history) = open_history(cli) else {
+        status!("There is no history database to record the session in!");
+        return Err(Exit::Failure);
+    };
+
+    let roles: Vec<(u32, Role)> = party.iter().zip(&party_config.index)
+        .filter(|(character, _)| character.id != 0)
+        .filter_map(|(character, &job_index)| Some((character.id, character.class_jobs[job_index].role()?)))
+        .collect();
+    match history.record_session(&roles) {
+        Ok(()) => {
+            status!("Recorded the session playing configuration #{}.", rank);
+            Ok(())
+        },
+        Err(e) => {
+            status!("Failed to record the session: {}", e);
+            Err(Exit::Failure)
+        }
+    }
+}
+
+/// For each of `party_configs`, the better one it is folded into for only differing from it by two members swapping
+/// roles, and those two.
+fn role_swaps(party: &[PlayerCharacter], party_configs: &[PartyConfig]) -> Vec<Option<(usize, (usize, usize))>> {
+    let mut folded: Vec<Option<(usize, (usize, usize))>> = Vec::with_capacity(party_configs.len());
+    for party_config in party_configs {
+        let into = (0..folded.len())
+            .filter(|&rank| folded[rank].is_none())
+            .find_map(|rank| Some((rank, party_configs[rank].role_swap(party_config, party)?)));
+        folded.push(into);
+    }
+    folded
+}
+
+/// The order `sort <order>` asks for, or the one after `order` if none is given.
+fn sort_command(order: Order, args: &str) -> Result<Order, String> {
+    if args.is_empty() {
+        Ok(order.next())
+    } else {
+        args.parse()
+    }
+}
+
+fn print_configs(cli: &Cli, party: &[PlayerCharacter], party_configs: &[PartyConfig], scoring: &Scoring, constraints: &Constraints) {
+    let shown = &party_configs[..party_configs.len().min(cli.top)];
+    let folded = if cli.show_swaps { vec![None; shown.len()] } else { role_swaps(party, shown) };
+
+    for (rank, party_config) in shown.iter().enumerate() {
+        if folded[rank].is_some() {
+            continue;
+        }
+
+        println!("#{}", rank + 1);
+        output::print_party_config(party, party_config, scoring);
+        for (later, swap) in folded.iter().enumerate() {
+            if let Some((_, (a, b))) = swap.filter(|&(into, _)| into == rank) {
+                let names = format!("{}⇄{}", party[a].display_name(), party[b].display_name());
+                println!("- {}: {} (#{})", i18n::text(Message::SwapAvailable), names, later + 1);
+            }
+        }
+        if cli.explain {
+            output::print_explanation(party, &explain::explain(party, party_config, scoring, constraints), scoring);
+        }
+        println!();
+    }
+
+    if cli.frontline {
+        output::print_frontline(party, constraints);
+    }
 }
 
-impl ServerList {
-    fn exists(&self, name: &str) -> bool {
-        self.servers.contains(&name.to_owned())
+/// Asks for commands after the configurations have been shown, until nothing is entered. `refresh <name>` gets
+/// the latest data for one member, e.g. after they log out, and `set <name> <job> <level>` pretends one of their
+/// jobs is at another level. `lock <name>=<job>`, `unlock <name>`, `exclude <job>`, `include <job>` and
+/// `weight <name> <value>` change the constraints and scoring for the rest of the session. Every command shows the
+/// configurations again, except `compare <rank> <rank>`, which shows two of those last shown side by side, and
+/// `pf <rank>`, which writes a Party Finder description for one of them.
+fn run_commands(
+    cli: &Cli,
+    provider: &dyn CharacterProvider,
+    mut party: Vec<PlayerCharacter>,
+    mut party_configs: Vec<PartyConfig>,
+    mut scoring: Scoring,
+    mut constraints: Constraints
+) {
+    let mut input = String::new();
+    // Changing the constraints or scoring doesn't change which jobs the party could play, so the assignments are
+    // only made once, when first needed, and again after the party changes.
+    let mut assignments: Option<Assignments> = None;
+    let mut order = cli.sort;
+
+    loop {
+        input.clear();
+
+        println!("{}", i18n::text(Message::CommandPrompt));
+        io::stdin().read_line(&mut input).unwrap();
+
+        let line = input.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let party_changed = matches!(command, "refresh" | "set" | "add" | "remove");
+        let result = match command {
+            "" => return,
+            "refresh" => refresh_command(provider, &mut party, args.trim(), constraints.mode),
+            "add" => add_command(cli, provider, &mut party, &constraints, args.trim()),
+            "remove" => remove_command(&mut party, &mut constraints, args.trim()),
+            "set" => set_command(&mut party, args.trim()),
+            "lock" => lock_command(&party, &mut constraints, args.trim()),
+            "unlock" => unlock_command(&party, &mut constraints, args.trim()),
+            "exclude" => exclude_command(&mut constraints, args.trim(), true),
+            "include" => exclude_command(&mut constraints, args.trim(), false),
+            "weight" => weight_command(&mut scoring, args.trim()),
+            "compare" => {
+                if let Err(e) = compare_command(&party, &party_configs, &scoring, args.trim()) {
+                    println!("{}", e);
+                }
+                continue;
+            },
+            "pf" => {
+                match party_finder_command(&party, &party_configs, args.trim()) {
+                    Ok(description) => println!("{}", description),
+                    Err(e) => println!("{}", e)
+                }
+                continue;
+            },
+            // The configurations already found are only put in another order, without searching again.
+            "sort" => {
+                match sort_command(order, args.trim()) {
+                    Ok(new_order) => {
+                        order = new_order;
+                        order.sort(&party, &mut party_configs);
+                        println!("In order of {}:\n", order.name());
+                        print_configs(cli, &party, &party_configs, &scoring, &constraints);
+                    },
+                    Err(e) => println!("{}", e)
+                }
+                continue;
+            }
+            _ => Err(i18n::text(Message::UnknownCommand).to_owned())
+        };
+
+        if let Err(e) = result {
+            println!("{}", e);
+            continue;
+        }
+
+        println!("{}\n", i18n::text(Message::Determining));
+        party_configs = if party_changed {
+            assignments = None;
+            rank_with_progress(&party, &scoring, &constraints, cli.top, cli.max_time, &[]).0
+        } else {
+            assignments.get_or_insert_with(|| Assignments::new(&party)).rank_top(&party, &scoring, &constraints, cli.top)
+        };
+        order.sort(&party, &mut party_configs);
+        if party_configs.is_empty() {
+            println!("{}", i18n::text(Message::NoConfigurations));
+        } else {
+            print_configs(cli, &party, &party_configs, &scoring, &constraints);
+        }
     }
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct Pagination {
-    pub results: u8
+fn find_member(party: &[PlayerCharacter], name: &str) -> Result<usize, String> {
+    party.iter()
+        .position(|character| character.matches_name(name))
+        .ok_or_else(|| i18n::format(Message::NotInParty, &[&name]))
+}
+
+fn refresh_command(provider: &dyn CharacterProvider, party: &mut [PlayerCharacter], name: &str, mode: Mode) -> Result<(), String> {
+    let member = find_member(party, name)?;
+
+    println!("{}", i18n::format(Message::GettingCharacter, &[&party[member].name]));
+    party[member] = refresh_member(provider, &party[member], mode)
+        .map_err(|e| format!("Failed to refresh {}: {}", party[member].name, e))?;
+    Ok(())
+}
+
+/// Handles `add <name>` or `add <name>@<world>`, looking the character up on the party's world unless given another.
+fn add_command(
+    cli: &Cli,
+    provider: &dyn CharacterProvider,
+    party: &mut Vec<PlayerCharacter>,
+    constraints: &Constraints,
+    entry: &str
+) -> Result<(), String> {
+    if entry.is_empty() {
+        return Err(i18n::text(Message::InvalidAdd).to_owned());
+    }
+    // Duty Support and partial parties leave at least one slot for somebody else.
+    let max_members = if constraints.fills_roles() { 3 } else { 4 };
+    if party.len() >= max_members {
+        return Err(i18n::format(Message::PartyFull, &[&max_members]));
+    }
+
+    let server_name = party.iter().find_map(|character| character.server.clone()).unwrap_or_default();
+    let (name, world) = split_world(entry, &server_name);
+    let searching = cli.from_dir.is_none() && !cli.demo;
+    let mut character = match fetch_character(provider, name, world, searching, true) {
+        Fetched::Found(character) | Fetched::Manual(character) => character,
+        Fetched::NotFound | Fetched::Skipped(_) => return Err(i18n::format(Message::LeftOut, &[&name]))
+    };
+
+    if party.iter().any(|member| member.is_same_character(&character)) && !cli.allow_same_character {
+        return Err(i18n::format(Message::DuplicateCharacter, &[&character.name]));
+    }
+    if character.is_hidden() {
+        println!("{}", i18n::format(Message::HiddenJobs, &[&character.name]));
+        if !prompt_jobs(&mut character) {
+            return Err(i18n::format(Message::LeftOut, &[&character.name]));
+        }
+    }
+    character.retain_mode(constraints.mode);
+    if character.server.is_none() && !world.is_empty() {
+        character.server = Some(world.to_owned());
+    }
+
+    party.push(character);
+    Ok(())
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct PlayerSearchEntry {
-    #[serde(rename = "ID")]
-    pub id: u32,
-    pub name: String
+/// Handles `remove <name>`, along with anything the constraints say about them alone.
+fn remove_command(party: &mut Vec<PlayerCharacter>, constraints: &mut Constraints, name: &str) -> Result<(), String> {
+    let member = find_member(party, name)?;
+    if party.len() <= 2 {
+        return Err(i18n::text(Message::TooFewCharacters).to_owned());
+    }
+
+    let character = party.remove(member);
+    constraints.locks.retain(|lock| !character.matches_name(&lock.character));
+    constraints.roles.retain(|preference| !character.matches_name(&preference.character));
+    constraints.job_filters.retain(|filter| !character.matches_name(&filter.character));
+    Ok(())
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct PlayerSearchResult {
-    pub pagination: Pagination,
-    pub results: Vec<PlayerSearchEntry>
+/// Handles `set <name> <job> <level>`. The level only lasts until the program exits.
+fn set_command(party: &mut [PlayerCharacter], args: &str) -> Result<(), String> {
+    let mut parts = args.rsplitn(3, ' ');
+    let (Some(level), Some(job), Some(name)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(i18n::text(Message::InvalidSet).to_owned());
+    };
+
+    let level = match level.parse::<u8>() {
+        Ok(level) if (1..=job::LEVEL_CAP).contains(&level) => level,
+        _ => return Err(i18n::text(Message::InvalidSet).to_owned())
+    };
+
+    let character = &mut party[find_member(party, name.trim())?];
+    let Some(class_job) = character.class_jobs.iter_mut().find(|class_job| class_job.job.matches(job)) else {
+        return Err(i18n::format(Message::JobNotUnlocked, &[&character.name, &job]));
+    };
+
+    class_job.set_level(level);
+    println!("{}", i18n::format(Message::LevelSet, &[&character.name, &class_job.display_name(), &level]));
+    Ok(())
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct ClassUnlockedState {
-    pub name: String
+/// Handles `lock <name>=<job>`, replacing any job the member was already locked to.
+fn lock_command(party: &[PlayerCharacter], constraints: &mut Constraints, args: &str) -> Result<(), String> {
+    let lock: Lock = args.parse().map_err(|_| i18n::text(Message::InvalidLock).to_owned())?;
+    let character = &party[find_member(party, &lock.character)?];
+
+    constraints.locks.retain(|existing| !character.matches_name(&existing.character));
+    constraints.locks.push(lock);
+    Ok(())
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct ClassJob {
-    #[serde(rename = "ClassID")]
-    pub class_id: u8,
-    pub level: u8,
-    unlocked_state: ClassUnlockedState
+/// Handles `unlock <name>`, letting the member play any job again.
+fn unlock_command(party: &[PlayerCharacter], constraints: &mut Constraints, name: &str) -> Result<(), String> {
+    let character = &party[find_member(party, name)?];
+    constraints.locks.retain(|lock| !character.matches_name(&lock.character));
+    Ok(())
 }
 
-impl ClassJob {
-    fn name(&self) -> &str {
-        &self.unlocked_state.name
+/// Handles `exclude <job>`, or `include <job>` to undo it.
+fn exclude_command(constraints: &mut Constraints, name: &str, exclude: bool) -> Result<(), String> {
+    let Some(info) = job::JOBS.iter().find(|info| info.matches(name)) else {
+        return Err(i18n::format(Message::UnknownJob, &[&name]));
+    };
+
+    constraints.excluded_jobs.retain(|excluded| !info.matches(excluded));
+    if exclude {
+        constraints.excluded_jobs.push(info.job_name.to_owned());
     }
+    Ok(())
+}
+
+/// Handles `compare <rank> <rank>`, for ranks among the configurations last shown.
+fn compare_command(party: &[PlayerCharacter], party_configs: &[PartyConfig], scoring: &Scoring, args: &str) -> Result<(), String> {
+    let ranks: Vec<usize> = args.split_whitespace().map(str::parse).collect::<Result<_, _>>()
+        .map_err(|_| i18n::text(Message::InvalidCompare).to_owned())?;
+    let [first, second] = ranks[..] else {
+        return Err(i18n::text(Message::InvalidCompare).to_owned());
+    };
+
+    compare(party, party_configs, scoring, first, second)
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct PlayerCharacter {
-    pub class_jobs: Vec<ClassJob>,
-    pub name: String
+/// Prints the configurations ranked `first` and `second` side by side.
+fn compare(party: &[PlayerCharacter], party_configs: &[PartyConfig], scoring: &Scoring, first: usize, second: usize) -> Result<(), String> {
+    let at = |rank: usize| rank.checked_sub(1)
+        .and_then(|index| party_configs.get(index))
+        .ok_or_else(|| i18n::format(Message::NoSuchConfiguration, &[&rank]));
+
+    output::print_comparison(party, (first, at(first)?), (second, at(second)?), scoring);
+    Ok(())
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "PascalCase")]
-struct CharacterMeta {
-    pub character: PlayerCharacter
+/// Handles `pf <rank>`, for a rank among the configurations last shown.
+fn party_finder_command(party: &[PlayerCharacter], party_configs: &[PartyConfig], args: &str) -> Result<String, String> {
+    let rank: usize = args.parse().map_err(|_| i18n::text(Message::InvalidPartyFinder).to_owned())?;
+    let party_config = rank.checked_sub(1)
+        .and_then(|index| party_configs.get(index))
+        .ok_or_else(|| i18n::format(Message::NoSuchConfiguration, &[&rank]))?;
+
+    Ok(output::party_finder(party, party_config))
 }
 
-const TANK: [u8; 4] = [1, 3, 32, 37];
-const HEALER: [u8; 3] = [6, 26, 33];
-const DPS: [u8; 10] = [2, 4, 29, 34, 5, 31, 38, 7, 26, 35];
+/// Handles `weight <var|avg|bonus|diversity|duplicate> <value>`.
+fn weight_command(scoring: &mut Scoring, args: &str) -> Result<(), String> {
+    let invalid = || i18n::text(Message::InvalidWeight).to_owned();
+    let (name, value) = args.split_once(' ').ok_or_else(invalid)?;
+    let value: f64 = value.trim().parse().ok().filter(|value: &f64| value.is_finite()).ok_or_else(invalid)?;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct PartyConfig {
-    pub index: [usize; 4],
-    pub var: u32,
-    pub avg: u32
+    let weight = match name {
+        "var" => &mut scoring.weight_var,
+        "avg" => &mut scoring.weight_avg,
+        "bonus" => &mut scoring.weight_bonus,
+        "diversity" => &mut scoring.weight_diversity,
+        "duplicate" => &mut scoring.weight_duplicate,
+        _ => return Err(invalid())
+    };
+    *weight = value;
+    Ok(())
 }
 
-impl Ord for PartyConfig {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.var.cmp(&self.var)
+/// Parses an interval like `30m`, `6h` or `1d`.
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let (amount, unit) = s.split_at(s.len().saturating_sub(1));
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(format!("invalid interval {} (expected a number followed by s, m, h or d)", s))
+    };
+
+    match amount.parse::<u64>() {
+        Ok(amount) if amount > 0 => Ok(Duration::from_secs(amount * secs)),
+        _ => Err(format!("invalid interval {} (expected a number followed by s, m, h or d)", s))
     }
 }
 
-impl PartialOrd for PartyConfig {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(other.var.cmp(&self.var))
+/// Parses a number of sessions a week, at most one for each day's roulette bonus.
+fn parse_sessions_per_week(s: &str) -> Result<u32, String> {
+    match s.parse() {
+        Ok(sessions @ 1..=7) => Ok(sessions),
+        _ => Err(format!("invalid number of sessions {} (expected 1 to 7, one for each daily roulette bonus)", s))
     }
 }
 
-fn main() {
-    println!("Getting list of FFXIV servers...");
-    let server_list = reqwest::blocking::get("https://xivapi.com/servers").unwrap()
-        .json::<ServerList>().unwrap();
-    
-    let mut server_name = String::new();
+fn parse_duty(s: &str) -> Result<&'static Duty, String> {
+    duty::find(s).ok_or_else(|| format!("no duty called {} was found", s))
+}
 
-    while !server_list.exists(&server_name) {
-        server_name.clear();
+#[cfg(feature = "script")]
+fn parse_score_script(s: &str) -> Result<Arc<ScoreScript>, String> {
+    ScoreScript::load(s.as_ref()).map(Arc::new).map_err(|e| format!("{}: {}", s, e))
+}
+
+/// Parses a proxy URL like `http://proxy.example.com:8080`.
+fn parse_proxy(s: &str) -> Result<Proxy, String> {
+    Proxy::all(s).map_err(|e| format!("invalid proxy {}: {}", s, e))
+}
 
-        println!("Please enter the name of your FFXIV server:");
-        io::stdin().read_line(&mut server_name).unwrap();
+/// Describes `interval` in the largest unit that divides it exactly, e.g. "6 hour(s)".
+fn format_interval(interval: Duration) -> String {
+    let secs = interval.as_secs();
+    match secs {
+        _ if secs.is_multiple_of(24 * 60 * 60) => format!("{} day(s)", secs / (24 * 60 * 60)),
+        _ if secs.is_multiple_of(60 * 60) => format!("{} hour(s)", secs / (60 * 60)),
+        _ if secs.is_multiple_of(60) => format!("{} minute(s)", secs / 60),
+        _ => format!("{} second(s)", secs)
+    }
+}
 
-        server_name = server_name.trim().to_owned();
+/// Fetches the latest data for every member of `party`, keeping what the user flagged about them.
+fn refresh_party(provider: &dyn CharacterProvider, party: &[PlayerCharacter], mode: Mode) -> xiv_levelling::Result<Vec<PlayerCharacter>> {
+    party.iter()
+        .map(|character| refresh_member(provider, character, mode))
+        .collect()
+}
 
-        if !server_list.exists(&server_name) {
-            println!("Server {} does not exist!", server_name);
-        }
+/// Fetches the latest data for `character` and their alts, keeping what the user flagged about them.
+fn refresh_member(provider: &dyn CharacterProvider, character: &PlayerCharacter, mode: Mode) -> xiv_levelling::Result<PlayerCharacter> {
+    let mut refreshed = provider.refresh_character(character.id)?;
+    for alt in &character.alts {
+        refreshed.add_alt(provider.refresh_character(alt.id)?);
     }
+    refreshed.retain_mode(mode);
+    refreshed.buffs = character.buffs.clone();
+    refreshed.trial = character.trial;
+    refreshed.expansion = character.expansion;
+    refreshed.play_history = character.play_history.clone();
+    refreshed.role_history = character.role_history.clone();
+    refreshed.nickname = character.nickname.clone();
+    refreshed.server = refreshed.server.or_else(|| character.server.clone());
+    Ok(refreshed)
+}
 
-    let mut party: Vec<PlayerCharacter> = Vec::new();
+/// Refreshes the party every --watch interval forever, printing the best configuration whenever it differs from
+/// `best`, and posting it to --webhook if given, and announcing any duties it has levelled into and milestones members
+/// have reached.
+fn run_watch(
+    cli: &Cli,
+    client: &Client,
+    provider: &dyn CharacterProvider,
+    mut party: Vec<PlayerCharacter>,
+    scoring: &Scoring,
+    constraints: &Constraints,
+    mut best: PartyConfig
+) {
+    let Some(interval) = cli.watch else {
+        return;
+    };
+    let webhook = cli.webhook.as_deref().map(|url| (client, url));
+    let mut level = output::duty_level(&party, &best);
+    #[cfg(feature = "history")]
+    let mut history = open_history(cli);
 
-    let mut character_name = " ".to_owned();
-    while !character_name.is_empty() && party.len() < 4 {
-        character_name.clear();
+    loop {
+        println!("Waiting {} before checking again...", format_interval(interval));
+        thread::sleep(interval);
 
-        println!("Character {} Name (press enter to stop):", party.len() + 1);
-        io::stdin().read_line(&mut character_name).unwrap();
+        party = match refresh_party(provider, &party, constraints.mode) {
+            Ok(refreshed) => refreshed,
+            Err(e) => {
+                println!("Failed to refresh the party: {}", e);
+                continue;
+            }
+        };
 
-        character_name = character_name.trim().to_owned();
+        #[cfg(feature = "history")]
+        if let Some(history) = &mut history {
+            let milestones: Vec<String> = party.iter().flat_map(|character| record_history(history, character)).collect();
+            announce_milestones(cli, client, &milestones);
+        }
 
-        if !character_name.is_empty() {
-            println!("Searching for {} in the Lodestone...", character_name);
-            let player_search = reqwest::blocking::get(format!("https://xivapi.com/character/search?name={}&server={}", character_name, server_name)).unwrap()
-                .json::<PlayerSearchResult>().unwrap();
-            
-            if player_search.pagination.results == 1 {
-                let search_result = &player_search.results[0];
-                println!("Found character {} with ID {}!", search_result.name, search_result.id);
+        let Some(party_config) = optimizer::rank_top(&party, scoring, constraints, 1).pop() else {
+            continue;
+        };
 
-                println!("Getting character data for {}...", search_result.name);
+        let new_level = output::duty_level(&party, &party_config);
+        let unlocked = duty::unlocked_between(level, new_level);
+        level = new_level;
+        if !unlocked.is_empty() {
+            println!("\nThe party has reached Lv {} and unlocked {}!", level, unlocked.join(", "));
+            #[cfg(feature = "notify")]
+            notify::unlocked(level, &unlocked);
+        }
 
-                let mut character_meta = reqwest::blocking::get(format!("https://xivapi.com/character/{}", search_result.id)).unwrap()
-                    .json::<CharacterMeta>().unwrap();
-                
-                character_meta.character.class_jobs.retain(|x| TANK.contains(&x.class_id) || HEALER.contains(&x.class_id) || DPS.contains(&x.class_id));
-                party.push(character_meta.character);
+        if party_config.index == best.index {
+            continue;
+        }
+        best = party_config;
 
-            } else if player_search.pagination.results == 0 {
-                println!("No character with that name was found!");
-            } else {
-                println!("Multiple characters were found!");
+        println!("\nThe best configuration has changed!");
+        output::print_party_config(&party, &party_config, scoring);
+        println!();
+
+        if let Some((client, url)) = webhook {
+            let members: Vec<String> = party.iter().zip(&party_config.index)
+                .map(|(character, &job_index)| {
+                    let class_job = &character.class_jobs[job_index];
+                    format!("{}: {} Lv {}", character.name, class_job.display_name(), class_job.level)
+                })
+                .collect();
+
+            if let Err(e) = webhook::post(client, url, &format!("The best levelling configuration has changed!\n{}", members.join("\n"))) {
+                println!("Failed to post to the webhook: {}", e);
             }
         }
     }
+}
 
-    if party.len() < 2 {
-        println!("Party must consist of at least two characters!");
+/// Prints every valid configuration as a line of JSON as soon as the search finds it.
+fn stream_ndjson(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints, top: usize) -> Result<(), Exit> {
+    // Holding the best score's lock while printing keeps each line's best score in order.
+    let best_score = Mutex::new(f64::INFINITY);
+    let started = Instant::now();
+    let party_configs = optimizer::rank_top_streaming(party, scoring, constraints, top, &|party_config| {
+        let mut best_score = best_score.lock().unwrap();
+        *best_score = best_score.min(party_config.score);
+        println!("{}", output::config_json(party, party_config, *best_score));
+    });
+    tracing::info!(elapsed = ?started.elapsed(), "streamed every configuration");
+
+    if party_configs.is_empty() {
+        return Err(Exit::NoConfigurations);
+    }
+    Ok(())
+}
+
+/// The Lodestone IDs a session of `party` is saved with. Members entered by hand have none to look them up by.
+fn session_ids(party: &[PlayerCharacter]) -> Vec<u32> {
+    party.iter().map(|character| character.id).filter(|&id| id != 0).collect()
+}
+
+/// The best configurations the last search found, if it was of `party`, as job IDs.
+fn last_best(party: &[PlayerCharacter]) -> Vec<Vec<u8>> {
+    let ids = session_ids(party);
+    Session::default_path()
+        .and_then(|path| Session::load(&path).ok())
+        .filter(|session| session.characters == ids)
+        .map(|session| session.best)
+        .unwrap_or_default()
+}
+
+/// Keeps the best configurations found in the saved session, if it is of `party`, for the next search to start from.
+fn remember_best(party: &[PlayerCharacter], party_configs: &[PartyConfig]) {
+    let ids = session_ids(party);
+    let Some(path) = Session::default_path() else {
         return;
+    };
+    let Some(mut session) = Session::load(&path).ok().filter(|session| session.characters == ids) else {
+        return;
+    };
+
+    session.best = party_configs.iter()
+        .map(|party_config| {
+            party.iter().zip(&party_config.index)
+                .map(|(character, &job_index)| character.class_jobs[job_index].job.job_id)
+                .collect()
+        })
+        .collect();
+    if let Err(e) = session.save(&path) {
+        tracing::warn!("failed to save the best configurations to {}: {}", path.display(), e);
     }
+}
 
-    println!("Determining best possible party configurations for levelling...\n");
-    let mut party_configs: BinaryHeap<PartyConfig> = BinaryHeap::new();
+/// Ranks the best `limit` configurations, showing a progress bar if the search is large enough to take a while. If
+/// `max_time` runs out first, the best found so far are returned.
+fn rank_with_progress(
+    party: &[PlayerCharacter],
+    scoring: &Scoring,
+    constraints: &Constraints,
+    limit: usize,
+    max_time: Option<Duration>,
+    known: &[PartyConfig]
+) -> (Vec<PartyConfig>, SearchStats) {
+    let started = Instant::now();
+    let party_configs = rank_with_progress_bar(party, scoring, constraints, limit, max_time, known);
+    tracing::info!(elapsed = ?started.elapsed(), "ranked the best {} configurations", limit);
+    party_configs
+}
 
-    let mut combination = vec![];
-    for _ in 0..party.len() {
-        combination.push(0);
+fn rank_with_progress_bar(
+    party: &[PlayerCharacter],
+    scoring: &Scoring,
+    constraints: &Constraints,
+    limit: usize,
+    max_time: Option<Duration>,
+    known: &[PartyConfig]
+) -> (Vec<PartyConfig>, SearchStats) {
+    let total = optimizer::search_size(party, scoring, constraints);
+    let show_bar = total >= PROGRESS_THRESHOLD && !output::is_quiet();
+    if !show_bar && max_time.is_none() {
+        return optimizer::rank_top_from(party, scoring, constraints, limit, None, known);
     }
 
-    loop {
-        let mut num_tanks = 0;
-        let mut num_healers = 0;
-        let mut all_max = true;
-        let mut all_unlocked = true;
+    // The last search's best are worth seeing while waiting on this one, which may well find them again.
+    if show_bar && !known.is_empty() {
+        status!("The best last time, with the levels now:");
+        for party_config in known.iter().take(limit) {
+            status!("- {}", output::config_line(party, party_config));
+        }
+        status!();
+    }
 
-        for i in 0..combination.len() {
-            let class_job = &party[i].class_jobs[combination[i]];
+    let progress = Progress::default();
+    let bar = if show_bar {
+        ProgressBar::new(total).with_style(
+            ProgressStyle::with_template("{bar:40} {pos}/{len} combinations, {msg} ({eta} left)").unwrap()
+        )
+    } else {
+        ProgressBar::hidden()
+    };
 
-            if TANK.contains(&class_job.class_id) {
-                num_tanks += 1;
-            } else if HEALER.contains(&class_job.class_id) {
-                num_healers += 1;
-            }
+    let started = Instant::now();
+    let party_configs = thread::scope(|scope| {
+        let search = scope.spawn(|| optimizer::rank_top_from(party, scoring, constraints, limit, Some(&progress), known));
 
-            if class_job.level == 0 {
-                all_unlocked = false;
-            } else if class_job.level < 80 {
-                all_max = false;
+        while !search.is_finished() {
+            if max_time.is_some_and(|max_time| started.elapsed() >= max_time) {
+                progress.cancelled.store(true, Ordering::Relaxed);
             }
+            bar.set_position(progress.evaluated.load(Ordering::Relaxed));
+            bar.set_message(format!("{} valid", progress.found.load(Ordering::Relaxed)));
+            thread::sleep(Duration::from_millis(100));
         }
 
-        if num_tanks == 1 && num_healers == 1 && all_unlocked && !all_max {
-            let mut index = [0, 0, 0, 0];
-            let mut var = 0;
-            let mut avg = 0;
+        search.join().unwrap()
+    });
 
-            for i in 0..combination.len() {
-                index[i] = combination[i];
+    bar.finish_and_clear();
 
-                let job1 = &party[i].class_jobs[combination[i]];
-                for j in 0..combination.len() {
-                    if i != j {
-                        let job2 = &party[j].class_jobs[combination[j]];
-                        var += (job1.level as i16 - job2.level as i16).abs() as u32;
-                    }
+    let evaluated = progress.evaluated.load(Ordering::Relaxed);
+    if evaluated < total {
+        status!(
+            "Stopped searching after {} with {:.1}% of the {} combinations covered, so these are only the best found so far.\n",
+            format_interval(max_time.unwrap_or_default()),
+            evaluated as f64 / total as f64 * 100.0,
+            total
+        );
+    }
+
+    party_configs
+}
+
+/// Says how a search went, for --stats.
+fn print_search_stats(stats: &SearchStats, elapsed: Duration) {
+    status!("Search statistics:");
+    status!(
+        "- Jobs left out: {} not unlocked, {} by the constraints, {} interchangeable with another",
+        stats.locked_jobs,
+        stats.filtered_jobs,
+        stats.equivalent_jobs
+    );
+    status!("- Combinations examined: {} of {}", stats.examined(), stats.combinations);
+    status!("- Ruled out for their roles: {}", stats.wrong_roles);
+    status!("- Ruled out for sharing jobs or DPS sub-roles: {}", stats.not_varied);
+    status!("- Ruled out for levels too far apart: {}", stats.wide_gap);
+    status!("- Skipped for not beating those already found: {}", stats.pruned);
+    status!("- Scored with the roles needed: {}", stats.evaluated);
+    status!("- Ruled out for the level cap or average level: {}", stats.out_of_range);
+    status!("- Valid configurations: {}", stats.found);
+    status!("- Time taken: {:.2?}", elapsed);
+    status!("- Peak memory used: {:.1} MiB\n", memory::peak() as f64 / (1024.0 * 1024.0));
+}
+
+/// The duty `cli` asks to aim for, if any.
+fn target_duty(cli: &Cli) -> Option<&'static Duty> {
+    cli.target_duty.or_else(|| cli.target_level.and_then(|level| duty::highest_available(level, DutyKind::Dungeon, 1).pop()))
+}
+
+/// The profile `cli` asks for, or an empty one that changes nothing.
+fn cli_profile(cli: &Cli, config: &Config) -> Profile {
+    cli.profile.as_ref().and_then(|name| config.profiles.get(name)).cloned().unwrap_or_default()
+}
+
+fn cli_constraints(cli: &Cli, config: &Config) -> Constraints {
+    let profile = cli_profile(cli, config);
+
+    // A target duty narrows the level band to jobs that can enter it and aren't above its level cap.
+    let target = target_duty(cli);
+    let floor = if cli.all_levels { None } else { cli.min_level.or(profile.min_level).or(config.min_level) };
+    let min_level = floor.max(target.map(|duty| duty.level));
+    let max_level = match (cli.max_level.or(profile.max_level).or(config.max_level), target) {
+        (Some(max_level), Some(duty)) => Some(max_level.min(duty.sync)),
+        (max_level, duty) => max_level.or(duty.map(|duty| duty.sync))
+    };
+
+    let mut constraints = Constraints {
+        locks: cli.locks.clone(),
+        min_level,
+        max_level,
+        min_avg: cli.min_avg.or(profile.min_avg),
+        max_avg: cli.max_avg.or(profile.max_avg),
+        max_gap: cli.max_gap.or(profile.max_gap),
+        dedup: !cli.show_duplicates,
+        mode: cli.mode,
+        blue_mage: cli.blu,
+        duty_support: cli.duty_support,
+        partial: cli.partial,
+        composition: cli.comp.or(profile.composition).unwrap_or_default(),
+        diverse_dps: cli.diverse_dps || profile.diverse_dps,
+        unique_jobs: cli.unique_jobs || profile.unique_jobs,
+        excluded_jobs: profile.exclude_jobs.clone(),
+        ..Constraints::default()
+    };
+
+    config.apply(&mut constraints);
+    constraints
+}
+
+/// The formats `--format` can name besides text and ndjson: those built in, and webhook if `--webhook` is given.
+fn formatters(cli: &Cli, client: &Client) -> formatter::Registry {
+    let mut formatters = formatter::Registry::default();
+    if let Some(url) = &cli.webhook {
+        formatters.register(Box::new(Webhook { client: client.clone(), url: url.clone() }));
+    }
+    formatters
+}
+
+fn http_settings(cli: &Cli) -> http::Settings {
+    let settings = cli.timeout.map_or_else(http::Settings::default, http::Settings::with_timeout);
+    http::Settings {
+        proxy: cli.proxy.clone(),
+        ..settings
+    }
+}
+
+fn cli_scoring(cli: &Cli, config: &Config) -> Scoring {
+    let profile = cli_profile(cli, config);
+    let mut scoring = Scoring {
+        metric: cli.metric,
+        balance: cli.balance,
+        weight_var: cli.weight_var.or(profile.weight_var).unwrap_or(1.0),
+        weight_avg: cli.weight_avg.or(profile.weight_avg).unwrap_or_default(),
+        weight_bonus: cli.weight_bonus.or(profile.weight_bonus).unwrap_or_default(),
+        weight_diversity: cli.weight_diversity.or(profile.weight_diversity).unwrap_or_default(),
+        weight_duplicate: cli.weight_duplicate.or(profile.weight_duplicate).unwrap_or_default(),
+        target_sync: target_duty(cli).map(|duty| duty.sync),
+        in_need: cli.in_need,
+        weight_in_need: cli.weight_in_need,
+        weight_queue: cli.weight_queue,
+        #[cfg(feature = "fflogs")]
+        weight_familiarity: cli.weight_familiarity,
+        #[cfg(feature = "history")]
+        weight_fairness: cli.weight_fairness.unwrap_or_default(),
+        custom_score: cli.score.clone().map(|expression| Arc::new(expression) as Arc<dyn CustomScore>),
+        tie_seed: cli.shuffle_ties.then(|| cli.seed.unwrap_or_else(random_seed)),
+        ..Scoring::default()
+    };
+
+    if let (Some(seed), None) = (scoring.tie_seed, cli.seed) {
+        status!("Shuffling tied configurations with seed {} (pass --seed {} to repeat this order).", seed, seed);
+    }
+
+    #[cfg(feature = "script")]
+    if let Some(script) = &cli.score_script {
+        scoring.custom_score = Some(script.clone());
+    }
+
+    config.apply_scoring(&mut scoring);
+    scoring.job_weights.extend(profile.job_weights);
+    scoring
+}
+
+/// A seed that differs from run to run, taken from the random keys the standard library gives each `RandomState`.
+fn random_seed() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Shows how old the cached data of each member and their alts is, since offline it may be well out of date.
+fn print_data_ages(provider: &dyn CharacterProvider, party: &[PlayerCharacter]) {
+    status!("{}", i18n::text(Message::OfflineData));
+    let members = party.iter()
+        .flat_map(|character| iter::once((character.id, &character.name)).chain(character.alts.iter().map(|alt| (alt.id, &alt.name))));
+    for (id, name) in members {
+        // Characters entered by hand have nothing cached.
+        if let Some(age) = provider.character_age(id).filter(|_| id != 0) {
+            status!("{}", i18n::format(Message::DataAge, &[name, &describe_age(age)]));
+        }
+    }
+    status!();
+}
+
+/// Describes `age` to the nearest minute, in at most two units, e.g. "3d 4h" or "25m".
+fn describe_age(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Describes how `character`'s levels have changed since `previous`, e.g. "Bob: DRG 64 → 67 (+3)".
+fn describe_level_changes(character: &PlayerCharacter, previous: &StoredCharacter) -> Option<String> {
+    let changes: Vec<String> = previous.level_changes(character).into_iter()
+        .map(|(class_job, level)| format!("{} {} → {} ({:+})", class_job.abbreviation(), level, class_job.level, class_job.level as i16 - level as i16))
+        .collect();
+
+    if changes.is_empty() {
+        None
+    } else {
+        Some(format!("{}: {}", character.display_name(), changes.join(", ")))
+    }
+}
+
+fn apply_members(cli: &Cli, config: &Config, party: &mut [PlayerCharacter]) {
+    config.apply_members(party);
+    for flag in &cli.buffs {
+        for character in party.iter_mut().filter(|character| character.matches_name(&flag.character)) {
+            character.buffs.push(flag.buff);
+        }
+    }
+    for name in &cli.trials {
+        for character in party.iter_mut().filter(|character| character.matches_name(name)) {
+            character.trial = true;
+        }
+    }
+    for flag in &cli.expansions {
+        for character in party.iter_mut().filter(|character| character.matches_name(&flag.character)) {
+            character.expansion = Some(flag.expansion);
+        }
+    }
+}
+
+fn run_free_company(cli: &Cli, api: &XivApi, config: &Config, server_name: &str, name: &str) -> Result<(), Exit> {
+    if cli.fc_size != 4 && cli.fc_size != 8 {
+        println!("Free company parties must have either four or eight members!");
+        return Err(Exit::InvalidConstraints);
+    }
+
+    println!("Searching for {} in the Lodestone...", name);
+    let free_company_search = match api.search_free_company(name, server_name) {
+        Ok(free_company_search) => free_company_search,
+        Err(e) => {
+            println!("Failed to search for {}: {}", name, e);
+            return Err(Exit::from(&e));
+        }
+    };
+
+    let free_company = match free_company_search.pagination.results {
+        1 => &free_company_search.results[0],
+        0 => {
+            println!("No free company with that name was found!");
+            return Err(Exit::NotFound);
+        },
+        _ => {
+            println!("Multiple free companies were found!");
+            return Err(Exit::Ambiguous);
+        }
+    };
+
+    println!("Getting the member list of {}...", free_company.name);
+    let members = match api.free_company_members(&free_company.id) {
+        Ok(members) => members,
+        Err(e) => {
+            println!("Failed to get the member list of {}: {}", free_company.name, e);
+            return Err(Exit::from(&e));
+        }
+    };
+
+    let mut pool = Vec::with_capacity(members.len());
+    for (i, member) in members.iter().enumerate() {
+        println!("Getting character data for {} ({} of {})...", member.name, i + 1, members.len());
+        match api.character(member.id) {
+            Ok(mut character) => {
+                character.retain_mode(cli.mode);
+                pool.push(character);
+            },
+            Err(e) => println!("Skipping {}: {}", member.name, e)
+        }
+    }
+
+    apply_members(cli, config, &mut pool);
+
+    let constraints = cli_constraints(cli, config);
+    let scoring = cli_scoring(cli, config);
+
+    println!("Determining the best {}-member parties in {}...\n", cli.fc_size, free_company.name);
+    let selections = if cli.anneal {
+        let seed = cli.anneal_seed.unwrap_or_else(random_seed);
+        if cli.anneal_seed.is_none() {
+            status!("Annealing with seed {} (pass --anneal-seed {} to repeat this search).", seed, seed);
+        }
+        selection::anneal(&pool, cli.fc_size, &scoring, &constraints, cli.top, cli.anneal_steps, seed)
+    } else {
+        selection::select(&pool, cli.fc_size, &scoring, &constraints, cli.top)
+    };
+
+    if selections.is_empty() {
+        println!("No valid party configurations were found!");
+        return Err(Exit::NoConfigurations);
+    }
+
+    for (rank, selection) in selections.iter().enumerate() {
+        println!("#{}", rank + 1);
+        output::print_selection(&pool, selection, &scoring);
+        println!();
+    }
+
+    // Neither search tries every party, so the best found may not be the best there is.
+    if let Some(bound) = selection::score_bound(&pool, cli.fc_size, &scoring, &constraints) {
+        let best = selections[0].score;
+        println!("Best score found: {:.2}, at most {:.2} worse than the best possible ({:.2})", best, best - bound, bound);
+    }
+    Ok(())
+}
+
+/// Shows the configurations on the Pareto front of spread and average level.
+fn run_pareto(cli: &Cli, party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints) -> Result<(), Exit> {
+    status!("{}\n", i18n::text(Message::Determining));
+    let front = optimizer::pareto_front(party, scoring, constraints);
+    if front.is_empty() {
+        status!("{}", i18n::text(Message::NoConfigurations));
+        return Err(Exit::NoConfigurations);
+    }
+
+    if cli.quiet {
+        let best_score = front.iter().map(|party_config| party_config.score).fold(f64::INFINITY, f64::min);
+        for party_config in &front {
+            println!("{}", output::config_json(party, party_config, best_score));
+        }
+        return Ok(());
+    }
+
+    output::print_matrix(party);
+    println!();
+    println!(
+        "{} configuration(s) where the spread can't be lowered without lowering the average level, from the lowest spread:\n",
+        front.len()
+    );
+    for (rank, party_config) in front.iter().enumerate() {
+        println!("#{}", rank + 1);
+        output::print_party_config(party, party_config, scoring);
+        println!();
+    }
+    Ok(())
+}
+
+fn run_split(cli: &Cli, pool: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints) -> Result<(), Exit> {
+    println!("Determining the best ways to split the characters into two parties...\n");
+    let splits = selection::split(pool, scoring, constraints, cli.top);
+
+    if splits.is_empty() {
+        println!("No valid way to split the characters into two parties was found!");
+        return Err(Exit::NoConfigurations);
+    }
+
+    for (rank, split) in splits.iter().enumerate() {
+        println!("#{}", rank + 1);
+        for (i, split_party) in split.parties.iter().enumerate() {
+            println!("Party {}:", i + 1);
+            output::print_party_config(&split_party.party, &split_party.config, scoring);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+fn run_event(pool: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints) -> Result<(), Exit> {
+    println!("Forming the {} sign-ups into parties...\n", pool.len());
+    let event = selection::event(pool, scoring, constraints);
+
+    if event.parties.is_empty() {
+        println!("No party could be formed from the sign-ups!");
+        return Err(Exit::NoConfigurations);
+    }
+
+    for (i, event_party) in event.parties.iter().enumerate() {
+        println!("Party {}:", i + 1);
+        output::print_party_config(&event_party.party, &event_party.config, scoring);
+        println!();
+    }
+
+    if !event.left_out.is_empty() {
+        println!("Left out:");
+        for (member, reason) in &event.left_out {
+            let reason = match reason {
+                LeftOut::NoJobs => "none of their jobs are allowed".to_owned(),
+                LeftOut::TooFew => "too few were left over for another party".to_owned(),
+                LeftOut::Roles(roles) => {
+                    let roles: Vec<&str> = roles.iter().map(Role::name).collect();
+                    format!("plays {}, but those left over can't fill another party's tank and healer slots", roles.join(" or "))
                 }
+                LeftOut::Constraints => "those left over have no configuration the constraints allow".to_owned()
+            };
+            println!("- {}: {}", pool[*member].display_name(), reason);
+        }
+    }
+    Ok(())
+}
 
-                avg += job1.level as u32;
+fn run_projection(cli: &Cli, party: &[PlayerCharacter], party_configs: &[PartyConfig], rank: usize) -> Result<(), Exit> {
+    let Some(party_config) = rank.checked_sub(1).and_then(|index| party_configs.get(index)) else {
+        println!("There is no configuration #{}!", rank);
+        return Err(Exit::NoConfigurations);
+    };
+
+    let duty = match &cli.duty {
+        Some(name) => match duty::find(name) {
+            Some(duty) => duty,
+            None => {
+                println!("No duty called {} was found!", name);
+                return Err(Exit::Failure);
+            }
+        },
+        None => match duty::highest_available(party_config.min_level, DutyKind::Dungeon, 1).pop() {
+            Some(duty) => duty,
+            None => {
+                println!("Configuration #{} cannot enter any dungeons yet!", rank);
+                return Err(Exit::Failure);
             }
+        }
+    };
 
-            avg /= combination.len() as u32;
+    if duty.level > party_config.min_level {
+        println!("Configuration #{} is too low level for {}!", rank, duty.name);
+        return Err(Exit::Failure);
+    }
 
-            party_configs.push(PartyConfig {
-                index: index,
-                var: var,
-                avg: avg
-            })
+    match projection::project(party, party_config, duty, MAX_PROJECTED_RUNS) {
+        Some(projection) => output::print_projection(party, &projection),
+        None => {
+            println!("There is no EXP data for {}!", duty.name);
+            return Err(Exit::Failure);
         }
+    }
+    Ok(())
+}
 
-        for i in (0..combination.len()).rev() {
-            combination[i] += 1;
-            if combination[i] >= party[i].class_jobs.len() {
-                combination[i] = 0;
-            } else {
-                break;
+fn prompt_locks(constraints: &mut Constraints) {
+    let mut input = " ".to_owned();
+    while !input.is_empty() {
+        input.clear();
+
+        println!("{}", i18n::text(Message::LockPrompt));
+        io::stdin().read_line(&mut input).unwrap();
+
+        input = input.trim().to_owned();
+
+        if !input.is_empty() {
+            match input.parse() {
+                Ok(lock) => constraints.locks.push(lock),
+                Err(_) => println!("{}", i18n::text(Message::InvalidLock))
             }
         }
+    }
+}
 
-        let mut back_to_start = true;
-        for i in 0..combination.len() {
-            if combination[i] > 0 {
-                back_to_start = false;
-                break;
-            }
+/// Asks for the jobs and levels of a member whose profile is hidden. Returns false if none were given.
+fn prompt_jobs(character: &mut PlayerCharacter) -> bool {
+    let mut input = String::new();
+
+    loop {
+        input.clear();
+
+        println!("{}", i18n::format(Message::JobsPrompt, &[&character.name]));
+        io::stdin().read_line(&mut input).unwrap();
+
+        if input.trim().is_empty() {
+            return false;
         }
 
-        if back_to_start {
-            break;
+        match parse_manual_jobs(&input) {
+            Some(class_jobs) => {
+                character.class_jobs = class_jobs;
+                return true;
+            },
+            None => println!("{}", i18n::text(Message::InvalidJobs))
         }
     }
+}
 
-    let mut input = String::new();
-    while !(input.eq("q") || party_configs.is_empty()) {
-        if let Some(party_config) = party_configs.pop() {
-            for i in 0..party.len() {
-                let class_job = &party[i].class_jobs[party_config.index[i]];
-                println!("{0: <20}: {1: <15} Lv {2}", party[i].name, class_job.name(), class_job.level);
-            }
-            println!("- Lv Var: {}", party_config.var);
-            println!("- Lv Avg: {}", party_config.avg);
+/// Parses a list like `WAR 63, WHM 58`. A class shared by more than one job, like ACN, counts for each of them.
+fn parse_manual_jobs(input: &str) -> Option<Vec<ClassJob>> {
+    let mut class_jobs = Vec::new();
+    for entry in input.split(',') {
+        let (name, level) = entry.trim().rsplit_once(' ')?;
+        let level = level.parse().ok().filter(|level| (1..=job::LEVEL_CAP).contains(level))?;
+        let infos: Vec<_> = job::JOBS.iter().filter(|info| info.matches(name.trim())).collect();
+        if infos.is_empty() {
+            return None;
+        }
+        class_jobs.extend(infos.into_iter().map(|info| ClassJob::manual(info, level)));
+    }
+
+    xivapi::merge_class_jobs(&mut class_jobs);
+    Some(class_jobs)
+}
+
+fn prompt_roles(character: &PlayerCharacter, constraints: &mut Constraints) {
+    if prompt::available() {
+        if let Some(roles) = prompt::roles(&character.name) {
+            constraints.roles.push(RolePreference {
+                character: character.name.clone(),
+                roles
+            });
         }
+        return;
+    }
+
+    let mut input = String::new();
 
+    loop {
         input.clear();
+
+        println!("{}", i18n::format(Message::RolesPrompt, &[&character.name]));
         io::stdin().read_line(&mut input).unwrap();
-        input = input.trim().to_owned();
+
+        if input.trim().is_empty() {
+            return;
+        }
+
+        let roles: Result<Vec<Role>, String> = input.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|role| !role.is_empty())
+            .map(str::parse)
+            .collect();
+
+        match roles {
+            Ok(roles) => {
+                constraints.roles.push(RolePreference {
+                    character: character.name.clone(),
+                    roles
+                });
+                return;
+            },
+            Err(_) => println!("{}", i18n::text(Message::InvalidRoles))
+        }
     }
 }
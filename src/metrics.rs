@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds, in seconds, of the buckets durations are counted in.
+pub const DURATION_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+/// Counts of how long something took, by bucket, like a Prometheus histogram.
+#[derive(Debug, Default)]
+pub struct Histogram {
+    /// How many took at most each of `DURATION_BUCKETS`, but longer than the one before.
+    buckets: [AtomicU64; DURATION_BUCKETS.len()],
+    count: AtomicU64,
+    total_micros: AtomicU64
+}
+
+impl Histogram {
+    pub fn observe(&self, duration: Duration) {
+        let secs = duration.as_secs_f64();
+        if let Some(bucket) = DURATION_BUCKETS.iter().position(|&bound| secs <= bound) {
+            self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_micros.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// How many took at most each of `DURATION_BUCKETS`, counting the shorter buckets too, as Prometheus expects.
+    pub fn cumulative(&self) -> Vec<(f64, u64)> {
+        let mut total = 0;
+        DURATION_BUCKETS.iter()
+            .zip(&self.buckets)
+            .map(|(&bound, count)| {
+                total += count.load(Ordering::Relaxed);
+                (bound, total)
+            })
+            .collect()
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Total of every duration observed.
+    pub fn total(&self) -> Duration {
+        Duration::from_micros(self.total_micros.load(Ordering::Relaxed))
+    }
+}
+
+/// What a client has requested from XIVAPI, for monitoring a long-running service.
+#[derive(Debug, Default)]
+pub struct ApiMetrics {
+    /// Responses served from the cache.
+    pub cache_hits: AtomicU64,
+    /// Responses that had to be requested, because they weren't cached or were too old.
+    pub cache_misses: AtomicU64,
+    /// How long requests to XIVAPI took.
+    pub latency: Histogram
+}
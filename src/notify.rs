@@ -0,0 +1,15 @@
+use notify_rust::Notification;
+
+/// Shows a desktop notification that the party has reached `level` and can now run `unlocked`.
+pub fn unlocked(level: u8, unlocked: &[&str]) {
+    let result = Notification::new()
+        .summary(&format!("Your party has reached Lv {}!", level))
+        .body(&format!("Now available: {}", unlocked.join(", ")))
+        .appname("xiv-levelling")
+        .show();
+
+    // Not every system has a notification daemon, and the message has already been printed anyway.
+    if let Err(e) = result {
+        eprintln!("Failed to show a desktop notification: {}", e);
+    }
+}
@@ -0,0 +1,1432 @@
+use rayon::prelude::*;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fmt::Debug;
+use std::iter;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering as AtomicOrdering};
+
+use crate::constraints::Constraints;
+use crate::job::{self, JobInfo, Role, SubRole};
+use crate::xivapi::{ClassJob, PlayerCharacter};
+
+/// How the spread of levels within a configuration is measured.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Metric {
+    /// Sum of the level differences between every pair of members.
+    Pairwise,
+    /// Difference between the highest and lowest levels.
+    Range,
+    /// Standard deviation of the levels.
+    StdDev,
+    /// Largest difference between two consecutive levels when sorted.
+    MaxGap
+}
+
+impl Metric {
+    pub fn spread(&self, values: &[f64]) -> f64 {
+        let max = values.iter().copied().fold(f64::MIN, f64::max);
+        let min = values.iter().copied().fold(f64::MAX, f64::min);
+
+        match self {
+            Metric::Pairwise => {
+                let mut var = 0.0;
+                for (i, &value1) in values.iter().enumerate() {
+                    for (j, &value2) in values.iter().enumerate() {
+                        if i != j {
+                            var += (value1 - value2).abs();
+                        }
+                    }
+                }
+                var
+            },
+            Metric::Range => max - min,
+            Metric::StdDev => {
+                let n = values.len() as f64;
+                let mean = values.iter().sum::<f64>() / n;
+                let variance = values.iter().map(|&value| (value - mean).powi(2)).sum::<f64>() / n;
+                variance.sqrt()
+            },
+            Metric::MaxGap => {
+                let mut sorted = values.to_vec();
+                sorted.sort_unstable_by(f64::total_cmp);
+                sorted.windows(2).map(|pair| pair[1] - pair[0]).fold(0.0, f64::max)
+            }
+        }
+    }
+
+    /// Short label used when displaying the spread of a configuration.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Metric::Pairwise => "Var",
+            Metric::Range => "Range",
+            Metric::StdDev => "Std Dev",
+            Metric::MaxGap => "Max Gap"
+        }
+    }
+
+    /// Formats a spread value, only showing decimals for metrics that produce them.
+    pub fn format(&self, spread: f64) -> String {
+        match self {
+            Metric::StdDev => format!("{:.2}", spread),
+            _ => format!("{}", spread)
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pairwise" => Ok(Metric::Pairwise),
+            "range" => Ok(Metric::Range),
+            "stddev" => Ok(Metric::StdDev),
+            "max-gap" => Ok(Metric::MaxGap),
+            _ => Err(format!("unknown metric {} (expected pairwise, range, stddev or max-gap)", s))
+        }
+    }
+}
+
+/// What the spread of a configuration is measured over.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Balance {
+    /// Each member's job level.
+    Level,
+    /// Each member's total EXP earned on their job, so progress within a level counts.
+    Exp
+}
+
+impl Balance {
+    pub(crate) fn value(&self, class_job: &ClassJob) -> f64 {
+        match self {
+            Balance::Level => class_job.level as f64,
+            // Without progress data, assume the job has only just reached its level.
+            Balance::Exp => class_job.total_exp().unwrap_or_else(|| job::exp_to_reach(class_job.level)) as f64
+        }
+    }
+
+    /// What `value` would be for a job that has only just reached `level`.
+    pub(crate) fn value_at(&self, level: u8) -> f64 {
+        match self {
+            Balance::Level => level as f64,
+            Balance::Exp => job::exp_to_reach(level) as f64
+        }
+    }
+
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Balance::Level => "Lv",
+            Balance::Exp => "EXP"
+        }
+    }
+}
+
+impl FromStr for Balance {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "level" => Ok(Balance::Level),
+            "exp" => Ok(Balance::Exp),
+            _ => Err(format!("unknown balance {} (expected level or exp)", s))
+        }
+    }
+}
+
+/// What a custom score is given of a complete configuration.
+pub struct ScoreInput<'a> {
+    /// Each member and the job they play.
+    pub members: Vec<(&'a PlayerCharacter, &'a ClassJob)>,
+    pub spread: f64,
+    pub avg: f64,
+    pub min: u8,
+    pub max: u8,
+    pub bonus_count: u8,
+    /// The score the built-in weights give the configuration.
+    pub score: f64
+}
+
+/// Scores configurations in place of the built-in weights, for groups whose preferences they can't express. As with
+/// the built-in score, lower is better.
+pub trait CustomScore: Debug + Send + Sync {
+    fn score(&self, input: &ScoreInput) -> f64;
+
+    /// The first error scoring a configuration, if there was one. Configurations that fail to score rank last.
+    fn error(&self) -> Option<String> {
+        None
+    }
+}
+
+/// How much each statistic contributes to a configuration's score, where a lower score is better.
+#[derive(Clone, Debug)]
+pub struct Scoring {
+    pub metric: Metric,
+    pub balance: Balance,
+    pub weight_var: f64,
+    pub weight_avg: f64,
+    /// How strongly to prefer configurations where more members earn the armoury bonus.
+    pub weight_bonus: f64,
+    /// How strongly to prefer configurations whose DPS cover more sub-roles.
+    pub weight_diversity: f64,
+    /// How strongly to avoid configurations where more than one member plays the same job.
+    pub weight_duplicate: f64,
+    /// How much the group likes each job, by name: positive to prefer configurations with it and negative to avoid
+    /// them.
+    pub job_weights: BTreeMap<String, f64>,
+    /// Level cap of the duty the party is aiming for, if any. Every level or EXP a member is below it counts
+    /// against a configuration, since the duty would have rewarded a higher-level member for the same run.
+    pub target_sync: Option<u8>,
+    /// The role Duty Roulette is currently rewarding as in need. There is no API for it, so it has to be given.
+    pub in_need: Option<Role>,
+    /// How strongly to prefer configurations where the member furthest behind plays the in-need role, so the bonus
+    /// goes to whoever needs EXP the most.
+    pub weight_in_need: f64,
+    /// How strongly to prefer jobs members have been playing lately, going by their play history.
+    pub weight_familiarity: f64,
+    /// How strongly to prefer configurations that give members their favourite role if they've rarely had it lately,
+    /// and rotate them off roles they keep being given, going by their role history.
+    pub weight_fairness: f64,
+    /// How strongly to avoid configurations of a partial party that leave a tank or healer slot for the Duty Finder
+    /// to fill, since there are far fewer of them queueing than DPS.
+    pub weight_queue: f64,
+    /// Replaces the score of every complete configuration, if given.
+    pub custom_score: Option<Arc<dyn CustomScore>>,
+    /// Seed for shuffling configurations that tie on score and average level, if they should be. The same seed
+    /// always puts them in the same order.
+    pub tie_seed: Option<u64>
+}
+
+impl Scoring {
+    /// Whether scores can only get worse as the spread, duplicates or missing roles grow and as the average level,
+    /// bonuses and variety shrink, so that a best case bounds them. Weights below zero or a custom score break that.
+    pub(crate) fn can_bound(&self) -> bool {
+        let weights = [
+            self.weight_var,
+            self.weight_avg,
+            self.weight_bonus,
+            self.weight_diversity,
+            self.weight_duplicate,
+            self.weight_in_need,
+            self.weight_queue
+        ];
+        self.custom_score.is_none() && weights.iter().all(|&weight| weight >= 0.0)
+    }
+
+    /// Label for a configuration's spread, e.g. "Lv Var".
+    pub fn spread_label(&self) -> String {
+        format!("{} {}", self.balance.prefix(), self.metric.label())
+    }
+
+    /// The score of a complete configuration of `members`, which is `score` unless a custom score replaces it.
+    pub(crate) fn complete_score(&self, members: &[(&PlayerCharacter, &ClassJob)], spread: f64, avg: f64, bonus_count: u8, score: f64) -> f64 {
+        let Some(custom_score) = &self.custom_score else {
+            return score;
+        };
+
+        let levels = || members.iter().map(|(_, class_job)| class_job.level);
+        custom_score.score(&ScoreInput {
+            members: members.to_vec(),
+            spread,
+            avg,
+            min: levels().min().unwrap_or_default(),
+            max: levels().max().unwrap_or_default(),
+            bonus_count,
+            score
+        })
+    }
+
+    /// `job_bonus` is the sum of `job_bonus` over the configuration's jobs.
+    pub(crate) fn score(&self, spread: f64, avg: f64, bonus_count: u8, sub_role_count: u8, duplicate_count: u8, job_bonus: f64) -> f64 {
+        self.weight_var * spread - self.weight_avg * avg - self.weight_bonus * bonus_count as f64
+            - self.weight_diversity * sub_role_count as f64 + self.weight_duplicate * duplicate_count as f64 - job_bonus
+    }
+
+    /// How much `character` playing `class_job` improves a configuration on its own, regardless of the other
+    /// members' jobs.
+    pub(crate) fn job_bonus(&self, character: &PlayerCharacter, class_job: &ClassJob) -> f64 {
+        self.job_weight(class_job.job) - self.waste(class_job) + self.weight_familiarity * character.familiarity(class_job)
+            + self.weight_fairness * character.fairness(class_job)
+    }
+
+    /// `weight_in_need` if a member furthest behind, going by `values`, plays the in-need role in `class_jobs`.
+    pub(crate) fn in_need_bonus<'a>(&self, values: &[f64], class_jobs: impl Iterator<Item = &'a ClassJob>) -> f64 {
+        let Some(role) = self.in_need else {
+            return 0.0;
+        };
+
+        let lowest = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let covered = values.iter().zip(class_jobs).any(|(&value, class_job)| value == lowest && class_job.role() == Some(role));
+        if covered { self.weight_in_need } else { 0.0 }
+    }
+
+    /// `weight_queue` for each of a tank and healer missing from `class_jobs`.
+    pub(crate) fn queue_penalty<'a>(&self, class_jobs: impl Iterator<Item = &'a ClassJob> + Clone) -> f64 {
+        self.weight_queue * missing_roles(class_jobs) as f64
+    }
+
+    /// How far below the target duty's level cap `class_job` is, in whatever `balance` measures.
+    pub fn waste(&self, class_job: &ClassJob) -> f64 {
+        match self.target_sync {
+            Some(sync) => (self.balance.value_at(sync) - self.balance.value(class_job)).max(0.0),
+            None => 0.0
+        }
+    }
+
+    /// The group's weight for `info`, or 0 if they have no opinion on it.
+    pub fn job_weight(&self, info: &JobInfo) -> f64 {
+        self.job_weights.iter()
+            .filter(|(name, _)| info.matches(name))
+            .map(|(_, weight)| weight)
+            .sum()
+    }
+
+    /// Describes any job weight that can't apply, i.e. one naming a job that doesn't exist.
+    pub fn warnings(&self) -> Vec<String> {
+        self.job_weights.keys()
+            .filter(|name| !job::JOBS.iter().any(|info| info.matches(name)))
+            .map(|name| format!("There is no job called {} (in the job weights)!", name))
+            .collect()
+    }
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Scoring {
+            metric: Metric::Pairwise,
+            balance: Balance::Level,
+            weight_var: 1.0,
+            weight_avg: 0.0,
+            weight_bonus: 0.0,
+            weight_diversity: 0.0,
+            weight_duplicate: 0.0,
+            job_weights: BTreeMap::new(),
+            target_sync: None,
+            in_need: None,
+            weight_in_need: 5.0,
+            weight_familiarity: 0.0,
+            weight_fairness: 0.0,
+            weight_queue: 10.0,
+            custom_score: None,
+            tie_seed: None
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PartyConfig {
+    pub index: [usize; 4],
+    pub spread: f64,
+    pub avg: u32,
+    /// Level of the lowest member, which the party is effectively synced to.
+    pub min_level: u8,
+    /// Number of members whose job earns the armoury bonus.
+    pub bonus_count: u8,
+    pub score: f64,
+    /// Breaks ties between configurations with the same score and average level before their job names do, when
+    /// they are shuffled.
+    pub tie_break: u64,
+    /// Name of each member's job, used to break ties between otherwise equal configurations.
+    pub names: [&'static str; 4]
+}
+
+impl Ord for PartyConfig {
+    /// Configurations compare greater when they are better: a lower score, then a higher average level, then a lower
+    /// tie break, then alphabetically earlier job names.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.score.total_cmp(&self.score)
+            .then(self.avg.cmp(&other.avg))
+            .then(other.tie_break.cmp(&self.tie_break))
+            .then_with(|| other.names.cmp(&self.names))
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialEq for PartyConfig {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PartyConfig {}
+
+impl PartialOrd for PartyConfig {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Number of different DPS sub-roles among `class_jobs`, and whether any sub-role is played by more than one of them.
+pub(crate) fn sub_roles<'a>(class_jobs: impl IntoIterator<Item = &'a ClassJob>) -> (u8, bool) {
+    let mut covered: Vec<SubRole> = Vec::with_capacity(3);
+    let mut shared = false;
+
+    for sub_role in class_jobs.into_iter().filter_map(ClassJob::sub_role) {
+        if covered.contains(&sub_role) {
+            shared = true;
+        } else {
+            covered.push(sub_role);
+        }
+    }
+
+    (covered.len() as u8, shared)
+}
+
+/// Number of tank and healer roles none of `class_jobs` fill.
+pub(crate) fn missing_roles<'a>(class_jobs: impl Iterator<Item = &'a ClassJob> + Clone) -> u8 {
+    [Role::Tank, Role::Healer].iter()
+        .filter(|&&role| !class_jobs.clone().any(|class_job| class_job.role() == Some(role)))
+        .count() as u8
+}
+
+/// Number of `class_jobs` that are the same job as an earlier one.
+pub(crate) fn duplicate_jobs<'a>(class_jobs: impl IntoIterator<Item = &'a ClassJob>) -> u8 {
+    let mut seen: Vec<u8> = Vec::with_capacity(4);
+    let mut duplicates = 0;
+
+    for class_job in class_jobs {
+        if seen.contains(&class_job.job.job_id) {
+            duplicates += 1;
+        } else {
+            seen.push(class_job.job.job_id);
+        }
+    }
+
+    duplicates
+}
+
+/// Counters a search updates as it goes, so that another thread can report how far along it is or stop it early.
+#[derive(Debug, Default)]
+pub struct Progress {
+    /// Assignments of jobs to members either evaluated or ruled out so far, out of `search_size`.
+    pub evaluated: AtomicU64,
+    /// Valid configurations found so far.
+    pub found: AtomicU64,
+    /// Set to stop the search, which then returns the best configurations found so far.
+    pub cancelled: AtomicBool
+}
+
+/// Callback for streaming configurations out of a search, which may be called from several threads at once.
+pub type OnFound<'a> = dyn Fn(&PartyConfig) + Sync + 'a;
+
+/// State for a depth-first search over job assignments, one member at a time.
+struct Search<'a> {
+    party: &'a [PlayerCharacter],
+    scoring: &'a Scoring,
+    constraints: &'a Constraints,
+    /// The indices into each member's class_jobs that the constraints allow them to play.
+    allowed: &'a [Vec<usize>],
+    /// Whether each of each member's class_jobs earns the armoury bonus.
+    bonuses: &'a [Vec<bool>],
+    /// The `job_bonus` of each of each member's class_jobs.
+    job_bonuses: &'a [Vec<f64>],
+    /// Highest level each member could be assigned, used to bound the average of a partial assignment.
+    max_levels: &'a [u8],
+    /// Highest job bonus each member could be assigned, used to bound the bonus of a partial assignment.
+    max_job_bonuses: &'a [f64],
+    /// How many configurations to keep, if not all of them.
+    limit: Option<usize>,
+    /// A score none of the configurations kept can be worse than, known before the search from at least `limit`
+    /// valid configurations it will find.
+    bound: Option<f64>,
+    progress: Option<&'a Progress>,
+    /// Called with every valid configuration as soon as it is found.
+    on_found: Option<&'a OnFound<'a>>,
+    /// Number of assignments of the members from each index onwards.
+    subtree_sizes: &'a [u64],
+    job_indices: Vec<usize>,
+    /// The best `limit` configurations found so far, with the worst on top.
+    party_configs: BinaryHeap<Reverse<PartyConfig>>,
+    stats: SearchStats
+}
+
+/// How much work a search did, and what ruled out the assignments of jobs to members that didn't make it.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct SearchStats {
+    /// Assignments of the jobs left to search, out of `search_size`.
+    pub combinations: u64,
+    /// Jobs left out of the search before it started for not being unlocked.
+    pub locked_jobs: u64,
+    /// Jobs left out by the constraints, e.g. for being above the maximum level or excluded.
+    pub filtered_jobs: u64,
+    /// Jobs left out for being interchangeable with another of the same member's.
+    pub equivalent_jobs: u64,
+    /// Assignments ruled out for not having the roles the constraints ask for.
+    pub wrong_roles: u64,
+    /// Assignments ruled out for sharing a job or DPS sub-role when the constraints ask for variety.
+    pub not_varied: u64,
+    /// Assignments ruled out for their levels being further apart than the constraints allow.
+    pub wide_gap: u64,
+    /// Assignments skipped without being scored, because they couldn't beat the configurations already kept.
+    pub pruned: u64,
+    /// Complete assignments scored.
+    pub evaluated: u64,
+    /// Scored assignments ruled out for everyone being at the level cap, or the average level being outside the
+    /// constraints.
+    pub out_of_range: u64,
+    /// Valid configurations found.
+    pub found: u64
+}
+
+impl SearchStats {
+    fn add(self, other: SearchStats) -> SearchStats {
+        SearchStats {
+            combinations: self.combinations + other.combinations,
+            locked_jobs: self.locked_jobs + other.locked_jobs,
+            filtered_jobs: self.filtered_jobs + other.filtered_jobs,
+            equivalent_jobs: self.equivalent_jobs + other.equivalent_jobs,
+            wrong_roles: self.wrong_roles + other.wrong_roles,
+            not_varied: self.not_varied + other.not_varied,
+            wide_gap: self.wide_gap + other.wide_gap,
+            pruned: self.pruned + other.pruned,
+            evaluated: self.evaluated + other.evaluated,
+            out_of_range: self.out_of_range + other.out_of_range,
+            found: self.found + other.found
+        }
+    }
+
+    /// Assignments either scored or ruled out, which is less than `combinations` if the search was stopped early.
+    pub fn examined(&self) -> u64 {
+        self.wrong_roles + self.not_varied + self.wide_gap + self.pruned + self.evaluated
+    }
+}
+
+impl Search<'_> {
+    /// Whether the spread of a partial assignment can only grow as members are added, so that it bounds the
+    /// final score.
+    fn can_bound(&self) -> bool {
+        matches!(self.scoring.metric, Metric::Pairwise | Metric::Range) && self.scoring.can_bound()
+    }
+
+    /// The best score any completion of the current partial assignment could reach.
+    fn lower_bound(&self) -> f64 {
+        let assigned = self.job_indices.len();
+        let mut values = Vec::with_capacity(assigned);
+        let mut sum = 0;
+        let mut bonus_count = 0;
+        let mut job_bonus = 0.0;
+
+        for (i, (character, &job_index)) in self.party.iter().zip(&self.job_indices).enumerate() {
+            let class_job = &character.class_jobs[job_index];
+            values.push(self.scoring.balance.value(class_job));
+            sum += class_job.level as u32;
+            if self.bonuses[i][job_index] {
+                bonus_count += 1;
+            }
+            job_bonus += self.job_bonuses[i][job_index];
+        }
+
+        let remaining = self.party.len() - assigned;
+        sum += self.max_levels[assigned..].iter().map(|&level| level as u32).sum::<u32>();
+        job_bonus += self.max_job_bonuses[assigned..].iter().sum::<f64>();
+        // Until everyone is assigned, it isn't known who is furthest behind.
+        if self.scoring.in_need.is_some() {
+            job_bonus += self.scoring.weight_in_need;
+        }
+        bonus_count += remaining as u8;
+        let (sub_role_count, _) = sub_roles(self.assigned_jobs());
+        let sub_role_count = (sub_role_count + remaining as u8).min(3);
+        // Members still to be assigned can only add duplicates.
+        let duplicate_count = duplicate_jobs(self.assigned_jobs());
+
+        self.scoring.score(
+            self.scoring.metric.spread(&values),
+            sum as f64 / self.party.len() as f64,
+            bonus_count,
+            sub_role_count,
+            duplicate_count,
+            job_bonus
+        )
+    }
+
+    /// The jobs assigned to the members so far.
+    fn assigned_jobs(&self) -> impl Iterator<Item = &ClassJob> {
+        self.party.iter().zip(&self.job_indices).map(|(character, &job_index)| &character.class_jobs[job_index])
+    }
+
+    fn pruned(&self) -> bool {
+        // Anything streamed out must see every valid configuration, not just ones that could make the top.
+        let (Some(limit), None) = (self.limit, self.on_found) else {
+            return false;
+        };
+
+        let worst = match self.party_configs.peek() {
+            Some(Reverse(worst)) if self.party_configs.len() >= limit => Some(worst.score),
+            _ => None
+        };
+        match worst.into_iter().chain(self.bound).reduce(f64::min) {
+            Some(worst) if self.can_bound() => self.lower_bound() > worst,
+            _ => false
+        }
+    }
+
+    /// Counts every assignment of the members from `member` onwards as evaluated.
+    fn skip(&self, member: usize) {
+        if let Some(progress) = self.progress {
+            progress.evaluated.fetch_add(self.subtree_sizes[member], AtomicOrdering::Relaxed);
+        }
+    }
+
+    fn visit(&mut self, num_tanks: usize, num_healers: usize) {
+        // Whatever is left is neither evaluated nor ruled out, so it isn't counted.
+        if self.progress.is_some_and(|progress| progress.cancelled.load(AtomicOrdering::Relaxed)) {
+            return;
+        }
+
+        let member = self.job_indices.len();
+
+        let requires_roles = self.constraints.requires_roles();
+        let fills_roles = self.constraints.fills_roles();
+
+        if member == self.party.len() {
+            let roles_filled = if fills_roles {
+                member - num_tanks - num_healers <= 2
+            } else {
+                num_tanks == 1 && num_healers == 1
+            };
+            if !requires_roles || roles_filled {
+                self.evaluate();
+            } else {
+                self.stats.wrong_roles += 1;
+            }
+            self.skip(member);
+            return;
+        }
+
+        // Every remaining member would need to fill a missing tank or healer slot, unless an NPC can fill it.
+        let remaining = self.party.len() - member;
+        if requires_roles && !fills_roles && (1 - num_tanks) + (1 - num_healers) > remaining {
+            self.stats.wrong_roles += self.subtree_sizes[member];
+            self.skip(member);
+            return;
+        }
+
+        if member > 0 && self.pruned() {
+            self.stats.pruned += self.subtree_sizes[member];
+            self.skip(member);
+            return;
+        }
+
+        let allowed = self.allowed;
+        for &job_index in &allowed[member] {
+            self.assign(job_index, num_tanks, num_healers);
+        }
+    }
+
+    /// Assigns `job_index` to the next member and searches every assignment of the members after them.
+    fn assign(&mut self, job_index: usize, num_tanks: usize, num_healers: usize) {
+        let member = self.job_indices.len();
+        let (tanks, healers) = match self.party[member].class_jobs[job_index].role() {
+            Some(Role::Tank) => (num_tanks + 1, num_healers),
+            Some(Role::Healer) => (num_tanks, num_healers + 1),
+            _ => (num_tanks, num_healers)
+        };
+
+        if self.constraints.requires_roles() && (tanks > 1 || healers > 1) {
+            self.stats.wrong_roles += self.subtree_sizes[member + 1];
+            self.skip(member + 1);
+            return;
+        }
+
+        if self.constraints.unique_jobs && self.assigned_jobs().any(|class_job| class_job.job == self.party[member].class_jobs[job_index].job) {
+            self.stats.not_varied += self.subtree_sizes[member + 1];
+            self.skip(member + 1);
+            return;
+        }
+
+        if self.constraints.diverse_dps {
+            let (_, shared) = sub_roles(self.assigned_jobs().chain([&self.party[member].class_jobs[job_index]]));
+            if shared {
+                self.stats.not_varied += self.subtree_sizes[member + 1];
+                self.skip(member + 1);
+                return;
+            }
+        }
+
+        let levels = self.assigned_jobs().chain([&self.party[member].class_jobs[job_index]]).map(|class_job| class_job.level);
+        if !self.constraints.allows_gap(levels) {
+            self.stats.wide_gap += self.subtree_sizes[member + 1];
+            self.skip(member + 1);
+            return;
+        }
+
+        self.job_indices.push(job_index);
+        self.visit(tanks, healers);
+        self.job_indices.pop();
+    }
+
+    fn evaluate(&mut self) {
+        self.stats.evaluated += 1;
+
+        let Some(party_config) = score_config(self.party, self.scoring, self.constraints, self.bonuses, self.job_bonuses, &self.job_indices) else {
+            self.stats.out_of_range += 1;
+            return;
+        };
+        self.stats.found += 1;
+
+        if let Some(progress) = self.progress {
+            progress.found.fetch_add(1, AtomicOrdering::Relaxed);
+        }
+
+        if let Some(on_found) = self.on_found {
+            on_found(&party_config);
+        }
+
+        self.party_configs.push(Reverse(party_config));
+        truncate(&mut self.party_configs, self.limit);
+    }
+}
+
+/// Scores `party` playing the jobs at `job_indices`, or `None` if every member is already at the level cap or the
+/// average level is outside the constraints.
+fn score_config(
+    party: &[PlayerCharacter],
+    scoring: &Scoring,
+    constraints: &Constraints,
+    bonuses: &[Vec<bool>],
+    job_bonuses: &[Vec<f64>],
+    job_indices: &[usize]
+) -> Option<PartyConfig> {
+    let mut index = [0, 0, 0, 0];
+    let mut names = [""; 4];
+    let mut levels = Vec::with_capacity(party.len());
+    let mut values = Vec::with_capacity(party.len());
+    let mut bonus_count = 0;
+    let mut job_bonus = 0.0;
+
+    for (i, (character, &job_index)) in party.iter().zip(job_indices).enumerate() {
+        let class_job = &character.class_jobs[job_index];
+
+        index[i] = job_index;
+        job_bonus += job_bonuses[i][job_index];
+        names[i] = class_job.name();
+        levels.push(class_job.level);
+        values.push(scoring.balance.value(class_job));
+
+        if bonuses[i][job_index] {
+            bonus_count += 1;
+        }
+    }
+
+    let all_max = party.iter().zip(job_indices).all(|(character, &job_index)| {
+        let class_job = &character.class_jobs[job_index];
+        class_job.level >= character.job_cap(class_job)
+    });
+    if all_max {
+        return None;
+    }
+
+    let class_jobs = || party.iter().zip(job_indices).map(|(character, &job_index)| &character.class_jobs[job_index]);
+    let spread = scoring.metric.spread(&values);
+    let sum: u32 = levels.iter().map(|&level| level as u32).sum();
+    let (sub_role_count, _) = sub_roles(class_jobs());
+    let duplicate_count = duplicate_jobs(class_jobs());
+    let queue_penalty = if constraints.partial { scoring.queue_penalty(class_jobs()) } else { 0.0 };
+    let job_bonus = job_bonus + scoring.in_need_bonus(&values, class_jobs()) - queue_penalty;
+    let score = scoring.score(spread, sum as f64 / levels.len() as f64, bonus_count, sub_role_count, duplicate_count, job_bonus);
+    let score = if scoring.custom_score.is_some() {
+        let members: Vec<_> = party.iter().zip(class_jobs()).collect();
+        scoring.complete_score(&members, spread, sum as f64 / levels.len() as f64, bonus_count, score)
+    } else {
+        score
+    };
+    let avg = sum / levels.len() as u32;
+    let min_level = levels.iter().copied().min().unwrap_or_default();
+
+    if !constraints.allows_average(avg) {
+        return None;
+    }
+
+    let tie_break = scoring.tie_seed.map_or(0, |seed| shuffle_key(seed, &index));
+
+    Some(PartyConfig { index, spread, avg, min_level, bonus_count, score, tie_break, names })
+}
+
+/// A key that orders assignments randomly, but the same way every time for the same seed. Each job index is mixed in
+/// with a step of SplitMix64.
+fn shuffle_key(seed: u64, index: &[usize]) -> u64 {
+    index.iter().fold(seed, |key, &job_index| {
+        let mut key = (key ^ job_index as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        key = (key ^ (key >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        key = (key ^ (key >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        key ^ (key >> 31)
+    })
+}
+
+/// Whether `party` playing the jobs at `job_indices` has the roles, and the variety of jobs, the constraints ask for.
+/// The search checks the same as it assigns each member, to rule out whole branches at once.
+fn allows_assignment(party: &[PlayerCharacter], constraints: &Constraints, job_indices: &[usize]) -> bool {
+    let class_jobs = || party.iter().zip(job_indices).map(|(character, &job_index)| &character.class_jobs[job_index]);
+
+    if constraints.requires_roles() {
+        let tanks = class_jobs().filter(|class_job| class_job.role() == Some(Role::Tank)).count();
+        let healers = class_jobs().filter(|class_job| class_job.role() == Some(Role::Healer)).count();
+        let roles_filled = if constraints.fills_roles() {
+            tanks <= 1 && healers <= 1 && party.len() - tanks - healers <= 2
+        } else {
+            tanks == 1 && healers == 1
+        };
+        if !roles_filled {
+            return false;
+        }
+    }
+
+    (!constraints.unique_jobs || duplicate_jobs(class_jobs()) == 0)
+        && (!constraints.diverse_dps || !sub_roles(class_jobs()).1)
+        && constraints.allows_gap(class_jobs().map(|class_job| class_job.level))
+}
+
+/// Drops the worst configurations from `party_configs` until at most `limit` remain.
+fn truncate(party_configs: &mut BinaryHeap<Reverse<PartyConfig>>, limit: Option<usize>) {
+    if let Some(limit) = limit {
+        while party_configs.len() > limit {
+            party_configs.pop();
+        }
+    }
+}
+
+/// Keeps one of each group of `job_indices` sharing a role, level, EXP, armoury bonus, whether it's capped, job weight
+/// and familiarity, and also a DPS sub-role if `by_sub_role`: the one with the alphabetically earliest name, matching
+/// the tie-break between otherwise equal configurations.
+fn dedup_jobs(character: &PlayerCharacter, scoring: &Scoring, job_indices: Vec<usize>, by_sub_role: bool) -> Vec<usize> {
+    let mut kept: Vec<usize> = Vec::with_capacity(job_indices.len());
+
+    for job_index in job_indices {
+        let class_job = &character.class_jobs[job_index];
+        let equivalent = kept.iter_mut().find(|kept_index| {
+            let kept_job = &character.class_jobs[**kept_index];
+            let key = |class_job: &ClassJob| (
+                class_job.role(),
+                class_job.level,
+                class_job.exp_level,
+                character.has_armoury_bonus(class_job),
+                // A job at its cap, like Blue Mage at 70, counts differently from one with levels still to go.
+                class_job.level >= character.job_cap(class_job)
+            );
+            key(kept_job) == key(class_job)
+                && scoring.job_weight(kept_job.job) == scoring.job_weight(class_job.job)
+                && character.familiarity(kept_job) == character.familiarity(class_job)
+                && (!by_sub_role || kept_job.sub_role() == class_job.sub_role())
+        });
+
+        match equivalent {
+            Some(kept_index) if class_job.name() < character.class_jobs[*kept_index].name() => *kept_index = job_index,
+            Some(_) => {},
+            None => kept.push(job_index)
+        }
+    }
+
+    kept
+}
+
+/// The jobs each member of `party` may be assigned.
+fn allowed_jobs(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints) -> Vec<Vec<usize>> {
+    // Swapping a DPS for one of another sub-role only makes a different configuration if sub-roles matter.
+    let by_sub_role = constraints.diverse_dps || scoring.weight_diversity != 0.0;
+    // Likewise, once members may not share a job, any job could be the one that avoids a clash.
+    let dedup = constraints.dedup && !constraints.unique_jobs && scoring.weight_duplicate == 0.0;
+
+    // Jobs that haven't been unlocked can never be part of a valid configuration.
+    party.iter()
+        .map(|character| {
+            let job_indices = (0..character.class_jobs.len())
+                .filter(|&job_index| {
+                    let class_job = &character.class_jobs[job_index];
+                    class_job.level > 0 && constraints.allows(character, class_job)
+                })
+                .collect();
+
+            if dedup {
+                dedup_jobs(character, scoring, job_indices, by_sub_role)
+            } else {
+                job_indices
+            }
+        })
+        .collect()
+}
+
+/// How many of `party`'s jobs were left out of `allowed`, and why.
+fn left_out_jobs(party: &[PlayerCharacter], constraints: &Constraints, allowed: &[Vec<usize>]) -> SearchStats {
+    let mut stats = SearchStats::default();
+    for (character, job_indices) in party.iter().zip(allowed) {
+        let locked = character.class_jobs.iter().filter(|class_job| class_job.level == 0).count();
+        let filtered = character.class_jobs.iter()
+            .filter(|class_job| class_job.level > 0 && !constraints.allows(character, class_job))
+            .count();
+
+        stats.locked_jobs += locked as u64;
+        stats.filtered_jobs += filtered as u64;
+        stats.equivalent_jobs += (character.class_jobs.len() - locked - filtered - job_indices.len()) as u64;
+    }
+    stats
+}
+
+fn subtree_sizes(allowed: &[Vec<usize>]) -> Vec<u64> {
+    let mut sizes = vec![1; allowed.len() + 1];
+    for member in (0..allowed.len()).rev() {
+        sizes[member] = sizes[member + 1] * allowed[member].len() as u64;
+    }
+    sizes
+}
+
+/// Whether each of each member's class_jobs earns the armoury bonus.
+fn armoury_bonuses(party: &[PlayerCharacter]) -> Vec<Vec<bool>> {
+    party.iter()
+        .map(|character| character.class_jobs.iter().map(|class_job| character.has_armoury_bonus(class_job)).collect())
+        .collect()
+}
+
+/// The `job_bonus` of each of each member's class_jobs.
+fn job_bonuses(party: &[PlayerCharacter], scoring: &Scoring) -> Vec<Vec<f64>> {
+    party.iter()
+        .map(|character| character.class_jobs.iter().map(|class_job| scoring.job_bonus(character, class_job)).collect())
+        .collect()
+}
+
+/// Number of ways of assigning jobs to `party` that a search has to consider.
+pub fn search_size(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints) -> u64 {
+    subtree_sizes(&allowed_jobs(party, scoring, constraints))[0]
+}
+
+fn search(
+    party: &[PlayerCharacter],
+    scoring: &Scoring,
+    constraints: &Constraints,
+    limit: Option<usize>,
+    bound: Option<f64>,
+    progress: Option<&Progress>,
+    on_found: Option<&OnFound<'_>>
+) -> (BinaryHeap<PartyConfig>, SearchStats) {
+    let allowed = allowed_jobs(party, scoring, constraints);
+    let left_out = left_out_jobs(party, constraints, &allowed);
+
+    if allowed.iter().any(|job_indices| job_indices.is_empty()) {
+        return (BinaryHeap::new(), left_out);
+    }
+
+    let max_levels: Vec<u8> = party.iter().zip(&allowed)
+        .map(|(character, job_indices)| job_indices.iter().map(|&job_index| character.class_jobs[job_index].level).max().unwrap_or_default())
+        .collect();
+    let bonuses = armoury_bonuses(party);
+    let job_bonuses = job_bonuses(party, scoring);
+    let max_job_bonuses: Vec<f64> = job_bonuses.iter().zip(&allowed)
+        .map(|(job_bonuses, job_indices)| job_indices.iter().map(|&job_index| job_bonuses[job_index]).fold(f64::MIN, f64::max))
+        .collect();
+    let subtree_sizes = subtree_sizes(&allowed);
+
+    // Each of the first member's jobs is searched on its own thread. Every thread keeps its own best `limit`
+    // configurations, so the best of the merged heaps are the overall best, and the heap's total ordering keeps
+    // the merged results deterministic.
+    let party_configs = allowed[0].par_iter()
+        .map(|&job_index| {
+            let mut search = Search {
+                party,
+                scoring,
+                constraints,
+                allowed: &allowed,
+                bonuses: &bonuses,
+                job_bonuses: &job_bonuses,
+                max_levels: &max_levels,
+                max_job_bonuses: &max_job_bonuses,
+                limit,
+                bound,
+                progress,
+                on_found,
+                subtree_sizes: &subtree_sizes,
+                job_indices: Vec::with_capacity(party.len()),
+                party_configs: BinaryHeap::new(),
+                stats: SearchStats::default()
+            };
+
+            search.assign(job_index, 0, 0);
+            (search.party_configs, search.stats)
+        })
+        .reduce(|| (BinaryHeap::new(), SearchStats::default()), |(mut party_configs, stats), (mut other, other_stats)| {
+            party_configs.append(&mut other);
+            truncate(&mut party_configs, limit);
+            (party_configs, stats.add(other_stats))
+        });
+
+    let (party_configs, stats) = party_configs;
+    let stats = SearchStats { combinations: subtree_sizes[0], ..stats.add(left_out) };
+    tracing::info!(
+        assignments = subtree_sizes[0],
+        evaluated = stats.evaluated,
+        pruned = stats.pruned,
+        kept = party_configs.len(),
+        "searched the configurations of {} members",
+        party.len()
+    );
+
+    let party_configs = party_configs.into_iter()
+        .map(|Reverse(party_config)| party_config)
+        .collect();
+    (party_configs, stats)
+}
+
+/// Finds every valid party configuration, with the lowest-scoring configuration at the top of the heap.
+pub fn optimize(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints) -> BinaryHeap<PartyConfig> {
+    search(party, scoring, constraints, None, None, None, None).0
+}
+
+/// Like `optimize`, but only keeps the best `limit` configurations, skipping any part of the search that can't
+/// beat them.
+pub fn optimize_top(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints, limit: usize) -> BinaryHeap<PartyConfig> {
+    search(party, scoring, constraints, Some(limit), None, None, None).0
+}
+
+/// Like `optimize`, but returns the configurations as a list ordered best-first.
+pub fn rank(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints) -> Vec<PartyConfig> {
+    // The heap's ordering is reversed so that it pops the best configuration first.
+    let mut party_configs = optimize(party, scoring, constraints).into_sorted_vec();
+    party_configs.reverse();
+    party_configs
+}
+
+/// Like `rank`, but only returns the best `limit` configurations.
+pub fn rank_top(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints, limit: usize) -> Vec<PartyConfig> {
+    let mut party_configs = optimize_top(party, scoring, constraints, limit).into_sorted_vec();
+    party_configs.reverse();
+    party_configs
+}
+
+/// Like `rank_top`, but updates `progress` as the search goes.
+pub fn rank_top_with_progress(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints, limit: usize, progress: &Progress) -> Vec<PartyConfig> {
+    rank_top_with_stats(party, scoring, constraints, limit, Some(progress)).0
+}
+
+/// Like `rank_top`, but also says how the search went, and updates `progress`, if given, as it goes.
+pub fn rank_top_with_stats(
+    party: &[PlayerCharacter],
+    scoring: &Scoring,
+    constraints: &Constraints,
+    limit: usize,
+    progress: Option<&Progress>
+) -> (Vec<PartyConfig>, SearchStats) {
+    rank_top_from(party, scoring, constraints, limit, progress, &[])
+}
+
+/// Like `rank_top_with_stats`, but starts from `known` configurations, best first, such as those `rescore` finds. With
+/// at least `limit` of them, anything worse than them all is skipped from the start.
+pub fn rank_top_from(
+    party: &[PlayerCharacter],
+    scoring: &Scoring,
+    constraints: &Constraints,
+    limit: usize,
+    progress: Option<&Progress>,
+    known: &[PartyConfig]
+) -> (Vec<PartyConfig>, SearchStats) {
+    let bound = limit.checked_sub(1).and_then(|worst| known.get(worst)).map(|party_config| party_config.score);
+    let (party_configs, stats) = search(party, scoring, constraints, Some(limit), bound, progress, None);
+    let mut party_configs = party_configs.into_vec();
+
+    // Anything no better than the known configurations was pruned without them being found again, which a search
+    // stopped early may not have got round to, so they're kept alongside whatever it did find.
+    for party_config in known {
+        if !party_configs.iter().any(|found| found.index == party_config.index) {
+            party_configs.push(*party_config);
+        }
+    }
+    party_configs.sort_by(|a, b| b.cmp(a));
+    party_configs.truncate(limit);
+    (party_configs, stats)
+}
+
+/// The configurations of `party` playing the jobs in `seeds`, given by the job ID each member plays, e.g. from an
+/// earlier search, scored against their current levels. Those the search would no longer consider are left out, and
+/// the rest are ordered best first.
+pub fn rescore(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints, seeds: &[Vec<u8>]) -> Vec<PartyConfig> {
+    let allowed = allowed_jobs(party, scoring, constraints);
+    let bonuses = armoury_bonuses(party);
+    let job_bonuses = job_bonuses(party, scoring);
+
+    let mut party_configs: Vec<PartyConfig> = seeds.iter()
+        .filter(|job_ids| job_ids.len() == party.len())
+        .filter_map(|job_ids| {
+            party.iter().zip(job_ids).zip(&allowed)
+                .map(|((character, &job_id), allowed)| {
+                    character.class_jobs.iter()
+                        .position(|class_job| class_job.job.job_id == job_id)
+                        .filter(|job_index| allowed.contains(job_index))
+                })
+                .collect::<Option<Vec<usize>>>()
+        })
+        .filter(|job_indices| allows_assignment(party, constraints, job_indices))
+        .filter_map(|job_indices| score_config(party, scoring, constraints, &bonuses, &job_bonuses, &job_indices))
+        .collect();
+    party_configs.sort_by(|a, b| b.cmp(a));
+    party_configs.dedup_by(|a, b| a.index == b.index);
+    party_configs
+}
+
+/// Like `rank_top`, but calls `on_found` with every valid configuration as it is found, not just the best.
+pub fn rank_top_streaming(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints, limit: usize, on_found: &OnFound<'_>) -> Vec<PartyConfig> {
+    let mut party_configs = search(party, scoring, constraints, Some(limit), None, None, Some(on_found)).0.into_sorted_vec();
+    party_configs.reverse();
+    party_configs
+}
+
+/// Every valid configuration of a party, worked out one at a time as they're asked for, in the order the search
+/// visits them rather than best-first. Taking only a few never does the work of finding the rest.
+pub struct Configs<'a> {
+    party: &'a [PlayerCharacter],
+    scoring: &'a Scoring,
+    constraints: &'a Constraints,
+    allowed: Vec<Vec<usize>>,
+    bonuses: Vec<Vec<bool>>,
+    job_bonuses: Vec<Vec<f64>>,
+    /// Position within each member's allowed jobs of the next assignment to try, or `None` once there are none left.
+    positions: Option<Vec<usize>>
+}
+
+/// Moves `positions` within each member's `allowed` jobs on to the next assignment, the last member's job changing
+/// fastest. Returns false once every assignment has been visited.
+fn advance(positions: &mut [usize], allowed: &[Vec<usize>]) -> bool {
+    for member in (0..positions.len()).rev() {
+        positions[member] += 1;
+        if positions[member] < allowed[member].len() {
+            return true;
+        }
+        positions[member] = 0;
+    }
+    false
+}
+
+impl Iterator for Configs<'_> {
+    type Item = PartyConfig;
+
+    fn next(&mut self) -> Option<PartyConfig> {
+        loop {
+            let positions = self.positions.as_mut()?;
+            let job_indices: Vec<usize> = positions.iter().zip(&self.allowed)
+                .map(|(&position, allowed)| allowed[position])
+                .collect();
+            if !advance(positions, &self.allowed) {
+                self.positions = None;
+            }
+
+            if allows_assignment(self.party, self.constraints, &job_indices) {
+                if let Some(party_config) = score_config(self.party, self.scoring, self.constraints, &self.bonuses, &self.job_bonuses, &job_indices) {
+                    return Some(party_config);
+                }
+            }
+        }
+    }
+}
+
+/// Lazily finds the valid configurations of `party`, in no particular order of score.
+pub fn enumerate_configs<'a>(party: &'a [PlayerCharacter], scoring: &'a Scoring, constraints: &'a Constraints) -> Configs<'a> {
+    let allowed = allowed_jobs(party, scoring, constraints);
+    let positions = (!party.is_empty() && allowed.iter().all(|job_indices| !job_indices.is_empty()))
+        .then(|| vec![0; party.len()]);
+
+    Configs {
+        party,
+        scoring,
+        constraints,
+        bonuses: armoury_bonuses(party),
+        job_bonuses: job_bonuses(party, scoring),
+        allowed,
+        positions
+    }
+}
+
+impl PartyConfig {
+    /// Whether this is at least as good as `other` at both a low spread and a high average level, and better at one.
+    pub fn dominates(&self, other: &PartyConfig) -> bool {
+        self.spread <= other.spread && self.avg >= other.avg && (self.spread < other.spread || self.avg > other.avg)
+    }
+
+    /// The two members of `party` who swap roles between this and `other`, if every other member plays the same job
+    /// in both.
+    pub fn role_swap(&self, other: &PartyConfig, party: &[PlayerCharacter]) -> Option<(usize, usize)> {
+        let role = |party_config: &PartyConfig, member: usize| party[member].class_jobs[party_config.index[member]].role();
+        let differing: Vec<usize> = (0..party.len()).filter(|&member| self.index[member] != other.index[member]).collect();
+        let &[a, b] = differing.as_slice() else {
+            return None;
+        };
+
+        let swapped = role(self, a) != role(self, b) && role(self, a) == role(other, b) && role(self, b) == role(other, a);
+        swapped.then_some((a, b))
+    }
+}
+
+/// The configurations no other beats at both a low spread and a high average level, from the lowest spread to the
+/// highest. Each is the best that can be had at one point of the trade-off between the two, whatever the weights.
+pub fn pareto_front(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints) -> Vec<PartyConfig> {
+    let mut front: Vec<PartyConfig> = Vec::new();
+
+    for party_config in enumerate_configs(party, scoring, constraints) {
+        if front.iter().any(|kept| kept.dominates(&party_config)) {
+            continue;
+        }
+        front.retain(|kept| !party_config.dominates(kept));
+        front.push(party_config);
+    }
+
+    front.sort_by(|a, b| a.spread.total_cmp(&b.spread).then_with(|| b.cmp(a)));
+    front
+}
+
+/// Like `rank`, but hands out the configurations best-first as they're asked for. The search still has to finish
+/// before the best is known, but taking only a few skips sorting the rest.
+pub fn ranked(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints) -> impl Iterator<Item = PartyConfig> {
+    let mut party_configs = optimize(party, scoring, constraints);
+    iter::from_fn(move || party_configs.pop())
+}
+
+/// Every assignment of unlocked jobs to a party, kept so that the best configurations under other scoring or
+/// constraints can be found by going over them again, rather than searching. Only a change to the party itself,
+/// such as a new level, needs them assigned again.
+pub struct Assignments {
+    job_indices: Vec<[usize; 4]>
+}
+
+impl Assignments {
+    pub fn new(party: &[PlayerCharacter]) -> Self {
+        let unlocked: Vec<Vec<usize>> = party.iter()
+            .map(|character| (0..character.class_jobs.len()).filter(|&job_index| character.class_jobs[job_index].level > 0).collect())
+            .collect();
+
+        let mut job_indices = Vec::with_capacity(subtree_sizes(&unlocked)[0] as usize);
+        if !party.is_empty() && unlocked.iter().all(|job_indices| !job_indices.is_empty()) {
+            let mut positions = vec![0; party.len()];
+            loop {
+                let mut index = [0; 4];
+                for (member, (&position, unlocked)) in positions.iter().zip(&unlocked).enumerate() {
+                    index[member] = unlocked[position];
+                }
+                job_indices.push(index);
+
+                if !advance(&mut positions, &unlocked) {
+                    break;
+                }
+            }
+        }
+
+        Assignments { job_indices }
+    }
+
+    pub fn len(&self) -> usize {
+        self.job_indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.job_indices.is_empty()
+    }
+
+    /// Like `rank_top`, but scores the kept assignments instead of searching. `party` must be the one they were
+    /// made for.
+    pub fn rank_top(&self, party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints, limit: usize) -> Vec<PartyConfig> {
+        // Whether each of each member's class_jobs is one the constraints let them play.
+        let allowed: Vec<Vec<bool>> = party.iter().zip(allowed_jobs(party, scoring, constraints))
+            .map(|(character, job_indices)| (0..character.class_jobs.len()).map(|job_index| job_indices.contains(&job_index)).collect())
+            .collect();
+        let bonuses = armoury_bonuses(party);
+        let job_bonuses = job_bonuses(party, scoring);
+
+        let party_configs = self.job_indices.par_iter()
+            .map(|index| &index[..party.len()])
+            .filter(|job_indices| job_indices.iter().zip(&allowed).all(|(&job_index, allowed)| allowed[job_index]))
+            .filter(|job_indices| allows_assignment(party, constraints, job_indices))
+            .filter_map(|job_indices| score_config(party, scoring, constraints, &bonuses, &job_bonuses, job_indices))
+            .fold(BinaryHeap::new, |mut party_configs, party_config| {
+                party_configs.push(Reverse(party_config));
+                truncate(&mut party_configs, Some(limit));
+                party_configs
+            })
+            .reduce(BinaryHeap::new, |mut party_configs, mut other| {
+                party_configs.append(&mut other);
+                truncate(&mut party_configs, Some(limit));
+                party_configs
+            });
+
+        // Sorting the reversed heap puts the best configuration first.
+        party_configs.into_sorted_vec().into_iter()
+            .map(|Reverse(party_config)| party_config)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// A character entered by hand with each of `jobs`, by abbreviation, at its level.
+    fn character(name: &str, jobs: &[(&str, u8)]) -> PlayerCharacter {
+        let class_jobs = jobs.iter()
+            .map(|&(abbreviation, level)| {
+                let info = job::JOBS.iter().find(|info| info.job_abbreviation == abbreviation).unwrap();
+                ClassJob::manual(info, level)
+            })
+            .collect();
+
+        PlayerCharacter {
+            id: 0,
+            class_jobs,
+            name: name.to_owned(),
+            server: None,
+            avatar: None,
+            portrait: None,
+            bio: None,
+            buffs: Vec::new(),
+            trial: false,
+            expansion: None,
+            play_history: None,
+            role_history: None,
+            nickname: None,
+            alts: Vec::new(),
+            gear_set: None
+        }
+    }
+
+    fn jobs<'a>(party: &'a [PlayerCharacter], party_config: &PartyConfig) -> Vec<&'a ClassJob> {
+        party.iter().zip(party_config.index).map(|(character, job_index)| &character.class_jobs[job_index]).collect()
+    }
+
+    #[test]
+    fn configs_have_one_tank_and_one_healer() {
+        let party = [
+            character("A", &[("PLD", 40), ("WHM", 42), ("DRG", 41)]),
+            character("B", &[("WAR", 38), ("SCH", 40), ("BRD", 39)]),
+            character("C", &[("BLM", 40), ("AST", 37)]),
+            character("D", &[("MNK", 41), ("DRK", 40)])
+        ];
+        let (scoring, constraints) = (Scoring::default(), Constraints::default());
+
+        let party_configs: Vec<PartyConfig> = enumerate_configs(&party, &scoring, &constraints).collect();
+        assert!(!party_configs.is_empty());
+        for party_config in &party_configs {
+            let roles: Vec<_> = jobs(&party, party_config).iter().map(|class_job| class_job.role()).collect();
+            assert_eq!(roles.iter().filter(|&&role| role == Some(Role::Tank)).count(), 1);
+            assert_eq!(roles.iter().filter(|&&role| role == Some(Role::Healer)).count(), 1);
+        }
+
+        let no_healer = [
+            character("A", &[("PLD", 40)]),
+            character("B", &[("WAR", 38), ("BRD", 39)]),
+            character("C", &[("BLM", 40)]),
+            character("D", &[("MNK", 41)])
+        ];
+        assert_eq!(enumerate_configs(&no_healer, &scoring, &constraints).count(), 0);
+    }
+
+    #[test]
+    fn configs_leave_out_locked_jobs() {
+        let party = [
+            character("A", &[("PLD", 40), ("GNB", 0)]),
+            character("B", &[("WHM", 40), ("SGE", 0)]),
+            character("C", &[("BLM", 40), ("RPR", 0)]),
+            character("D", &[("MNK", 41), ("DNC", 0)])
+        ];
+        let (scoring, constraints) = (Scoring::default(), Constraints::default());
+
+        let party_configs: Vec<PartyConfig> = enumerate_configs(&party, &scoring, &constraints).collect();
+        assert_eq!(party_configs.len(), 1);
+        assert!(jobs(&party, &party_configs[0]).iter().all(|class_job| class_job.level > 0));
+    }
+
+    #[test]
+    fn configs_leave_out_everyone_at_the_cap() {
+        let cap = job::LEVEL_CAP;
+        let party = [
+            character("A", &[("PLD", cap)]),
+            character("B", &[("WHM", cap)]),
+            character("C", &[("BLM", cap), ("DRG", cap - 1)]),
+            character("D", &[("MNK", cap)])
+        ];
+        let (scoring, constraints) = (Scoring::default(), Constraints::default());
+
+        let party_configs: Vec<PartyConfig> = enumerate_configs(&party, &scoring, &constraints).collect();
+        assert_eq!(party_configs.len(), 1);
+        assert_eq!(jobs(&party, &party_configs[0])[2].job.job_abbreviation, "DRG");
+    }
+
+    /// Jobs members of a generated party pick from, four of each role and eight DPS, none sharing a class.
+    const JOB_POOL: [&str; 16] = [
+        "PLD", "WAR", "DRK", "GNB", "WHM", "SCH", "AST", "SGE", "MNK", "DRG", "NIN", "SAM", "BRD", "MCH", "BLM", "RDM"
+    ];
+
+    /// `size` members, each with a few of `JOB_POOL` at random levels.
+    fn party_strategy(size: usize) -> impl Strategy<Value = Vec<Vec<(&'static str, u8)>>> {
+        let member = proptest::sample::subsequence(JOB_POOL.to_vec(), 1..=8)
+            .prop_flat_map(|jobs| {
+                let count = jobs.len();
+                (Just(jobs), proptest::collection::vec(1..=job::LEVEL_CAP, count))
+            })
+            .prop_map(|(jobs, levels)| jobs.into_iter().zip(levels).collect());
+        proptest::collection::vec(member, size)
+    }
+
+    fn metric_strategy() -> impl Strategy<Value = Metric> {
+        prop_oneof![Just(Metric::Pairwise), Just(Metric::Range), Just(Metric::StdDev), Just(Metric::MaxGap)]
+    }
+
+    fn build_party(members: &[Vec<(&str, u8)>], partial: bool) -> Vec<PlayerCharacter> {
+        // Partial parties leave a slot for the Duty Finder to fill.
+        let size = if partial { 3 } else { 4 };
+        members.iter().take(size).enumerate().map(|(i, jobs)| character(&i.to_string(), jobs)).collect()
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(1024))]
+
+        #[test]
+        fn ranked_configs_fill_the_roles(members in party_strategy(4), partial in any::<bool>()) {
+            let party = build_party(&members, partial);
+            let scoring = Scoring::default();
+            let constraints = Constraints { partial, ..Constraints::default() };
+
+            for party_config in rank_top(&party, &scoring, &constraints, 20) {
+                let roles: Vec<_> = jobs(&party, &party_config).iter().map(|class_job| class_job.role()).collect();
+                let tanks = roles.iter().filter(|&&role| role == Some(Role::Tank)).count();
+                let healers = roles.iter().filter(|&&role| role == Some(Role::Healer)).count();
+                if partial {
+                    prop_assert!(tanks <= 1 && healers <= 1 && party.len() - tanks - healers <= 2);
+                } else {
+                    prop_assert!(tanks == 1 && healers == 1);
+                }
+            }
+        }
+
+        #[test]
+        fn spread_is_the_metric_of_the_levels(members in party_strategy(4), metric in metric_strategy()) {
+            let party = build_party(&members, false);
+            let scoring = Scoring { metric, ..Scoring::default() };
+
+            for party_config in rank_top(&party, &scoring, &Constraints::default(), 20) {
+                let levels: Vec<f64> = jobs(&party, &party_config).iter().map(|class_job| class_job.level as f64).collect();
+                prop_assert_eq!(party_config.spread, metric.spread(&levels));
+            }
+        }
+
+        #[test]
+        fn ranking_is_monotone_and_matches_every_config(
+            members in party_strategy(4),
+            metric in metric_strategy(),
+            partial in any::<bool>(),
+            weights in (0.0..2.0, 0.0..2.0, 0.0..2.0, -20.0..20.0),
+            limit in 1..=5usize
+        ) {
+            let party = build_party(&members, partial);
+            let (weight_var, weight_avg, weight_bonus, weight_queue) = weights;
+            let scoring = Scoring { metric, weight_var, weight_avg, weight_bonus, weight_queue, ..Scoring::default() };
+            let constraints = Constraints { partial, ..Constraints::default() };
+
+            let ranked = rank_top(&party, &scoring, &constraints, limit);
+            for pair in ranked.windows(2) {
+                prop_assert!(pair[0] >= pair[1]);
+            }
+
+            // Branch and bound mustn't prune anything that would have ranked among the best.
+            let mut every: Vec<PartyConfig> = enumerate_configs(&party, &scoring, &constraints).collect();
+            every.sort_by(|a, b| b.cmp(a));
+            every.truncate(limit);
+            let indices = |party_configs: &[PartyConfig]| party_configs.iter().map(|party_config| party_config.index).collect::<Vec<_>>();
+            prop_assert_eq!(indices(&ranked), indices(&every));
+        }
+    }
+}
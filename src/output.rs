@@ -0,0 +1,901 @@
+use colored::{ColoredString, Colorize};
+use std::cmp::Reverse;
+use serde_json::json;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use xiv_levelling::advice::{self, Frequency};
+use xiv_levelling::catch_up::CatchUp;
+use xiv_levelling::constraints::Constraints;
+use xiv_levelling::duty::{self, Duty, DutyKind};
+use xiv_levelling::explain::Explanation;
+use xiv_levelling::frontline;
+use xiv_levelling::job::{self, JobInfo, Role};
+use xiv_levelling::optimizer::{Metric, PartyConfig, Scoring};
+use xiv_levelling::plan::Session;
+use xiv_levelling::projection::{self, Projection};
+use xiv_levelling::recommend::Recommendation;
+use xiv_levelling::reset::Resets;
+use xiv_levelling::selection::Selection;
+use xiv_levelling::unlock::Unlock;
+use xiv_levelling::world;
+use xiv_levelling::xivapi::{ClassJob, PlayHistory, PlayerCharacter};
+
+use crate::exit::Exit;
+use crate::i18n::{self, Message};
+
+const DUTIES_SHOWN: usize = 3;
+
+/// Configurations shown for each job that could be unlocked.
+const UNLOCKS_PREVIEWED: usize = 3;
+
+/// Members in a light party, the size the Duty Finder fills dungeons up to.
+const LIGHT_PARTY_SIZE: usize = 4;
+
+/// Most lines an in-game macro can hold, and most characters on each.
+const MACRO_LINES: usize = 15;
+const MACRO_LINE_LENGTH: usize = 180;
+
+static ABBREVIATE: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether jobs are shown by their three-letter abbreviations rather than their full names.
+pub fn set_abbreviate(abbreviate: bool) {
+    ABBREVIATE.store(abbreviate, Ordering::Relaxed);
+}
+
+static DUTY_SUPPORT: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether configurations list the dungeons they can fill out with Duty Support NPCs.
+pub fn set_duty_support(duty_support: bool) {
+    DUTY_SUPPORT.store(duty_support, Ordering::Relaxed);
+}
+
+static SESSIONS_PER_WEEK: OnceLock<u32> = OnceLock::new();
+
+/// Sets how many sessions a week the group plays, to estimate how long each configuration takes to reach the level
+/// cap. Only the first call has any effect.
+pub fn set_sessions_per_week(sessions: u32) {
+    let _ = SESSIONS_PER_WEEK.set(sessions);
+}
+
+static AVOIDED_DUTIES: OnceLock<Vec<&'static Duty>> = OnceLock::new();
+
+/// Sets the duties never to suggest. Only the first call has any effect.
+pub fn set_avoided_duties(duties: Vec<&'static Duty>) {
+    let _ = AVOIDED_DUTIES.set(duties);
+}
+
+/// The first `count` of `duties` that aren't to be avoided.
+fn suggested(duties: Vec<&'static Duty>, count: usize) -> Vec<&'static Duty> {
+    let avoided = AVOIDED_DUTIES.get().map_or(&[][..], Vec::as_slice);
+    duties.into_iter().filter(|duty| !avoided.contains(duty)).take(count).collect()
+}
+
+/// How each member's role is marked at the start of their line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Glyphs {
+    Unicode,
+    Ascii,
+    Off
+}
+
+impl FromStr for Glyphs {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unicode" => Ok(Glyphs::Unicode),
+            "ascii" => Ok(Glyphs::Ascii),
+            "none" => Ok(Glyphs::Off),
+            _ => Err(format!("unknown glyph style {} (expected unicode, ascii or none)", s))
+        }
+    }
+}
+
+/// How configurations are written out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    /// One JSON object per line, streamed as each configuration is found.
+    Ndjson,
+    /// Written once they're all ranked by the formatter registered by this name.
+    Formatter(String)
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    /// Any name besides text and ndjson is taken to be a formatter's, which is only known once they're registered.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "ndjson" => Ok(Format::Ndjson),
+            _ => Ok(Format::Formatter(s.to_owned()))
+        }
+    }
+}
+
+/// What ranked configurations are shown in order of.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Order {
+    /// Best score first.
+    Score,
+    /// Lowest spread first.
+    Spread,
+    /// Highest average level first.
+    Avg,
+    /// Highest lowest level first.
+    MinLevel,
+    /// Least EXP lost to level sync first.
+    Waste
+}
+
+impl Order {
+    pub fn name(self) -> &'static str {
+        match self {
+            Order::Score => "score",
+            Order::Spread => "spread",
+            Order::Avg => "avg",
+            Order::MinLevel => "min-level",
+            Order::Waste => "waste"
+        }
+    }
+
+    /// The order after this one, going round to the first after the last.
+    pub fn next(self) -> Order {
+        match self {
+            Order::Score => Order::Spread,
+            Order::Spread => Order::Avg,
+            Order::Avg => Order::MinLevel,
+            Order::MinLevel => Order::Waste,
+            Order::Waste => Order::Score
+        }
+    }
+
+    /// Puts `party_configs` in this order, keeping them in order of score otherwise.
+    pub fn sort(self, party: &[PlayerCharacter], party_configs: &mut [PartyConfig]) {
+        party_configs.sort_by(|a, b| b.cmp(a));
+        match self {
+            Order::Score => {}
+            Order::Spread => party_configs.sort_by(|a, b| a.spread.total_cmp(&b.spread)),
+            Order::Avg => party_configs.sort_by_key(|party_config| Reverse(party_config.avg)),
+            Order::MinLevel => party_configs.sort_by_key(|party_config| Reverse(party_config.min_level)),
+            Order::Waste => projection::sort_by_waste(party, party_configs)
+        }
+    }
+}
+
+impl FromStr for Order {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "score" => Ok(Order::Score),
+            "spread" => Ok(Order::Spread),
+            "avg" => Ok(Order::Avg),
+            "min-level" => Ok(Order::MinLevel),
+            "waste" => Ok(Order::Waste),
+            _ => Err(format!("unknown order {} (expected score, spread, avg, min-level or waste)", s))
+        }
+    }
+}
+
+static MACHINE_READABLE: AtomicBool = AtomicBool::new(false);
+
+/// Sets how configurations are written out.
+pub fn set_format(format: &Format) {
+    MACHINE_READABLE.store(*format != Format::Text, Ordering::Relaxed);
+}
+
+/// Reserves stdout for output read by another program, whatever the format, so messages go to stderr.
+pub fn reserve_stdout() {
+    MACHINE_READABLE.store(true, Ordering::Relaxed);
+}
+
+/// Whether stdout is reserved for machine-readable output, so messages for the user go to stderr.
+pub fn is_machine_readable() -> bool {
+    MACHINE_READABLE.load(Ordering::Relaxed)
+}
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether only the results are printed, as JSON, with no messages for the user.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+static GLYPHS: OnceLock<Glyphs> = OnceLock::new();
+
+/// Sets how roles are marked in the output. Only the first call has any effect.
+pub fn set_glyphs(glyphs: Glyphs) {
+    let _ = GLYPHS.set(glyphs);
+}
+
+fn role_glyph(role: Option<Role>) -> String {
+    match (GLYPHS.get().copied().unwrap_or(Glyphs::Ascii), role) {
+        (Glyphs::Unicode, Some(role)) => format!("{} ", role.icon()),
+        (Glyphs::Ascii, Some(role)) => format!("[{}] ", role.letter()),
+        _ => String::new()
+    }
+}
+
+/// `class_job`'s name padded to line up in a column, abbreviated if requested.
+fn job_column(class_job: &ClassJob) -> String {
+    if ABBREVIATE.load(Ordering::Relaxed) {
+        class_job.abbreviation().to_owned()
+    } else {
+        format!("{: <15}", class_job.display_name())
+    }
+}
+
+/// Lists the highest duties of `kind` a party synced to `level` can run.
+pub fn duty_list(level: u8, kind: DutyKind) -> String {
+    let duties = suggested(duty::highest_available(level, kind, usize::MAX), DUTIES_SHOWN);
+
+    if duties.is_empty() {
+        return i18n::text(Message::None).to_owned();
+    }
+
+    duties.iter()
+        .map(|duty| format!("{} (Lv {})", duty.name, duty.level))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Lists the highest dungeons a party synced to `level` can run with Duty Support, and the level each is played at.
+pub fn duty_support_list(level: u8) -> String {
+    let duties = suggested(duty::highest_duty_support(level, usize::MAX), DUTIES_SHOWN);
+
+    if duties.is_empty() {
+        return i18n::text(Message::None).to_owned();
+    }
+
+    duties.iter()
+        .map(|duty| format!("{} (Lv {})", duty.name, duty.synced_level(level)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Lists the roulettes a party synced to `level` can queue for.
+pub fn roulette_list(level: u8) -> String {
+    let roulettes: Vec<&str> = duty::available_roulettes(level).iter().map(|roulette| roulette.name).collect();
+
+    if roulettes.is_empty() {
+        i18n::text(Message::None).to_owned()
+    } else {
+        roulettes.join(", ")
+    }
+}
+
+/// The resets after the current time.
+fn resets_now() -> Resets {
+    Resets::after(SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default())
+}
+
+/// `duration` in its two largest units, e.g. "4d 2h" or "3h 12m".
+fn format_remaining(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// How long until each reset, e.g. "daily in 3h 12m (15:00 UTC), weekly in 4d 2h (Tuesday 08:00 UTC)", warning if
+/// today's roulette bonuses are about to run out.
+pub fn reset_summary(resets: &Resets) -> String {
+    let mut summary = format!(
+        "daily in {} (15:00 UTC), weekly in {} (Tuesday 08:00 UTC)",
+        format_remaining(resets.daily),
+        format_remaining(resets.weekly)
+    );
+    if resets.bonus_expiring() {
+        summary.push_str(", so run today's roulettes tonight before their bonuses expire");
+    }
+    summary
+}
+
+/// Describes the deep dungeon floors a party synced to `level` should run, if any.
+pub fn deep_dungeon_suggestion(level: u8) -> Option<String> {
+    let (deep_dungeon, floor_set) = duty::deep_dungeon(level)?;
+
+    Some(match floor_set.requirement {
+        Some(requirement) => format!("{}, floors {} (requires {})", deep_dungeon.name, floor_set.floors, requirement),
+        None => format!("{}, floors {}", deep_dungeon.name, floor_set.floors)
+    })
+}
+
+/// Lists the field operations a party synced to `level` could level in instead, with what unlocks each.
+pub fn field_operation_list(level: u8) -> Option<String> {
+    let field_operations = duty::field_operations(level);
+    if field_operations.is_empty() {
+        return None;
+    }
+
+    Some(field_operations.iter()
+        .map(|field_operation| format!("{} (requires {})", field_operation.name, field_operation.requirement))
+        .collect::<Vec<_>>()
+        .join(", "))
+}
+
+/// Describes the level a party whose lowest member is `level` plays its highest available dungeon at.
+pub fn sync_summary(level: u8) -> Option<String> {
+    let dungeon = suggested(duty::highest_available(level, DutyKind::Dungeon, usize::MAX), 1).pop()?;
+    Some(format!("Lv {} in {}", dungeon.synced_level(level), dungeon.name))
+}
+
+/// The level to suggest duties for: the lowest member's level, capped for anyone on the free trial.
+pub fn duty_level(party: &[PlayerCharacter], party_config: &PartyConfig) -> u8 {
+    party.iter().map(PlayerCharacter::level_cap).fold(party_config.min_level, u8::min)
+}
+
+/// `party_config` on one line, e.g. "Alice WAR Lv 63, Bob WHM Lv 61".
+pub fn config_line(party: &[PlayerCharacter], party_config: &PartyConfig) -> String {
+    let members: Vec<String> = party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| {
+            let class_job = &character.class_jobs[job_index];
+            format!("{} {} Lv {}", character.display_name(), class_job.abbreviation(), class_job.level)
+        })
+        .collect();
+    members.join(", ")
+}
+
+/// `party_config` as an in-game macro announcing it in party chat, e.g. "/p Alice -> WAR (Lv 63)" for each member,
+/// cut down to what a macro can hold.
+pub fn party_macro(party: &[PlayerCharacter], party_config: &PartyConfig, scoring: &Scoring) -> Vec<String> {
+    let mut lines = vec![format!(
+        "/p {}: {}, Lv Avg: {}",
+        scoring.spread_label(),
+        scoring.metric.format(party_config.spread),
+        party_config.avg
+    )];
+    for (character, &job_index) in party.iter().zip(&party_config.index) {
+        let class_job = &character.class_jobs[job_index];
+        lines.push(format!("/p {} -> {} (Lv {})", character.display_name(), class_job.abbreviation(), class_job.level));
+    }
+
+    lines.truncate(MACRO_LINES);
+    for line in &mut lines {
+        if let Some((end, _)) = line.char_indices().nth(MACRO_LINE_LENGTH) {
+            line.truncate(end);
+        }
+    }
+    lines
+}
+
+/// A description to paste into the Party Finder recruiting for `party_config`, e.g. "LF1M healer, levelling 61-70
+/// dungeons, all welcome", naming the roles the party still needs if it is short.
+pub fn party_finder(party: &[PlayerCharacter], party_config: &PartyConfig) -> String {
+    let roles: Vec<Option<Role>> = party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| character.class_jobs[job_index].role())
+        .collect();
+
+    let from = duty_level(party, party_config).saturating_sub(1) / 10 * 10 + 1;
+    // Crafters and gatherers have no roles, and nothing to queue for together.
+    let content = if roles.iter().any(Option::is_some) {
+        format!("levelling {}-{} dungeons", from, from + 9)
+    } else {
+        format!("levelling Lv {}-{} together", from, from + 9)
+    };
+
+    let short = LIGHT_PARTY_SIZE.saturating_sub(party.len());
+    if short == 0 {
+        return format!("Full party {}", content);
+    }
+
+    let needed = if roles.iter().any(Option::is_some) { needed_roles(&roles, short) } else { Vec::new() };
+
+    // A party short of fewer members than roles it lacks can take whichever comes along.
+    let separator = if needed.len() > short { "/" } else { ", " };
+    if needed.is_empty() {
+        format!("LF{}M, {}, all welcome", short, content)
+    } else {
+        format!("LF{}M {}, {}, all welcome", short, needed.join(separator), content)
+    }
+}
+
+/// The roles a party playing `roles` and `short` of a light party still needs, e.g. ["healer", "2 DPS"].
+fn needed_roles(roles: &[Option<Role>], short: usize) -> Vec<String> {
+    let mut needed = Vec::new();
+    for (role, name) in [(Role::Tank, "tank"), (Role::Healer, "healer")] {
+        if !roles.contains(&Some(role)) {
+            needed.push(name.to_owned());
+        }
+    }
+    match short.saturating_sub(needed.len()) {
+        0 => {},
+        1 => needed.push("DPS".to_owned()),
+        dps => needed.push(format!("{} DPS", dps))
+    }
+    needed
+}
+
+/// What queueing `party_config` through the Duty Finder is like if the party is short, e.g. "missing healer — long
+/// queue". Far fewer tanks and healers queue than DPS, so waiting on one of them takes much longer.
+pub fn queue_summary(party: &[PlayerCharacter], party_config: &PartyConfig) -> Option<String> {
+    let short = LIGHT_PARTY_SIZE.saturating_sub(party.len());
+    if short == 0 || DUTY_SUPPORT.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let roles: Vec<Option<Role>> = party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| character.class_jobs[job_index].role())
+        .collect();
+    let missing_role = !roles.contains(&Some(Role::Tank)) || !roles.contains(&Some(Role::Healer));
+    let speed = if missing_role { "long queue" } else { "quick queue" };
+    Some(format!("missing {} — {}", needed_roles(&roles, short).join(", "), speed))
+}
+
+/// Estimates what one run of the highest dungeon available to `party_config` earns each member.
+pub fn exp_estimate(party: &[PlayerCharacter], party_config: &PartyConfig) -> Option<String> {
+    let level = duty_level(party, party_config);
+    let dungeon = suggested(duty::highest_available(level, DutyKind::Dungeon, usize::MAX), 1).pop()?;
+    let exp = duty::completion_exp(dungeon)?;
+
+    let members: Vec<String> = party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| {
+            let class_job = &character.class_jobs[job_index];
+            let gain = (exp as f64 * character.exp_multiplier(class_job)) as u64;
+            let (level, _) = job::gain_exp(class_job.level, class_job.exp_level.unwrap_or_default(), gain);
+            format!("{} +{} Lv {} -> {}", character.display_name(), gain, class_job.level, level)
+        })
+        .collect();
+
+    Some(format!("{}: ~{} base EXP per run ({})", dungeon.name, exp, members.join(", ")))
+}
+
+/// Prints the members of `pool` chosen by `selection` and their jobs, coloured by role.
+pub fn print_selection(pool: &[PlayerCharacter], selection: &Selection, scoring: &Scoring) {
+    for &(member, job_index) in &selection.members {
+        let character = &pool[member];
+        let class_job = &character.class_jobs[job_index];
+
+        let line = format!("{0}{1: <20}: {2} Lv {3}", role_glyph(class_job.role()), character.display_name(), job_column(class_job), class_job.level);
+        println!("{}", role_colour(line, class_job.role()));
+    }
+    println!("- {}: {}", i18n::spread_label(scoring), scoring.metric.format(selection.spread));
+    println!("- {}: {}", i18n::text(Message::Average), selection.avg);
+    println!("- {}: {}", i18n::text(Message::Dungeons), duty_list(selection.min_level, DutyKind::Dungeon));
+}
+
+/// Prints how a configuration's score was worked out, for --explain.
+pub fn print_explanation(party: &[PlayerCharacter], explanation: &Explanation, scoring: &Scoring) {
+    println!("- Score breakdown:");
+    let gaps: Vec<String> = explanation.gaps.iter()
+        .map(|gap| format!("{} to {} {}", party[gap.members.0].display_name(), party[gap.members.1].display_name(), gap.gap))
+        .collect();
+    println!("  - Gaps: {}", gaps.join(", "));
+    if scoring.metric == Metric::Pairwise {
+        let total: f64 = explanation.gaps.iter().map(|gap| gap.gap).sum();
+        println!("  - {}: every gap counted both ways, 2 x {} = {}", scoring.spread_label(), total, 2.0 * total);
+    }
+    println!("  - Average: {} / {} = {:.2}", explanation.level_sum, party.len(), explanation.average);
+
+    for term in &explanation.terms {
+        println!("  - {}: {:.2} x {:.2} = {:+.2}", term.name, term.value, term.weight, term.contribution());
+    }
+    if explanation.custom {
+        println!("  - Replaced by the custom score: {:.2}", explanation.score);
+    } else {
+        println!("  - Score: {:.2}", explanation.score);
+    }
+}
+
+/// Prints the job each member of `party` should take into the daily Frontline.
+pub fn print_frontline(party: &[PlayerCharacter], constraints: &Constraints) {
+    println!("{}:", i18n::text(Message::Frontline));
+    for character in party {
+        match frontline::recommend(character, constraints) {
+            Some(job_index) => {
+                let class_job = &character.class_jobs[job_index];
+                let line = format!("- {0: <20}: {1} Lv {2}", character.display_name(), job_column(class_job), class_job.level);
+                println!("{}", role_colour(line, class_job.role()));
+            },
+            None => println!("- {0: <20}: {1}", character.display_name(), i18n::text(Message::NoFrontlineJob))
+        }
+    }
+}
+
+/// `party_config` as a single line of JSON, along with the best score found so far.
+pub fn config_json(party: &[PlayerCharacter], party_config: &PartyConfig, best_score: f64) -> String {
+    let members: Vec<_> = party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| {
+            let class_job = &character.class_jobs[job_index];
+            let alt = character.alt_of(class_job).map(|alt| &alt.name);
+            json!({
+                "name": character.name,
+                "nickname": character.nickname,
+                "alt": alt,
+                "job": class_job.display_name(),
+                "level": class_job.level,
+                "gear_level": character.gear_level(class_job),
+                "avatar": character.avatar,
+                "portrait": character.portrait
+            })
+        })
+        .collect();
+
+    json!({
+        "members": members,
+        "spread": party_config.spread,
+        "avg": party_config.avg,
+        "min_level": party_config.min_level,
+        "exp_waste": projection::exp_waste(party, party_config),
+        "score": party_config.score,
+        "best_score": best_score
+    }).to_string()
+}
+
+/// Why a run failed as a single line of JSON, for scripts reading the other lines as JSON too.
+pub fn error_json(exit: Exit) -> String {
+    json!({
+        "error": {
+            "kind": exit.kind(),
+            "code": exit.code()
+        }
+    }).to_string()
+}
+
+/// Prints a table of each member's level after every run in `projection`.
+pub fn print_projection(party: &[PlayerCharacter], projection: &Projection) {
+    print!("{0: <6}", "Run");
+    for character in party {
+        print!("{0: <20}", character.display_name());
+    }
+    println!();
+
+    for (run, levels) in projection.levels.iter().enumerate() {
+        print!("{0: <6}", run);
+        for level in levels {
+            print!("{0: <20}", format!("Lv {}", level));
+        }
+        println!();
+    }
+
+    let runs = projection.levels.len() - 1;
+    if projection.converged {
+        println!("All members converge after {} run(s) of {}.", runs, projection.duty.name);
+    } else {
+        println!("Members have not converged after {} run(s) of {}.", runs, projection.duty.name);
+    }
+}
+
+/// Prints how the member in `catch_up` can level alone until they are no longer holding the party back.
+/// Lists the jobs members could unlock to open up more of the best configurations, with a preview of those.
+pub fn print_unlocks(unlocks: &[Unlock], scoring: &Scoring) {
+    if unlocks.is_empty() {
+        println!("No job a member could unlock would add to the best configurations.");
+        return;
+    }
+
+    for unlock in unlocks {
+        let character = &unlock.party[unlock.member];
+        let class_job = &character.class_jobs[unlock.job_index];
+        println!(
+            "If {} unlocks {} (instant Lv {}), {} new configuration(s) among the best become available:",
+            character.display_name(), class_job.job.job_name, class_job.level, unlock.party_configs.len()
+        );
+
+        for party_config in unlock.party_configs.iter().take(UNLOCKS_PREVIEWED) {
+            let members: Vec<String> = unlock.party.iter().zip(&party_config.index)
+                .map(|(character, &job_index)| {
+                    let class_job = &character.class_jobs[job_index];
+                    format!("{} {} Lv {}", character.display_name(), class_job.display_name(), class_job.level)
+                })
+                .collect();
+            println!("- {} ({}: {})", members.join(", "), scoring.metric.label(), scoring.metric.format(party_config.spread));
+        }
+    }
+}
+
+/// Lists the job each member could start levelling to improve the best configurations most, and the role and level
+/// range the party is missing that it fills.
+pub fn print_recommendations(party: &[PlayerCharacter], recommendations: &[Recommendation], top: usize) {
+    println!("Jobs to start levelling for better configurations later:");
+    for (member, character) in party.iter().enumerate() {
+        let Some(recommendation) = recommendations.iter().find(|recommendation| recommendation.member == member) else {
+            println!("- {}: no job they could level would improve the configurations", character.display_name());
+            continue;
+        };
+
+        let role = match recommendation.job.role {
+            Some(Role::Tank) => "tank",
+            Some(Role::Healer) => "healer",
+            _ => "DPS"
+        };
+        let from = recommendation.level / 10 * 10;
+        println!(
+            "- {}: levelling {} to Lv {} helps most, as the party lacks a Lv {}-{} {} (average score of the best {}: {:.1} -> {:.1})",
+            character.display_name(), recommendation.job.job_name, recommendation.level, from, from + 9, role, top,
+            recommendation.before, recommendation.after
+        );
+    }
+}
+
+pub fn print_catch_up(party: &[PlayerCharacter], catch_up: &CatchUp) {
+    let character = &party[catch_up.member];
+    let class_job = &character.class_jobs[catch_up.job_index];
+    // A base class levelled past the point its job is unlocked is better levelled as the job from then on.
+    let upgrade = class_job.job.has_base_class() && !class_job.job_unlocked && class_job.job.job_available_at(catch_up.target);
+    let upgrade = if upgrade {
+        format!(", and it can become {} at Lv {}", class_job.job.job_name, job::JOB_UNLOCK_LEVEL)
+    } else {
+        String::new()
+    };
+    println!(
+        "{} is behind in every configuration. Levelling {} from Lv {} to Lv {} alone takes ~{} EXP{}:",
+        character.display_name(), class_job.display_name(), class_job.level, catch_up.target, catch_up.exp(), upgrade
+    );
+
+    for bracket in &catch_up.brackets {
+        let mut content = Vec::new();
+        if let Some(duty) = bracket.duty_support {
+            match bracket.runs {
+                Some(runs) => content.push(format!("{} with Duty Support (~{} run(s))", duty.name, runs)),
+                None => content.push(format!("{} with Duty Support", duty.name))
+            }
+        }
+        if let Some((deep_dungeon, floor_set)) = bracket.deep_dungeon {
+            content.push(format!("{}, floors {}", deep_dungeon.name, floor_set.floors));
+        }
+        if content.is_empty() {
+            content.push("quests and FATEs".to_owned());
+        }
+
+        println!("- Lv {}-{}: ~{} EXP in {}", bracket.from, bracket.to, bracket.exp, content.join(" or "));
+    }
+}
+
+/// Prints each day of a plan made by `plan::plan`, with where it gets every member.
+pub fn print_plan(party: &[PlayerCharacter], sessions: &[Session]) {
+    let resets = resets_now();
+    println!("{}: {}\n", i18n::text(Message::Resets), reset_summary(&resets));
+
+    for (day, session) in sessions.iter().enumerate() {
+        let mut heading = format!("Day {}: ", day + 1);
+        if let Some(roulette) = session.roulette {
+            heading.push_str(&format!("{} roulette, then ", roulette.name));
+        }
+        heading.push_str(&format!("{} run(s) of {}", session.runs, session.duty.name));
+        if let Some(member) = session.featured {
+            heading.push_str(&format!(" ({}'s turn on their favourite role)", party[member].display_name()));
+        }
+        println!("{}", heading.bold());
+        if day == 0 && session.roulette.is_some() && resets.bonus_expiring() {
+            println!("- Run the roulette before the daily reset in {}, or today's bonus is lost", format_remaining(resets.daily));
+        }
+
+        for ((character, &job_index), (before, after)) in party.iter().zip(&session.party_config.index).zip(&session.levels) {
+            let class_job = &character.class_jobs[job_index];
+            let line = format!("{0}{1: <20}: {2} Lv {3} -> {4}", role_glyph(class_job.role()), character.display_name(), job_column(class_job), before, after);
+            println!("{}", role_colour(line, class_job.role()));
+        }
+        println!("- ~{} EXP across the party", session.exp);
+        println!();
+    }
+
+    let total: u64 = sessions.iter().map(|session| session.exp).sum();
+    println!("~{} EXP across the party over {} day(s).", total, sessions.len());
+}
+
+/// Prints what a party synced to `level` can do to level, with the most EXP an hour first.
+pub fn print_advice(level: u8) {
+    let advice = advice::advise(level);
+    if advice.is_empty() {
+        println!("There is no levelling content to suggest at Lv {}.", level);
+        return;
+    }
+
+    println!("{}", format!("Levelling at Lv {}, most EXP first:", level).bold());
+    for (rank, advice) in (1..).zip(&advice) {
+        let per = match advice.frequency {
+            Frequency::Hourly => "an hour",
+            Frequency::Daily => "once a day"
+        };
+        match advice.requirement {
+            Some(requirement) => println!("{}. {}: ~{} EXP {} (requires {})", rank, advice.activity, advice.exp, per, requirement),
+            None => println!("{}. {}: ~{} EXP {}", rank, advice.activity, advice.exp, per)
+        }
+    }
+}
+
+/// Whether anyone in `party_config` is on Blue Mage, which can only queue as a premade party.
+pub fn is_premade_only(party: &[PlayerCharacter], party_config: &PartyConfig) -> bool {
+    party.iter().zip(&party_config.index)
+        .any(|(character, &job_index)| character.class_jobs[job_index].job.is_limited())
+}
+
+fn role_colour(line: String, role: Option<Role>) -> ColoredString {
+    match role {
+        Some(Role::Tank) => line.blue(),
+        Some(Role::Healer) => line.green(),
+        Some(Role::Dps) => line.red(),
+        None => line.normal()
+    }
+}
+
+/// The jobs `character` has unlocked as `info`, on themselves or any of their alts.
+fn played<'a>(character: &'a PlayerCharacter, info: &'a JobInfo) -> impl Iterator<Item = &'a ClassJob> {
+    character.class_jobs.iter().filter(move |class_job| class_job.level > 0 && class_job.job.job_name == info.job_name)
+}
+
+/// A row for every job anyone in `party` can play, with each member's level in it, or `None` if they haven't unlocked
+/// it. Each row's job is one of the members' for it, to name it by.
+pub fn matrix(party: &[PlayerCharacter]) -> Vec<(&ClassJob, Vec<Option<u8>>)> {
+    job::JOBS.iter()
+        .filter_map(|info| {
+            // Named after the job if anyone has unlocked it, rather than the base class.
+            let class_job = party.iter().flat_map(|character| played(character, info)).min_by_key(|class_job| !class_job.job_unlocked)?;
+            Some((class_job, party.iter().map(|character| played(character, info).map(|class_job| class_job.level).max()).collect()))
+        })
+        .collect()
+}
+
+/// Prints `matrix` with a column for each member. Jobs a member hasn't unlocked are left blank.
+pub fn print_matrix(party: &[PlayerCharacter]) {
+    let widths: Vec<usize> = party.iter().map(|character| character.display_name().chars().count().max(3)).collect();
+    let rows = matrix(party);
+
+    let labels: Vec<String> = rows.iter().map(|(class_job, _)| format!("{}{}", role_glyph(class_job.role()), job_column(class_job))).collect();
+    let label_width = labels.iter().map(|label| label.chars().count()).max().unwrap_or_default();
+
+    let mut header = " ".repeat(label_width);
+    for character in party {
+        header.push_str(&format!("  {: >3}", character.display_name()));
+    }
+    println!("{}", header.bold());
+
+    for (label, (class_job, levels)) in labels.into_iter().zip(&rows) {
+        let mut line = format!("{: <label_width$}", label);
+        for (level, width) in levels.iter().zip(&widths) {
+            let level = level.map(|level| level.to_string()).unwrap_or_default();
+            line.push_str(&format!("  {: >width$}", level));
+        }
+        line.truncate(line.trim_end().len());
+        println!("{}", role_colour(line, class_job.role()));
+    }
+}
+
+/// Describes how often and how recently `class_job` shows up in a member's recent reports.
+fn played_marker(play_history: &PlayHistory, class_job: &ClassJob) -> String {
+    let Some((reports, last_played)) = play_history.job(class_job.job) else {
+        return i18n::text(Message::NotPlayedMarker).to_owned();
+    };
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default();
+    let days = now.saturating_sub(last_played) / (24 * 60 * 60);
+    i18n::format(Message::PlayedMarker, &[&reports, &play_history.reports, &days])
+}
+
+/// Prints each member's job in `party_config`, coloured by role, with the lowest-level member highlighted and marked
+/// with how far they trail the average.
+pub fn print_party_config(party: &[PlayerCharacter], party_config: &PartyConfig, scoring: &Scoring) {
+    let class_jobs: Vec<_> = party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| &character.class_jobs[job_index])
+        .collect();
+    let lowest_level = class_jobs.iter().map(|class_job| class_job.level).min().unwrap_or_default();
+
+    for (character, class_job) in party.iter().zip(class_jobs) {
+        let mut line = format!("{0}{1: <20}: {2} Lv {3}", role_glyph(class_job.role()), character.display_name(), job_column(class_job), class_job.level);
+        if let Some(alt) = character.alt_of(class_job) {
+            line.push(' ');
+            line.push_str(&i18n::format(Message::AltMarker, &[&alt.name]));
+        }
+        if character.has_armoury_bonus(class_job) {
+            line.push(' ');
+            line.push_str(i18n::text(Message::ArmouryBonusMarker));
+        }
+        if let Some(gear_level) = character.under_geared(class_job) {
+            line.push(' ');
+            line.push_str(&i18n::format(Message::UnderGearedMarker, &[&gear_level]));
+        }
+        if let Some(play_history) = &character.play_history {
+            line.push(' ');
+            line.push_str(&played_marker(play_history, class_job));
+        }
+        let behind = party_config.avg.saturating_sub(class_job.level as u32);
+        if class_job.level == lowest_level && behind > 0 {
+            line.push(' ');
+            line.push_str(&i18n::format(Message::BottleneckMarker, &[&behind]));
+        }
+        let line = role_colour(line, class_job.role());
+
+        if class_job.level == lowest_level {
+            println!("{}", line.bold().underline());
+        } else {
+            println!("{}", line);
+        }
+    }
+    println!("- {}: {}", i18n::spread_label(scoring), scoring.metric.format(party_config.spread));
+    println!("- {}: {}", i18n::text(Message::Average), party_config.avg);
+    println!("- {}", i18n::format(Message::ArmouryBonus, &[&party_config.bonus_count, &party.len()]));
+    if let Some(travel) = travel_summary(party) {
+        println!("- {}: {}", i18n::text(Message::DataCenterTravel), travel);
+    }
+
+    // Duties only matter to combat jobs.
+    if party.iter().zip(&party_config.index).any(|(character, &job_index)| character.class_jobs[job_index].role().is_none()) {
+        return;
+    }
+
+    let level = duty_level(party, party_config);
+    if is_premade_only(party, party_config) {
+        println!("- {}", i18n::text(Message::PremadeOnly));
+    } else if let Some(queue) = queue_summary(party, party_config) {
+        println!("- {}: {}", i18n::text(Message::Queue), queue);
+    }
+    if let Some(sync) = sync_summary(level) {
+        println!("- {}: {}", i18n::text(Message::SyncedLevel), sync);
+    }
+    println!("- {}: {}", i18n::text(Message::Dungeons), duty_list(level, DutyKind::Dungeon));
+    if DUTY_SUPPORT.load(Ordering::Relaxed) {
+        println!("- {}: {}", i18n::text(Message::DutySupport), duty_support_list(level));
+    }
+    println!("- {}: {}", i18n::text(Message::Trials), duty_list(level, DutyKind::Trial));
+    println!("- {}: {}", i18n::text(Message::Roulettes), roulette_list(level));
+    println!("- {}: {}", i18n::text(Message::Resets), reset_summary(&resets_now()));
+    if let Some(deep_dungeon) = deep_dungeon_suggestion(level) {
+        println!("- {}: {}", i18n::text(Message::DeepDungeon), deep_dungeon);
+    }
+    if let Some(field_operations) = field_operation_list(level) {
+        println!("- {}: {}", i18n::text(Message::FieldOperations), field_operations);
+    }
+    if let Some(estimate) = exp_estimate(party, party_config) {
+        println!("- {}: {}", i18n::text(Message::EstimatedExp), estimate);
+    }
+    if let Some(waste) = projection::exp_waste(party, party_config).filter(|&waste| waste > 0) {
+        println!("- {}: ~{} per run", i18n::text(Message::ExpWaste), waste);
+    }
+    if let Some(&sessions) = SESSIONS_PER_WEEK.get() {
+        match projection::weeks_to_cap(party, party_config, sessions) {
+            Some(0) => {}
+            Some(weeks) => println!("- {}: ~{} at {} sessions a week", i18n::text(Message::WeeksToCap), weeks, sessions),
+            None => println!("- {}: too many to tell", i18n::text(Message::WeeksToCap))
+        }
+    }
+}
+
+/// Who has to travel to another data center for the party to queue together, e.g. "Bob (Primal → Aether)".
+pub fn travel_summary(party: &[PlayerCharacter]) -> Option<String> {
+    let travel = world::travel(party);
+    if travel.is_empty() {
+        return None;
+    }
+
+    Some(travel.iter()
+        .map(|travel| format!("{} ({} → {})", party[travel.member].display_name(), travel.from.name, travel.to.name))
+        .collect::<Vec<_>>()
+        .join(", "))
+}
+
+/// Prints two ranked configurations side by side, highlighting the members who play something different in each.
+pub fn print_comparison(party: &[PlayerCharacter], first: (usize, &PartyConfig), second: (usize, &PartyConfig), scoring: &Scoring) {
+    const WIDTH: usize = 28;
+    let cell = |class_job: &ClassJob| format!("{}{} Lv {}", role_glyph(class_job.role()), job_column(class_job), class_job.level);
+
+    println!("{: <24}{: <WIDTH$}#{}", "", format!("#{}", first.0), second.0);
+    for (member, character) in party.iter().enumerate() {
+        let (first_job, second_job) = (first.1.index[member], second.1.index[member]);
+        let cells = (cell(&character.class_jobs[first_job]), cell(&character.class_jobs[second_job]));
+
+        if first_job == second_job {
+            println!("  {: <20}: {: <WIDTH$}{}", character.display_name(), cells.0, cells.1);
+        } else {
+            println!("{}", format!("* {: <20}: {: <WIDTH$}{}", character.display_name(), cells.0, cells.1).bold());
+        }
+    }
+
+    let spread = (scoring.metric.format(first.1.spread), scoring.metric.format(second.1.spread));
+    println!("  {: <20}: {: <WIDTH$}{}", i18n::spread_label(scoring), spread.0, spread.1);
+    println!("  {: <20}: {: <WIDTH$}{}", i18n::text(Message::Average), first.1.avg, second.1.avg);
+}
@@ -0,0 +1,81 @@
+use std::io;
+
+use xiv_levelling::optimizer::{PartyConfig, Scoring};
+use xiv_levelling::xivapi::PlayerCharacter;
+
+use crate::output;
+
+const HELP: &str = "Commands: enter or n (next), p (previous), g <n> (go to rank n), x (hide this configuration), \
+    x <name> (hide every configuration with this member on their job here), q (quit)";
+
+/// Interactively pages through `party_configs`, which must be ordered best-first and non-empty. Hidden
+/// configurations stay hidden until the pager is closed.
+pub fn run(party: &[PlayerCharacter], party_configs: &[PartyConfig], scoring: &Scoring) {
+    // Indices into `party_configs` that haven't been hidden, best-first.
+    let mut visible: Vec<usize> = (0..party_configs.len()).collect();
+    let mut rank = 0;
+    let mut show = true;
+    let mut input = String::new();
+
+    loop {
+        if show {
+            println!("#{} of {}", rank + 1, visible.len());
+            output::print_party_config(party, &party_configs[visible[rank]], scoring);
+        }
+        show = false;
+
+        input.clear();
+        io::stdin().read_line(&mut input).unwrap();
+
+        let mut args = input.split_whitespace();
+        match (args.next(), args.next()) {
+            (Some("q"), _) => break,
+            (None, _) | (Some("n"), _) => {
+                if rank + 1 < visible.len() {
+                    rank += 1;
+                    show = true;
+                } else {
+                    println!("This is the last configuration!");
+                }
+            },
+            (Some("p"), _) => {
+                if rank > 0 {
+                    rank -= 1;
+                    show = true;
+                } else {
+                    println!("This is the first configuration!");
+                }
+            },
+            (Some("g"), Some(target)) => match target.parse::<usize>() {
+                Ok(target) if target >= 1 && target <= visible.len() => {
+                    rank = target - 1;
+                    show = true;
+                },
+                _ => println!("Rank must be between 1 and {}!", visible.len())
+            },
+            (Some("x"), None) => {
+                visible.remove(rank);
+                show = true;
+            },
+            (Some("x"), Some(_)) => {
+                let name = input.trim()[1..].trim();
+                let Some(member) = party.iter().position(|character| character.matches_name(name)) else {
+                    println!("Nobody in the party is called {}!", name);
+                    continue;
+                };
+
+                let job_index = party_configs[visible[rank]].index[member];
+                visible.retain(|&config| party_configs[config].index[member] != job_index);
+                println!("Hid every configuration with {} as {}.", party[member].name, party[member].class_jobs[job_index].display_name());
+                show = true;
+            },
+            _ => println!("{}", HELP)
+        }
+
+        if visible.is_empty() {
+            println!("Every configuration has been hidden!");
+            break;
+        }
+        rank = rank.min(visible.len() - 1);
+    }
+}
@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use xiv_levelling::job;
+use xiv_levelling::xivapi::{self, ClassJob, PlayerCharacter};
+
+/// A member of a party file, which `resolve` writes and `optimize` reads. Only what the search needs is kept, so the
+/// file can be edited by hand, e.g. to see what a job at another level would change.
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct Member {
+    /// Lodestone ID, or 0 for a member entered by hand.
+    #[serde(default)]
+    id: u32,
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    server: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    avatar: Option<String>,
+    /// Level of each job, by name or abbreviation, e.g. `{"WAR": 73, "White Mage": 16}`.
+    jobs: BTreeMap<String, u8>,
+    /// EXP earned towards the next level of each job, where known.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    exp: BTreeMap<String, u32>,
+    /// Other characters played by the same player, whose jobs can be picked for them too.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    alts: Vec<Member>
+}
+
+impl Member {
+    fn with_jobs<'a>(id: u32, name: &str, class_jobs: impl Iterator<Item = &'a ClassJob> + Clone) -> Member {
+        let unlocked = class_jobs.filter(|class_job| class_job.level > 0);
+        Member {
+            id,
+            name: name.to_owned(),
+            server: None,
+            avatar: None,
+            jobs: unlocked.clone().map(|class_job| (class_job.job.job_abbreviation.to_owned(), class_job.level)).collect(),
+            exp: unlocked
+                .filter_map(|class_job| Some((class_job.job.job_abbreviation.to_owned(), class_job.exp_level?)))
+                .collect(),
+            alts: Vec::new()
+        }
+    }
+
+    fn from_character(character: &PlayerCharacter) -> Member {
+        let jobs_of = |alt: Option<usize>| character.class_jobs.iter().filter(move |class_job| class_job.alt == alt);
+        Member {
+            server: character.server.clone(),
+            avatar: character.avatar.clone(),
+            alts: character.alts.iter()
+                .enumerate()
+                .map(|(index, alt)| Member::with_jobs(alt.id, &alt.name, jobs_of(Some(index))))
+                .collect(),
+            ..Member::with_jobs(character.id, &character.name, jobs_of(None))
+        }
+    }
+
+    fn into_character(self) -> Result<PlayerCharacter, String> {
+        let mut class_jobs = Vec::with_capacity(self.jobs.len());
+        for (name, &level) in &self.jobs {
+            let infos: Vec<_> = job::JOBS.iter().filter(|info| info.matches(name)).collect();
+            if infos.is_empty() {
+                return Err(format!("unknown job {} for {}", name, self.name));
+            }
+
+            let exp_level = self.exp.get(name).copied();
+            class_jobs.extend(infos.into_iter().map(|info| ClassJob { exp_level, ..ClassJob::manual(info, level) }));
+        }
+        xivapi::merge_class_jobs(&mut class_jobs);
+
+        let mut character = PlayerCharacter {
+            id: self.id,
+            class_jobs,
+            name: self.name,
+            server: self.server,
+            avatar: self.avatar,
+            portrait: None,
+            bio: None,
+            buffs: Vec::new(),
+            trial: false,
+            expansion: None,
+            play_history: None,
+            role_history: None,
+            nickname: None,
+            alts: Vec::new(),
+            gear_set: None
+        };
+        for alt in self.alts {
+            character.add_alt(alt.into_character()?);
+        }
+        Ok(character)
+    }
+}
+
+/// Writes `party` as a party file to `path`, or stdout if there is none.
+pub fn write(path: Option<&Path>, party: &[PlayerCharacter]) -> io::Result<()> {
+    let members: Vec<Member> = party.iter().map(Member::from_character).collect();
+    let json = serde_json::to_string_pretty(&members)?;
+    match path {
+        Some(path) => fs::write(path, json + "\n"),
+        None => {
+            println!("{}", json);
+            Ok(())
+        }
+    }
+}
+
+/// Reads the party in the party file at `path`, or stdin if it is `-`.
+pub fn read(path: &Path) -> Result<Vec<PlayerCharacter>, String> {
+    let json = if path.as_os_str() == "-" {
+        let mut json = String::new();
+        io::stdin().read_to_string(&mut json).map(|_| json)
+    } else {
+        fs::read_to_string(path)
+    }
+        .map_err(|e| e.to_string())?;
+
+    let members: Vec<Member> = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    members.into_iter().map(Member::into_character).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::exit::Exit;
+    use crate::Cli;
+
+    /// A party file in the temporary directory, removed again once the test is done with it.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> TempFile {
+            TempFile(std::env::temp_dir().join(format!("xiv-levelling-{}-{}.json", name, std::process::id())))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn read_json(name: &str, json: &str) -> Result<Vec<PlayerCharacter>, String> {
+        let file = TempFile::new(name);
+        fs::write(&file.0, json).unwrap();
+        read(&file.0)
+    }
+
+    fn class_job(abbreviation: &str, level: u8) -> ClassJob {
+        let info = job::JOBS.iter().find(|info| info.job_abbreviation == abbreviation).unwrap();
+        ClassJob::manual(info, level)
+    }
+
+    fn character(id: u32, name: &str, class_jobs: Vec<ClassJob>) -> PlayerCharacter {
+        PlayerCharacter {
+            id,
+            class_jobs,
+            name: name.to_owned(),
+            server: None,
+            avatar: None,
+            portrait: None,
+            bio: None,
+            buffs: Vec::new(),
+            trial: false,
+            expansion: None,
+            play_history: None,
+            role_history: None,
+            nickname: None,
+            alts: Vec::new(),
+            gear_set: None
+        }
+    }
+
+    /// A job as (alt, abbreviation, level, EXP, unlocked), in a form that can be compared.
+    type JobSummary<'a> = (Option<usize>, &'a str, u8, Option<u32>, bool);
+
+    fn summary(character: &PlayerCharacter) -> Vec<JobSummary<'_>> {
+        let mut jobs: Vec<_> = character.class_jobs.iter()
+            .map(|class_job| {
+                let abbreviation = class_job.job.job_abbreviation;
+                (class_job.alt, abbreviation, class_job.level, class_job.exp_level, class_job.job_unlocked)
+            })
+            .collect();
+        jobs.sort();
+        jobs
+    }
+
+    #[test]
+    fn round_trips_alts_and_exp() {
+        let mut alice = character(12_345, "Alice One", vec![
+            ClassJob { exp_level: Some(1_200), ..class_job("WAR", 73) },
+            class_job("PLD", 20),
+            class_job("BLM", 0)
+        ]);
+        alice.server = Some("Zalera".to_owned());
+        alice.add_alt(character(0, "Alice Two", vec![class_job("WHM", 52)]));
+
+        let file = TempFile::new("round-trip");
+        write(Some(&file.0), &[alice]).unwrap();
+        let party = read(&file.0).unwrap();
+
+        assert_eq!(party.len(), 1);
+        let read_alice = &party[0];
+        assert_eq!((read_alice.id, read_alice.name.as_str()), (12_345, "Alice One"));
+        assert_eq!(read_alice.server.as_deref(), Some("Zalera"));
+        assert_eq!(read_alice.alts.len(), 1);
+        assert_eq!((read_alice.alts[0].id, read_alice.alts[0].name.as_str()), (0, "Alice Two"));
+        // Locked jobs aren't written, and Paladin at 20 is still Gladiator.
+        assert_eq!(summary(read_alice), [
+            (None, "PLD", 20, None, false),
+            (None, "WAR", 73, Some(1_200), true),
+            (Some(0), "WHM", 52, None, true)
+        ]);
+    }
+
+    #[test]
+    fn jobs_are_given_by_name_or_abbreviation() {
+        let party = read_json("names", r#"[
+            {"name": "Bram", "jobs": {"WAR": 50, "white mage": 40, "Arcanist": 30}, "exp": {"white mage": 500}}
+        ]"#).unwrap();
+
+        // Arcanist is the class of both Scholar and Summoner.
+        assert_eq!(summary(&party[0]), [
+            (None, "SCH", 30, None, true),
+            (None, "SMN", 30, None, true),
+            (None, "WAR", 50, None, true),
+            (None, "WHM", 40, Some(500), true)
+        ]);
+
+        let unknown = read_json("unknown", r#"[{"name": "Bram", "jobs": {"Gunslinger": 50}}]"#);
+        assert_eq!(unknown.unwrap_err(), "unknown job Gunslinger for Bram");
+    }
+
+    #[test]
+    fn a_class_and_its_job_are_merged() {
+        let party = read_json("merged", r#"[{"name": "Cyra", "jobs": {"GLA": 20, "Paladin": 45, "MRD": 12}}]"#).unwrap();
+        assert_eq!(summary(&party[0]), [(None, "PLD", 45, None, true), (None, "WAR", 12, None, false)]);
+    }
+
+    #[test]
+    fn parties_too_large_for_the_options_are_rejected() {
+        let members: Vec<_> = (1..=5).map(|n| format!(r#"{{"name": "Member {}", "jobs": {{"WAR": 50}}}}"#, n)).collect();
+        let party = read_json("too-large", &format!("[{}]", members.join(", "))).unwrap();
+
+        let cli = Cli::parse_from(["xiv-levelling", "optimize", "party.json"]);
+        assert_eq!(crate::check_party_size(&cli, &party, None), Err(Exit::InvalidConstraints));
+        assert_eq!(crate::check_party_size(&cli, &party[..4], None), Ok(()));
+
+        let cli = Cli::parse_from(["xiv-levelling", "--split", "optimize", "party.json"]);
+        assert_eq!(crate::check_party_size(&cli, &party, None), Ok(()));
+    }
+}
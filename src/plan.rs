@@ -0,0 +1,144 @@
+use std::str::FromStr;
+
+use crate::constraints::Constraints;
+use crate::duty::{self, Duty, DutyKind, Roulette};
+use crate::job::{self, Role};
+use crate::optimizer::{Assignments, PartyConfig, Scoring};
+use crate::xivapi::PlayerCharacter;
+
+/// How many of the best configurations each day's is picked from.
+const CANDIDATES: usize = 20;
+
+/// The role a member most enjoys playing, e.g. `Alice=tank`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Favourite {
+    pub character: String,
+    pub role: Role
+}
+
+impl FromStr for Favourite {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((character, role)) if !character.trim().is_empty() => Ok(Favourite {
+                character: character.trim().to_owned(),
+                role: role.trim().parse().map_err(|_| format!("invalid favourite {} (expected Name=tank, healer or dps)", s))?
+            }),
+            _ => Err(format!("invalid favourite {} (expected Name=Role)", s))
+        }
+    }
+}
+
+/// One day's session: what to play, and how far it gets everyone.
+#[derive(Debug)]
+pub struct Session {
+    pub party_config: PartyConfig,
+    /// The roulette to start the session with, for its daily bonus.
+    pub roulette: Option<&'static Roulette>,
+    /// The dungeon to run for the rest of the session.
+    pub duty: &'static Duty,
+    pub runs: u32,
+    /// The member whose turn it was to play their favourite role, if they could.
+    pub featured: Option<usize>,
+    /// EXP earned by the whole party.
+    pub exp: u64,
+    /// Each member's level before and after the session.
+    pub levels: Vec<(u8, u8)>
+}
+
+/// Each member's favourite role: the one they asked for, or else that of their highest-level job.
+pub fn favourite_roles(party: &[PlayerCharacter], favourites: &[Favourite]) -> Vec<Option<Role>> {
+    party.iter()
+        .map(|character| {
+            favourites.iter()
+                .find(|favourite| character.matches_name(&favourite.character))
+                .map(|favourite| favourite.role)
+                .or_else(|| character.class_jobs.iter().filter(|class_job| class_job.role().is_some()).max_by_key(|class_job| class_job.level)?.role())
+        })
+        .collect()
+}
+
+/// Plans `days` sessions of `runs` dungeons each. Each day, a different member gets to play their favourite role if
+/// any of the best configurations allow it, and the configuration earning the party the most EXP is picked. Levels
+/// carry over from one day to the next.
+pub fn plan(
+    party: &[PlayerCharacter],
+    scoring: &Scoring,
+    constraints: &Constraints,
+    favourites: &[Option<Role>],
+    days: usize,
+    runs: u32
+) -> Vec<Session> {
+    let mut party = party.to_vec();
+    let assignments = Assignments::new(&party);
+    let mut sessions = Vec::with_capacity(days);
+
+    for day in 0..days {
+        let candidates: Vec<(PartyConfig, u8, &'static Duty, u64)> = assignments.rank_top(&party, scoring, constraints, CANDIDATES).into_iter()
+            .filter_map(|party_config| {
+                let level = party.iter().map(PlayerCharacter::level_cap).fold(party_config.min_level, u8::min);
+                let duty = duty::highest_available(level, DutyKind::Dungeon, 1).pop()?;
+                let exp = session_exp(&party, &party_config, duty, runs);
+                Some((party_config, level, duty, exp))
+            })
+            .collect();
+
+        let featured = (!party.is_empty()).then(|| day % party.len());
+        let plays_favourite = |party_config: &PartyConfig, member: usize| {
+            let role = party[member].class_jobs[party_config.index[member]].role();
+            role.is_some() && role == favourites.get(member).copied().flatten()
+        };
+        let featured = featured.filter(|&member| candidates.iter().any(|(party_config, _, _, _)| plays_favourite(party_config, member)));
+
+        // Reversed so that ties go to the better-ranked configuration.
+        let Some((party_config, level, duty, exp)) = candidates.into_iter()
+            .rev()
+            .filter(|(party_config, _, _, _)| featured.is_none_or(|member| plays_favourite(party_config, member)))
+            .max_by_key(|&(_, _, _, exp)| exp)
+        else {
+            break;
+        };
+
+        let mut levels = Vec::with_capacity(party.len());
+        let gained = member_exp(&party, &party_config, duty, runs);
+        for ((character, &job_index), gain) in party.iter_mut().zip(&party_config.index).zip(gained) {
+            let class_job = &mut character.class_jobs[job_index];
+            let before = class_job.level;
+            let (level, exp_level) = job::gain_exp(class_job.level, class_job.exp_level.unwrap_or_default(), gain);
+            class_job.level = level;
+            class_job.exp_level = class_job.exp_level.map(|_| exp_level);
+            levels.push((before, level));
+        }
+
+        sessions.push(Session {
+            party_config,
+            roulette: duty::available_roulettes(level).into_iter().next(),
+            duty,
+            runs,
+            featured,
+            exp,
+            levels
+        });
+    }
+
+    sessions
+}
+
+/// The EXP each member earns from `runs` runs of `duty`, including their bonuses. EXP past a member's level cap is
+/// wasted, so isn't counted.
+fn member_exp(party: &[PlayerCharacter], party_config: &PartyConfig, duty: &Duty, runs: u32) -> Vec<u64> {
+    let exp = duty::completion_exp(duty).unwrap_or_default() as f64 * runs as f64;
+    party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| {
+            let class_job = &character.class_jobs[job_index];
+            let earned = job::exp_to_reach(class_job.level) + class_job.exp_level.unwrap_or_default() as u64;
+            let remaining = job::exp_to_reach(character.level_cap()).saturating_sub(earned);
+            ((exp * character.exp_multiplier(class_job)) as u64).min(remaining)
+        })
+        .collect()
+}
+
+fn session_exp(party: &[PlayerCharacter], party_config: &PartyConfig, duty: &Duty, runs: u32) -> u64 {
+    member_exp(party, party_config, duty, runs).into_iter().sum()
+}
@@ -0,0 +1,94 @@
+use std::process;
+use std::time::{Duration, SystemTime};
+
+use xiv_levelling::history::{History, Snapshot};
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Most points drawn in a single sparkline.
+const WIDTH: usize = 30;
+
+/// A job's name and its level in each snapshot, oldest first.
+type JobLevels = (&'static str, Vec<u8>);
+
+/// Whether `wanted` is `name` or its first name, ignoring case.
+fn matches_name(name: &str, wanted: &str) -> bool {
+    name.eq_ignore_ascii_case(wanted)
+        || name.split_whitespace().next().is_some_and(|first_name| first_name.eq_ignore_ascii_case(wanted))
+}
+
+/// Draws `levels` as a sparkline on a scale from `low` to `high`, sampling evenly if there are too many to fit.
+fn sparkline(levels: &[u8], low: u8, high: u8) -> String {
+    let points = levels.len().min(WIDTH);
+    let range = (high - low).max(1) as usize;
+
+    (0..points)
+        .map(|i| {
+            let level = levels[i * (levels.len() - 1) / (points - 1).max(1)];
+            BARS[(level - low) as usize * (BARS.len() - 1) / range]
+        })
+        .collect()
+}
+
+/// Prints a sparkline of each job every matching character has levelled in the last `days` days. Every character
+/// in the history is shown if `names` is empty.
+pub fn run(names: &[String], days: u64) {
+    let history = match History::default_path().map(|path| History::open(&path)) {
+        Some(Ok(history)) => history,
+        Some(Err(e)) => {
+            eprintln!("Failed to open the history database: {}", e);
+            process::exit(1);
+        },
+        None => {
+            eprintln!("There is nowhere to keep a history database on this system!");
+            process::exit(1);
+        }
+    };
+
+    let since = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+    let characters: Vec<(u32, String)> = history.characters().unwrap_or_default().into_iter()
+        .filter(|(_, name)| names.is_empty() || names.iter().any(|wanted| matches_name(name, wanted)))
+        .collect();
+
+    if characters.is_empty() {
+        println!("No matching characters have been looked up yet!");
+        return;
+    }
+
+    let mut charts: Vec<(String, Vec<JobLevels>)> = Vec::new();
+    for (id, name) in characters {
+        let snapshots = match history.snapshots(id, since) {
+            Ok(snapshots) => snapshots,
+            Err(e) => {
+                eprintln!("Failed to read the history of {}: {}", name, e);
+                continue;
+            }
+        };
+
+        let mut jobs: Vec<JobLevels> = Vec::new();
+        for Snapshot { job, level, .. } in snapshots {
+            match jobs.iter_mut().find(|(job_name, _)| *job_name == job.job_name) {
+                Some((_, levels)) => levels.push(level),
+                None => jobs.push((job.job_name, vec![level]))
+            }
+        }
+        jobs.retain(|(_, levels)| levels.first() != levels.last());
+
+        charts.push((name, jobs));
+    }
+
+    // Every chart shares a scale, so members can be compared against each other.
+    let all_levels = charts.iter().flat_map(|(_, jobs)| jobs.iter().flat_map(|(_, levels)| levels.iter().copied()));
+    let low = all_levels.clone().min().unwrap_or_default();
+    let high = all_levels.max().unwrap_or_default();
+
+    for (name, jobs) in charts {
+        println!("{}", name);
+        if jobs.is_empty() {
+            println!("  No levels gained in the last {} day(s).", days);
+        }
+        for (job_name, levels) in jobs {
+            let (first, last) = (levels[0], levels[levels.len() - 1]);
+            println!("  {0: <15} {1} Lv {2} -> {3} (+{4})", job_name, sparkline(&levels, low, high), first, last, last - first);
+        }
+    }
+}
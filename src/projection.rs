@@ -0,0 +1,133 @@
+use crate::advice;
+use crate::duty::{self, Duty, DutyKind};
+use crate::job;
+use crate::optimizer::PartyConfig;
+use crate::xivapi::PlayerCharacter;
+
+/// Dungeon runs in each session a group plays, after the roulette it starts with.
+pub const RUNS_PER_SESSION: u32 = 4;
+
+/// Most weeks `weeks_to_cap` looks ahead.
+const MAX_WEEKS: u32 = 520;
+
+/// Simulated levels of each member of a configuration over successive runs of a duty.
+#[derive(Debug)]
+pub struct Projection {
+    pub duty: &'static Duty,
+    /// Each member's level before the first run, then after every run.
+    pub levels: Vec<Vec<u8>>,
+    /// Whether every member ended up at the same level.
+    pub converged: bool
+}
+
+/// Effective EXP `party_config` loses to level sync on each run of the highest dungeon its lowest member can enter:
+/// what each member would earn from the highest dungeon at their own level, less what they earn from the party's.
+/// Returns `None` if there is no EXP data for the party's dungeon.
+pub fn exp_waste(party: &[PlayerCharacter], party_config: &PartyConfig) -> Option<u64> {
+    let level = party.iter().map(PlayerCharacter::level_cap).fold(party_config.min_level, u8::min);
+    let exp = duty::completion_exp(duty::highest_available(level, DutyKind::Dungeon, 1).pop()?)?;
+
+    let waste = party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| {
+            // A job at its cap earns nothing anywhere, so it has nothing to lose.
+            let class_job = &character.class_jobs[job_index];
+            if class_job.level >= character.job_cap(class_job) {
+                return 0;
+            }
+
+            let own = duty::highest_available(class_job.level.min(character.level_cap()), DutyKind::Dungeon, 1).pop()
+                .and_then(duty::completion_exp)
+                .unwrap_or(exp);
+            (own.saturating_sub(exp) as f64 * character.exp_multiplier(class_job)) as u64
+        })
+        .sum();
+    Some(waste)
+}
+
+/// Orders `party_configs` by how much EXP they lose to level sync, least first, keeping their order otherwise.
+pub fn sort_by_waste(party: &[PlayerCharacter], party_configs: &mut [PartyConfig]) {
+    party_configs.sort_by_cached_key(|party_config| exp_waste(party, party_config).unwrap_or(u64::MAX));
+}
+
+/// Roughly how many weeks of `sessions_per_week` sessions it takes every member of `party_config` to reach their cap
+/// on their job. Each session is the roulette with the biggest daily bonus the party can queue for, then
+/// `RUNS_PER_SESSION` runs of the highest dungeon it can enter. Members already at their cap keep playing with the
+/// rest. Returns `None` if there is no EXP data for a dungeon on the way, or it would take more than `MAX_WEEKS`.
+pub fn weeks_to_cap(party: &[PlayerCharacter], party_config: &PartyConfig, sessions_per_week: u32) -> Option<u32> {
+    let members: Vec<_> = party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| (character, &character.class_jobs[job_index]))
+        .collect();
+    let mut progress: Vec<(u8, u32)> = members.iter()
+        .map(|(_, class_job)| (class_job.level, class_job.exp_level.unwrap_or_default()))
+        .collect();
+    let caps: Vec<u8> = members.iter().map(|(character, class_job)| character.job_cap(class_job)).collect();
+    let party_cap = party.iter().map(PlayerCharacter::level_cap).min().unwrap_or(job::LEVEL_CAP);
+
+    let mut sessions = 0;
+    while progress.iter().zip(&caps).any(|(&(level, _), &cap)| level < cap) {
+        if sessions >= MAX_WEEKS * sessions_per_week {
+            return None;
+        }
+
+        let level = progress.iter().map(|&(level, _)| level).fold(party_cap, u8::min);
+        let exp = duty::completion_exp(duty::highest_available(level, DutyKind::Dungeon, 1).pop()?)? as f64;
+        let bonus = advice::best_roulette_bonus(level);
+
+        for (((character, class_job), (level, exp_level)), &cap) in members.iter().zip(&mut progress).zip(&caps) {
+            if *level >= cap {
+                continue;
+            }
+
+            // The armoury bonus only lasts while the job is below the character's highest level.
+            let mut multiplier = character.exp_multiplier(class_job);
+            if character.has_armoury_bonus(class_job) && *level >= character.highest_level_with(class_job) {
+                multiplier -= 1.0;
+            }
+            let roulette = job::EXP_TO_NEXT_LEVEL[*level as usize - 1] as f64 * bonus;
+            let gain = exp * RUNS_PER_SESSION as f64 * multiplier + roulette;
+
+            (*level, *exp_level) = job::gain_exp(*level, *exp_level, gain as u64);
+            if *level >= cap {
+                (*level, *exp_level) = (cap, 0);
+            }
+        }
+        sessions += 1;
+    }
+
+    Some(sessions.div_ceil(sessions_per_week))
+}
+
+/// Simulates up to `max_runs` runs of `duty` by `party_config`, stopping once every member is at the same level.
+/// Returns `None` if there is no EXP data for `duty`.
+pub fn project(party: &[PlayerCharacter], party_config: &PartyConfig, duty: &'static Duty, max_runs: usize) -> Option<Projection> {
+    let exp = duty::completion_exp(duty)? as f64;
+
+    let mut progress: Vec<(u8, u32)> = party.iter().zip(&party_config.index)
+        .map(|(character, &job_index)| {
+            let class_job = &character.class_jobs[job_index];
+            (class_job.level, class_job.exp_level.unwrap_or_default())
+        })
+        .collect();
+    let mut levels = vec![progress.iter().map(|&(level, _)| level).collect::<Vec<_>>()];
+
+    // Members stop at the level cap, so anyone already there is eventually caught up with.
+    let converged = |levels: &[u8]| levels.iter().all(|&level| level == levels[0]);
+
+    while levels.len() <= max_runs && !converged(levels.last().unwrap()) {
+        for ((character, &job_index), (level, exp_level)) in party.iter().zip(&party_config.index).zip(&mut progress) {
+            // The armoury bonus only lasts while the job is below the character's highest level.
+            let class_job = &character.class_jobs[job_index];
+            let mut multiplier = character.exp_multiplier(class_job);
+            if character.has_armoury_bonus(class_job) && *level >= character.highest_level_with(class_job) {
+                multiplier -= 1.0;
+            }
+
+            (*level, *exp_level) = job::gain_exp(*level, *exp_level, (exp * multiplier) as u64);
+        }
+
+        levels.push(progress.iter().map(|&(level, _)| level).collect());
+    }
+
+    let converged = converged(levels.last().unwrap());
+    Some(Projection { duty, levels, converged })
+}
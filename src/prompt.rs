@@ -0,0 +1,99 @@
+use dialoguer::theme::{ColorfulTheme, SimpleTheme, Theme};
+use dialoguer::{Confirm, FuzzySelect, MultiSelect, Select};
+use std::io::{self, IsTerminal};
+
+use xiv_levelling::job::Role;
+use xiv_levelling::xivapi::{PlayerSearchEntry, ServerList};
+
+use crate::i18n::{self, Message};
+
+const ROLES: [(Role, &str); 3] = [(Role::Tank, "tank"), (Role::Healer, "healer"), (Role::Dps, "dps")];
+
+/// Whether the widgets can be drawn. They need a terminal to read keys from and another to draw on, otherwise the
+/// plain prompts are used.
+pub fn available() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+fn colourful() -> bool {
+    colored::control::SHOULD_COLORIZE.should_colorize()
+}
+
+fn theme() -> Box<dyn Theme> {
+    if colourful() {
+        Box::new(ColorfulTheme::default())
+    } else {
+        Box::new(SimpleTheme)
+    }
+}
+
+/// Asks for a server from `servers`, filtered as the user types. Returns `None` if nothing was picked.
+pub fn server(servers: &ServerList) -> Option<String> {
+    let theme = theme();
+    FuzzySelect::with_theme(theme.as_ref())
+        .with_prompt(i18n::text(Message::ServerSelectPrompt))
+        .items(servers.names())
+        .highlight_matches(colourful())
+        .interact_opt()
+        .ok()
+        .flatten()
+        .map(|index| servers.names()[index].clone())
+}
+
+/// `entry` with what tells it apart from others of the same name, e.g. "Cloud Strife (123) on Zalera, Crystal:
+/// https://...".
+fn describe(entry: &PlayerSearchEntry) -> String {
+    let mut description = format!("{} ({})", entry.name, entry.id);
+    match (entry.world(), entry.data_center()) {
+        (Some(world), Some(data_center)) => description.push_str(&format!(" on {}, {}", world, data_center)),
+        (Some(world), None) => description.push_str(&format!(" on {}", world)),
+        _ => {}
+    }
+    if let Some(avatar) = &entry.avatar {
+        description.push_str(&format!(": {}", avatar));
+    }
+    description
+}
+
+/// Asks which of several characters called `name` was meant. Returns `None` if none of them were.
+pub fn character<'a>(name: &str, results: &'a [PlayerSearchEntry]) -> Option<&'a PlayerSearchEntry> {
+    let items: Vec<String> = results.iter().map(describe).collect();
+
+    let theme = theme();
+    Select::with_theme(theme.as_ref())
+        .with_prompt(i18n::format(Message::ChooseCharacter, &[&name]))
+        .items(&items)
+        .default(0)
+        .interact_opt()
+        .ok()
+        .flatten()
+        .map(|index| &results[index])
+}
+
+/// Asks whether the only character found, `found`, is the one that was searched for.
+pub fn confirm_character(found: &PlayerSearchEntry) -> bool {
+    let theme = theme();
+    Confirm::with_theme(theme.as_ref())
+        .with_prompt(i18n::format(Message::ConfirmCharacter, &[&found.name, &found.id]))
+        .default(true)
+        .interact()
+        .unwrap_or(false)
+}
+
+/// Asks which roles `name` is willing to play. Returns `None` if they'll play any.
+pub fn roles(name: &str) -> Option<Vec<Role>> {
+    let items: Vec<String> = ROLES.iter().map(|(role, role_name)| format!("{} {}", role.icon(), role_name)).collect();
+
+    let theme = theme();
+    let picked = MultiSelect::with_theme(theme.as_ref())
+        .with_prompt(i18n::format(Message::RolesSelectPrompt, &[&name]))
+        .items(&items)
+        .interact()
+        .ok()?;
+
+    if picked.is_empty() || picked.len() == ROLES.len() {
+        None
+    } else {
+        Some(picked.into_iter().map(|index| ROLES[index].0).collect())
+    }
+}
@@ -0,0 +1,193 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[cfg(feature = "network")]
+use crate::xivapi::XivApi;
+use crate::xivapi::{self, Pagination, PlayerCharacter, PlayerSearchEntry, PlayerSearchResult, ServerList};
+use crate::Result;
+
+/// A source of character data that the party search can be run against.
+pub trait CharacterProvider {
+    fn servers(&self) -> Result<ServerList>;
+
+    fn search_character(&self, name: &str, server: &str) -> Result<PlayerSearchResult>;
+
+    fn character(&self, id: u32) -> Result<PlayerCharacter>;
+
+    /// Like `character`, but bypasses anything the provider has cached.
+    fn refresh_character(&self, id: u32) -> Result<PlayerCharacter> {
+        self.character(id)
+    }
+
+    /// How old the provider's cached copy of the character with Lodestone ID `id` is, if it keeps one.
+    fn character_age(&self, _id: u32) -> Option<Duration> {
+        None
+    }
+}
+
+impl<T: CharacterProvider + ?Sized> CharacterProvider for &T {
+    fn servers(&self) -> Result<ServerList> {
+        (**self).servers()
+    }
+
+    fn search_character(&self, name: &str, server: &str) -> Result<PlayerSearchResult> {
+        (**self).search_character(name, server)
+    }
+
+    fn character(&self, id: u32) -> Result<PlayerCharacter> {
+        (**self).character(id)
+    }
+
+    fn refresh_character(&self, id: u32) -> Result<PlayerCharacter> {
+        (**self).refresh_character(id)
+    }
+
+    fn character_age(&self, id: u32) -> Option<Duration> {
+        (**self).character_age(id)
+    }
+}
+
+#[cfg(feature = "network")]
+impl CharacterProvider for XivApi {
+    fn servers(&self) -> Result<ServerList> {
+        XivApi::servers(self)
+    }
+
+    fn search_character(&self, name: &str, server: &str) -> Result<PlayerSearchResult> {
+        XivApi::search_character(self, name, server)
+    }
+
+    fn character(&self, id: u32) -> Result<PlayerCharacter> {
+        XivApi::character(self, id)
+    }
+
+    fn refresh_character(&self, id: u32) -> Result<PlayerCharacter> {
+        XivApi::refresh_character(self, id)
+    }
+
+    fn character_age(&self, id: u32) -> Option<Duration> {
+        XivApi::character_age(self, id)
+    }
+}
+
+/// A fictional party bundled with the tool, saved in the same form as XIVAPI's character responses.
+const DEMO_PARTY: [&str; 4] = [
+    include_str!("../demo/aeryn-vale.json"),
+    include_str!("../demo/bram-stoutheart.json"),
+    include_str!("../demo/cyra-moonwhisper.json"),
+    include_str!("../demo/dax-emberfall.json")
+];
+
+/// A provider serving a fixed set of characters from memory, for running without network access.
+#[derive(Clone, Debug, Default)]
+pub struct MockProvider {
+    servers: Vec<String>,
+    characters: Vec<PlayerCharacter>
+}
+
+impl MockProvider {
+    /// Serves `characters` on every server in `servers`, or on any server if `servers` is empty.
+    pub fn new(servers: Vec<String>, characters: Vec<PlayerCharacter>) -> Self {
+        MockProvider { servers, characters }
+    }
+
+    /// Serves every character response saved from XIVAPI as a `.json` file in `dir`, on any server.
+    pub fn load_dir(dir: &Path) -> Result<Self> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<_>>()?;
+        paths.retain(|path| path.extension().is_some_and(|extension| extension == "json"));
+        paths.sort();
+
+        let mut characters = Vec::with_capacity(paths.len());
+        for path in paths {
+            characters.push(xivapi::parse_character(&fs::read_to_string(path)?)?);
+        }
+
+        Ok(MockProvider::new(Vec::new(), characters))
+    }
+
+    /// Serves the fictional party bundled with the tool, on any server, to try it out on without looking anyone up.
+    pub fn demo() -> Result<Self> {
+        let characters = DEMO_PARTY.iter()
+            .map(|json| xivapi::parse_character(json))
+            .collect::<Result<_>>()?;
+
+        Ok(MockProvider::new(Vec::new(), characters))
+    }
+
+    /// Names of every character served, in the order they were given.
+    pub fn names(&self) -> Vec<String> {
+        self.characters.iter().map(|character| character.name.clone()).collect()
+    }
+}
+
+impl CharacterProvider for MockProvider {
+    fn servers(&self) -> Result<ServerList> {
+        Ok(ServerList::new(self.servers.clone()))
+    }
+
+    fn search_character(&self, name: &str, server: &str) -> Result<PlayerSearchResult> {
+        let results: Vec<PlayerSearchEntry> = self.characters.iter()
+            .filter(|character| {
+                (self.servers.is_empty() || self.servers.iter().any(|known| known == server)) && character.matches_name(name)
+            })
+            .map(|character| PlayerSearchEntry {
+                id: character.id,
+                name: character.name.clone(),
+                server: character.server.clone(),
+                avatar: character.avatar.clone()
+            })
+            .collect();
+
+        Ok(PlayerSearchResult {
+            pagination: Pagination {
+                results: results.len() as u8
+            },
+            results
+        })
+    }
+
+    fn character(&self, id: u32) -> Result<PlayerCharacter> {
+        self.characters.iter()
+            .find(|character| character.id == id)
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no character with ID {}", id)).into())
+    }
+}
+
+/// Uses `primary`, falling back to `fallback` whenever a request to it fails.
+pub struct FallbackProvider<P, F> {
+    primary: P,
+    fallback: F
+}
+
+impl<P: CharacterProvider, F: CharacterProvider> FallbackProvider<P, F> {
+    pub fn new(primary: P, fallback: F) -> Self {
+        FallbackProvider { primary, fallback }
+    }
+}
+
+impl<P: CharacterProvider, F: CharacterProvider> CharacterProvider for FallbackProvider<P, F> {
+    fn servers(&self) -> Result<ServerList> {
+        self.primary.servers().or_else(|_| self.fallback.servers())
+    }
+
+    fn search_character(&self, name: &str, server: &str) -> Result<PlayerSearchResult> {
+        self.primary.search_character(name, server).or_else(|_| self.fallback.search_character(name, server))
+    }
+
+    fn character(&self, id: u32) -> Result<PlayerCharacter> {
+        self.primary.character(id).or_else(|_| self.fallback.character(id))
+    }
+
+    fn refresh_character(&self, id: u32) -> Result<PlayerCharacter> {
+        self.primary.refresh_character(id).or_else(|_| self.fallback.refresh_character(id))
+    }
+
+    fn character_age(&self, id: u32) -> Option<Duration> {
+        self.primary.character_age(id).or_else(|| self.fallback.character_age(id))
+    }
+}
@@ -0,0 +1,98 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pythonize::{depythonize, pythonize};
+use serde::Serialize;
+
+use crate::constraints::Constraints;
+use crate::job::{Mode, Role};
+use crate::optimizer::{self, Scoring};
+use crate::xivapi;
+
+#[derive(Serialize)]
+struct Member<'a> {
+    name: &'a str,
+    job: &'a str,
+    level: u8,
+    role: Option<Role>,
+    armoury_bonus: bool,
+    avatar: Option<&'a str>,
+    portrait: Option<&'a str>
+}
+
+#[derive(Serialize)]
+struct Configuration<'a> {
+    rank: usize,
+    spread: f64,
+    avg: u32,
+    min_level: u8,
+    members: Vec<Member<'a>>
+}
+
+/// Ranks the `top` best configurations of `party_json`, a JSON array of XIVAPI `/character/{id}` responses, and
+/// returns them as a list of dicts, best first. `constraints` is a dict of the fields of `Constraints`, as saved in
+/// sessions, e.g. `{"min_level": 50, "unique_jobs": True}`.
+#[pyfunction]
+#[pyo3(signature = (party_json, constraints = None, top = 5))]
+fn optimize<'py>(
+    py: Python<'py>,
+    party_json: &str,
+    constraints: Option<&Bound<'py, PyAny>>,
+    top: usize
+) -> PyResult<Bound<'py, PyAny>> {
+    let mut party = xivapi::parse_party(party_json).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    for character in &mut party {
+        character.retain_mode(Mode::Combat);
+    }
+
+    if party.len() < 2 || party.len() > 4 {
+        return Err(PyValueError::new_err("Party must consist of between two and four characters!"));
+    }
+
+    let mut constraints: serde_json::Value = match constraints {
+        Some(constraints) => depythonize(constraints)?,
+        None => serde_json::Value::Object(serde_json::Map::new())
+    };
+    if let Some(fields) = constraints.as_object_mut() {
+        // Constraints takes defaults for anything missing, so misspelt fields would otherwise go unnoticed.
+        let known = serde_json::to_value(Constraints::default()).unwrap_or_default();
+        if let Some(field) = fields.keys().find(|field| known.get(field.as_str()).is_none()) {
+            return Err(PyValueError::new_err(format!("Unknown constraint {}!", field)));
+        }
+        // Equivalent configurations are left out unless asked for, as they are everywhere else.
+        fields.entry("dedup").or_insert(true.into());
+    }
+    let constraints: Constraints = serde_json::from_value(constraints)
+        .map_err(|e| PyValueError::new_err(format!("Invalid constraints: {}", e)))?;
+
+    let party_configs = optimizer::rank_top(&party, &Scoring::default(), &constraints, top);
+
+    let configurations: Vec<Configuration> = party_configs.iter().enumerate()
+        .map(|(rank, party_config)| Configuration {
+            rank: rank + 1,
+            spread: party_config.spread,
+            avg: party_config.avg,
+            min_level: party_config.min_level,
+            members: party.iter().zip(&party_config.index)
+                .map(|(character, &job_index)| {
+                    let class_job = &character.class_jobs[job_index];
+                    Member {
+                        name: &character.name,
+                        job: class_job.display_name(),
+                        level: class_job.level,
+                        role: class_job.role(),
+                        armoury_bonus: character.has_armoury_bonus(class_job),
+                        avatar: character.avatar.as_deref(),
+                        portrait: character.portrait.as_deref()
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(pythonize(py, &configurations)?)
+}
+
+#[pymodule]
+fn xiv_levelling(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(optimize, module)?)
+}
@@ -0,0 +1,87 @@
+use crate::constraints::Constraints;
+use crate::job::{self, JobInfo};
+use crate::optimizer::{self, PartyConfig, Scoring};
+use crate::unlock;
+use crate::xivapi::{ClassJob, PlayerCharacter};
+
+/// A job a member could start levelling, and how much better the best configurations would be once it caught up with
+/// the rest of the party.
+#[derive(Debug)]
+pub struct Recommendation {
+    /// Index into the party of the member to level the job.
+    pub member: usize,
+    pub job: &'static JobInfo,
+    /// Level the job would be levelled to: the average of the best configuration, or as close as the member can get.
+    pub level: u8,
+    /// Average score of the best configurations as they are, and with the job at `level`. Lower is better.
+    pub before: f64,
+    pub after: f64
+}
+
+/// Average score of the first `count` of `party_configs`.
+fn average_score(party_configs: &[PartyConfig], count: usize) -> f64 {
+    let scores: Vec<f64> = party_configs.iter().take(count).map(|party_config| party_config.score).collect();
+    scores.iter().sum::<f64>() / scores.len().max(1) as f64
+}
+
+/// Whether `character` could level `job` up to `level`: it's behind, it doesn't start any higher or at their level
+/// cap, and they own it and could unlock it if they haven't.
+fn could_level(character: &PlayerCharacter, job: &'static JobInfo, level: u8) -> bool {
+    let current = character.class_jobs.iter()
+        .filter(|class_job| class_job.job.class_id == job.class_id)
+        .map(|class_job| class_job.level)
+        .max()
+        .unwrap_or_default();
+    let highest = character.class_jobs.iter()
+        .filter(|class_job| class_job.job.is_combat())
+        .map(|class_job| class_job.level)
+        .max()
+        .unwrap_or_default();
+    let unlockable = current > 0 || highest >= job.starting_level;
+
+    job.is_combat()
+        && !job.is_limited()
+        && current < level
+        && job.starting_level <= level
+        && job.starting_level < character.level_cap()
+        && unlockable
+        && (!character.trial || job.in_free_trial())
+        && character.owns(&ClassJob::manual(job, level))
+}
+
+/// For each member, the one job they could start levelling that would most improve the `limit` best configurations
+/// once it reached the average level of the best one, found by ranking them again with it. Members who couldn't
+/// improve them with any job are left out.
+pub fn recommend(
+    party: &[PlayerCharacter],
+    party_configs: &[PartyConfig],
+    scoring: &Scoring,
+    constraints: &Constraints,
+    limit: usize
+) -> Vec<Recommendation> {
+    let Some(best) = party_configs.first() else {
+        return Vec::new();
+    };
+    // Only as many as there are now are compared, since there can only be more with another job.
+    let count = party_configs.len().min(limit);
+    let before = average_score(party_configs, count);
+
+    let mut recommendations = Vec::new();
+    for (member, character) in party.iter().enumerate() {
+        let level = (best.avg as u8).min(character.level_cap());
+        let best_job = job::JOBS.iter()
+            .filter(|info| could_level(character, info, level))
+            .map(|info| {
+                let (levelled, _) = unlock::with_level(party, member, info, level);
+                (info, average_score(&optimizer::rank_top(&levelled, scoring, constraints, count), count))
+            })
+            .filter(|&(_, after)| after < before)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if let Some((job, after)) = best_job {
+            recommendations.push(Recommendation { member, job, level, before, after });
+        }
+    }
+
+    recommendations
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+const SECS_PER_WEEK: u64 = 7 * SECS_PER_DAY;
+
+/// When duties and roulette bonuses reset each day, in seconds after midnight UTC.
+const DAILY_RESET: u64 = 15 * 60 * 60;
+/// When the weekly reset is, in seconds into a week starting on a Thursday like the Unix epoch did: Tuesday at 08:00
+/// UTC.
+const WEEKLY_RESET: u64 = 5 * SECS_PER_DAY + 8 * 60 * 60;
+
+/// How close to the daily reset a roulette bonus not yet claimed counts as about to expire: long enough for a
+/// roulette or two.
+const EXPIRING: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// How long until the next daily and weekly resets. They happen at the same moment everywhere, so these hold in any
+/// timezone.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Resets {
+    pub daily: Duration,
+    pub weekly: Duration
+}
+
+impl Resets {
+    /// The resets after `now`, in seconds since the Unix epoch.
+    pub fn after(now: u64) -> Resets {
+        Resets {
+            daily: until(now, SECS_PER_DAY, DAILY_RESET),
+            weekly: until(now, SECS_PER_WEEK, WEEKLY_RESET)
+        }
+    }
+
+    /// Whether today's roulette bonuses run out soon enough that they should be run tonight rather than left.
+    pub fn bonus_expiring(&self) -> bool {
+        self.daily <= EXPIRING
+    }
+}
+
+/// Time from `now` until the next reset happening every `period` seconds, `offset` seconds into each.
+fn until(now: u64, period: u64, offset: u64) -> Duration {
+    let into = (now + period - offset) % period;
+    Duration::from_secs(period - into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tuesday 5 March 2024, 00:00 UTC.
+    const TUESDAY: u64 = 1_709_596_800;
+    const HOUR: u64 = 60 * 60;
+
+    fn secs(duration: Duration) -> u64 {
+        duration.as_secs()
+    }
+
+    #[test]
+    fn daily_reset_is_at_15_00_utc() {
+        assert_eq!(secs(Resets::after(TUESDAY).daily), 15 * HOUR);
+        assert_eq!(secs(Resets::after(TUESDAY + 15 * HOUR - 1).daily), 1);
+        // At the reset itself, the next one is a whole day away.
+        assert_eq!(secs(Resets::after(TUESDAY + 15 * HOUR).daily), SECS_PER_DAY);
+        assert_eq!(secs(Resets::after(TUESDAY + 15 * HOUR + 1).daily), SECS_PER_DAY - 1);
+        assert_eq!(secs(Resets::after(0).daily), 15 * HOUR);
+    }
+
+    #[test]
+    fn weekly_reset_is_on_tuesday_at_08_00_utc() {
+        assert_eq!(secs(Resets::after(TUESDAY + 8 * HOUR - 1).weekly), 1);
+        assert_eq!(secs(Resets::after(TUESDAY + 8 * HOUR).weekly), SECS_PER_WEEK);
+        assert_eq!(secs(Resets::after(TUESDAY + 8 * HOUR + 1).weekly), SECS_PER_WEEK - 1);
+        // Late on Monday, the reset is the coming morning rather than a week later.
+        assert_eq!(secs(Resets::after(TUESDAY - 1).weekly), 8 * HOUR + 1);
+        assert_eq!(secs(Resets::after(TUESDAY - SECS_PER_DAY).weekly), SECS_PER_DAY + 8 * HOUR);
+        // The epoch was a Thursday at midnight.
+        assert_eq!(secs(Resets::after(0).weekly), 5 * SECS_PER_DAY + 8 * HOUR);
+    }
+
+    #[test]
+    fn bonus_expires_within_two_hours_of_the_daily_reset() {
+        assert!(!Resets::after(TUESDAY + 13 * HOUR - 1).bonus_expiring());
+        assert!(Resets::after(TUESDAY + 13 * HOUR).bonus_expiring());
+        assert!(Resets::after(TUESDAY + 15 * HOUR - 1).bonus_expiring());
+        assert!(!Resets::after(TUESDAY + 15 * HOUR).bonus_expiring());
+    }
+}
@@ -0,0 +1,95 @@
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::job::Role;
+use crate::optimizer::{CustomScore, ScoreInput};
+use crate::{Error, Result};
+
+/// How many operations a script may take to score one configuration, so that one stuck in a loop fails instead of
+/// hanging the search.
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// A Rhai script that scores configurations, where lower is better. It is given:
+///
+/// - `members`: an array with a map for each member, of their `name`, `job`, `abbreviation`, `role` ("tank",
+///   "healer", "dps" or ""), `level` and whether they earn the `armoury_bonus`;
+/// - `spread`, `avg`, `min`, `max` and `bonus_count`, as shown for each configuration;
+/// - `score`: the score the built-in weights give it, for scripts that only adjust it.
+///
+/// For example, to prefer configurations where Alice tanks:
+///
+/// ```rhai
+/// let alice = members.filter(|member| member.name == "Alice");
+/// if alice.len() > 0 && alice[0].role == "tank" { score - 10 } else { score }
+/// ```
+#[derive(Debug)]
+pub struct ScoreScript {
+    engine: Engine,
+    ast: AST,
+    error: OnceLock<String>
+}
+
+impl ScoreScript {
+    pub fn new(source: &str) -> Result<Self> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine.compile(source).map_err(|e| Error::Script(e.to_string()))?;
+
+        Ok(ScoreScript { engine, ast, error: OnceLock::new() })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::new(&fs::read_to_string(path)?)
+    }
+
+    fn eval(&self, input: &ScoreInput) -> std::result::Result<f64, String> {
+        let members: Array = input.members.iter()
+            .map(|(character, class_job)| {
+                let role = match class_job.role() {
+                    Some(Role::Tank) => "tank",
+                    Some(Role::Healer) => "healer",
+                    Some(Role::Dps) => "dps",
+                    None => ""
+                };
+
+                let mut member = Map::new();
+                member.insert("name".into(), character.name.clone().into());
+                member.insert("job".into(), class_job.display_name().into());
+                member.insert("abbreviation".into(), class_job.abbreviation().into());
+                member.insert("role".into(), role.into());
+                member.insert("level".into(), (class_job.level as i64).into());
+                member.insert("armoury_bonus".into(), character.has_armoury_bonus(class_job).into());
+                Dynamic::from_map(member)
+            })
+            .collect();
+
+        let mut scope = Scope::new();
+        scope.push("members", members);
+        scope.push("spread", input.spread);
+        scope.push("avg", input.avg);
+        scope.push("min", input.min as i64);
+        scope.push("max", input.max as i64);
+        scope.push("bonus_count", input.bonus_count as i64);
+        scope.push("score", input.score);
+
+        let result: Dynamic = self.engine.eval_ast_with_scope(&mut scope, &self.ast).map_err(|e| e.to_string())?;
+        result.as_float()
+            .or_else(|_| result.as_int().map(|score| score as f64))
+            .map_err(|type_name| format!("expected the script to return a number, but it returned {}", type_name))
+    }
+}
+
+impl CustomScore for ScoreScript {
+    fn score(&self, input: &ScoreInput) -> f64 {
+        self.eval(input).unwrap_or_else(|e| {
+            let _ = self.error.set(e);
+            f64::INFINITY
+        })
+    }
+
+    fn error(&self) -> Option<String> {
+        self.error.get().cloned()
+    }
+}
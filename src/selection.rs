@@ -0,0 +1,725 @@
+use crate::constraints::Constraints;
+use crate::job::Role;
+use crate::optimizer::{self, PartyConfig, Scoring};
+use crate::xivapi::PlayerCharacter;
+
+/// A subset of a larger pool of characters, each assigned a job.
+#[derive(Clone, Debug)]
+pub struct Selection {
+    /// Index into the pool and into that character's class_jobs for each chosen member.
+    pub members: Vec<(usize, usize)>,
+    pub spread: f64,
+    pub avg: u32,
+    pub min_level: u8,
+    pub score: f64
+}
+
+/// One of the light parties in a `Split` or an `Event`.
+#[derive(Clone, Debug)]
+pub struct SplitParty {
+    /// Index into the pool of each member of this party.
+    pub members: Vec<usize>,
+    pub party: Vec<PlayerCharacter>,
+    pub config: PartyConfig
+}
+
+/// A division of a pool of characters into two light parties, each with its best configuration.
+#[derive(Clone, Debug)]
+pub struct Split {
+    pub parties: [SplitParty; 2],
+    /// Sum of both parties' scores, where lower is better.
+    pub score: f64
+}
+
+/// Why a sign-up to an `Event` wasn't put in any of its parties.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LeftOut {
+    /// The constraints allow none of their jobs.
+    NoJobs,
+    /// Fewer were left over than another party needs.
+    TooFew,
+    /// Enough were left over for another party, but not to fill its tank and healer slots. Has the roles they play.
+    Roles(Vec<Role>),
+    /// Those left over could fill another party's roles, but in no configuration the constraints allow.
+    Constraints
+}
+
+/// Sign-ups to a free company event formed into as many light parties as their roles allow.
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub parties: Vec<SplitParty>,
+    /// Index into the pool of each sign-up who isn't in a party, and why.
+    pub left_out: Vec<(usize, LeftOut)>,
+    /// Sum of the parties' scores, where lower is better.
+    pub score: f64
+}
+
+/// How many of the best parties from those not yet placed are tried for the next party of an event, since the very
+/// best may take a tank or healer another party can't do without.
+const EVENT_CANDIDATES: usize = 20;
+
+/// Most rounds of swapping members between an event's parties.
+const EVENT_PASSES: usize = 20;
+
+fn best_party(pool: &[PlayerCharacter], members: Vec<usize>, scoring: &Scoring, constraints: &Constraints) -> Option<SplitParty> {
+    let party: Vec<PlayerCharacter> = members.iter().map(|&member| pool[member].clone()).collect();
+    let config = optimizer::optimize_top(&party, scoring, constraints, 1).pop()?;
+    Some(SplitParty { members, party, config })
+}
+
+/// Finds up to `count` of the best ways to divide a pool of five to eight characters into two light parties.
+///
+/// With the ilp feature, the divisions are found by an integer program, and every division is only tried if the
+/// solver fails.
+pub fn split(pool: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints, count: usize) -> Vec<Split> {
+    #[cfg(feature = "ilp")]
+    match split_exact(pool, scoring, constraints, count) {
+        Ok(splits) => return splits,
+        Err(e) => tracing::warn!("solver failed, so trying every split instead: {}", e)
+    }
+
+    split_brute_force(pool, scoring, constraints, count)
+}
+
+/// Puts the party with the first member of the pool first, so each division only has one form.
+fn new_split(first: SplitParty, second: SplitParty) -> Split {
+    let score = first.config.score + second.config.score;
+    let parties = if first.members[0] < second.members[0] { [first, second] } else { [second, first] };
+    Split { parties, score }
+}
+
+fn sort_splits(splits: &mut Vec<Split>, count: usize) {
+    splits.sort_by(|a, b| a.score.total_cmp(&b.score).then_with(|| a.parties[0].members.cmp(&b.parties[0].members)));
+    splits.truncate(count);
+}
+
+/// Solves the division as set partitioning: every party of two to four members that has a valid configuration is a
+/// binary variable costing its best score, and exactly two of them have to cover each member once. After each
+/// solution, a cut rules it out so the next best can be found.
+#[cfg(feature = "ilp")]
+fn split_exact(
+    pool: &[PlayerCharacter],
+    scoring: &Scoring,
+    constraints: &Constraints,
+    count: usize
+) -> Result<Vec<Split>, good_lp::ResolutionError> {
+    use good_lp::{constraint, microlp, variable, Expression, ProblemVariables, Solution, SolverModel};
+
+    let candidates: Vec<SplitParty> = (1..1u32 << pool.len())
+        .filter(|mask| (2..=4).contains(&mask.count_ones()))
+        .filter_map(|mask| {
+            let members = (0..pool.len()).filter(|&member| mask & (1 << member) != 0).collect();
+            best_party(pool, members, scoring, constraints)
+        })
+        .collect();
+
+    let mut variables = ProblemVariables::new();
+    let chosen = variables.add_vector(variable().binary(), candidates.len());
+    let objective: Expression = candidates.iter().zip(&chosen).map(|(candidate, &x)| candidate.config.score * x).sum();
+
+    let mut found: Vec<(usize, usize)> = Vec::new();
+    let mut splits = Vec::new();
+    while splits.len() < count {
+        let mut problem = variables.clone().minimise(objective.clone()).using(microlp);
+        problem = problem.with(constraint!(chosen.iter().copied().sum::<Expression>() == 2));
+        for member in 0..pool.len() {
+            let covering: Expression = candidates.iter()
+                .zip(&chosen)
+                .filter(|(candidate, _)| candidate.members.contains(&member))
+                .map(|(_, &x)| x)
+                .sum();
+            problem = problem.with(constraint!(covering == 1));
+        }
+        for &(a, b) in &found {
+            problem = problem.with(constraint!(chosen[a] + chosen[b] <= 1));
+        }
+
+        let solution = match problem.solve() {
+            Ok(solution) => solution,
+            Err(good_lp::ResolutionError::Infeasible) => break,
+            Err(e) => return Err(e)
+        };
+        let picked: Vec<usize> = (0..candidates.len()).filter(|&index| solution.value(chosen[index]) > 0.5).collect();
+        let &[a, b] = picked.as_slice() else {
+            return Err(good_lp::ResolutionError::Str(format!("expected two parties, but {} were chosen", picked.len())));
+        };
+
+        found.push((a, b));
+        splits.push(new_split(candidates[a].clone(), candidates[b].clone()));
+    }
+
+    sort_splits(&mut splits, count);
+    Ok(splits)
+}
+
+/// Tries every division of the pool.
+fn split_brute_force(pool: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints, count: usize) -> Vec<Split> {
+    let mut splits = Vec::new();
+
+    // The first member always goes in the first party, so each division is only tried once.
+    for mask in (0..1u32 << pool.len()).filter(|mask| mask & 1 == 1) {
+        let (first, second): (Vec<usize>, Vec<usize>) = (0..pool.len()).partition(|&member| mask & (1 << member) != 0);
+        if !(2..=4).contains(&first.len()) || !(2..=4).contains(&second.len()) {
+            continue;
+        }
+
+        let Some(first) = best_party(pool, first, scoring, constraints) else {
+            continue;
+        };
+        let Some(second) = best_party(pool, second, scoring, constraints) else {
+            continue;
+        };
+
+        splits.push(new_split(first, second));
+    }
+
+    sort_splits(&mut splits, count);
+    splits
+}
+
+/// Roles `character` could play, going by the jobs the constraints allow them.
+fn playable_roles(character: &PlayerCharacter, constraints: &Constraints) -> Vec<Role> {
+    let mut roles: Vec<Role> = character.class_jobs.iter()
+        .filter(|class_job| class_job.level > 0 && constraints.allows(character, class_job))
+        .filter_map(|class_job| class_job.role())
+        .collect();
+    roles.sort_unstable_by_key(|&role| role as u8);
+    roles.dedup();
+    roles
+}
+
+/// The most light parties `members` of `pool` could form, going by the roles they play if the constraints need a tank
+/// and a healer in each.
+///
+/// By Hall's theorem, the slots can all be filled if, for every set of roles, at least as many members play one of
+/// them as there are slots for them.
+fn max_parties(pool: &[PlayerCharacter], members: &[usize], constraints: &Constraints) -> usize {
+    let most = members.len() / 4;
+    if !constraints.requires_roles() {
+        return most;
+    }
+
+    let roles: Vec<Vec<Role>> = members.iter().map(|&member| playable_roles(&pool[member], constraints)).collect();
+    let slots = role_slots(4);
+    (0..=most).rev()
+        .find(|&parties| {
+            (1..1u8 << slots.len()).all(|set| {
+                let in_set: Vec<Role> = (0..slots.len()).filter(|&i| set & (1 << i) != 0).map(|i| slots[i].0).collect();
+                let needed: usize = (0..slots.len()).filter(|&i| set & (1 << i) != 0).map(|i| slots[i].1 * parties).sum();
+                let playing = roles.iter().filter(|played| played.iter().any(|role| in_set.contains(role))).count();
+                playing >= needed
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// Forms a pool of sign-ups to a free company event into as many light parties as their roles allow, keeping the sum
+/// of the parties' scores low.
+///
+/// Each party in turn is the best of those not yet placed that still leaves enough tanks and healers for the rest.
+/// Members are then swapped between parties, and with whoever is left over, while that lowers the total.
+pub fn event(pool: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints) -> Event {
+    let (mut remaining, no_jobs): (Vec<usize>, Vec<usize>) = (0..pool.len())
+        .partition(|&member| !playable_roles(&pool[member], constraints).is_empty());
+    let target = max_parties(pool, &remaining, constraints);
+
+    let mut parties: Vec<SplitParty> = Vec::new();
+    while parties.len() < target {
+        let others: Vec<PlayerCharacter> = remaining.iter().map(|&member| pool[member].clone()).collect();
+        let still_needed = target - parties.len() - 1;
+        let next = select(&others, 4, scoring, constraints, EVENT_CANDIDATES).into_iter()
+            .map(|selection| selection.members.iter().map(|&(member, _)| remaining[member]).collect::<Vec<usize>>())
+            .filter(|members| {
+                let rest: Vec<usize> = remaining.iter().copied().filter(|member| !members.contains(member)).collect();
+                max_parties(pool, &rest, constraints) >= still_needed
+            })
+            .find_map(|members| best_party(pool, members, scoring, constraints));
+
+        let Some(party) = next else {
+            break;
+        };
+        remaining.retain(|member| !party.members.contains(member));
+        parties.push(party);
+    }
+
+    improve_event(pool, &mut parties, &mut remaining, scoring, constraints);
+
+    let reason = if remaining.len() < 4 {
+        LeftOut::TooFew
+    } else if max_parties(pool, &remaining, constraints) == 0 {
+        LeftOut::Roles(Vec::new())
+    } else {
+        LeftOut::Constraints
+    };
+    let mut left_out: Vec<(usize, LeftOut)> = no_jobs.into_iter().map(|member| (member, LeftOut::NoJobs))
+        .chain(remaining.into_iter().map(|member| {
+            let reason = match &reason {
+                LeftOut::Roles(_) => LeftOut::Roles(playable_roles(&pool[member], constraints)),
+                reason => reason.clone()
+            };
+            (member, reason)
+        }))
+        .collect();
+    left_out.sort_by_key(|&(member, _)| member);
+
+    let score = parties.iter().map(|party| party.config.score).sum();
+    Event { parties, left_out, score }
+}
+
+/// `members` with `member` in place of the one in `slot`, sorted again.
+fn with_member(members: &[usize], slot: usize, member: usize) -> Vec<usize> {
+    let mut members = members.to_vec();
+    members[slot] = member;
+    members.sort_unstable();
+    members
+}
+
+/// Swaps members between `parties`, and with those `left_over`, while any swap lowers the sum of the scores.
+fn improve_event(
+    pool: &[PlayerCharacter],
+    parties: &mut [SplitParty],
+    left_over: &mut [usize],
+    scoring: &Scoring,
+    constraints: &Constraints
+) {
+    for _ in 0..EVENT_PASSES {
+        let mut improved = false;
+
+        for first in 0..parties.len() {
+            for slot in 0..parties[first].members.len() {
+                let member = parties[first].members[slot];
+                let mut swapped = false;
+                'parties: for second in first + 1..parties.len() {
+                    for other_slot in 0..parties[second].members.len() {
+                        let other = parties[second].members[other_slot];
+                        let a = best_party(pool, with_member(&parties[first].members, slot, other), scoring, constraints);
+                        let b = best_party(pool, with_member(&parties[second].members, other_slot, member), scoring, constraints);
+                        if let (Some(a), Some(b)) = (a, b) {
+                            if a.config.score + b.config.score < parties[first].config.score + parties[second].config.score {
+                                parties[first] = a;
+                                parties[second] = b;
+                                swapped = true;
+                                break 'parties;
+                            }
+                        }
+                    }
+                }
+
+                if !swapped {
+                    for spare in left_over.iter_mut() {
+                        let candidate = best_party(pool, with_member(&parties[first].members, slot, *spare), scoring, constraints);
+                        if let Some(candidate) = candidate.filter(|candidate| candidate.config.score < parties[first].config.score) {
+                            parties[first] = candidate;
+                            *spare = member;
+                            swapped = true;
+                            break;
+                        }
+                    }
+                }
+                improved |= swapped;
+            }
+        }
+
+        if !improved {
+            break;
+        }
+    }
+}
+
+/// Tanks, healers and DPS needed for a party of `size`: one tank and healer per light party of four.
+fn role_slots(size: usize) -> [(Role, usize); 3] {
+    let per_role = (size / 4).max(1);
+    [(Role::Tank, per_role), (Role::Healer, per_role), (Role::Dps, size.saturating_sub(2 * per_role))]
+}
+
+/// Picks up to `count` of the best `size`-member parties from `pool`.
+///
+/// A pool the size of a free company has far too many subsets to try them all, so instead, for every level
+/// someone in the pool has a job at, the members whose jobs sit closest to that level are chosen to fill each
+/// role.
+pub fn select(pool: &[PlayerCharacter], size: usize, scoring: &Scoring, constraints: &Constraints, count: usize) -> Vec<Selection> {
+    let mut targets: Vec<u8> = pool.iter()
+        .flat_map(|character| character.class_jobs.iter().map(|class_job| class_job.level))
+        .filter(|&level| level > 0)
+        .collect();
+    targets.sort_unstable();
+    targets.dedup();
+
+    let mut selections: Vec<Selection> = Vec::new();
+    // Without role requirements, any free slot will do.
+    let requires_roles = constraints.requires_roles();
+
+    for target in targets {
+        // Every job each member could fill a slot with, cheapest (closest to the target) first.
+        let mut candidates: Vec<(u8, usize, usize, Role)> = Vec::new();
+        for (member, character) in pool.iter().enumerate() {
+            for (job_index, class_job) in character.class_jobs.iter().enumerate() {
+                if class_job.level == 0 || !constraints.allows(character, class_job) {
+                    continue;
+                }
+                if let Some(role) = class_job.role() {
+                    candidates.push((class_job.level.abs_diff(target), member, job_index, role));
+                }
+            }
+        }
+        candidates.sort_by_key(|&(cost, member, job_index, _)| (cost, member, job_index));
+
+        let mut slots = role_slots(size);
+        let mut members: Vec<(usize, usize)> = Vec::with_capacity(size);
+        for (_, member, job_index, role) in candidates {
+            if members.iter().any(|&(chosen, _)| chosen == member) {
+                continue;
+            }
+            if let Some((_, free)) = slots.iter_mut().find(|(slot_role, free)| (!requires_roles || *slot_role == role) && *free > 0) {
+                *free -= 1;
+                members.push((member, job_index));
+            }
+        }
+
+        if members.len() < size {
+            continue;
+        }
+        members.sort_unstable();
+
+        if selections.iter().any(|selection| selection.members == members) {
+            continue;
+        }
+
+        selections.extend(evaluate(pool, members, scoring, constraints));
+    }
+
+    sort_selections(&mut selections, count);
+    selections
+}
+
+fn sort_selections(selections: &mut Vec<Selection>, count: usize) {
+    selections.sort_by(|a, b| a.score.total_cmp(&b.score).then(b.avg.cmp(&a.avg)).then_with(|| a.members.cmp(&b.members)));
+    selections.truncate(count);
+}
+
+/// Scores the party of `members`, which must be sorted, if it meets the constraints.
+fn evaluate(pool: &[PlayerCharacter], members: Vec<(usize, usize)>, scoring: &Scoring, constraints: &Constraints) -> Option<Selection> {
+    let size = members.len();
+    let class_jobs: Vec<_> = members.iter().map(|&(member, job_index)| (&pool[member], &pool[member].class_jobs[job_index])).collect();
+    let values: Vec<f64> = class_jobs.iter().map(|(_, class_job)| scoring.balance.value(class_job)).collect();
+    let sum: u32 = class_jobs.iter().map(|(_, class_job)| class_job.level as u32).sum();
+    let bonus_count = class_jobs.iter().filter(|(character, class_job)| character.has_armoury_bonus(class_job)).count() as u8;
+
+    let spread = scoring.metric.spread(&values);
+    let avg = sum / size as u32;
+    let min_level = class_jobs.iter().map(|(_, class_job)| class_job.level).min().unwrap_or_default();
+    let (sub_role_count, shared) = optimizer::sub_roles(class_jobs.iter().map(|(_, class_job)| *class_job));
+    let job_bonus = class_jobs.iter().map(|(character, class_job)| scoring.job_bonus(character, class_job)).sum::<f64>()
+        + scoring.in_need_bonus(&values, class_jobs.iter().map(|(_, class_job)| *class_job));
+    let duplicate_count = optimizer::duplicate_jobs(class_jobs.iter().map(|(_, class_job)| *class_job));
+    let score = scoring.score(spread, sum as f64 / size as f64, bonus_count, sub_role_count, duplicate_count, job_bonus);
+    let score = scoring.complete_score(&class_jobs, spread, sum as f64 / size as f64, bonus_count, score);
+
+    let diverse = !(constraints.diverse_dps && shared);
+    let unique = !(constraints.unique_jobs && duplicate_count > 0);
+    let close = constraints.allows_gap(class_jobs.iter().map(|(_, class_job)| class_job.level));
+    (constraints.allows_average(avg) && diverse && unique && close).then_some(Selection { members, spread, avg, min_level, score })
+}
+
+/// Random numbers from SplitMix64, which is plenty for choosing moves and saves a dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// A number in `0.0..1.0`.
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Whether `members` fill the role slots of a party their size, if the constraints need them to.
+fn fills_roles(pool: &[PlayerCharacter], members: &[(usize, usize)], constraints: &Constraints) -> bool {
+    !constraints.requires_roles() || role_slots(members.len()).iter().all(|&(role, slots)| {
+        members.iter().filter(|&&(member, job_index)| pool[member].class_jobs[job_index].role() == Some(role)).count() == slots
+    })
+}
+
+/// Moves scored before cooling starts, to learn how much a move usually changes the score by.
+const WARM_UP: u64 = 100;
+
+/// How much cooler the search is at the end than at the start.
+const FINAL_COOLING: f64 = 0.001;
+
+/// Picks up to `count` good `size`-member parties from `pool` by simulated annealing, starting from what `select`
+/// finds. Each of `steps` moves either swaps a member for someone else or changes a member's job, and a move that
+/// makes the score worse is still taken now and then, less often as the search cools, to escape local optima.
+pub fn anneal(
+    pool: &[PlayerCharacter],
+    size: usize,
+    scoring: &Scoring,
+    constraints: &Constraints,
+    count: usize,
+    steps: u64,
+    seed: u64
+) -> Vec<Selection> {
+    let mut found = select(pool, size, scoring, constraints, count);
+    let options: Vec<(usize, usize)> = pool.iter()
+        .enumerate()
+        .flat_map(|(member, character)| {
+            character.class_jobs.iter()
+                .enumerate()
+                .filter(move |(_, class_job)| class_job.level > 0 && class_job.role().is_some() && constraints.allows(character, class_job))
+                .map(move |(job_index, _)| (member, job_index))
+        })
+        .collect();
+    let Some(start) = found.first().cloned() else {
+        return found;
+    };
+
+    let mut rng = Rng(seed);
+    let mut current = start;
+    let mut total_change = 0.0;
+    let mut samples = 0;
+
+    for step in 0..steps {
+        let mut members = current.members.clone();
+        let slot = rng.below(size);
+        let (member, job_index) = options[rng.below(options.len())];
+        if members.iter().enumerate().any(|(other, &(chosen, _))| other != slot && chosen == member) {
+            continue;
+        }
+        members[slot] = (member, job_index);
+        members.sort_unstable();
+        if members == current.members || !fills_roles(pool, &members, constraints) {
+            continue;
+        }
+        let Some(candidate) = evaluate(pool, members, scoring, constraints) else {
+            continue;
+        };
+
+        let change = candidate.score - current.score;
+        // Only better moves are taken until there is an idea of how big a move is. From then, the search starts hot
+        // enough to take a typical move about a third of the time, and cools a thousandfold by the end.
+        let temperature = if samples < WARM_UP {
+            total_change += change.abs();
+            samples += 1;
+            0.0
+        } else {
+            total_change / samples as f64 * FINAL_COOLING.powf(step as f64 / steps as f64)
+        };
+        if change > 0.0 && (temperature <= 0.0 || rng.unit() >= (-change / temperature).exp()) {
+            continue;
+        }
+
+        if !found.iter().any(|selection| selection.members == candidate.members) {
+            found.push(candidate.clone());
+            if found.len() > 4 * count {
+                sort_selections(&mut found, count);
+            }
+        }
+        current = candidate;
+    }
+
+    sort_selections(&mut found, count);
+    found
+}
+
+/// A score no `size`-member party from `pool` can beat, to tell how far a heuristic search might be from the best.
+/// It takes every bonus at once, with no spread and the highest average anyone could have, so it is rarely reached.
+/// There is none with a custom score, or weights below zero, which could make scores arbitrarily low.
+pub fn score_bound(pool: &[PlayerCharacter], size: usize, scoring: &Scoring, constraints: &Constraints) -> Option<f64> {
+    if !scoring.can_bound() {
+        return None;
+    }
+
+    // The best level and job bonus each member could bring, on their own.
+    let mut levels = Vec::new();
+    let mut job_bonuses = Vec::new();
+    for character in pool {
+        let allowed = || character.class_jobs.iter()
+            .filter(|class_job| class_job.level > 0 && class_job.role().is_some() && constraints.allows(character, class_job));
+        if let Some(level) = allowed().map(|class_job| class_job.level).max() {
+            levels.push(level as f64);
+            job_bonuses.push(allowed().map(|class_job| scoring.job_bonus(character, class_job)).fold(f64::NEG_INFINITY, f64::max));
+        }
+    }
+    if levels.len() < size {
+        return None;
+    }
+    levels.sort_by(|a, b| b.total_cmp(a));
+    job_bonuses.sort_by(|a, b| b.total_cmp(a));
+
+    let avg = levels[..size].iter().sum::<f64>() / size as f64;
+    let job_bonus = job_bonuses[..size].iter().sum::<f64>() + if scoring.in_need.is_some() { scoring.weight_in_need } else { 0.0 };
+    Some(scoring.score(0.0, avg, size as u8, size as u8, 0, job_bonus))
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::constraints::Composition;
+    use crate::job;
+    use crate::xivapi::ClassJob;
+
+    /// A character entered by hand with each of `jobs`, by abbreviation, at its level.
+    fn character(name: &str, jobs: &[(&str, u8)]) -> PlayerCharacter {
+        let class_jobs = jobs.iter()
+            .map(|&(abbreviation, level)| {
+                let info = job::JOBS.iter().find(|info| info.job_abbreviation == abbreviation).unwrap();
+                ClassJob::manual(info, level)
+            })
+            .collect();
+
+        PlayerCharacter {
+            id: 0,
+            class_jobs,
+            name: name.to_owned(),
+            server: None,
+            avatar: None,
+            portrait: None,
+            bio: None,
+            buffs: Vec::new(),
+            trial: false,
+            expansion: None,
+            play_history: None,
+            role_history: None,
+            nickname: None,
+            alts: Vec::new(),
+            gear_set: None
+        }
+    }
+
+    #[test]
+    fn split_divides_everyone_into_two_parties() {
+        let pool = [
+            character("A", &[("WAR", 50), ("DRG", 52)]),
+            character("B", &[("WHM", 51)]),
+            character("C", &[("PLD", 49), ("BLM", 50)]),
+            character("D", &[("SCH", 50), ("SMN", 50)]),
+            character("E", &[("BRD", 48), ("NIN", 53)])
+        ];
+
+        let splits = split_brute_force(&pool, &Scoring::default(), &Constraints::default(), 100);
+        assert!(!splits.is_empty());
+        assert!(splits.windows(2).all(|pair| pair[0].score <= pair[1].score));
+        for split in &splits {
+            let [first, second] = &split.parties;
+            assert_eq!(first.members[0], 0);
+            assert_eq!(first.members.len() + second.members.len(), pool.len());
+            assert!(first.members.iter().all(|member| !second.members.contains(member)));
+            // Only A and C can tank and only B and D can heal, so each party has one of each.
+            for party in [first, second] {
+                assert_eq!(party.members.iter().filter(|&&member| member == 0 || member == 2).count(), 1);
+                assert_eq!(party.members.iter().filter(|&&member| member == 1 || member == 3).count(), 1);
+            }
+            assert_eq!(split.score, first.config.score + second.config.score);
+        }
+    }
+
+    #[cfg(feature = "ilp")]
+    #[test]
+    fn split_exact_matches_trying_every_split() {
+        let pool = [
+            character("A", &[("WAR", 50), ("WHM", 48), ("DRG", 53)]),
+            character("B", &[("PLD", 51), ("BLM", 47)]),
+            character("C", &[("SCH", 52), ("SMN", 52), ("MNK", 45)]),
+            character("D", &[("AST", 49), ("BRD", 50)]),
+            character("E", &[("GNB", 60), ("DNC", 55)]),
+            character("F", &[("NIN", 50), ("SGE", 44), ("DRK", 58)])
+        ];
+        let scoring = Scoring::default();
+        let constraints = Constraints::default();
+
+        // Asking for every split leaves no ties at the cut-off, so both have to find the same ones in the same order.
+        for count in [1, 3, 100] {
+            let exact = split_exact(&pool, &scoring, &constraints, count).unwrap();
+            let brute_force = split_brute_force(&pool, &scoring, &constraints, count);
+
+            let scores = |splits: &[Split]| splits.iter().map(|split| split.score).collect::<Vec<_>>();
+            assert_eq!(scores(&exact), scores(&brute_force));
+            if count == 100 {
+                let members = |splits: &[Split]| {
+                    splits.iter().map(|split| split.parties.clone().map(|party| party.members)).collect::<Vec<_>>()
+                };
+                assert!(exact.len() > 3);
+                assert_eq!(members(&exact), members(&brute_force));
+            }
+        }
+    }
+
+    /// The most parties `roles` can fill, found by trying every member in every role they play, or none.
+    fn max_parties_enumerated(roles: &[Vec<Role>], tanks: usize, healers: usize, dps: usize) -> usize {
+        match roles.split_first() {
+            None => tanks.min(healers).min(dps / 2),
+            Some((played, rest)) => {
+                let left_out = max_parties_enumerated(rest, tanks, healers, dps);
+                played.iter()
+                    .map(|role| match role {
+                        Role::Tank => max_parties_enumerated(rest, tanks + 1, healers, dps),
+                        Role::Healer => max_parties_enumerated(rest, tanks, healers + 1, dps),
+                        Role::Dps => max_parties_enumerated(rest, tanks, healers, dps + 1)
+                    })
+                    .fold(left_out, usize::max)
+            }
+        }
+    }
+
+    #[test]
+    fn max_parties_of_a_known_pool() {
+        // Only two of them can heal, so only two parties can form however many tanks and DPS there are.
+        let pool = [
+            character("A", &[("WAR", 50)]),
+            character("B", &[("PLD", 50), ("WHM", 50)]),
+            character("C", &[("GNB", 50), ("DRG", 50)]),
+            character("D", &[("SCH", 50)]),
+            character("E", &[("BLM", 50)]),
+            character("F", &[("BRD", 50)]),
+            character("G", &[("NIN", 50)]),
+            character("H", &[("DRK", 50), ("MNK", 50)]),
+            character("I", &[("SAM", 50)]),
+            character("J", &[("CRP", 50)]),
+            character("K", &[("RPR", 50)]),
+            character("L", &[("DNC", 50)])
+        ];
+        let members: Vec<usize> = (0..pool.len()).collect();
+        assert_eq!(max_parties(&pool, &members, &Constraints::default()), 2);
+        assert_eq!(max_parties(&pool, &members[..8], &Constraints::default()), 2);
+        assert_eq!(max_parties(&pool, &members[4..], &Constraints::default()), 0);
+
+        let any = Constraints { composition: Composition::Any, ..Constraints::default() };
+        assert_eq!(max_parties(&pool, &members, &any), 3);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn max_parties_matches_every_assignment(played in prop::collection::vec(0..8u8, 0..=10)) {
+            // Each member plays a tank, healer and DPS job by the bits of their entry, so some play nothing at all.
+            let pool: Vec<PlayerCharacter> = played.iter()
+                .map(|&bits| {
+                    let jobs: Vec<(&str, u8)> = [("WAR", 50), ("WHM", 50), ("DRG", 50)].iter()
+                        .enumerate()
+                        .filter(|&(bit, _)| bits & (1 << bit) != 0)
+                        .map(|(_, &job)| job)
+                        .collect();
+                    character("Member", &jobs)
+                })
+                .collect();
+            let members: Vec<usize> = (0..pool.len()).collect();
+            let constraints = Constraints::default();
+
+            let roles: Vec<Vec<Role>> = pool.iter().map(|character| playable_roles(character, &constraints)).collect();
+            prop_assert_eq!(max_parties(&pool, &members, &constraints), max_parties_enumerated(&roles, 0, 0, 0));
+        }
+    }
+}
@@ -0,0 +1,403 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::io::Cursor;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use xiv_levelling::cache::Cache;
+use xiv_levelling::constraints::Constraints;
+use xiv_levelling::job::{self, Mode, Role};
+use xiv_levelling::language::Language;
+use xiv_levelling::metrics::Histogram;
+use xiv_levelling::optimizer::{self, PartyConfig, Scoring};
+use xiv_levelling::provider::CharacterProvider;
+use xiv_levelling::xivapi::{self, ClassJob, PlayerCharacter, XivApi};
+
+#[cfg(feature = "graphql")]
+use crate::graphql;
+use crate::verify::{self, Registry};
+
+pub const DEFAULT_TOP: usize = 5;
+pub const MAX_TOP: usize = 100;
+
+/// What the server has handled since it started, for `/metrics`.
+#[derive(Default)]
+pub struct Metrics {
+    /// How many requests got each status code, by path.
+    requests: Mutex<BTreeMap<(&'static str, u16), u64>>,
+    /// How long ranking configurations took, without looking up the characters.
+    optimize: Histogram
+}
+
+/// Everything requests are handled with, for as long as the server runs.
+struct State {
+    api: Arc<XivApi>,
+    registry: Mutex<Registry>,
+    metrics: Arc<Metrics>,
+    #[cfg(feature = "graphql")]
+    schema: graphql::Schema
+}
+
+/// A member given by their job levels rather than looked up on the Lodestone.
+#[derive(Deserialize)]
+pub struct ManualMember {
+    pub name: String,
+    /// Level of each job, by name or abbreviation, e.g. `{"Warrior": 73, "WHM": 16}`.
+    pub jobs: BTreeMap<String, u8>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OptimizeRequest {
+    server: Option<String>,
+    #[serde(default)]
+    characters: Vec<String>,
+    #[serde(default)]
+    members: Vec<ManualMember>,
+    top: Option<usize>
+}
+
+#[derive(Serialize)]
+pub struct MemberResponse {
+    pub name: String,
+    pub job: String,
+    pub level: u8,
+    pub role: Option<Role>,
+    pub armoury_bonus: bool,
+    pub avatar: Option<String>,
+    pub portrait: Option<String>
+}
+
+#[derive(Serialize)]
+pub struct ConfigurationResponse {
+    pub rank: usize,
+    pub spread: f64,
+    pub avg: u32,
+    pub min_level: u8,
+    pub members: Vec<MemberResponse>
+}
+
+#[derive(Serialize)]
+struct OptimizeResponse {
+    configurations: Vec<ConfigurationResponse>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VerifyStartRequest {
+    /// Whoever is registering the character, as the caller identifies them.
+    user: String,
+    server: String,
+    name: String
+}
+
+#[derive(Serialize)]
+struct VerifyStartResponse {
+    character_id: u32,
+    /// What the user needs to put in the character's Lodestone bio.
+    token: String
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VerifyFinishRequest {
+    user: String
+}
+
+#[derive(Serialize)]
+struct VerifyFinishResponse {
+    character_id: u32,
+    name: String
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String
+}
+
+fn manual_character(id: u32, member: &ManualMember) -> Result<PlayerCharacter, String> {
+    let mut class_jobs = Vec::with_capacity(member.jobs.len());
+    for (name, &level) in &member.jobs {
+        let infos: Vec<_> = job::JOBS.iter().filter(|info| info.matches(name)).collect();
+        if infos.is_empty() {
+            return Err(format!("Unknown job {} for {}!", name, member.name));
+        }
+
+        class_jobs.extend(infos.into_iter().map(|info| ClassJob::manual(info, level)));
+    }
+    xivapi::merge_class_jobs(&mut class_jobs);
+
+    Ok(PlayerCharacter {
+        id,
+        class_jobs,
+        name: member.name.clone(),
+        server: None,
+        avatar: None,
+        portrait: None,
+        bio: None,
+        buffs: Vec::new(),
+        trial: false,
+        expansion: None,
+        play_history: None,
+        role_history: None,
+        nickname: None,
+        alts: Vec::new(),
+        gear_set: None
+    })
+}
+
+fn lookup_character(api: &dyn CharacterProvider, name: &str, server: &str) -> Result<PlayerCharacter, String> {
+    let player_search = api.search_character(name, server).map_err(|e| e.to_string())?;
+
+    match player_search.pagination.results {
+        1 => {
+            let mut character = api.character(player_search.results[0].id).map_err(|e| e.to_string())?;
+            character.retain_mode(Mode::Combat);
+            Ok(character)
+        },
+        0 => Err(format!("No character named {} was found on {}!", name, server)),
+        _ => Err(format!("Multiple characters named {} were found on {}!", name, server))
+    }
+}
+
+/// The characters named `characters` on `server`, followed by `members`.
+pub fn party(
+    api: &dyn CharacterProvider,
+    server: Option<&str>,
+    characters: &[String],
+    members: &[ManualMember]
+) -> Result<Vec<PlayerCharacter>, String> {
+    let mut party: Vec<PlayerCharacter> = Vec::new();
+    if !characters.is_empty() {
+        let server = server.ok_or("A server is needed to look up characters!")?;
+        for name in characters {
+            let character = lookup_character(api, name, server)?;
+            if party.iter().any(|member| member.is_same_character(&character)) {
+                return Err(format!("{} was entered more than once!", character.name));
+            }
+            party.push(character);
+        }
+    }
+    for (i, member) in members.iter().enumerate() {
+        party.push(manual_character(i as u32, member)?);
+    }
+
+    if party.len() < 2 || party.len() > 4 {
+        return Err("Party must consist of between two and four characters!".to_owned());
+    }
+
+    Ok(party)
+}
+
+/// The best `limit` configurations of `party`, timed for `/metrics`.
+pub fn rank(metrics: &Metrics, party: &[PlayerCharacter], constraints: &Constraints, limit: usize) -> Vec<PartyConfig> {
+    let started = Instant::now();
+    let party_configs = optimizer::rank_top(party, &Scoring::default(), constraints, limit);
+    metrics.optimize.observe(started.elapsed());
+    party_configs
+}
+
+/// What to respond with for `party_configs`, numbered on from `first_rank`.
+pub fn configurations(
+    party: &[PlayerCharacter],
+    party_configs: &[PartyConfig],
+    first_rank: usize
+) -> Vec<ConfigurationResponse> {
+    party_configs.iter().enumerate()
+        .map(|(rank, party_config)| ConfigurationResponse {
+            rank: first_rank + rank,
+            spread: party_config.spread,
+            avg: party_config.avg,
+            min_level: party_config.min_level,
+            members: party.iter().zip(&party_config.index)
+                .map(|(character, &job_index)| {
+                    let class_job = &character.class_jobs[job_index];
+                    MemberResponse {
+                        name: character.name.clone(),
+                        job: class_job.display_name().to_owned(),
+                        level: class_job.level,
+                        role: class_job.role(),
+                        armoury_bonus: character.has_armoury_bonus(class_job),
+                        avatar: character.avatar.clone(),
+                        portrait: character.portrait.clone()
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn optimize(api: &dyn CharacterProvider, metrics: &Metrics, body: &str) -> Result<OptimizeResponse, String> {
+    let request: OptimizeRequest = serde_json::from_str(body).map_err(|e| format!("Invalid request: {}", e))?;
+    let party = party(api, request.server.as_deref(), &request.characters, &request.members)?;
+
+    let constraints = Constraints {
+        dedup: true,
+        ..Constraints::default()
+    };
+    let top = request.top.unwrap_or(DEFAULT_TOP).min(MAX_TOP);
+    let party_configs = rank(metrics, &party, &constraints, top);
+
+    Ok(OptimizeResponse { configurations: configurations(&party, &party_configs, 1) })
+}
+
+fn verify_start(api: &dyn CharacterProvider, registry: &Mutex<Registry>, body: &str) -> Result<VerifyStartResponse, String> {
+    let request: VerifyStartRequest = serde_json::from_str(body).map_err(|e| format!("Invalid request: {}", e))?;
+    let (character_id, token) = verify::begin(api, registry, &request.user, &request.name, &request.server)?;
+    Ok(VerifyStartResponse { character_id, token })
+}
+
+fn verify_finish(api: &dyn CharacterProvider, registry: &Mutex<Registry>, body: &str) -> Result<VerifyFinishResponse, String> {
+    let request: VerifyFinishRequest = serde_json::from_str(body).map_err(|e| format!("Invalid request: {}", e))?;
+    let character = verify::finish(api, registry, &request.user)?;
+    Ok(VerifyFinishResponse { character_id: character.id, name: character.name })
+}
+
+/// Responds with whatever `handler` makes of the request's body, or a 400 if either fails.
+fn post<T: Serialize>(request: &mut Request, handler: impl FnOnce(&str) -> Result<T, String>) -> Response<Cursor<Vec<u8>>> {
+    let mut body = String::new();
+    match request.as_reader().read_to_string(&mut body) {
+        Ok(_) => match handler(&body) {
+            Ok(value) => json_response(200, &value),
+            Err(error) => json_response(400, &ErrorResponse { error })
+        },
+        Err(e) => json_response(400, &ErrorResponse { error: e.to_string() })
+    }
+}
+
+fn json_response<T: Serialize>(status: u16, value: &T) -> Response<Cursor<Vec<u8>>> {
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    Response::from_data(serde_json::to_vec(value).unwrap()).with_status_code(status).with_header(header)
+}
+
+/// Appends `histogram` to `text` in the Prometheus text format.
+fn write_histogram(text: &mut String, name: &str, help: &str, histogram: &Histogram) {
+    writeln!(text, "# HELP {} {}", name, help).unwrap();
+    writeln!(text, "# TYPE {} histogram", name).unwrap();
+    for (bound, count) in histogram.cumulative() {
+        writeln!(text, "{}_bucket{{le=\"{}\"}} {}", name, bound, count).unwrap();
+    }
+    writeln!(text, "{}_bucket{{le=\"+Inf\"}} {}", name, histogram.count()).unwrap();
+    writeln!(text, "{}_sum {}", name, histogram.total().as_secs_f64()).unwrap();
+    writeln!(text, "{}_count {}", name, histogram.count()).unwrap();
+}
+
+/// Everything in `metrics` and what `api` has requested, in the Prometheus text format.
+fn metrics_response(api: &XivApi, metrics: &Metrics) -> Response<Cursor<Vec<u8>>> {
+    let mut text = String::new();
+
+    writeln!(text, "# HELP xiv_levelling_requests_total Requests handled, by path and status code.").unwrap();
+    writeln!(text, "# TYPE xiv_levelling_requests_total counter").unwrap();
+    for ((path, status), count) in metrics.requests.lock().unwrap().iter() {
+        writeln!(text, "xiv_levelling_requests_total{{path=\"{}\",status=\"{}\"}} {}", path, status, count).unwrap();
+    }
+
+    let api_metrics = api.metrics();
+    writeln!(text, "# HELP xiv_levelling_xivapi_cache_hits_total XIVAPI responses served from the cache.").unwrap();
+    writeln!(text, "# TYPE xiv_levelling_xivapi_cache_hits_total counter").unwrap();
+    writeln!(text, "xiv_levelling_xivapi_cache_hits_total {}", api_metrics.cache_hits.load(Ordering::Relaxed)).unwrap();
+    writeln!(text, "# HELP xiv_levelling_xivapi_cache_misses_total XIVAPI responses that weren't cached or were too old.").unwrap();
+    writeln!(text, "# TYPE xiv_levelling_xivapi_cache_misses_total counter").unwrap();
+    writeln!(text, "xiv_levelling_xivapi_cache_misses_total {}", api_metrics.cache_misses.load(Ordering::Relaxed)).unwrap();
+    write_histogram(
+        &mut text,
+        "xiv_levelling_xivapi_request_duration_seconds",
+        "How long requests to XIVAPI took.",
+        &api_metrics.latency
+    );
+    write_histogram(
+        &mut text,
+        "xiv_levelling_optimize_duration_seconds",
+        "How long ranking configurations took.",
+        &metrics.optimize
+    );
+
+    let header = Header::from_bytes("Content-Type", "text/plain; version=0.0.4").unwrap();
+    Response::from_string(text).with_header(header)
+}
+
+/// Runs the GraphQL query in the request's body. Errors resolving it are in the response, with a 200.
+#[cfg(feature = "graphql")]
+fn graphql(state: &State, request: &mut Request) -> Response<Cursor<Vec<u8>>> {
+    let mut body = String::new();
+    if let Err(e) = request.as_reader().read_to_string(&mut body) {
+        return json_response(400, &ErrorResponse { error: e.to_string() });
+    }
+
+    match serde_json::from_str::<juniper::http::GraphQLRequest>(&body) {
+        Ok(query) => {
+            let context = graphql::Context { api: state.api.clone(), metrics: state.metrics.clone() };
+            let response = query.execute_sync(&state.schema, &context);
+            json_response(if response.is_ok() { 200 } else { 400 }, &response)
+        },
+        Err(e) => json_response(400, &ErrorResponse { error: format!("Invalid request: {}", e) })
+    }
+}
+
+/// GraphiQL, for trying out queries in a browser.
+#[cfg(feature = "graphql")]
+fn graphiql() -> Response<Cursor<Vec<u8>>> {
+    let header = Header::from_bytes("Content-Type", "text/html; charset=utf-8").unwrap();
+    Response::from_string(juniper::http::graphiql::graphiql_source("/graphql", None)).with_header(header)
+}
+
+fn handle(state: &State, mut request: Request) {
+    let api: &XivApi = &state.api;
+    let registry = &state.registry;
+    let metrics: &Metrics = &state.metrics;
+    // Anything else is counted together, so that made up paths can't add to the metrics forever.
+    let path = match request.url() {
+        "/party/optimize" => "/party/optimize",
+        "/verify/start" => "/verify/start",
+        "/verify/finish" => "/verify/finish",
+        "/metrics" => "/metrics",
+        "/graphql" => "/graphql",
+        _ => "other"
+    };
+
+    let response = match (request.method(), request.url()) {
+        (Method::Get, "/metrics") => metrics_response(api, metrics),
+        #[cfg(feature = "graphql")]
+        (Method::Get, "/graphql") => graphiql(),
+        #[cfg(feature = "graphql")]
+        (Method::Post, "/graphql") => graphql(state, &mut request),
+        (Method::Post, "/party/optimize") => post(&mut request, |body| optimize(api, metrics, body)),
+        (Method::Post, "/verify/start") => post(&mut request, |body| verify_start(api, registry, body)),
+        (Method::Post, "/verify/finish") => post(&mut request, |body| verify_finish(api, registry, body)),
+        (_, "/metrics") => json_response(405, &ErrorResponse { error: "Use GET for /metrics!".to_owned() }),
+        (_, url @ ("/party/optimize" | "/verify/start" | "/verify/finish")) => {
+            json_response(405, &ErrorResponse { error: format!("Use POST for {}!", url) })
+        },
+        _ => json_response(404, &ErrorResponse { error: "Not found!".to_owned() })
+    };
+    *metrics.requests.lock().unwrap().entry((path, response.status_code().0)).or_insert(0) += 1;
+
+    if let Err(e) = request.respond(response) {
+        eprintln!("Failed to respond to a request: {}", e);
+    }
+}
+
+pub fn run(port: u16, cache: Cache, client: reqwest::blocking::Client) {
+    let server = match Server::http(("0.0.0.0", port)) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Failed to listen on port {}: {}", port, e);
+            return;
+        }
+    };
+    let state = State {
+        api: Arc::new(XivApi::new(cache, Language::default(), client)),
+        registry: Mutex::new(Registry::load_default()),
+        metrics: Arc::new(Metrics::default()),
+        #[cfg(feature = "graphql")]
+        schema: graphql::schema()
+    };
+
+    println!("Listening on port {}!", port);
+    for request in server.incoming_requests() {
+        handle(&state, request);
+    }
+}
@@ -0,0 +1,57 @@
+use std::thread::{self, JoinHandle};
+
+use xiv_levelling::error::Result;
+use xiv_levelling::xivapi::ServerList;
+
+use crate::output;
+
+/// The servers worlds are checked against, fetched in the background so nobody waits on the list before they can
+/// type. It's only waited for once a world has to be checked, and if it can't be fetched any world is accepted.
+pub struct Servers {
+    /// The list fetched last, however long ago, until the one being fetched arrives.
+    list: Option<ServerList>,
+    fetching: Option<JoinHandle<Result<ServerList>>>
+}
+
+impl Servers {
+    /// Starts fetching the list with `fetch`, going by `cached` until then.
+    pub fn fetch(cached: Option<ServerList>, fetch: impl FnOnce() -> Result<ServerList> + Send + 'static) -> Servers {
+        Servers { list: cached, fetching: Some(thread::spawn(fetch)) }
+    }
+
+    /// The freshest list there is, without waiting for the one being fetched.
+    pub fn ready(&mut self) -> Option<&ServerList> {
+        if self.fetching.as_ref().is_some_and(JoinHandle::is_finished) {
+            self.wait();
+        }
+        self.list.as_ref()
+    }
+
+    /// Waits for the list being fetched, keeping the one there was if that fails.
+    fn wait(&mut self) {
+        let Some(fetching) = self.fetching.take() else {
+            return;
+        };
+        match fetching.join() {
+            Ok(Ok(list)) => self.list = Some(list),
+            Ok(Err(e)) if self.list.is_none() => {
+                status!("Failed to get the list of servers, so any world is accepted: {}", e);
+            },
+            _ => {}
+        }
+    }
+
+    /// Whether `name` is a server, or might be since there's no list to say. A server missing from an old list may
+    /// have opened since, so only the new one can say it isn't.
+    pub fn exists(&mut self, name: &str) -> bool {
+        if name.is_empty() {
+            return false;
+        }
+        if self.ready().is_some_and(|list| list.exists(name)) {
+            return true;
+        }
+
+        self.wait();
+        self.list.as_ref().is_none_or(|list| list.exists(name))
+    }
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use xiv_levelling::constraints::Constraints;
+use xiv_levelling::Result;
+
+/// Everything entered before the search, so it can be picked up again with `--resume`.
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Session {
+    /// The server characters without a world were searched on. Empty for saved characters.
+    pub server: String,
+    /// IDs of the members of the party, in the order they were entered. Members entered by hand aren't kept.
+    pub characters: Vec<u32>,
+    /// IDs of the alts pooled into each member, by the member's ID.
+    #[serde(default)]
+    pub alts: BTreeMap<u32, Vec<u32>>,
+    pub constraints: Constraints,
+    /// The job ID each member played in the best configurations the last search of the party found, best first, so
+    /// the next search of it can start from them.
+    #[serde(default)]
+    pub best: Vec<Vec<u8>>
+}
+
+impl Session {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("xiv-levelling").join("session.json"))
+    }
+
+    pub fn load(path: &Path) -> Result<Session> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // Writing to a temporary file first means a crash mid-write never leaves a truncated session.
+        let partial = path.with_extension("partial");
+        fs::write(&partial, serde_json::to_string(self)?)?;
+        fs::rename(partial, path)?;
+        Ok(())
+    }
+}
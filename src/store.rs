@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use xiv_levelling::xivapi::{ClassJob, PlayerCharacter};
+use xiv_levelling::Result;
+
+/// What was known about a character the last time they were looked up.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct StoredCharacter {
+    pub id: u32,
+    pub name: String,
+    /// The world they were found on. Empty if it wasn't known.
+    pub world: String,
+    /// Level of each job, by its abbreviation. Jobs left out of a lookup, e.g. by `--mode`, keep their last level.
+    pub levels: BTreeMap<String, u8>,
+    /// Seconds since the Unix epoch.
+    pub fetched_at: u64
+}
+
+impl StoredCharacter {
+    /// The jobs of `character` whose level has changed since this was stored, with the level they were at then.
+    pub fn level_changes<'a>(&self, character: &'a PlayerCharacter) -> Vec<(&'a ClassJob, u8)> {
+        character.class_jobs.iter()
+            .filter_map(|class_job| {
+                let &level = self.levels.get(class_job.job.job_abbreviation)?;
+                (level != class_job.level).then_some((class_job, level))
+            })
+            .collect()
+    }
+}
+
+/// Every character the tool has looked up, by Lodestone ID, so they can be found again without searching.
+#[derive(Deserialize, Serialize, Default, Debug)]
+pub struct CharacterStore {
+    characters: BTreeMap<u32, StoredCharacter>
+}
+
+impl CharacterStore {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("xiv-levelling").join("characters.json"))
+    }
+
+    /// Loads the store at `path`, treating a missing file as an empty store.
+    pub fn load(path: &Path) -> Result<CharacterStore> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(CharacterStore::default()),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // Writing to a temporary file first means a crash mid-write never leaves a truncated store.
+        let partial = path.with_extension("partial");
+        fs::write(&partial, serde_json::to_string(self)?)?;
+        fs::rename(partial, path)?;
+        Ok(())
+    }
+
+    /// The character called exactly `name` on `world`, ignoring case. Only full names match, since a first name
+    /// alone could belong to somebody else the search would find.
+    pub fn find(&self, name: &str, world: &str) -> Option<&StoredCharacter> {
+        self.characters.values()
+            .find(|stored| stored.name.eq_ignore_ascii_case(name) && stored.world.eq_ignore_ascii_case(world))
+    }
+
+    /// Every stored character, by ID.
+    pub fn characters(&self) -> impl Iterator<Item = &StoredCharacter> {
+        self.characters.values()
+    }
+
+    /// Forgets every stored character `forget` picks, returning them.
+    pub fn forget(&mut self, forget: impl Fn(&StoredCharacter) -> bool) -> Vec<StoredCharacter> {
+        let ids: Vec<u32> = self.characters.values().filter(|stored| forget(stored)).map(|stored| stored.id).collect();
+        ids.iter().filter_map(|id| self.characters.remove(id)).collect()
+    }
+
+    /// Names of every stored character.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.characters.values().map(|stored| stored.name.as_str())
+    }
+
+    /// Stores `character`'s levels as they are now, returning what was stored for them before.
+    pub fn record(&mut self, character: &PlayerCharacter, world: &str) -> Option<StoredCharacter> {
+        let fetched_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let previous = self.characters.get(&character.id).cloned();
+        let mut stored = previous.clone().unwrap_or_else(|| StoredCharacter {
+            id: character.id,
+            name: character.name.clone(),
+            world: world.to_owned(),
+            levels: BTreeMap::new(),
+            fetched_at
+        });
+
+        stored.name = character.name.clone();
+        if !world.is_empty() {
+            stored.world = world.to_owned();
+        }
+        stored.levels.extend(character.class_jobs.iter().map(|class_job| (class_job.job.job_abbreviation.to_owned(), class_job.level)));
+        stored.fetched_at = fetched_at;
+        self.characters.insert(character.id, stored);
+
+        previous
+    }
+}
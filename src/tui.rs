@@ -0,0 +1,351 @@
+use arboard::Clipboard;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashSet;
+use std::io;
+
+use xiv_levelling::constraints::Constraints;
+use xiv_levelling::job::Role;
+use xiv_levelling::optimizer::{self, PartyConfig, Scoring};
+use xiv_levelling::provider::CharacterProvider;
+use xiv_levelling::xivapi::PlayerCharacter;
+
+use crate::output;
+
+/// A rule about the role one member plays, set while browsing to narrow down the configurations shown.
+#[derive(Copy, Clone, PartialEq, Eq)]
+struct RoleRule {
+    /// Index into the party of the member.
+    member: usize,
+    role: Role,
+    /// Whether the member must play the role, rather than must not.
+    must: bool
+}
+
+impl RoleRule {
+    fn allows(&self, party: &[PlayerCharacter], config: &PartyConfig) -> bool {
+        let role = party[self.member].class_jobs[config.index[self.member]].role();
+        (role == Some(self.role)) == self.must
+    }
+
+    /// e.g. "must tank" or "must not heal".
+    fn describe(&self) -> String {
+        let verb = match self.role {
+            Role::Tank => "tank",
+            Role::Healer => "heal",
+            Role::Dps => "play DPS"
+        };
+        format!("{} {}", if self.must { "must" } else { "must not" }, verb)
+    }
+}
+
+struct App<'a> {
+    provider: &'a dyn CharacterProvider,
+    scoring: Scoring,
+    constraints: Constraints,
+    party: Vec<PlayerCharacter>,
+    max_results: usize,
+    configs: Vec<PartyConfig>,
+    excluded: HashSet<[usize; 4]>,
+    visible: Vec<usize>,
+    /// The member the role keys set rules for.
+    member: usize,
+    role_rules: Vec<RoleRule>,
+    list_state: ListState,
+    filter: String,
+    editing_filter: bool,
+    message: Option<String>,
+    /// Opened on first copy and kept open, as on some platforms what was copied is lost once it closes.
+    clipboard: Option<Clipboard>
+}
+
+impl<'a> App<'a> {
+    fn new(provider: &'a dyn CharacterProvider, scoring: Scoring, constraints: Constraints, party: Vec<PlayerCharacter>, max_results: usize) -> Self {
+        let mut app = App {
+            provider,
+            scoring,
+            constraints,
+            party,
+            max_results,
+            configs: Vec::new(),
+            excluded: HashSet::new(),
+            visible: Vec::new(),
+            member: 0,
+            role_rules: Vec::new(),
+            list_state: ListState::default(),
+            filter: String::new(),
+            editing_filter: false,
+            message: None,
+            clipboard: None
+        };
+
+        app.recompute();
+        app
+    }
+
+    fn recompute(&mut self) {
+        self.configs = optimizer::rank_top(&self.party, &self.scoring, &self.constraints, self.max_results);
+        self.update_visible();
+    }
+
+    fn matches_filter(&self, config: &PartyConfig) -> bool {
+        let filter = self.filter.to_lowercase();
+
+        self.party.iter().zip(&config.index).any(|(character, &job_index)| {
+            character.name.to_lowercase().contains(&filter)
+                || character.class_jobs[job_index].display_name().to_lowercase().contains(&filter)
+        })
+    }
+
+    fn update_visible(&mut self) {
+        self.visible = (0..self.configs.len())
+            .filter(|&i| {
+                let config = &self.configs[i];
+                !self.excluded.contains(&config.index)
+                    && self.matches_filter(config)
+                    && self.role_rules.iter().all(|rule| rule.allows(&self.party, config))
+            })
+            .collect();
+
+        let selected = match self.list_state.selected() {
+            _ if self.visible.is_empty() => None,
+            Some(selected) => Some(selected.min(self.visible.len() - 1)),
+            None => Some(0)
+        };
+        self.list_state.select(selected);
+    }
+
+    fn exclude_selected(&mut self) {
+        if let Some(&config) = self.list_state.selected().and_then(|selected| self.visible.get(selected)) {
+            self.excluded.insert(self.configs[config].index);
+            self.update_visible();
+        }
+    }
+
+    fn select_member(&mut self, member: usize) {
+        if let Some(character) = self.party.get(member) {
+            self.member = member;
+            self.message = Some(format!("Role keys now set rules for {}.", character.display_name()));
+        }
+    }
+
+    /// Cycles the selected member's rule for `role` from none, to must play it, to must not, and back to none.
+    /// Only one role can be a must, so making one a must drops any other.
+    fn toggle_role(&mut self, role: Role) {
+        let member = self.member;
+        let existing = self.role_rules.iter().position(|rule| rule.member == member && rule.role == role);
+        let name = self.party[member].display_name();
+
+        self.message = Some(match existing {
+            Some(index) if self.role_rules[index].must => {
+                self.role_rules[index].must = false;
+                format!("{} {}.", name, self.role_rules[index].describe())
+            },
+            Some(index) => {
+                self.role_rules.remove(index);
+                format!("{} can play any role again.", name)
+            },
+            None => {
+                self.role_rules.retain(|rule| rule.member != member || !rule.must);
+                let rule = RoleRule { member, role, must: true };
+                self.role_rules.push(rule);
+                format!("{} {}.", name, rule.describe())
+            }
+        });
+        self.update_visible();
+    }
+
+    /// Copies the selected configuration to the clipboard, either as plain text or as an in-game macro announcing it
+    /// in party chat.
+    fn copy_selected(&mut self, as_macro: bool) {
+        let Some(&config) = self.list_state.selected().and_then(|selected| self.visible.get(selected)) else {
+            return;
+        };
+
+        let party_config = &self.configs[config];
+        let lines = if as_macro {
+            output::party_macro(&self.party, party_config, &self.scoring)
+        } else {
+            let mut lines = vec![format!("{}: {}, Lv Avg: {}", self.scoring.spread_label(), self.scoring.metric.format(party_config.spread), party_config.avg)];
+            for (character, &job_index) in self.party.iter().zip(&party_config.index) {
+                let class_job = &character.class_jobs[job_index];
+                lines.push(format!("{}: {} Lv {}", character.display_name(), class_job.display_name(), class_job.level));
+            }
+            lines
+        };
+
+        let copied = if as_macro { "Copied the configuration as a macro." } else { "Copied the configuration." };
+        self.copy(lines.join("\n"), copied);
+    }
+
+    /// Copies a Party Finder description recruiting for the selected configuration to the clipboard.
+    fn copy_party_finder(&mut self) {
+        let Some(&config) = self.list_state.selected().and_then(|selected| self.visible.get(selected)) else {
+            return;
+        };
+
+        let description = output::party_finder(&self.party, &self.configs[config]);
+        self.copy(description, "Copied a Party Finder description.");
+    }
+
+    /// Puts `text` on the clipboard, showing `copied` if it worked.
+    fn copy(&mut self, text: String, copied: &str) {
+        let clipboard = match self.clipboard.take().map_or_else(Clipboard::new, Ok) {
+            Ok(clipboard) => self.clipboard.insert(clipboard),
+            Err(e) => {
+                self.message = Some(format!("Failed to open the clipboard: {}", e));
+                return;
+            }
+        };
+
+        self.message = Some(match clipboard.set_text(text) {
+            Ok(()) => copied.to_owned(),
+            Err(e) => format!("Failed to copy the configuration: {}", e)
+        });
+    }
+
+    fn refresh(&mut self) {
+        let mut refreshed = Vec::new();
+
+        for character in &self.party {
+            match self.provider.refresh_character(character.id) {
+                Ok(mut refreshed_character) => {
+                    refreshed_character.retain_mode(self.constraints.mode);
+                    refreshed_character.buffs = character.buffs.clone();
+                    refreshed_character.trial = character.trial;
+                    refreshed_character.expansion = character.expansion;
+                    refreshed_character.play_history = character.play_history.clone();
+                    refreshed_character.role_history = character.role_history.clone();
+                    refreshed_character.nickname = character.nickname.clone();
+                    refreshed_character.server = refreshed_character.server.or_else(|| character.server.clone());
+                    refreshed.push(refreshed_character);
+                },
+                Err(e) => {
+                    self.message = Some(format!("Failed to refresh {}: {}", character.display_name(), e));
+                    return;
+                }
+            }
+        }
+
+        self.party = refreshed;
+        self.recompute();
+        self.message = Some("Refreshed party data from the Lodestone.".to_owned());
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if self.editing_filter {
+                match key.code {
+                    KeyCode::Enter => self.editing_filter = false,
+                    KeyCode::Esc => {
+                        self.filter.clear();
+                        self.editing_filter = false;
+                    },
+                    KeyCode::Backspace => {
+                        self.filter.pop();
+                    },
+                    KeyCode::Char(c) => self.filter.push(c),
+                    _ => {}
+                }
+
+                self.update_visible();
+                continue;
+            }
+
+            self.message = None;
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => self.list_state.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => self.list_state.select_previous(),
+                KeyCode::Char('/') => self.editing_filter = true,
+                KeyCode::Char('x') => self.exclude_selected(),
+                KeyCode::Char('r') => self.refresh(),
+                KeyCode::Char('c') => self.copy_selected(false),
+                KeyCode::Char('C') => self.copy_selected(true),
+                KeyCode::Char('p') => self.copy_party_finder(),
+                KeyCode::Char(c @ '1'..='4') => self.select_member(c as usize - '1' as usize),
+                KeyCode::Char('t') => self.toggle_role(Role::Tank),
+                KeyCode::Char('h') => self.toggle_role(Role::Healer),
+                KeyCode::Char('d') => self.toggle_role(Role::Dps),
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] = Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(main);
+
+        let mut party_lines = Vec::new();
+        for (member, character) in self.party.iter().enumerate() {
+            let mut heading = format!("{}. {}", member + 1, character.display_name());
+            for rule in self.role_rules.iter().filter(|rule| rule.member == member) {
+                heading.push_str(&format!(" ({})", rule.describe()));
+            }
+            let heading = Line::from(heading).bold();
+            party_lines.push(if member == self.member { heading.reversed() } else { heading });
+            for class_job in character.class_jobs.iter().filter(|class_job| class_job.level > 0) {
+                party_lines.push(Line::from(format!("  {0: <15} Lv {1}", class_job.display_name(), class_job.level)));
+            }
+        }
+        frame.render_widget(Paragraph::new(party_lines).block(Block::bordered().title("Party")), left);
+
+        let items: Vec<ListItem> = self.visible.iter().enumerate()
+            .map(|(rank, &config)| {
+                let party_config = &self.configs[config];
+
+                let mut lines = vec![
+                    Line::from(format!("#{} - {}: {}, Lv Avg: {}", rank + 1, self.scoring.spread_label(), self.scoring.metric.format(party_config.spread), party_config.avg)).bold()
+                ];
+                for (character, &job_index) in self.party.iter().zip(&party_config.index) {
+                    let class_job = &character.class_jobs[job_index];
+                    lines.push(Line::from(format!("  {0: <20}: {1: <15} Lv {2}", character.display_name(), class_job.display_name(), class_job.level)));
+                }
+
+                ListItem::new(Text::from(lines))
+            })
+            .collect();
+
+        let configs = List::new(items)
+            .block(Block::bordered().title(format!("Configurations ({} of {})", self.visible.len(), self.configs.len())))
+            .highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(configs, right, &mut self.list_state);
+
+        let status_line = if self.editing_filter {
+            format!("Filter: {}_", self.filter)
+        } else if let Some(message) = &self.message {
+            message.clone()
+        } else if !self.filter.is_empty() {
+            format!("Filter: {} | ↑/↓ scroll  / filter  x exclude  1-4 member  t/h/d role rule  c/C copy text/macro  p copy PF  r refresh  q quit", self.filter)
+        } else {
+            "↑/↓ scroll  / filter  x exclude  1-4 member  t/h/d role rule  c/C copy text/macro  p copy PF  r refresh  q quit".to_owned()
+        };
+        frame.render_widget(Paragraph::new(status_line), status);
+    }
+}
+
+/// Shows the party and its ranked configurations in a full-screen terminal UI until the user quits.
+pub fn run(provider: &dyn CharacterProvider, scoring: Scoring, constraints: Constraints, party: Vec<PlayerCharacter>, max_results: usize) -> io::Result<()> {
+    let mut app = App::new(provider, scoring, constraints, party, max_results);
+
+    let mut terminal = ratatui::try_init()?;
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+
+    result
+}
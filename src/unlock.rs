@@ -0,0 +1,103 @@
+use std::cmp::Reverse;
+
+use crate::constraints::Constraints;
+use crate::job::{self, JobInfo};
+use crate::optimizer::{self, PartyConfig, Scoring};
+use crate::xivapi::{ClassJob, PlayerCharacter};
+
+/// A job a member hasn't unlocked yet but could, and the configurations it would add to the best.
+#[derive(Debug)]
+pub struct Unlock {
+    /// Index into the party of the member who could unlock the job.
+    pub member: usize,
+    /// Index into their jobs of the job.
+    pub job_index: usize,
+    /// The party with the job at the level it starts at, which `party_configs` are of.
+    pub party: Vec<PlayerCharacter>,
+    /// Configurations playing the job that would be among the best, best first.
+    pub party_configs: Vec<PartyConfig>
+}
+
+/// Whether `character` could unlock `class_job` now, going by the level it starts at, which their highest job has
+/// to have reached and has to be below their level cap, and the expansions they own.
+fn can_unlock(character: &PlayerCharacter, class_job: &ClassJob) -> bool {
+    let job = class_job.job;
+    let highest = character.class_jobs.iter()
+        .filter(|class_job| class_job.job.is_combat())
+        .map(|class_job| class_job.level)
+        .max()
+        .unwrap_or_default();
+
+    class_job.level == 0
+        && job.is_combat()
+        && !job.is_limited()
+        && highest >= job.starting_level
+        && job.starting_level < character.level_cap()
+        && (!character.trial || job.in_free_trial())
+        && character.owns(class_job)
+}
+
+/// `party` with `member`'s `job` at `level`, and the index into their jobs of it. Characters entered by hand or saved
+/// with older data may leave out jobs they haven't unlocked, which are added.
+pub(crate) fn with_level(
+    party: &[PlayerCharacter],
+    member: usize,
+    job: &'static JobInfo,
+    level: u8
+) -> (Vec<PlayerCharacter>, usize) {
+    let mut party = party.to_vec();
+    let class_jobs = &mut party[member].class_jobs;
+    let job_index = match class_jobs.iter().position(|class_job| class_job.job == job) {
+        Some(job_index) => job_index,
+        None => {
+            class_jobs.push(ClassJob::manual(job, 0));
+            class_jobs.len() - 1
+        }
+    };
+    class_jobs[job_index].set_level(level);
+
+    (party, job_index)
+}
+
+/// The jobs members could unlock that would add configurations at least as good as the worst of `party_configs`,
+/// which are the best found without them. Those adding the most come first.
+pub fn unlocks(
+    party: &[PlayerCharacter],
+    party_configs: &[PartyConfig],
+    scoring: &Scoring,
+    constraints: &Constraints
+) -> Vec<Unlock> {
+    let Some(worst) = party_configs.last() else {
+        return Vec::new();
+    };
+
+    let mut unlocks = Vec::new();
+    for (member, character) in party.iter().enumerate() {
+        for info in job::JOBS.iter() {
+            // Levelling a class levels every job it upgrades into, like Arcanist's Summoner and Scholar.
+            if character.class_jobs.iter().any(|class_job| class_job.class_id() == info.class_id && class_job.level > 0) {
+                continue;
+            }
+            let class_job = character.class_jobs.iter()
+                .find(|class_job| class_job.job == info)
+                .cloned()
+                .unwrap_or_else(|| ClassJob::manual(info, 0));
+            if !can_unlock(character, &class_job) {
+                continue;
+            }
+
+            let (unlocked, job_index) = with_level(party, member, info, info.starting_level);
+            let added: Vec<PartyConfig> = optimizer::rank_top(&unlocked, scoring, constraints, party_configs.len())
+                .into_iter()
+                .filter(|party_config| party_config.index[member] == job_index && party_config >= worst)
+                .collect();
+
+            if !added.is_empty() {
+                unlocks.push(Unlock { member, job_index, party: unlocked, party_configs: added });
+            }
+        }
+    }
+
+    unlocks.sort_by_key(|unlock| Reverse(unlock.party_configs.len()));
+    unlocks
+}
@@ -0,0 +1,27 @@
+use self_update::backends::github::Update;
+use self_update::cargo_crate_version;
+use std::process;
+
+fn update() -> Result<self_update::Status, self_update::errors::Error> {
+    Update::configure()
+        .repo_owner("drwhut")
+        .repo_name("xiv-levelling")
+        .bin_name("xiv-levelling")
+        .show_download_progress(true)
+        .current_version(cargo_crate_version!())
+        .build()?
+        .update()
+}
+
+pub fn run() {
+    println!("Checking GitHub for a newer release of xiv-levelling...");
+
+    match update() {
+        Ok(status) if status.updated() => println!("Updated xiv-levelling to version {}!", status.version()),
+        Ok(status) => println!("xiv-levelling is already up to date (version {}).", status.version()),
+        Err(e) => {
+            eprintln!("Failed to update xiv-levelling: {}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use xiv_levelling::provider::CharacterProvider;
+use xiv_levelling::xivapi::PlayerCharacter;
+
+/// How long a user has to put their token in their bio before they need a new one.
+const TOKEN_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// A verification waiting for the user to put `token` in their character's bio.
+#[derive(Deserialize, Serialize, Debug)]
+struct Pending {
+    character: u32,
+    token: String,
+    /// Seconds since the Unix epoch when the token was made.
+    created: u64
+}
+
+/// Characters that users of the bot or server have proven they own, by putting a token in their Lodestone bio.
+///
+/// Users are identified however the caller likes, e.g. by Discord user ID.
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct Registry {
+    /// The character each user has verified.
+    registered: BTreeMap<String, u32>,
+    pending: BTreeMap<String, Pending>
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default()
+}
+
+/// A token that is hard to guess. The standard library seeds every `RandomState` randomly, which saves a dependency
+/// on a random number generator.
+fn token() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(now());
+    format!("xivl-{:016x}", hasher.finish())
+}
+
+impl Registry {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("xiv-levelling").join("registry.json"))
+    }
+
+    /// Loads the registry at `path`, treating a missing file as an empty registry.
+    pub fn load(path: &Path) -> xiv_levelling::Result<Registry> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Registry::default()),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    /// Loads the registry from its default path, starting afresh if it can't be read.
+    pub fn load_default() -> Registry {
+        let Some(path) = Registry::default_path() else {
+            return Registry::default();
+        };
+
+        Registry::load(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to load {}: {}", path.display(), e);
+            Registry::default()
+        })
+    }
+
+    /// Saves the registry to its default path, if there is one.
+    fn save_default(&self) {
+        if let Some(path) = Registry::default_path() {
+            if let Err(e) = self.save(&path) {
+                eprintln!("Failed to save {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> xiv_levelling::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let partial = path.with_extension("partial");
+        fs::write(&partial, serde_json::to_string(self)?)?;
+        fs::rename(partial, path)?;
+        Ok(())
+    }
+
+    /// Starts verifying that `user` owns `character`, returning the token they need to put in its bio. Any earlier
+    /// verification by `user` is replaced.
+    pub fn start(&mut self, user: &str, character: u32) -> String {
+        let token = token();
+        self.pending.insert(user.to_owned(), Pending { character, token: token.clone(), created: now() });
+        token
+    }
+
+    /// Checks the bio of the character `user` is verifying, fetched fresh from `api`, and registers the character to
+    /// them if it contains their token.
+    pub fn confirm(&mut self, user: &str, api: &dyn CharacterProvider) -> Result<PlayerCharacter, String> {
+        let pending = match self.pending.get(user) {
+            Some(pending) if now().saturating_sub(pending.created) <= TOKEN_MAX_AGE.as_secs() => pending,
+            _ => return Err("There is no verification in progress, or it has expired! Start a new one to get a token.".to_owned())
+        };
+
+        let character = api.refresh_character(pending.character).map_err(|e| e.to_string())?;
+        if !character.bio.as_deref().unwrap_or_default().contains(&pending.token) {
+            return Err(format!(
+                "{} isn't in the character's Lodestone bio yet! It can take a few minutes for changes to show up.",
+                pending.token
+            ));
+        }
+
+        self.pending.remove(user);
+        self.registered.insert(user.to_owned(), character.id);
+        Ok(character)
+    }
+}
+
+/// Looks up `name` on `server` and starts verifying that `user` owns them, returning the character's ID and the token
+/// to put in their bio.
+pub fn begin(api: &dyn CharacterProvider, registry: &Mutex<Registry>, user: &str, name: &str, server: &str) -> Result<(u32, String), String> {
+    let player_search = api.search_character(name, server).map_err(|e| e.to_string())?;
+    let id = match player_search.pagination.results {
+        1 => player_search.results[0].id,
+        0 => return Err(format!("No character named {} was found on {}!", name, server)),
+        _ => return Err(format!("Multiple characters named {} were found on {}!", name, server))
+    };
+
+    let mut registry = registry.lock().unwrap();
+    let token = registry.start(user, id);
+    registry.save_default();
+    Ok((id, token))
+}
+
+/// Finishes `user`'s verification, returning the character now registered to them.
+pub fn finish(api: &dyn CharacterProvider, registry: &Mutex<Registry>, user: &str) -> Result<PlayerCharacter, String> {
+    let mut registry = registry.lock().unwrap();
+    let character = registry.confirm(user, api)?;
+    registry.save_default();
+    Ok(character)
+}
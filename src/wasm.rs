@@ -0,0 +1,70 @@
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::constraints::Constraints;
+use crate::job::{Mode, Role};
+use crate::optimizer::{self, Scoring};
+use crate::xivapi;
+
+#[derive(Serialize)]
+struct Member<'a> {
+    name: &'a str,
+    job: &'a str,
+    level: u8,
+    role: Option<Role>,
+    armoury_bonus: bool,
+    avatar: Option<&'a str>,
+    portrait: Option<&'a str>
+}
+
+#[derive(Serialize)]
+struct Configuration<'a> {
+    spread: f64,
+    avg: u32,
+    min_level: u8,
+    members: Vec<Member<'a>>
+}
+
+/// Ranks the `top` best configurations of `party_json`, a JSON array of XIVAPI `/character/{id}` responses, and
+/// returns them as a JSON array, best first.
+#[wasm_bindgen]
+pub fn optimize(party_json: &str, top: usize) -> Result<String, JsError> {
+    let mut party = xivapi::parse_party(party_json).map_err(|e| JsError::new(&e.to_string()))?;
+    for character in &mut party {
+        character.retain_mode(Mode::Combat);
+    }
+
+    if party.len() < 2 || party.len() > 4 {
+        return Err(JsError::new("Party must consist of between two and four characters!"));
+    }
+
+    let constraints = Constraints {
+        dedup: true,
+        ..Constraints::default()
+    };
+    let party_configs = optimizer::rank_top(&party, &Scoring::default(), &constraints, top);
+
+    let configurations: Vec<Configuration> = party_configs.iter()
+        .map(|party_config| Configuration {
+            spread: party_config.spread,
+            avg: party_config.avg,
+            min_level: party_config.min_level,
+            members: party.iter().zip(&party_config.index)
+                .map(|(character, &job_index)| {
+                    let class_job = &character.class_jobs[job_index];
+                    Member {
+                        name: &character.name,
+                        job: class_job.display_name(),
+                        level: class_job.level,
+                        role: class_job.role(),
+                        armoury_bonus: character.has_armoury_bonus(class_job),
+                        avatar: character.avatar.as_deref(),
+                        portrait: character.portrait.as_deref()
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    serde_json::to_string(&configurations).map_err(|e| JsError::new(&e.to_string()))
+}
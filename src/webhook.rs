@@ -0,0 +1,14 @@
+use reqwest::blocking::Client;
+use serde_json::json;
+
+use crate::Result;
+
+/// Posts `content` as a message to a Discord-compatible webhook at `url`.
+pub fn post(client: &Client, url: &str, content: &str) -> Result<()> {
+    client.post(url)
+        .json(&json!({ "content": content }))
+        .send()?
+        .error_for_status()?;
+
+    Ok(())
+}
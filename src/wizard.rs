@@ -0,0 +1,152 @@
+use std::fmt::Display;
+use std::io;
+use std::str::FromStr;
+
+use xiv_levelling::constraints::{Composition, Constraints, Lock};
+use xiv_levelling::job;
+use xiv_levelling::optimizer::{self, Scoring};
+use xiv_levelling::xivapi::PlayerCharacter;
+
+use crate::output;
+
+/// Most configurations counted for a preview, past which there are only said to be at least this many.
+const PREVIEW_LIMIT: usize = 100_000;
+
+/// How many configurations `constraints` keep, e.g. "1234", or "100000+" if there are too many to count.
+fn preview(party: &[PlayerCharacter], scoring: &Scoring, constraints: &Constraints) -> String {
+    let count = optimizer::enumerate_configs(party, scoring, constraints).take(PREVIEW_LIMIT).count();
+    if count == PREVIEW_LIMIT {
+        format!("{}+", count)
+    } else {
+        count.to_string()
+    }
+}
+
+fn composition_name(composition: Composition) -> &'static str {
+    match composition {
+        Composition::Standard => "standard",
+        Composition::Any => "any"
+    }
+}
+
+/// Asks `question` until the answer can be read, giving `None` if nothing is entered.
+fn ask<T: FromStr>(question: &str) -> Option<T>
+where
+    T::Err: Display
+{
+    let mut input = String::new();
+    loop {
+        input.clear();
+        println!("{}", question);
+        if io::stdin().read_line(&mut input).unwrap_or_default() == 0 {
+            return None;
+        }
+
+        let answer = input.trim();
+        if answer.is_empty() {
+            return None;
+        }
+        match answer.parse() {
+            Ok(value) => return Some(value),
+            Err(e) => println!("{}", e)
+        }
+    }
+}
+
+/// Asks for a level bound, keeping it only if some configuration is left with it.
+fn ask_level(
+    party: &[PlayerCharacter],
+    scoring: &Scoring,
+    constraints: &mut Constraints,
+    question: &str,
+    bound: fn(&mut Constraints) -> &mut Option<u8>
+) -> Option<u8> {
+    let level: u8 = ask(question)?;
+    let previous = bound(constraints).replace(level);
+    if !constraints.is_satisfiable() || optimizer::enumerate_configs(party, scoring, constraints).next().is_none() {
+        *bound(constraints) = previous;
+        println!("No configuration is left at that level, so it wasn't kept.");
+        return None;
+    }
+
+    println!("That keeps {} configurations.", preview(party, scoring, constraints));
+    Some(level)
+}
+
+/// Walks through the composition, level band, locks and weights, showing how many configurations each choice keeps
+/// and the best one the weights pick. Anything left blank keeps what the flags and config file set.
+pub fn run(party: &[PlayerCharacter], scoring: &mut Scoring, constraints: &mut Constraints) {
+    // The flags that would make the same choices, to skip the questions next time.
+    let mut flags = Vec::new();
+
+    println!("Setting up the search. Press enter to keep what's in brackets.");
+    println!("There are {} configurations to begin with.\n", preview(party, scoring, constraints));
+
+    if !constraints.fills_roles() && constraints.mode.requires_roles() {
+        let with = |composition| Constraints { composition, ..constraints.clone() };
+        println!(
+            "Composition: standard has one tank and one healer, as the Duty Finder needs ({} configurations), and any \
+             allows any roles, for unsynced content ({} configurations).",
+            preview(party, scoring, &with(Composition::Standard)),
+            preview(party, scoring, &with(Composition::Any))
+        );
+        let question = format!("Composition [{}]:", composition_name(constraints.composition));
+        if let Some(composition) = ask::<Composition>(&question) {
+            constraints.composition = composition;
+            flags.push(format!("--comp {}", composition_name(composition)));
+        }
+        println!();
+    }
+
+    println!("Level band: jobs below or above it are left out, e.g. ones nobody wants to level yet.");
+    let question = format!("Lowest level [{}]:", constraints.min_level.map_or("any".to_owned(), |level| level.to_string()));
+    if let Some(level) = ask_level(party, scoring, constraints, &question, |constraints| &mut constraints.min_level) {
+        flags.push(format!("--min-level {}", level));
+    }
+    let question = format!("Highest level [{}]:", constraints.max_level.map_or("any".to_owned(), |level| level.to_string()));
+    if let Some(level) = ask_level(party, scoring, constraints, &question, |constraints| &mut constraints.max_level) {
+        flags.push(format!("--max-level {}", level));
+    }
+    println!();
+
+    println!("Locks: a member kept on one job, e.g. a main they're levelling anyway.");
+    for character in party {
+        let question = format!("Job for {} [any]:", character.display_name());
+        while let Some(job) = ask::<String>(&question) {
+            if !job::JOBS.iter().any(|info| info.matches(&job)) {
+                println!("There is no job called {}!", job);
+                continue;
+            }
+
+            constraints.locks.push(Lock { character: character.name.clone(), job: job.clone() });
+            if optimizer::enumerate_configs(party, scoring, constraints).next().is_none() {
+                constraints.locks.pop();
+                println!("No configuration is left with {} on {}, so the lock wasn't kept.", character.display_name(), job);
+                continue;
+            }
+
+            println!("That keeps {} configurations.", preview(party, scoring, constraints));
+            flags.push(format!("--lock \"{}={}\"", character.name, job));
+            break;
+        }
+    }
+    println!();
+
+    println!("Weights: how the configurations left are ranked.");
+    if let Some(weight) = ask::<f64>(&format!("Weight on a tight level spread [{}]:", scoring.weight_var)) {
+        scoring.weight_var = weight;
+        flags.push(format!("--weight-var {}", weight));
+    }
+    if let Some(weight) = ask::<f64>(&format!("Weight on a higher average level [{}]:", scoring.weight_avg)) {
+        scoring.weight_avg = weight;
+        flags.push(format!("--weight-avg {}", weight));
+    }
+    if let Some(best) = optimizer::rank_top(party, scoring, constraints, 1).pop() {
+        println!("The best is now {}.", output::config_line(party, &best));
+    }
+    println!();
+
+    if !flags.is_empty() {
+        println!("To make the same choices without the questions, pass: {}\n", flags.join(" "));
+    }
+}
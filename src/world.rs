@@ -0,0 +1,62 @@
+use crate::xivapi::PlayerCharacter;
+
+/// A group of worlds whose players can party up with each other without travelling.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DataCenter {
+    pub name: &'static str,
+    pub worlds: &'static [&'static str]
+}
+
+const fn data_center(name: &'static str, worlds: &'static [&'static str]) -> DataCenter {
+    DataCenter { name, worlds }
+}
+
+pub static DATA_CENTERS: [DataCenter; 11] = [
+    data_center("Aether", &["Adamantoise", "Cactuar", "Faerie", "Gilgamesh", "Jenova", "Midgardsormr", "Sargatanas", "Siren"]),
+    data_center("Crystal", &["Balmung", "Brynhildr", "Coeurl", "Diabolos", "Goblin", "Malboro", "Mateus", "Zalera"]),
+    data_center("Dynamis", &["Cuchulainn", "Golem", "Halicarnassus", "Kraken", "Maduin", "Marilith", "Rafflesia", "Seraph"]),
+    data_center("Primal", &["Behemoth", "Excalibur", "Exodus", "Famfrit", "Hyperion", "Lamia", "Leviathan", "Ultros"]),
+    data_center("Chaos", &["Cerberus", "Louisoix", "Moogle", "Omega", "Phantom", "Ragnarok", "Sagittarius", "Spriggan"]),
+    data_center("Light", &["Alpha", "Lich", "Odin", "Phoenix", "Raiden", "Shiva", "Twintania", "Zodiark"]),
+    data_center("Materia", &["Bismarck", "Ravana", "Sephirot", "Sophia", "Zurvan"]),
+    data_center("Elemental", &["Aegis", "Atomos", "Carbuncle", "Garuda", "Gungnir", "Kujata", "Tonberry", "Typhon"]),
+    data_center("Gaia", &["Alexander", "Bahamut", "Durandal", "Fenrir", "Ifrit", "Ridill", "Tiamat", "Ultima"]),
+    data_center("Mana", &["Anima", "Asura", "Chocobo", "Hades", "Ixion", "Masamune", "Pandaemonium", "Titan"]),
+    data_center("Meteor", &["Belias", "Mandragora", "Ramuh", "Shinryu", "Unicorn", "Valefor", "Yojimbo", "Zeromus"])
+];
+
+/// The data center `world` is on, ignoring case, if it is a known world.
+pub fn find(world: &str) -> Option<&'static DataCenter> {
+    DATA_CENTERS.iter().find(|data_center| data_center.worlds.iter().any(|name| name.eq_ignore_ascii_case(world)))
+}
+
+/// A member who has to travel to another data center to queue with the rest of the party.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Travel {
+    /// Index into the party of the member.
+    pub member: usize,
+    pub from: &'static DataCenter,
+    pub to: &'static DataCenter
+}
+
+/// Who has to travel for everyone to be on the same data center. The party meets on whichever the most members are
+/// on already, or the first of those member's if there is a tie. Members whose world isn't known stay put.
+pub fn travel(party: &[PlayerCharacter]) -> Vec<Travel> {
+    let homes: Vec<Option<&'static DataCenter>> = party.iter()
+        .map(|character| character.server.as_deref().and_then(find))
+        .collect();
+
+    let count = |data_center: &DataCenter| homes.iter().filter(|&&home| home == Some(data_center)).count();
+    // Reversed so that ties go to the earlier member.
+    let Some(meeting) = homes.iter().rev().flatten().copied().max_by_key(|&data_center| count(data_center)) else {
+        return Vec::new();
+    };
+
+    homes.iter()
+        .enumerate()
+        .filter_map(|(member, home)| {
+            let from = (*home)?;
+            (from != meeting).then_some(Travel { member, from, to: meeting })
+        })
+        .collect()
+}
@@ -0,0 +1,751 @@
+#[cfg(feature = "network")]
+use reqwest::blocking::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::fmt;
+#[cfg(feature = "network")]
+use std::sync::atomic::Ordering;
+#[cfg(feature = "network")]
+use std::time::{Duration, Instant};
+
+use crate::buff::Buff;
+#[cfg(feature = "network")]
+use crate::cache::Cache;
+use crate::job::{self, Expansion, JobInfo, Mode, Role, SubRole};
+#[cfg(feature = "network")]
+use crate::language::Language;
+#[cfg(feature = "network")]
+use crate::metrics::ApiMetrics;
+use crate::world;
+use crate::{Error, Result};
+
+#[cfg(feature = "network")]
+const BASE_URL: &str = "https://xivapi.com";
+
+#[cfg(feature = "network")]
+const SERVERS_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+#[cfg(feature = "network")]
+const SEARCH_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+#[cfg(feature = "network")]
+const CHARACTER_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Deserialize, Debug)]
+#[serde(transparent)]
+pub struct ServerList {
+    servers: Vec<String>
+}
+
+impl ServerList {
+    pub fn new(servers: Vec<String>) -> Self {
+        ServerList { servers }
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        self.servers.iter().any(|server| server == name)
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.servers
+    }
+
+    /// The servers whose names start with `prefix`, ignoring case.
+    pub fn starting_with(&self, prefix: &str) -> Vec<&str> {
+        self.servers.iter()
+            .filter(|server| server.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix)))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct Pagination {
+    pub results: u8
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlayerSearchEntry {
+    #[serde(rename = "ID")]
+    pub id: u32,
+    pub name: String,
+    /// The character's world, which may be followed by its data center, e.g. "Zalera [Crystal]".
+    #[serde(default)]
+    pub server: Option<String>,
+    #[serde(default)]
+    pub avatar: Option<String>
+}
+
+impl PlayerSearchEntry {
+    /// Whether `name` is this character's full name or first name, ignoring case.
+    pub fn matches_name(&self, name: &str) -> bool {
+        matches_name(&self.name, name)
+    }
+
+    /// The world the character is on, without any data center after it.
+    pub fn world(&self) -> Option<&str> {
+        self.server.as_deref().and_then(|server| server.split_whitespace().next())
+    }
+
+    /// The data center the character is on, as given after their world or else looked up from it.
+    pub fn data_center(&self) -> Option<&str> {
+        let given = self.server.as_deref()
+            .and_then(|server| server.split_once('['))
+            .map(|(_, data_center)| data_center.trim_end_matches(']').trim());
+        given.or_else(|| Some(world::find(self.world()?)?.name))
+    }
+}
+
+fn matches_name(full_name: &str, name: &str) -> bool {
+    full_name.eq_ignore_ascii_case(name)
+        || full_name.split_whitespace().next().is_some_and(|first_name| first_name.eq_ignore_ascii_case(name))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlayerSearchResult {
+    pub pagination: Pagination,
+    pub results: Vec<PlayerSearchEntry>
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct FreeCompanySearchEntry {
+    /// Free company IDs are too large for XIVAPI to return as numbers.
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub name: String
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct FreeCompanySearchResult {
+    pub pagination: Pagination,
+    pub results: Vec<FreeCompanySearchEntry>
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct FreeCompanyMember {
+    #[serde(rename = "ID")]
+    pub id: u32,
+    pub name: String
+}
+
+#[cfg(feature = "network")]
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct FreeCompanyMeta {
+    free_company_members: Vec<FreeCompanyMember>
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "PascalCase")]
+struct ClassUnlockedState {
+    #[serde(rename = "ID")]
+    pub id: Option<u8>,
+    /// Name of the class or job, in the language the data was requested in.
+    pub name: Option<String>
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct RawClassJob {
+    #[serde(rename = "ClassID")]
+    pub class_id: u8,
+    #[serde(rename = "JobID")]
+    pub job_id: u8,
+    #[serde(default)]
+    pub level: u8,
+    pub exp_level: Option<u32>,
+    pub exp_level_max: Option<u32>,
+    #[serde(default)]
+    unlocked_state: ClassUnlockedState
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClassJob {
+    pub job: &'static JobInfo,
+    pub level: u8,
+    /// EXP earned towards the next level, if XIVAPI provided it.
+    pub exp_level: Option<u32>,
+    pub exp_level_max: Option<u32>,
+    pub job_unlocked: bool,
+    /// What `name` is called in the language the character was requested in, if not English.
+    pub localised_name: Option<String>,
+    /// Index into the character's `alts` of the alt this job is played on, or `None` for the character themselves.
+    pub alt: Option<usize>
+}
+
+impl ClassJob {
+    /// A job entered by hand rather than read from XIVAPI, so its EXP progress is unknown.
+    pub fn manual(job: &'static JobInfo, level: u8) -> Self {
+        ClassJob {
+            job,
+            level,
+            exp_level: None,
+            exp_level_max: None,
+            job_unlocked: job.job_available_at(level),
+            localised_name: None,
+            alt: None
+        }
+    }
+
+    /// Changes the level as if the job had just reached it. Below the level the job is unlocked at, it is back to
+    /// being its base class.
+    pub fn set_level(&mut self, level: u8) {
+        self.level = level;
+        self.exp_level = self.exp_level.map(|_| 0);
+        if !self.job.job_available_at(level) {
+            self.job_unlocked = false;
+            self.localised_name = None;
+        }
+    }
+
+    pub fn class_id(&self) -> u8 {
+        self.job.class_id
+    }
+
+    pub fn role(&self) -> Option<Role> {
+        self.job.role
+    }
+
+    pub fn sub_role(&self) -> Option<SubRole> {
+        self.job.sub_role
+    }
+
+    /// Total EXP earned on this job since level 1, if XIVAPI provided the progress into the current level.
+    pub fn total_exp(&self) -> Option<u64> {
+        self.exp_level.map(|exp_level| job::exp_to_reach(self.level) + exp_level as u64)
+    }
+
+    pub fn name(&self) -> &'static str {
+        if self.job_unlocked {
+            self.job.job_name
+        } else {
+            self.job.class_name
+        }
+    }
+
+    /// Three-letter abbreviation of `name`, e.g. PLD.
+    pub fn abbreviation(&self) -> &'static str {
+        if self.job_unlocked {
+            self.job.job_abbreviation
+        } else {
+            self.job.class_abbreviation
+        }
+    }
+
+    /// Like `name`, but in the language the character was requested in.
+    pub fn display_name(&self) -> &str {
+        self.localised_name.as_deref().unwrap_or(self.name())
+    }
+}
+
+impl fmt::Display for ClassJob {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.display_name())
+    }
+}
+
+/// Merges entries for the same class and job, such as Gladiator and Paladin listed apart, keeping the higher level.
+/// It is only named after the job if the job is unlocked and the level is high enough for it to have been.
+pub fn merge_class_jobs(class_jobs: &mut Vec<ClassJob>) {
+    let mut merged: Vec<ClassJob> = Vec::with_capacity(class_jobs.len());
+
+    for class_job in class_jobs.drain(..) {
+        match merged.iter_mut().find(|kept| kept.job == class_job.job) {
+            Some(kept) => {
+                let job_unlocked = kept.job_unlocked || class_job.job_unlocked;
+                if class_job.level > kept.level {
+                    *kept = class_job;
+                }
+                kept.job_unlocked = job_unlocked;
+            },
+            None => merged.push(class_job)
+        }
+    }
+
+    for class_job in &mut merged {
+        if class_job.job_unlocked && !class_job.job.job_available_at(class_job.level) {
+            class_job.job_unlocked = false;
+            class_job.localised_name = None;
+        }
+    }
+
+    *class_jobs = merged;
+}
+
+/// Resolves each class/job entry against the job table, dropping any the table doesn't know about or that can't be
+/// read, so one odd entry doesn't lose the rest. Hidden profiles have no entries at all.
+fn deserialize_class_jobs<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<ClassJob>, D::Error> {
+    let entries = Option::<Vec<serde_json::Value>>::deserialize(deserializer)?.unwrap_or_default();
+
+    let mut class_jobs = entries.into_iter()
+        .filter_map(|entry| match RawClassJob::deserialize(entry) {
+            Ok(raw) => Some(raw),
+            Err(e) => {
+                tracing::warn!("skipping an unreadable class/job entry: {}", e);
+                None
+            }
+        })
+        .filter_map(|raw| {
+            job::lookup(raw.class_id, raw.job_id).map(|info| ClassJob {
+                job: info,
+                level: raw.level,
+                exp_level: raw.exp_level,
+                exp_level_max: raw.exp_level_max,
+                job_unlocked: raw.unlocked_state.id == Some(raw.job_id),
+                localised_name: raw.unlocked_state.name,
+                alt: None
+            })
+        })
+        .collect();
+    merge_class_jobs(&mut class_jobs);
+    Ok(class_jobs)
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlayerCharacter {
+    #[serde(rename = "ID")]
+    pub id: u32,
+    #[serde(default, deserialize_with = "deserialize_class_jobs")]
+    pub class_jobs: Vec<ClassJob>,
+    pub name: String,
+    /// The world the character is on, if known.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    pub server: Option<String>,
+    /// URL of the character's face, as shown next to their name on the Lodestone.
+    #[serde(default)]
+    pub avatar: Option<String>,
+    /// URL of the character's full-body portrait.
+    #[serde(default)]
+    pub portrait: Option<String>,
+    /// The character's self-introduction on the Lodestone.
+    #[serde(default)]
+    pub bio: Option<String>,
+    /// EXP buffs the user has flagged for this character. XIVAPI knows nothing about these.
+    #[serde(skip)]
+    pub buffs: Vec<Buff>,
+    /// Whether the user has flagged this character as playing on the free trial.
+    #[serde(skip)]
+    pub trial: bool,
+    /// The latest expansion the user has said this character owns, or `None` if they own every one.
+    #[serde(skip)]
+    pub expansion: Option<Expansion>,
+    /// Which jobs the character has been logged playing on FFLogs, if looked up.
+    #[serde(skip)]
+    pub play_history: Option<PlayHistory>,
+    /// Which roles the character played in the party's recent sessions, if recorded.
+    #[serde(skip)]
+    pub role_history: Option<RoleHistory>,
+    /// What the user calls the character in place of their name, if anything.
+    #[serde(skip)]
+    pub nickname: Option<String>,
+    /// Other characters played by the same player, whose jobs have been added to `class_jobs`.
+    #[serde(skip)]
+    pub alts: Vec<Alt>,
+    /// The gear the character last had equipped, if XIVAPI provided it in a form that could be read.
+    #[serde(default, deserialize_with = "deserialize_lenient")]
+    pub gear_set: Option<GearSet>
+}
+
+/// How many levels a job's gear may be below the job before it is flagged as under-geared.
+pub const UNDER_GEARED_LEVELS: u8 = 10;
+
+/// A character's equipped gear, which the Lodestone only shows for whichever job they last played.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct GearSet {
+    #[serde(rename = "ClassID")]
+    pub class_id: u8,
+    #[serde(rename = "JobID")]
+    pub job_id: u8,
+    #[serde(default)]
+    gear: BTreeMap<String, GearPiece>
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct GearPiece {
+    /// The item's details with XIVAPI's extended data, or otherwise only its ID.
+    item: Option<serde_json::Value>
+}
+
+impl GearPiece {
+    fn level_equip(&self) -> Option<u32> {
+        self.item.as_ref()?.get("LevelEquip")?.as_u64().map(|level| level as u32)
+    }
+}
+
+impl GearSet {
+    /// Average level needed to equip each piece of gear, leaving out the soul crystal, or `None` if the levels are
+    /// unknown.
+    pub fn level(&self) -> Option<u8> {
+        let levels: Vec<u32> = self.gear.iter()
+            .filter(|(slot, _)| slot.as_str() != "SoulCrystal")
+            .filter_map(|(_, piece)| piece.level_equip())
+            .collect();
+
+        if levels.is_empty() {
+            None
+        } else {
+            Some((levels.iter().sum::<u32>() / levels.len() as u32) as u8)
+        }
+    }
+
+    /// Whether this is the gear for `class_job`. A class that hasn't become its job yet is listed with the class
+    /// as its job.
+    fn is_for(&self, class_job: &ClassJob) -> bool {
+        self.class_id == class_job.class_id() && (self.job_id == class_job.job.job_id || self.job_id == self.class_id)
+    }
+}
+
+/// Another character of a player, pooled into the same party slot so any of their jobs can be suggested.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alt {
+    pub id: u32,
+    pub name: String
+}
+
+/// How often and how recently a character has played each job, going by their recent reports on FFLogs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayHistory {
+    /// How many recent reports the character appears in.
+    pub reports: u32,
+    /// For each job name, how many of those reports it was played in, and when it was last played in seconds since
+    /// the Unix epoch.
+    pub jobs: BTreeMap<String, (u32, u64)>
+}
+
+impl PlayHistory {
+    /// How many reports `info` was played in, and when it was last played.
+    pub fn job(&self, info: &JobInfo) -> Option<(u32, u64)> {
+        self.jobs.iter().find(|(name, _)| info.matches(name)).map(|(_, &played)| played)
+    }
+
+    /// The share of reports `info` was played in, from 0 to 1.
+    pub fn familiarity(&self, info: &JobInfo) -> f64 {
+        match self.job(info) {
+            Some((reports, _)) if self.reports > 0 => reports as f64 / self.reports as f64,
+            _ => 0.0
+        }
+    }
+}
+
+/// The roles a character played in the party's recent sessions, and the one they would most like to play.
+#[derive(Clone, Debug, Default)]
+pub struct RoleHistory {
+    /// The role played in each session, most recent first.
+    pub roles: Vec<Role>,
+    pub favourite: Option<Role>
+}
+
+impl RoleHistory {
+    /// How much playing `role` would even out who gets to play what, from -1 to 1: up to 1 for their favourite role
+    /// the fewer sessions they got it, and down to -1 for any other role the more sessions they were given it.
+    pub fn fairness(&self, role: Role) -> f64 {
+        if self.roles.is_empty() {
+            return 0.0;
+        }
+
+        let share = self.roles.iter().filter(|&&played| played == role).count() as f64 / self.roles.len() as f64;
+        if self.favourite == Some(role) { 1.0 - share } else { -share }
+    }
+}
+
+impl PlayerCharacter {
+    /// How much of the character's recent play has been on `class_job`, from 0 to 1, or 0 if that isn't known.
+    pub fn familiarity(&self, class_job: &ClassJob) -> f64 {
+        self.play_history.as_ref().map_or(0.0, |play_history| play_history.familiarity(class_job.job))
+    }
+
+    /// How much playing `class_job` would even out the roles played in recent sessions, from -1 to 1, or 0 if that
+    /// isn't known.
+    pub fn fairness(&self, class_job: &ClassJob) -> f64 {
+        match (&self.role_history, class_job.role()) {
+            (Some(role_history), Some(role)) => role_history.fairness(role),
+            _ => 0.0
+        }
+    }
+
+    pub fn highest_level(&self) -> u8 {
+        self.class_jobs.iter().map(|class_job| class_job.level).max().unwrap_or_default()
+    }
+
+    /// Highest level of the jobs on the same character as `class_job`, since each alt has its own armoury.
+    pub fn highest_level_with(&self, class_job: &ClassJob) -> u8 {
+        self.class_jobs.iter()
+            .filter(|other| other.alt == class_job.alt)
+            .map(|other| other.level)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Pools `alt`'s jobs with this character's, so the optimizer can pick whichever of the player's characters
+    /// suits the party best.
+    pub fn add_alt(&mut self, alt: PlayerCharacter) {
+        let index = self.alts.len();
+        self.alts.push(Alt { id: alt.id, name: alt.name });
+        self.class_jobs.extend(alt.class_jobs.into_iter().map(|class_job| ClassJob { alt: Some(index), ..class_job }));
+    }
+
+    /// The alt `class_job` is played on, or `None` if it's this character's own job.
+    pub fn alt_of(&self, class_job: &ClassJob) -> Option<&Alt> {
+        class_job.alt.and_then(|index| self.alts.get(index))
+    }
+
+    /// Average level of the gear equipped on `class_job`, if the character last played it. Alts' gear is unknown.
+    pub fn gear_level(&self, class_job: &ClassJob) -> Option<u8> {
+        self.gear_set.as_ref()
+            .filter(|gear_set| class_job.alt.is_none() && gear_set.is_for(class_job))
+            .and_then(GearSet::level)
+    }
+
+    /// The level of `class_job`'s gear if it is far below the job's own level, e.g. a level 78 job still in level 60
+    /// gear.
+    pub fn under_geared(&self, class_job: &ClassJob) -> Option<u8> {
+        self.gear_level(class_job).filter(|&gear_level| class_job.level >= gear_level.saturating_add(UNDER_GEARED_LEVELS))
+    }
+
+    /// Whether the character has hidden their classes and jobs on the Lodestone, so nothing is known about them.
+    pub fn is_hidden(&self) -> bool {
+        self.class_jobs.is_empty()
+    }
+
+    /// Whether `class_job` earns the armoury bonus, which doubles EXP for jobs below the character's highest level.
+    pub fn has_armoury_bonus(&self, class_job: &ClassJob) -> bool {
+        class_job.level < self.highest_level_with(class_job)
+    }
+
+    /// How much EXP `class_job` earns relative to an unbuffed job, including the armoury bonus.
+    pub fn exp_multiplier(&self, class_job: &ClassJob) -> f64 {
+        let armoury = if self.has_armoury_bonus(class_job) { 1.0 } else { 0.0 };
+        1.0 + armoury + self.buffs.iter().map(Buff::bonus).sum::<f64>()
+    }
+
+    /// Highest level this character's duties can be, which is lower on the free trial or without every expansion.
+    pub fn level_cap(&self) -> u8 {
+        let cap = if self.trial { job::TRIAL_LEVEL_CAP } else { job::LEVEL_CAP };
+        self.expansion.map_or(cap, |expansion| cap.min(expansion.level_cap()))
+    }
+
+    /// Highest level this character can take `class_job` to: its own cap, or theirs if that's lower.
+    pub fn job_cap(&self, class_job: &ClassJob) -> u8 {
+        class_job.job.level_cap().min(self.level_cap())
+    }
+
+    /// Whether `class_job` has reached a cap this character can't pass without another expansion or leaving the free
+    /// trial, so it can't earn any EXP for now.
+    pub fn is_walled(&self, class_job: &ClassJob) -> bool {
+        let cap = self.job_cap(class_job);
+        class_job.level >= cap && cap < class_job.job.level_cap()
+    }
+
+    /// Whether this character owns the expansion that added `class_job`.
+    pub fn owns(&self, class_job: &ClassJob) -> bool {
+        self.expansion.is_none_or(|expansion| class_job.job.expansion <= expansion)
+    }
+
+    /// Drops every job that isn't levelled in `mode`.
+    pub fn retain_mode(&mut self, mode: Mode) {
+        self.class_jobs.retain(|class_job| mode.includes(class_job.job));
+    }
+
+    /// What to call the character in the output: their nickname, or else their name.
+    pub fn display_name(&self) -> &str {
+        self.nickname.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Whether `name` is this character's full name, first name or nickname, ignoring case.
+    pub fn matches_name(&self, name: &str) -> bool {
+        matches_name(&self.name, name)
+            || self.nickname.as_ref().is_some_and(|nickname| nickname.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether this and `other` share a character, going by Lodestone ID and including alts, e.g. if two spellings
+    /// of a name found the same one. Characters entered by hand have no ID, so they are compared by name and world.
+    pub fn is_same_character(&self, other: &PlayerCharacter) -> bool {
+        if self.id == 0 || other.id == 0 {
+            return self.id == other.id && self.name.eq_ignore_ascii_case(&other.name) && self.server == other.server;
+        }
+
+        let ids = |character: &PlayerCharacter| {
+            let mut ids: Vec<u32> = character.alts.iter().map(|alt| alt.id).filter(|&id| id != 0).collect();
+            ids.push(character.id);
+            ids
+        };
+        let other_ids = ids(other);
+        ids(self).iter().any(|id| other_ids.contains(id))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct CharacterMeta {
+    pub character: PlayerCharacter
+}
+
+/// Deserializes an optional extra as `None` if it can't be read, rather than failing on what matters.
+fn deserialize_lenient<'de, D: Deserializer<'de>, T: DeserializeOwned>(deserializer: D) -> std::result::Result<Option<T>, D::Error> {
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
+/// Parses a response from XIVAPI. If it doesn't have the structure expected, the error says where, since XIVAPI
+/// changes from time to time.
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let e = e.into_inner();
+        if e.is_data() { Error::Schema(path, e) } else { Error::Json(e) }
+    })?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// Parses a character response saved from XIVAPI's `/character/{id}` endpoint.
+pub fn parse_character(json: &str) -> Result<PlayerCharacter> {
+    let character_meta: CharacterMeta = from_json(json)?;
+    Ok(character_meta.character)
+}
+
+/// Parses a JSON array of responses saved from XIVAPI's `/character/{id}` endpoint.
+pub fn parse_party(json: &str) -> Result<Vec<PlayerCharacter>> {
+    let character_metas: Vec<CharacterMeta> = from_json(json)?;
+    Ok(character_metas.into_iter().map(|character_meta| character_meta.character).collect())
+}
+
+/// Client for the XIVAPI endpoints, backed by the response cache.
+#[cfg(feature = "network")]
+pub struct XivApi {
+    cache: Cache,
+    language: Language,
+    client: Client,
+    metrics: ApiMetrics
+}
+
+#[cfg(feature = "network")]
+impl XivApi {
+    pub fn new(cache: Cache, language: Language, client: Client) -> Self {
+        XivApi { cache, language, client, metrics: ApiMetrics::default() }
+    }
+
+    /// How many requests have been served from the cache or XIVAPI, and how long XIVAPI took.
+    pub fn metrics(&self) -> &ApiMetrics {
+        &self.metrics
+    }
+
+    /// The URL of `path`, with the query parameters every request takes.
+    fn url(&self, path: &str) -> String {
+        let separator = if path.contains('?') { '&' } else { '?' };
+        format!("{}{}{}language={}", BASE_URL, path, separator, self.language.code())
+    }
+
+    /// Keeps the localised job names XIVAPI returned only if they aren't in English, where the job table's names
+    /// are used instead.
+    fn localise(&self, mut character: PlayerCharacter) -> PlayerCharacter {
+        if self.language == Language::English {
+            for class_job in &mut character.class_jobs {
+                class_job.localised_name = None;
+            }
+        }
+        character
+    }
+
+    pub(crate) fn language(&self) -> Language {
+        self.language
+    }
+
+    fn get<T: DeserializeOwned>(&self, path: &str, max_age: Duration) -> Result<T> {
+        self.get_url(&self.url(path), max_age)
+    }
+
+    /// Like `get`, but for a full URL, which may be on another version of the API.
+    pub(crate) fn get_url<T: DeserializeOwned>(&self, url: &str, max_age: Duration) -> Result<T> {
+        if let Some(body) = self.cache.get(url, max_age) {
+            match from_json(&body) {
+                Ok(value) => {
+                    tracing::debug!("cache hit for {}", url);
+                    self.metrics.cache_hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(value);
+                },
+                Err(e) => tracing::warn!("ignoring unreadable cache entry for {}: {}", url, e)
+            }
+        }
+
+        self.metrics.cache_misses.fetch_add(1, Ordering::Relaxed);
+        self.fetch(url)
+    }
+
+    /// The response to `url` from the last time it was fetched, however long ago, without making a request.
+    pub(crate) fn cached<T: DeserializeOwned>(&self, url: &str) -> Option<T> {
+        let body = self.cache.get(url, Duration::MAX)?;
+        from_json(&body).ok()
+    }
+
+    /// Requests `url` from XIVAPI regardless of what is cached, storing the new response.
+    fn fetch<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        if self.cache.is_offline() {
+            return Err(Error::Offline(url.to_owned()));
+        }
+
+        tracing::debug!("GET {}", url);
+        let started = Instant::now();
+        let body = self.client.get(url).send()?.error_for_status()?.text()?;
+        self.metrics.latency.observe(started.elapsed());
+        tracing::debug!(elapsed = ?started.elapsed(), bytes = body.len(), "response from {}", url);
+        let value = from_json(&body)?;
+
+        // The cache is only an optimisation, so failing to write to it is not fatal.
+        if let Err(e) = self.cache.put(url, &body) {
+            tracing::warn!("failed to cache the response from {}: {}", url, e);
+        }
+
+        Ok(value)
+    }
+
+    pub fn servers(&self) -> Result<ServerList> {
+        self.get("/servers", SERVERS_MAX_AGE)
+    }
+
+    /// The server list from the last time it was fetched, however long ago, without making a request.
+    pub fn cached_servers(&self) -> Option<ServerList> {
+        self.cached(&self.url("/servers"))
+    }
+
+    pub fn search_character(&self, name: &str, server: &str) -> Result<PlayerSearchResult> {
+        self.get(&format!("/character/search?name={}&server={}", name, server), SEARCH_MAX_AGE)
+    }
+
+    pub fn search_free_company(&self, name: &str, server: &str) -> Result<FreeCompanySearchResult> {
+        self.get(&format!("/freecompany/search?name={}&server={}", name, server), SEARCH_MAX_AGE)
+    }
+
+    /// Fetches the member list of the free company with Lodestone ID `id`.
+    pub fn free_company_members(&self, id: &str) -> Result<Vec<FreeCompanyMember>> {
+        let free_company_meta: FreeCompanyMeta = self.get(&format!("/freecompany/{}?data=FCM", id), SEARCH_MAX_AGE)?;
+        Ok(free_company_meta.free_company_members)
+    }
+
+    /// Fetches a character by Lodestone ID, with every class and job including crafters and gatherers, and the
+    /// levels of their equipped gear.
+    pub fn character(&self, id: u32) -> Result<PlayerCharacter> {
+        let character_meta: CharacterMeta = self.get(&format!("/character/{}?extended=1", id), CHARACTER_MAX_AGE)?;
+        Ok(self.localise(character_meta.character))
+    }
+
+    /// Like `character`, but always fetches the latest data from the Lodestone.
+    pub fn refresh_character(&self, id: u32) -> Result<PlayerCharacter> {
+        let character_meta: CharacterMeta = self.fetch(&self.url(&format!("/character/{}?extended=1", id)))?;
+        Ok(self.localise(character_meta.character))
+    }
+
+    /// How long ago the character with Lodestone ID `id` was fetched, if they are cached.
+    pub fn character_age(&self, id: u32) -> Option<Duration> {
+        self.cache.age(&self.url(&format!("/character/{}?extended=1", id)))
+    }
+}
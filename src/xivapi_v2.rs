@@ -0,0 +1,130 @@
+use serde::Deserialize;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::provider::CharacterProvider;
+use crate::xivapi::{PlayerCharacter, PlayerSearchResult, ServerList, XivApi};
+use crate::{Error, Result};
+
+const BASE_URL: &str = "https://beta.xivapi.com/api/1";
+
+const SERVERS_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How many rows of a sheet to ask for at once.
+const PAGE_SIZE: usize = 500;
+
+const NO_CHARACTERS: &str = "XIVAPI v2 only serves game data, not characters, so they have to be found on the \
+    Lodestone (build with the lodestone feature)";
+
+/// Which version of XIVAPI to look things up on.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ApiVersion {
+    /// The original API at xivapi.com.
+    #[default]
+    V1,
+    /// The beta at beta.xivapi.com, which is replacing it.
+    V2
+}
+
+impl FromStr for ApiVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "v1" | "1" => Ok(ApiVersion::V1),
+            "v2" | "2" => Ok(ApiVersion::V2),
+            _ => Err(format!("unknown API version {} (expected v1 or v2)", s))
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct Sheet<T> {
+    rows: Vec<Row<T>>
+}
+
+#[derive(Deserialize, Debug)]
+struct Row<T> {
+    row_id: u32,
+    fields: T
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct World {
+    name: String,
+    #[serde(default)]
+    is_public: bool
+}
+
+/// Client for XIVAPI v2, sharing the response cache and HTTP client of `XivApi`. Version 2 only has sheets of game
+/// data, so it can list the servers, but character requests fail and need to fall back to another provider.
+pub struct XivApiV2 {
+    api: XivApi
+}
+
+impl XivApiV2 {
+    pub fn new(api: XivApi) -> Self {
+        XivApiV2 { api }
+    }
+
+    /// The URL of rows of `sheet` after row `after`, with only `fields`.
+    fn sheet_url(&self, sheet: &str, fields: &str, after: Option<u32>) -> String {
+        let mut url = format!(
+            "{}/sheet/{}?fields={}&limit={}&language={}",
+            BASE_URL,
+            sheet,
+            fields,
+            PAGE_SIZE,
+            self.api.language().code()
+        );
+        if let Some(after) = after {
+            url.push_str(&format!("&after={}", after));
+        }
+        url
+    }
+
+    /// The names of every world players can make characters on, in the order of the World sheet.
+    fn worlds(&self, cached: bool) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        let mut after = None;
+
+        loop {
+            let url = self.sheet_url("World", "Name,IsPublic", after);
+            let sheet: Sheet<World> = if cached {
+                self.api.cached(&url).ok_or(Error::Offline(url))?
+            } else {
+                self.api.get_url(&url, SERVERS_MAX_AGE)?
+            };
+
+            let Some(last) = sheet.rows.last() else {
+                return Ok(names);
+            };
+            after = Some(last.row_id);
+            names.extend(sheet.rows.into_iter().filter(|row| row.fields.is_public).map(|row| row.fields.name));
+        }
+    }
+
+    pub fn servers(&self) -> Result<ServerList> {
+        self.worlds(false).map(ServerList::new)
+    }
+
+    /// The server list from the last time it was fetched, however long ago, without making a request.
+    pub fn cached_servers(&self) -> Option<ServerList> {
+        self.worlds(true).ok().map(ServerList::new)
+    }
+}
+
+impl CharacterProvider for XivApiV2 {
+    fn servers(&self) -> Result<ServerList> {
+        XivApiV2::servers(self)
+    }
+
+    fn search_character(&self, _name: &str, _server: &str) -> Result<PlayerSearchResult> {
+        Err(Error::Unsupported(NO_CHARACTERS))
+    }
+
+    fn character(&self, _id: u32) -> Result<PlayerCharacter> {
+        Err(Error::Unsupported(NO_CHARACTERS))
+    }
+}
@@ -0,0 +1,43 @@
+use xiv_levelling::constraints::Constraints;
+use xiv_levelling::optimizer::{self, Scoring};
+use xiv_levelling::provider::{CharacterProvider, MockProvider};
+use xiv_levelling::xivapi::PlayerCharacter;
+
+/// Looks up each member of the demo party the way a search from the prompt does: by name, then by ID.
+fn demo_party() -> Vec<PlayerCharacter> {
+    let provider = MockProvider::demo().unwrap();
+    provider.names()
+        .iter()
+        .map(|name| {
+            let search = provider.search_character(name, "Zalera").unwrap();
+            assert_eq!(search.results.len(), 1, "{} wasn't found once", name);
+            provider.character(search.results[0].id).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn searches_the_demo_party() {
+    let party = demo_party();
+    let constraints = Constraints { dedup: true, ..Constraints::default() };
+    let party_configs = optimizer::rank_top(&party, &Scoring::default(), &constraints, 5);
+
+    assert_eq!(party_configs.len(), 5);
+    assert!(party_configs.windows(2).all(|pair| pair[0] >= pair[1]));
+
+    let best = &party_configs[0];
+    let jobs: Vec<(&str, &str, u8)> = party.iter()
+        .zip(best.index)
+        .map(|(character, job_index)| {
+            let class_job = &character.class_jobs[job_index];
+            (character.name.as_str(), class_job.job.job_name, class_job.level)
+        })
+        .collect();
+    assert_eq!(jobs, [
+        ("Aeryn Vale", "Scholar", 50),
+        ("Bram Stoutheart", "Warrior", 50),
+        ("Cyra Moonwhisper", "Black Mage", 51),
+        ("Dax Emberfall", "Samurai", 50)
+    ]);
+    assert_eq!((best.avg, best.min_level), (50, 50));
+}